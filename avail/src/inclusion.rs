@@ -0,0 +1,197 @@
+/// Lookup of data submissions already included on chain, so a submission whose outcome was lost
+/// (e.g. after a timeout) is recorded instead of being posted a second time.
+use crate::submit_data::TransactionInfo;
+use avail::data_availability::{events::DataSubmitted, tx::SubmitData};
+use avail_rust::{
+    block_api::{BlockEvents, BlockExtOptionsSimple, BlockWithTx},
+    codec::{self, Decode},
+    ext::sp_crypto_hashing::keccak_256,
+    prelude::*,
+};
+use std::collections::HashMap;
+
+/// Fee paid by the signer of an extrinsic, `TransactionFeePaid { who, actual_fee, tip }` of the
+/// TransactionPayment pallet. Only the fee is kept as it includes the tip.
+#[derive(Debug, Clone)]
+struct TransactionFeePaid {
+    actual_fee: u128,
+}
+
+impl HasHeader for TransactionFeePaid {
+    const HEADER_INDEX: (u8, u8) = (7, 0);
+}
+
+impl Decode for TransactionFeePaid {
+    fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+        let _who = AccountId::decode(input)?;
+        let actual_fee = u128::decode(input)?;
+        let _tip = u128::decode(input)?;
+        Ok(Self { actual_fee })
+    }
+}
+
+/// Hex encoded hash identifying the data of a submission on chain
+pub fn data_hash(data: &[u8]) -> String {
+    hex::encode(keccak_256(data))
+}
+
+/// `submit_data` extrinsic signed by one of our signers
+#[derive(Debug, Clone)]
+pub struct SubmittedData {
+    pub block_hash: H256,
+    pub block_number: u32,
+    pub ext_index: u32,
+    pub app_id: u32,
+    /// Hex encoded public key of the signer
+    pub signer: String,
+}
+
+/// Blocks searched by `find_submitted_data`, kept from one search to the next so the body of a
+/// block is fetched once
+#[derive(Debug, Default)]
+pub struct BlockCache {
+    /// Hashes of finalized blocks by number, they can't be reorged out
+    finalized: HashMap<u32, H256>,
+    /// Submissions of our signers in each block, keyed by their `data_hash`
+    blocks: HashMap<H256, Vec<(String, SubmittedData)>>,
+}
+
+/// Collects the `submit_data` extrinsics signed by `signers` from the last `blocks` finalized
+/// blocks up to the best block, keyed by the `data_hash` of their data
+///
+/// Blocks not yet finalized are searched too, a submission included in one of them must not be
+/// posted again. Only the blocks missing from `cache` are fetched.
+pub async fn find_submitted_data(
+    client: &Client,
+    signers: &[AccountId],
+    blocks: u32,
+    cache: &mut BlockCache,
+) -> Result<HashMap<String, SubmittedData>, String> {
+    let signers: HashMap<String, String> = signers
+        .iter()
+        .map(|account| (account.to_string(), hex::encode(account.0)))
+        .collect();
+    let finalized = client
+        .finalized()
+        .block_height()
+        .await
+        .map_err(|e| e.to_string())?;
+    let best = client
+        .best()
+        .block_height()
+        .await
+        .map_err(|e| e.to_string())?;
+    let first = finalized.saturating_sub(blocks);
+
+    let mut searched = HashMap::new();
+    for block_number in first..=best.max(finalized) {
+        let block_hash = match cache.finalized.get(&block_number) {
+            Some(block_hash) => *block_hash,
+            None => {
+                let Some(block_hash) = client
+                    .chain()
+                    .block_hash(Some(block_number))
+                    .await
+                    .map_err(|e| e.to_string())?
+                else {
+                    continue;
+                };
+                if block_number <= finalized {
+                    cache.finalized.insert(block_number, block_hash);
+                }
+                block_hash
+            }
+        };
+        let txs = match cache.blocks.remove(&block_hash) {
+            Some(txs) => txs,
+            None => submitted_in_block(client, &signers, block_hash, block_number).await?,
+        };
+        searched.insert(block_hash, txs);
+    }
+    // Blocks out of the searched range, or reorged out, are dropped
+    cache
+        .finalized
+        .retain(|block_number, _| *block_number >= first);
+    cache.blocks = searched;
+
+    Ok(cache
+        .blocks
+        .values()
+        .flatten()
+        .map(|(hash, submitted)| (hash.clone(), submitted.clone()))
+        .collect())
+}
+
+/// `submit_data` extrinsics of a block signed by `signers`, keyed by the `data_hash` of their data
+async fn submitted_in_block(
+    client: &Client,
+    signers: &HashMap<String, String>,
+    block_hash: H256,
+    block_number: u32,
+) -> Result<Vec<(String, SubmittedData)>, String> {
+    let txs = BlockWithTx::new(client.clone(), block_hash)
+        .all::<SubmitData>(BlockExtOptionsSimple::default())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(txs
+        .into_iter()
+        .filter_map(|tx| {
+            let signer = tx
+                .ss58_address()
+                .and_then(|address| signers.get(&address))?;
+            Some((
+                data_hash(&tx.call.data),
+                SubmittedData {
+                    block_hash,
+                    block_number,
+                    ext_index: tx.ext_index(),
+                    app_id: tx.app_id(),
+                    signer: signer.clone(),
+                },
+            ))
+        })
+        .collect())
+}
+
+/// Reads back the data and inclusion details of a submission found by `find_submitted_data`
+///
+/// Fails when the extrinsic did not execute successfully, such a submission was not included.
+pub async fn get_submitted_data(
+    client: &Client,
+    submitted: &SubmittedData,
+) -> Result<(Vec<u8>, TransactionInfo), String> {
+    let Some(tx) = BlockWithTx::new(client.clone(), submitted.block_hash)
+        .get::<SubmitData>(submitted.ext_index)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Err("Submitted data not found in block".into());
+    };
+    let Some(events) = BlockEvents::new(client.clone(), submitted.block_hash)
+        .ext(submitted.ext_index)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Err("No events found for submitted data".into());
+    };
+    if !events.is_extrinsic_success_present() {
+        return Err("Transaction was executed but execution failed.".into());
+    }
+    let Some(event) = events.first::<DataSubmitted>() else {
+        return Err("Failed to find DataSubmitted event".into());
+    };
+    let Some(fee) = events.first::<TransactionFeePaid>() else {
+        return Err("Failed to find TransactionFeePaid event".into());
+    };
+
+    let info = TransactionInfo {
+        to_address: submitted.signer.clone(),
+        data_hash: hex::encode(event.data_hash.0),
+        tx_hash: hex::encode(tx.ext_hash().0),
+        block_hash: hex::encode(submitted.block_hash.0),
+        gas_fee: fee.actual_fee,
+        extrinsic_index: submitted.ext_index,
+        block_number: submitted.block_number,
+    };
+    Ok((tx.call.data, info))
+}
@@ -1,2 +1,3 @@
+pub mod inclusion;
 pub mod retrieve_data;
 pub mod submit_data;
@@ -3,6 +3,11 @@ use avail::data_availability::events::DataSubmitted;
 use avail_rust::prelude::*;
 use hex::{self, ToHex};
 
+/// Blocks a submission stays valid for once signed, it can't be included after them
+pub const MORTALITY_PERIOD: u64 = 32;
+/// Target block time of Avail
+pub const BLOCK_TIME_SECS: u64 = 20;
+
 #[derive(Debug)]
 pub struct TransactionInfo {
     pub to_address: String,
@@ -29,7 +34,8 @@ impl<'a> SubmitDataAvail<'a> {
         }
     }
     pub async fn submit_data(&self, data: &[u8]) -> Result<TransactionInfo, String> {
-        let options = Options::new(self.app_id as u32);
+        let options =
+            Options::new(self.app_id as u32).mortality(MortalityOption::Period(MORTALITY_PERIOD));
         let submittable = self
             .client
             .tx()
@@ -8,6 +8,8 @@ import {PausableUpgradeable} from "@openzeppelin/contracts-upgradeable/utils/Pau
 import {ReentrancyGuardTransientUpgradeable} from "@openzeppelin/contracts-upgradeable/utils/ReentrancyGuardTransientUpgradeable.sol";
 import {IERC20} from "@openzeppelin/contracts/token/ERC20/IERC20.sol";
 import {IERC20Permit} from "@openzeppelin/contracts/token/ERC20/extensions/IERC20Permit.sol";
+import {ECDSA} from "@openzeppelin/contracts/utils/cryptography/ECDSA.sol";
+import {MessageHashUtils} from "@openzeppelin/contracts/utils/cryptography/MessageHashUtils.sol";
 
 import {SafeERC20} from "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
 
@@ -34,6 +36,9 @@ contract TurboDAResolver is
     /// @notice Mapping to track which token addresses are valid for deposits
     mapping(address => bool) public validTokenAddresses;
 
+    /// @notice Mapping to track which withdrawal nonces have been paid out
+    mapping(uint256 => bool) public usedNonces;
+
     /**
      * @dev Emitted when a deposit is made
      * @param orderId The unique identifier of the order
@@ -68,6 +73,22 @@ contract TurboDAResolver is
      */
     event WithdrawalAddressUpdate(address withdrawalAddress);
 
+    /**
+     * @dev Emitted when a withdrawal signed by an operator is paid out
+     * @param nonce The unique identifier of the withdrawal
+     * @param userID The identifier of the user the credits were withdrawn from
+     * @param tokenAddress The address of the token paid out (address(0) for ETH)
+     * @param amount The amount paid out
+     * @param recipient The address receiving the funds
+     */
+    event Withdrawal(
+        uint256 indexed nonce,
+        bytes userID,
+        address indexed tokenAddress,
+        uint256 amount,
+        address indexed recipient
+    );
+
     /// @notice Custom errors for gas-efficient error handling
     error InvalidAmount();
     error InvalidTokenAddress();
@@ -188,6 +209,69 @@ contract TurboDAResolver is
         IERC20(tokenAddress).safeTransfer(withdrawalAddress, amount);
     }
 
+    /**
+     * @dev Pays out a withdrawal of credits signed by an operator
+     * @notice The operator signs, as an Ethereum signed message, the hash of
+     * `abi.encode(block.chainid, address(this), userID, tokenAddress, amount, recipient, nonce)`.
+     * Each nonce is paid out once, anyone may submit the signature.
+     * @param userID The identifier of the user the credits were withdrawn from
+     * @param tokenAddress The address of the token to pay out (address(0) for ETH)
+     * @param amount The amount to pay out
+     * @param recipient The address receiving the funds
+     * @param nonce The unique identifier of the withdrawal
+     * @param signature The signature of an operator
+     */
+    function withdraw(
+        bytes calldata userID,
+        address tokenAddress,
+        uint256 amount,
+        address recipient,
+        uint256 nonce,
+        bytes calldata signature
+    ) external whenNotPaused nonReentrant {
+        if (amount == 0) {
+            revert InvalidAmount();
+        }
+        if (usedNonces[nonce]) {
+            revert NonceAlreadyUsed();
+        }
+        if (tokenAddress != address(0) && !validTokenAddresses[tokenAddress]) {
+            revert InvalidTokenAddress();
+        }
+
+        bytes32 digest = keccak256(
+            abi.encode(
+                block.chainid,
+                address(this),
+                userID,
+                tokenAddress,
+                amount,
+                recipient,
+                nonce
+            )
+        );
+        (address signer, ECDSA.RecoverError error, ) = ECDSA.tryRecover(
+            MessageHashUtils.toEthSignedMessageHash(digest),
+            signature
+        );
+        if (
+            error != ECDSA.RecoverError.NoError ||
+            !hasRole(OPERATOR_ROLE, signer)
+        ) {
+            revert InvalidSignature();
+        }
+
+        usedNonces[nonce] = true;
+        emit Withdrawal(nonce, userID, tokenAddress, amount, recipient);
+
+        if (tokenAddress == address(0)) {
+            (bool sent, ) = recipient.call{value: amount}("");
+            if (!sent) revert ETHTransferFailed();
+        } else {
+            IERC20(tokenAddress).safeTransfer(recipient, amount);
+        }
+    }
+
     /**
      * @dev Updates the withdrawal address
      * @param _newWithdrawalAddress The new address for withdrawals
@@ -53,6 +53,34 @@ contract TurboDAResolverTest is Test {
         return vm.sign(privateKey, permitHash);
     }
 
+    function _getWithdrawalSignature(
+        bytes memory userID,
+        address tokenAddress,
+        uint256 amount,
+        address recipient,
+        uint256 withdrawalNonce,
+        uint256 privateKey
+    ) internal view returns (bytes memory) {
+        bytes32 digest = keccak256(
+            abi.encode(
+                block.chainid,
+                address(depositContract),
+                userID,
+                tokenAddress,
+                amount,
+                recipient,
+                withdrawalNonce
+            )
+        );
+        (uint8 v, bytes32 r, bytes32 s) = vm.sign(
+            privateKey,
+            keccak256(
+                abi.encodePacked("\x19Ethereum Signed Message:\n32", digest)
+            )
+        );
+        return abi.encodePacked(r, s, v);
+    }
+
     function setUp() public {
         (user1, user1PrivateKey) = makeAddrAndKey("user1");
         (user2, user2PrivateKey) = makeAddrAndKey("user2");
@@ -300,4 +328,148 @@ contract TurboDAResolverTest is Test {
             "Total deposit amount should match"
         );
     }
+
+    function testSuccessfulERC20Withdrawal() public {
+        uint256 amount = 10 ether;
+        bytes memory userID = bytes("user@example.com");
+        mockToken.mint(address(depositContract), amount);
+
+        bytes memory signature = _getWithdrawalSignature(
+            userID,
+            address(mockToken),
+            amount,
+            user2,
+            1,
+            user1PrivateKey
+        );
+
+        vm.prank(user2);
+        depositContract.withdraw(
+            userID,
+            address(mockToken),
+            amount,
+            user2,
+            1,
+            signature
+        );
+        assertEq(
+            mockToken.balanceOf(user2),
+            amount,
+            "Withdrawn amount should match"
+        );
+        assertTrue(depositContract.usedNonces(1), "Nonce should be used");
+    }
+
+    function testSuccessfulEthWithdrawal() public {
+        uint256 amount = 1 ether;
+        bytes memory userID = bytes("user@example.com");
+        vm.deal(address(depositContract), amount);
+
+        bytes memory signature = _getWithdrawalSignature(
+            userID,
+            address(0),
+            amount,
+            user2,
+            2,
+            user1PrivateKey
+        );
+
+        uint256 balanceBefore = user2.balance;
+        depositContract.withdraw(
+            userID,
+            address(0),
+            amount,
+            user2,
+            2,
+            signature
+        );
+        assertEq(
+            user2.balance - balanceBefore,
+            amount,
+            "Withdrawn amount should match"
+        );
+    }
+
+    function testRevertOnReusedWithdrawalNonce() public {
+        uint256 amount = 10 ether;
+        bytes memory userID = bytes("user@example.com");
+        mockToken.mint(address(depositContract), 2 * amount);
+
+        bytes memory signature = _getWithdrawalSignature(
+            userID,
+            address(mockToken),
+            amount,
+            user2,
+            3,
+            user1PrivateKey
+        );
+        depositContract.withdraw(
+            userID,
+            address(mockToken),
+            amount,
+            user2,
+            3,
+            signature
+        );
+
+        vm.expectRevert(TurboDAResolver.NonceAlreadyUsed.selector);
+        depositContract.withdraw(
+            userID,
+            address(mockToken),
+            amount,
+            user2,
+            3,
+            signature
+        );
+    }
+
+    function testRevertOnWithdrawalNotSignedByOperator() public {
+        uint256 amount = 10 ether;
+        bytes memory userID = bytes("user@example.com");
+        mockToken.mint(address(depositContract), amount);
+
+        bytes memory signature = _getWithdrawalSignature(
+            userID,
+            address(mockToken),
+            amount,
+            user2,
+            4,
+            user2PrivateKey
+        );
+
+        vm.expectRevert(TurboDAResolver.InvalidSignature.selector);
+        depositContract.withdraw(
+            userID,
+            address(mockToken),
+            amount,
+            user2,
+            4,
+            signature
+        );
+    }
+
+    function testRevertOnAlteredWithdrawal() public {
+        uint256 amount = 10 ether;
+        bytes memory userID = bytes("user@example.com");
+        mockToken.mint(address(depositContract), 2 * amount);
+
+        bytes memory signature = _getWithdrawalSignature(
+            userID,
+            address(mockToken),
+            amount,
+            user2,
+            5,
+            user1PrivateKey
+        );
+
+        vm.expectRevert(TurboDAResolver.InvalidSignature.selector);
+        depositContract.withdraw(
+            userID,
+            address(mockToken),
+            2 * amount,
+            user2,
+            5,
+            signature
+        );
+    }
 }
@@ -1,41 +1,65 @@
 use crate::redis::Redis;
 use actix_web::{
-    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    dev::{self, forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
     error as actix_error,
     http::header::HeaderMap,
-    Error,
+    web::{Bytes, BytesMut},
+    Error, HttpMessage,
 };
 use db::{
-    models::api::ApiKey,
-    schema::api_keys::{self, dsl::*},
+    models::{api::ApiKey, signing_keys::SigningKey},
+    schema::{
+        api_keys::{self, dsl::*},
+        signing_keys,
+    },
 };
 use diesel::prelude::*;
 use diesel::QueryDsl;
-use futures_util::future::LocalBoxFuture;
+use futures_util::{future::LocalBoxFuture, StreamExt};
 use sha3::{Digest, Keccak256};
 use std::{
     fmt::Display,
     future::{ready, Ready},
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
 };
 use turbo_da_core::logger::{debug, error, info, warn};
+use uuid::Uuid;
+
+/// Headers carrying a signed request, used as an alternative to `X-API-KEY`.
+const KEY_ID_HEADER: &str = "X-KEY-ID";
+const TIMESTAMP_HEADER: &str = "X-TIMESTAMP";
+const SIGNATURE_HEADER: &str = "X-SIGNATURE";
+/// Signing keys are cached for a bounded time, so a deleted key stops working even when the
+/// cache couldn't be invalidated
+const SIGNING_KEY_CACHE_SECS: u64 = 300;
 
 pub struct Auth {
     redis: Redis,
     database_url: String,
+    signature_max_age: u64,
+    payload_size: usize,
 }
 
 impl Auth {
-    pub fn new(redis: Redis, database_url: String) -> Self {
+    pub fn new(
+        redis: Redis,
+        database_url: String,
+        signature_max_age: u64,
+        payload_size: usize,
+    ) -> Self {
         Auth {
             redis,
             database_url,
+            signature_max_age,
+            payload_size,
         }
     }
 }
 
 impl<S, B> Transform<S, ServiceRequest> for Auth
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -47,22 +71,26 @@ where
 
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(AuthMiddleware {
-            service,
+            service: Rc::new(service),
             redis: self.redis.clone(),
             database_url: self.database_url.clone(),
+            signature_max_age: self.signature_max_age,
+            payload_size: self.payload_size,
         }))
     }
 }
 
 pub struct AuthMiddleware<S> {
-    service: S,
+    service: Rc<S>,
     redis: Redis,
     database_url: String,
+    signature_max_age: u64,
+    payload_size: usize,
 }
 
 impl<S, B> Service<ServiceRequest> for AuthMiddleware<S>
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -75,6 +103,9 @@ where
     fn call(&self, mut req: ServiceRequest) -> Self::Future {
         let auth_header = req.headers().get("X-API-KEY");
         if auth_header.is_none() {
+            if req.headers().contains_key(SIGNATURE_HEADER) {
+                return self.call_signed(req);
+            }
             return Box::pin(async move { Err(actix_error::ErrorUnauthorized("Missing API key")) });
         }
 
@@ -169,11 +200,259 @@ where
     }
 }
 
-fn insert_headers<B, T: Display>(
+impl<S, B> AuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    /// Authenticates a request signed with a registered ed25519 or secp256k1 key.
+    ///
+    /// The signature covers the method, path with its query string, timestamp and SHA-256 hash
+    /// of the body (see [`signing_message`]). Requests outside of `signature_max_age` are rejected and
+    /// each signature is remembered in redis for twice that window so it cannot be replayed.
+    fn call_signed(
+        &self,
+        mut req: ServiceRequest,
+    ) -> LocalBoxFuture<'static, Result<ServiceResponse<B>, Error>> {
+        let service = Rc::clone(&self.service);
+        let redis = self.redis.clone();
+        let database_url = self.database_url.clone();
+        let signature_max_age = self.signature_max_age;
+        let payload_size = self.payload_size;
+
+        Box::pin(async move {
+            let (key_id, timestamp, signature) =
+                parse_signature_headers(req.headers()).map_err(actix_error::ErrorUnauthorized)?;
+
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|_| {
+                    actix_error::ErrorInternalServerError("Internal error. Contact admin")
+                })?
+                .as_secs();
+            check_timestamp(now, timestamp, signature_max_age)
+                .map_err(actix_error::ErrorUnauthorized)?;
+
+            let mut payload = req.take_payload();
+            let mut body = BytesMut::new();
+            while let Some(chunk) = payload.next().await {
+                let chunk = chunk?;
+                if body.len() + chunk.len() > payload_size {
+                    return Err(actix_error::ErrorPayloadTooLarge("Payload too large"));
+                }
+                body.extend_from_slice(&chunk);
+            }
+            let body = body.freeze();
+
+            let key = get_signing_key(&redis, &database_url, &key_id)?;
+
+            let path = req
+                .uri()
+                .path_and_query()
+                .map_or(req.path(), |path| path.as_str());
+            let message = signing_message(req.method().as_str(), path, timestamp, &body);
+            verify_signature(&key.key_type, &key.public_key, &message, &signature)
+                .map_err(actix_error::ErrorUnauthorized)?;
+
+            match redis.set_if_absent(
+                &format!("signature:{}", hex::encode(&signature)),
+                &key_id.to_string(),
+                signature_max_age * 2,
+            ) {
+                Ok(true) => {}
+                Ok(false) => {
+                    return Err(actix_error::ErrorUnauthorized(
+                        "Request signature has already been used",
+                    ));
+                }
+                Err(e) => {
+                    error(&format!(
+                        "Failed to record request signature in redis: {}",
+                        e
+                    ));
+                    return Err(actix_error::ErrorInternalServerError(
+                        "Internal error. Contact admin",
+                    ));
+                }
+            }
+
+            let headers = req.headers_mut();
+            try_insert_header(headers, "user_id", &key.user_id)
+                .map_err(actix_error::ErrorInternalServerError)?;
+            try_insert_header(headers, "app_id", &key.app_id)
+                .map_err(actix_error::ErrorInternalServerError)?;
+
+            req.set_payload(bytes_to_payload(body));
+
+            let res = service.call(req).await?;
+
+            debug(&format!("Signing key {} is valid", key_id));
+            Ok(res)
+        })
+    }
+}
+
+/// Extracts the key id, unix timestamp (seconds) and signature bytes from a signed request.
+fn parse_signature_headers(headers: &HeaderMap) -> Result<(Uuid, u64, Vec<u8>), String> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| format!("Missing or invalid {} header", name))
+    };
+
+    let key_id = Uuid::parse_str(header(KEY_ID_HEADER)?)
+        .map_err(|_| format!("Invalid {} header", KEY_ID_HEADER))?;
+    let timestamp = header(TIMESTAMP_HEADER)?
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid {} header", TIMESTAMP_HEADER))?;
+    let signature = hex::decode(header(SIGNATURE_HEADER)?.trim_start_matches("0x"))
+        .map_err(|_| format!("Invalid {} header", SIGNATURE_HEADER))?;
+
+    Ok((key_id, timestamp, signature))
+}
+
+/// Rejects a request signed more than `max_age` seconds away from `now`.
+fn check_timestamp(now: u64, timestamp: u64, max_age: u64) -> Result<(), String> {
+    if now.abs_diff(timestamp) > max_age {
+        return Err("Request timestamp is outside of the allowed window".to_string());
+    }
+    Ok(())
+}
+
+/// Builds the message a client signs:
+/// `{METHOD}\n{PATH}\n{TIMESTAMP}\n{hex(sha256(body))}`
+///
+/// `{PATH}` includes the query string when there is one, e.g. `/v1/user/submit_data?mode=raw`.
+pub fn signing_message(method: &str, path: &str, timestamp: u64, body: &[u8]) -> Vec<u8> {
+    let body_hash = hex::encode(sha2::Sha256::digest(body));
+    format!("{}\n{}\n{}\n{}", method, path, timestamp, body_hash).into_bytes()
+}
+
+/// Verifies `signature` over `message` with a hex encoded public key.
+///
+/// * `ed25519` - 64 byte signature over the raw message
+/// * `secp256k1` - 64 byte `r || s` ECDSA signature over the SHA-256 of the message
+pub fn verify_signature(
+    key_type: &str,
+    public_key: &str,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), String> {
+    let public_key = hex::decode(public_key)
+        .map_err(|_| "Registered public key is not valid hex".to_string())?;
+
+    match key_type {
+        "ed25519" => {
+            let public_key: [u8; 32] = public_key
+                .as_slice()
+                .try_into()
+                .map_err(|_| "Invalid ed25519 public key".to_string())?;
+            let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&public_key)
+                .map_err(|_| "Invalid ed25519 public key".to_string())?;
+            let signature = ed25519_dalek::Signature::from_slice(signature)
+                .map_err(|_| "Invalid ed25519 signature".to_string())?;
+            verifying_key
+                .verify_strict(message, &signature)
+                .map_err(|_| "Invalid request signature".to_string())
+        }
+        "secp256k1" => {
+            use k256::ecdsa::signature::Verifier;
+
+            let verifying_key = k256::ecdsa::VerifyingKey::from_sec1_bytes(&public_key)
+                .map_err(|_| "Invalid secp256k1 public key".to_string())?;
+            let signature = k256::ecdsa::Signature::from_slice(signature)
+                .map_err(|_| "Invalid secp256k1 signature".to_string())?;
+            verifying_key
+                .verify(message, &signature)
+                .map_err(|_| "Invalid request signature".to_string())
+        }
+        _ => Err(format!("Unsupported key type {}", key_type)),
+    }
+}
+
+/// Fields of a registered signing key needed to authenticate a request.
+struct RegisteredKey {
+    user_id: String,
+    app_id: Uuid,
+    key_type: String,
+    public_key: String,
+}
+
+/// Looks up a signing key in redis, falling back to the database and caching the result.
+fn get_signing_key(
+    redis: &Redis,
+    database_url: &str,
+    key_id: &Uuid,
+) -> Result<RegisteredKey, Error> {
+    let cache_key = format!("signing_key:{}", key_id);
+
+    // Cached as `user_id:app_id:key_type:public_key`; split from the right as user ids are emails
+    if let Ok(value) = redis.get(cache_key.as_str()) {
+        let mut parts = value.rsplitn(4, ':');
+        if let (Some(public_key), Some(key_type), Some(app), Some(user)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        {
+            if let Ok(app) = Uuid::parse_str(app) {
+                return Ok(RegisteredKey {
+                    user_id: user.to_string(),
+                    app_id: app,
+                    key_type: key_type.to_string(),
+                    public_key: public_key.to_string(),
+                });
+            }
+        }
+    }
+
+    let mut conn = PgConnection::establish(database_url).map_err(|e| {
+        error(&format!("Failed to connect to database: {}", e));
+        actix_error::ErrorInternalServerError("Internal error. Contact admin")
+    })?;
+
+    let key = signing_keys::table
+        .filter(signing_keys::id.eq(key_id))
+        .select(SigningKey::as_select())
+        .first::<SigningKey>(&mut conn)
+        .map_err(|_| {
+            actix_error::ErrorUnauthorized("Invalid key id: Signing key does not exist")
+        })?;
+
+    match redis.set_with_expiry(
+        cache_key.as_str(),
+        format!(
+            "{}:{}:{}:{}",
+            key.user_id, key.app_id, key.key_type, key.public_key
+        )
+        .as_str(),
+        SIGNING_KEY_CACHE_SECS,
+    ) {
+        Ok(_) => info(&format!(
+            "Signing key set in redis for user {}:{}",
+            key.user_id, key.app_id
+        )),
+        Err(e) => error(&format!("Failed to set signing key in redis: {}", e)),
+    }
+
+    Ok(RegisteredKey {
+        user_id: key.user_id,
+        app_id: key.app_id,
+        key_type: key.key_type,
+        public_key: key.public_key,
+    })
+}
+
+fn bytes_to_payload(body: Bytes) -> dev::Payload {
+    let (_, mut payload) = actix_http::h1::Payload::create(true);
+    payload.unread_data(body);
+    dev::Payload::from(payload)
+}
+
+fn try_insert_header<T: Display>(
     headers: &mut HeaderMap,
     key: &str,
     value: &T,
-) -> Result<(), LocalBoxFuture<'static, Result<ServiceResponse<B>, Error>>> {
+) -> Result<(), String> {
     if let (Ok(parsed_key), Ok(parsed_value)) = (
         key.parse::<actix_web::http::header::HeaderName>(),
         value
@@ -185,8 +464,100 @@ fn insert_headers<B, T: Display>(
     } else {
         let error_message = format!("Failed to parse {} or its value", key);
         warn(&error_message);
-        Err(Box::pin(async move {
-            Err(actix_error::ErrorInternalServerError(error_message))
-        }))
+        Err(error_message)
+    }
+}
+
+fn insert_headers<B, T: Display>(
+    headers: &mut HeaderMap,
+    key: &str,
+    value: &T,
+) -> Result<(), LocalBoxFuture<'static, Result<ServiceResponse<B>, Error>>> {
+    try_insert_header(headers, key, value).map_err(|error_message| {
+        Box::pin(async move { Err(actix_error::ErrorInternalServerError(error_message)) })
+            as LocalBoxFuture<'static, Result<ServiceResponse<B>, Error>>
+    })
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{check_timestamp, signing_message, verify_signature};
+
+    const PATH: &str = "/v1/user/submit_data?mode=raw";
+    const TIMESTAMP: u64 = 1_700_000_000;
+
+    fn ed25519_key(seed: u8) -> ed25519_dalek::SigningKey {
+        ed25519_dalek::SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn ed25519_sign(key: &ed25519_dalek::SigningKey, message: &[u8]) -> (String, Vec<u8>) {
+        use ed25519_dalek::Signer;
+
+        let public_key = hex::encode(key.verifying_key().to_bytes());
+        (public_key, key.sign(message).to_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_valid_signatures_are_accepted() {
+        let message = signing_message("POST", PATH, TIMESTAMP, b"{\"data\":\"hi\"}");
+
+        let (public_key, signature) = ed25519_sign(&ed25519_key(1), &message);
+        assert_eq!(
+            verify_signature("ed25519", &public_key, &message, &signature),
+            Ok(())
+        );
+
+        use k256::ecdsa::signature::Signer;
+        let key = k256::ecdsa::SigningKey::from_slice(&[2; 32]).unwrap();
+        let signature: k256::ecdsa::Signature = key.sign(&message);
+        let public_key = hex::encode(key.verifying_key().to_sec1_bytes());
+        assert_eq!(
+            verify_signature("secp256k1", &public_key, &message, &signature.to_bytes()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_tampered_requests_are_rejected() {
+        let message = signing_message("POST", PATH, TIMESTAMP, b"{\"data\":\"hi\"}");
+        let (public_key, signature) = ed25519_sign(&ed25519_key(1), &message);
+
+        let tampered = [
+            signing_message("POST", PATH, TIMESTAMP, b"{\"data\":\"bye\"}"),
+            signing_message(
+                "POST",
+                "/v1/user/submit_data?mode=x",
+                TIMESTAMP,
+                b"{\"data\":\"hi\"}",
+            ),
+            signing_message("POST", PATH, TIMESTAMP + 1, b"{\"data\":\"hi\"}"),
+        ];
+        for message in tampered {
+            assert_eq!(
+                verify_signature("ed25519", &public_key, &message, &signature),
+                Err("Invalid request signature".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_signatures_of_another_key_are_rejected() {
+        let message = signing_message("POST", PATH, TIMESTAMP, b"{}");
+        let (_, signature) = ed25519_sign(&ed25519_key(1), &message);
+        let (other_key, _) = ed25519_sign(&ed25519_key(3), &message);
+
+        assert_eq!(
+            verify_signature("ed25519", &other_key, &message, &signature),
+            Err("Invalid request signature".to_string())
+        );
+        assert!(verify_signature("secp256k1", &other_key, &message, &signature).is_err());
+    }
+
+    #[test]
+    fn test_expired_timestamps_are_rejected() {
+        assert!(check_timestamp(TIMESTAMP + 60, TIMESTAMP, 60).is_ok());
+        assert!(check_timestamp(TIMESTAMP - 60, TIMESTAMP, 60).is_ok());
+        assert!(check_timestamp(TIMESTAMP + 61, TIMESTAMP, 60).is_err());
+        assert!(check_timestamp(TIMESTAMP - 61, TIMESTAMP, 60).is_err());
     }
 }
@@ -6,6 +6,8 @@ use serde::{Deserialize, Serialize};
 use std::{env, error::Error, fs, io, vec::Vec};
 use toml;
 use turbo_da_core::logger::{error, info, warn};
+use turbo_da_core::price_oracle::PriceOracleConfig;
+use turbo_da_core::treasury::TreasuryConfig;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppConfig {
@@ -22,6 +24,23 @@ pub struct AppConfig {
     pub rate_limit_window_size: u64,
     pub rate_limit_max_requests: u64,
     pub enigma_url: String,
+    /// Maximum age in seconds of a signed request's timestamp
+    #[serde(default = "default_signature_max_age")]
+    pub signature_max_age: u64,
+    /// AVAIL/USD price source, required to bill balances held in USD
+    #[serde(default)]
+    pub coingecko_api_url: String,
+    #[serde(default)]
+    pub coingecko_api_key: String,
+    #[serde(default)]
+    pub price_oracle: PriceOracleConfig,
+    /// Balance monitoring and top ups of the signers
+    #[serde(default)]
+    pub treasury: TreasuryConfig,
+}
+
+fn default_signature_max_age() -> u64 {
+    300
 }
 
 impl Default for AppConfig {
@@ -40,6 +59,11 @@ impl Default for AppConfig {
             rate_limit_window_size: 60,
             rate_limit_max_requests: 100,
             enigma_url: String::new(),
+            signature_max_age: default_signature_max_age(),
+            coingecko_api_url: String::new(),
+            coingecko_api_key: String::new(),
+            price_oracle: PriceOracleConfig::default(),
+            treasury: TreasuryConfig::default(),
         }
     }
 }
@@ -228,6 +252,16 @@ impl AppConfig {
 
         let enigma_url = env::var("ENIGMA_URL")?;
 
+        let signature_max_age = env::var("SIGNATURE_MAX_AGE")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or_else(default_signature_max_age);
+
+        let coingecko_api_url = env::var("COINGECKO_API_URL").unwrap_or_default();
+        let coingecko_api_key = env::var("COINGECKO_API_KEY").unwrap_or_default();
+        let price_oracle = PriceOracleConfig::from_env();
+        let treasury = TreasuryConfig::from_env();
+
         Ok(AppConfig {
             port,
             database_url,
@@ -242,6 +276,11 @@ impl AppConfig {
             rate_limit_window_size,
             rate_limit_max_requests,
             enigma_url,
+            signature_max_age,
+            coingecko_api_url,
+            coingecko_api_key,
+            price_oracle,
+            treasury,
         })
     }
 }
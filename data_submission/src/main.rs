@@ -28,7 +28,12 @@ use enigma::EnigmaEncryptionService;
 use observability::{init_meter, init_tracer};
 use std::sync::Arc;
 use tokio::sync::broadcast;
-use turbo_da_core::{logger::info, utils::generate_keygen_list};
+use turbo_da_core::{
+    logger::info,
+    price_oracle::PriceFeed,
+    treasury::{Signers, Treasury},
+    utils::{generate_avail_sdk, generate_keygen_list},
+};
 use workload_scheduler::consumer::Consumer;
 
 #[actix_web::main]
@@ -41,7 +46,7 @@ async fn main() -> Result<(), std::io::Error> {
     info(&format!("Starting Data Submission server...."));
     let accounts =
         generate_keygen_list(app_config.number_of_threads, &app_config.private_keys).await;
-    let shared_keypair = web::Data::new(accounts);
+    let signers = Arc::new(Signers::new(accounts));
 
     let db_config =
         AsyncDieselConnectionManager::<AsyncPgConnection>::new(&app_config.database_url);
@@ -59,19 +64,34 @@ async fn main() -> Result<(), std::io::Error> {
 
     let enigma = web::Data::new(EnigmaEncryptionService::new(app_config.enigma_url.clone()));
 
+    let prices = PriceFeed::from_config(
+        &app_config.coingecko_api_url,
+        &app_config.coingecko_api_key,
+        &app_config.price_oracle,
+    )
+    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+
+    let treasury = Treasury::new(
+        generate_avail_sdk(&Arc::new(app_config.avail_rpc_endpoint.clone())).await,
+        signers.clone(),
+        app_config.treasury.clone(),
+    );
+    tokio::spawn(treasury.run());
+
     let consumer_server = Consumer::new(
         Arc::new(sender.clone()),
-        Arc::new(shared_keypair.clone()),
+        signers.clone(),
         Arc::new(shared_pool.clone()),
         Arc::new(app_config.avail_rpc_endpoint.clone()),
         Arc::new(enigma.clone()),
         Arc::new(shared_redis.clone()),
-        app_config.number_of_threads,
+        prices,
     );
 
     let port = app_config.port;
 
     let shared_config = web::Data::new(app_config);
+    let shared_signers = web::Data::from(signers);
 
     tokio::spawn(async move {
         consumer_server.start_workers().await;
@@ -89,12 +109,14 @@ async fn main() -> Result<(), std::io::Error> {
                     .wrap(Auth::new(
                         shared_redis.clone(),
                         shared_config.database_url.clone(),
+                        shared_config.signature_max_age,
+                        shared_config.payload_size,
                     ))
                     .app_data(web::PayloadConfig::new(shared_config.payload_size))
                     .app_data(shared_producer_send.clone())
                     .app_data(shared_config.clone())
                     .app_data(shared_pool.clone())
-                    .app_data(shared_keypair.clone())
+                    .app_data(shared_signers.clone())
                     .app_data(enigma.clone())
                     .service(submit_data)
                     .service(submit_raw_data)
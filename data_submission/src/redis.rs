@@ -26,6 +26,16 @@ impl Redis {
         Ok(conn.set(key, value).map_err(|e| e.to_string())?)
     }
 
+    /// Sets `key`, expiring it after `ttl_seconds`.
+    pub fn set_with_expiry(&self, key: &str, value: &str, ttl_seconds: u64) -> Result<(), String> {
+        let mut conn = match self.redis_pool.get() {
+            Ok(conn) => conn,
+            Err(e) => return Err(e.to_string()),
+        };
+        conn.set_ex(key, value, ttl_seconds)
+            .map_err(|e| e.to_string())
+    }
+
     pub fn get(&self, key: &str) -> Result<String, String> {
         let mut conn = match self.redis_pool.get() {
             Ok(conn) => conn,
@@ -36,4 +46,22 @@ impl Redis {
             Err(e) => Err(e.to_string()),
         }
     }
+
+    /// Sets `key` only if it does not already exist, expiring it after `ttl_seconds`.
+    /// Returns `true` if the key was set.
+    pub fn set_if_absent(&self, key: &str, value: &str, ttl_seconds: u64) -> Result<bool, String> {
+        let mut conn = match self.redis_pool.get() {
+            Ok(conn) => conn,
+            Err(e) => return Err(e.to_string()),
+        };
+        let result: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl_seconds)
+            .query(&mut *conn)
+            .map_err(|e| e.to_string())?;
+        Ok(result.is_some())
+    }
 }
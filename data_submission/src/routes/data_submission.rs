@@ -1,4 +1,3 @@
-use crate::config::AppConfig;
 use crate::utils::{map_user_id_to_thread, retrieve_app_id};
 use crate::workload_scheduler::common::Response;
 use actix_web::{
@@ -19,6 +18,7 @@ use serde_json::json;
 use tokio::sync::broadcast::Sender;
 use turbo_da_core::{
     logger::error,
+    treasury::Signers,
     utils::{format_size, generate_submission_id, get_connection, retrieve_user_id},
 };
 
@@ -34,7 +34,7 @@ pub struct SubmitData {
 /// * `request_payload` - JSON payload containing the data string
 /// * `sender` - Channel sender for broadcasting responses
 /// * `injected_dependency` - Database connection pool
-/// * `config` - Application configuration
+/// * `signers` - Signer keys, the submission is routed to a funded one
 /// * `http_request` - HTTP request containing user authentication
 ///
 /// # Returns
@@ -45,7 +45,7 @@ pub async fn submit_data(
     request_payload: web::Json<SubmitData>,
     sender: web::Data<Sender<Response>>,
     injected_dependency: web::Data<Pool<AsyncPgConnection>>,
-    config: web::Data<AppConfig>,
+    signers: web::Data<Signers>,
     http_request: HttpRequest,
 ) -> impl Responder {
     if request_payload.data.len() == 0 {
@@ -56,7 +56,7 @@ pub async fn submit_data(
         request_payload.data.as_bytes().to_vec(),
         sender,
         injected_dependency,
-        config,
+        signers,
         http_request,
     )
     .await
@@ -68,7 +68,7 @@ pub async fn submit_data(
 /// * `request_payload` - Raw bytes payload
 /// * `sender` - Channel sender for broadcasting responses
 /// * `injected_dependency` - Database connection pool
-/// * `config` - Application configuration
+/// * `signers` - Signer keys, the submission is routed to a funded one
 /// * `http_request` - HTTP request containing user authentication
 ///
 /// # Returns
@@ -79,7 +79,7 @@ pub async fn submit_raw_data(
     request_payload: Bytes,
     sender: web::Data<Sender<Response>>,
     injected_dependency: web::Data<Pool<AsyncPgConnection>>,
-    config: web::Data<AppConfig>,
+    signers: web::Data<Signers>,
     http_request: HttpRequest,
 ) -> impl Responder {
     if request_payload.len() == 0 {
@@ -90,7 +90,7 @@ pub async fn submit_raw_data(
         request_payload.to_vec(),
         sender,
         injected_dependency,
-        config,
+        signers,
         http_request,
     )
     .await
@@ -100,7 +100,7 @@ async fn _submit_data(
     request_payload: Vec<u8>,
     sender: web::Data<Sender<Response>>,
     injected_dependency: web::Data<Pool<AsyncPgConnection>>,
-    config: web::Data<AppConfig>,
+    signers: web::Data<Signers>,
     http_request: HttpRequest,
 ) -> HttpResponse {
     let app_id = match retrieve_app_id(&http_request) {
@@ -144,7 +144,7 @@ async fn _submit_data(
     };
 
     let consumer_response = Response {
-        thread_id: map_user_id_to_thread(&config),
+        thread_id: map_user_id_to_thread(&signers),
         raw_payload: request_payload.into(),
         submission_id,
         app_id,
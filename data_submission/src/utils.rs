@@ -1,13 +1,18 @@
-use crate::config::AppConfig;
 use actix_web::HttpRequest;
 use rand::Rng;
+use turbo_da_core::treasury::Signers;
 use uuid::Uuid;
-/// Maps a user ID to a thread number based on the app config
+/// Maps a submission to the thread of a funded signer
+///
+/// Falls back to any thread when every signer is underfunded, the submission is then resubmitted
+/// by the fallback monitor once it fails.
 ///
 /// # Arguments
-/// * `config` - Application configuration containing number of threads
-pub fn map_user_id_to_thread(config: &AppConfig) -> i32 {
-    rand::thread_rng().gen_range(0..config.number_of_threads)
+/// * `signers` - Signer keys, one per thread
+pub fn map_user_id_to_thread(signers: &Signers) -> i32 {
+    signers
+        .pick_funded()
+        .unwrap_or_else(|| rand::thread_rng().gen_range(0..signers.len())) as i32
 }
 
 /// Retrieves user ID from HTTP request headers
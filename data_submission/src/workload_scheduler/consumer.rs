@@ -5,16 +5,22 @@ use super::common::Response;
 use crate::redis::Redis;
 use actix_web::web;
 use avail_rust::Keypair;
-use avail_utils::submit_data::SubmitDataAvail;
+use avail_utils::{
+    inclusion::data_hash,
+    submit_data::{SubmitDataAvail, TransactionInfo},
+};
 use bigdecimal::BigDecimal;
 use db::{
     controllers::{
-        customer_expenditure::{add_error_entry, get_did_fallback_resolved},
+        customer_expenditure::{
+            add_error_entry, get_did_fallback_resolved, lease_submission, release_submission,
+            set_expected_data_hash, worker_id,
+        },
         misc::{get_account_by_id, update_database_on_submission},
         users::TxParams,
     },
     errors::*,
-    models::apps::Apps,
+    models::{apps::Apps, user_model::BalanceUnit},
 };
 use diesel_async::{pooled_connection::deadpool::Pool, AsyncPgConnection};
 use enigma::{
@@ -29,35 +35,42 @@ use tokio::{
     time::{timeout, Duration},
 };
 use turbo_da_core::logger::{debug, error, info};
+use turbo_da_core::price_oracle::PriceFeed;
+use turbo_da_core::treasury::Signers;
 use turbo_da_core::utils::{format_size, generate_avail_sdk, get_connection, Convertor};
 
 pub struct Consumer {
     sender: Arc<Sender<Response>>,
-    keypair: Arc<web::Data<Vec<Keypair>>>,
+    signers: Arc<Signers>,
     injected_dependency: Arc<web::Data<Pool<AsyncPgConnection>>>,
     endpoints: Arc<Vec<String>>,
     enigma: Arc<web::Data<EnigmaEncryptionService>>,
     redis: Arc<Redis>,
+    prices: Arc<PriceFeed>,
     number_of_threads: i32,
 }
 
 impl Consumer {
     pub fn new(
         sender: Arc<Sender<Response>>,
-        keypair: Arc<web::Data<Vec<Keypair>>>,
+        signers: Arc<Signers>,
         injected_dependency: Arc<web::Data<Pool<AsyncPgConnection>>>,
         endpoints: Arc<Vec<String>>,
         enigma: Arc<web::Data<EnigmaEncryptionService>>,
         redis: Arc<Redis>,
-        number_of_threads: i32,
+        prices: Arc<PriceFeed>,
     ) -> Self {
+        // One worker thread is spawned per signer, `generate_keygen_list` derives exactly
+        // `number_of_threads` signers so the configured thread count is unchanged
+        let number_of_threads = signers.len() as i32;
         Consumer {
             sender,
-            keypair,
+            signers,
             injected_dependency,
             endpoints,
             enigma,
             redis,
+            prices,
             number_of_threads,
         }
     }
@@ -99,11 +112,12 @@ impl Consumer {
 
     pub async fn spawn_thread(&self, i: i32, heartbeat_tx: tokio::sync::mpsc::Sender<i32>) {
         let injected_dependency = self.injected_dependency.clone();
-        let keygen = self.keypair.clone();
+        let signers = self.signers.clone();
         let sender = self.sender.clone();
         let endpoints = self.endpoints.clone();
         let enigma = self.enigma.clone();
         let redis = self.redis.clone();
+        let prices = self.prices.clone();
 
         tokio::spawn(async move {
             info(&format!("Spawning thread number {}", i));
@@ -124,14 +138,30 @@ impl Consumer {
                     continue;
                 }
 
+                // Hand the submission over to a funded signer, it is submitted anyway when none
+                // is left
+                if !signers.is_funded(i as usize) {
+                    if let Some(thread_id) = signers.pick_funded() {
+                        info(&format!(
+                            "Signer of thread {} is underfunded, routing submission {} to thread {}",
+                            i, response.submission_id, thread_id
+                        ));
+                        let _ = sender.send(Response {
+                            thread_id: thread_id as i32,
+                            ..response
+                        });
+                        continue;
+                    }
+                }
+
                 let result = Self::response_handler(
                     &response,
                     &injected_dependency,
                     &endpoints,
-                    &keygen,
+                    signers.keypair(i as usize),
                     &enigma,
                     Arc::clone(&redis),
-                    i,
+                    &prices,
                 )
                 .await;
 
@@ -149,10 +179,10 @@ impl Consumer {
         response: &Response,
         injected_dependency: &web::Data<Pool<AsyncPgConnection>>,
         endpoints: &Arc<Vec<String>>,
-        keygen: &Vec<Keypair>,
+        keypair: &Keypair,
         enigma: &EnigmaEncryptionService,
         redis: Arc<Redis>,
-        i: i32,
+        prices: &PriceFeed,
     ) -> Result<(), String> {
         let mut connection = get_connection(&injected_dependency)
             .await
@@ -165,17 +195,53 @@ impl Consumer {
             return Err("Fallback resolved transaction".to_string());
         }
 
+        // The fallback monitor, or another instance, may be submitting the same payload
+        if !lease_submission(&mut connection, &response.submission_id, worker_id()).await? {
+            return Err("Submission is leased by another worker".to_string());
+        }
+
+        let result = Self::submit(
+            response,
+            &mut connection,
+            endpoints,
+            keypair,
+            enigma,
+            redis,
+            prices,
+        )
+        .await;
+
+        if let Err(e) =
+            release_submission(&mut connection, &response.submission_id, worker_id()).await
+        {
+            error(&format!(
+                "Failed to release lease on submission {}: {}",
+                response.submission_id, e
+            ));
+        }
+        result
+    }
+
+    async fn submit(
+        response: &Response,
+        connection: &mut AsyncPgConnection,
+        endpoints: &Arc<Vec<String>>,
+        keypair: &Keypair,
+        enigma: &EnigmaEncryptionService,
+        redis: Arc<Redis>,
+        prices: &PriceFeed,
+    ) -> Result<(), String> {
         let sdk = generate_avail_sdk(&endpoints).await;
 
-        let submit_data_class =
-            SubmitDataAvail::new(&sdk, &keygen[i as usize], response.avail_app_id);
+        let submit_data_class = SubmitDataAvail::new(&sdk, keypair, response.avail_app_id);
 
         let mut process_response = ProcessSubmitResponse::new(
-            &response,
-            &mut connection,
+            response,
+            connection,
             submit_data_class,
             enigma,
             redis,
+            prices,
         );
 
         match timeout(
@@ -187,7 +253,7 @@ impl Consumer {
             Ok(result) => {
                 if result.is_err() {
                     let err = result.err().unwrap().to_string();
-                    update_error_entry(response, &mut connection, err.clone()).await;
+                    update_error_entry(response, connection, err.clone()).await;
                     return Err(err);
                 } else {
                     info(&format!(
@@ -198,7 +264,7 @@ impl Consumer {
                 }
             }
             Err(_) => {
-                update_error_entry(response, &mut connection, TIMEOUT_ERROR.to_string()).await;
+                update_error_entry(response, connection, TIMEOUT_ERROR.to_string()).await;
                 Err(TIMEOUT_ERROR.to_string())
             }
         }
@@ -211,6 +277,7 @@ pub struct ProcessSubmitResponse<'a> {
     submit_avail_class: SubmitDataAvail<'a>,
     enigma: &'a EnigmaEncryptionService,
     redis: Arc<Redis>,
+    prices: &'a PriceFeed,
 }
 
 impl<'a> ProcessSubmitResponse<'a> {
@@ -220,6 +287,7 @@ impl<'a> ProcessSubmitResponse<'a> {
         submit_avail_class: SubmitDataAvail<'a>,
         enigma: &'a EnigmaEncryptionService,
         redis: Arc<Redis>,
+        prices: &'a PriceFeed,
     ) -> Self {
         Self {
             response,
@@ -227,6 +295,7 @@ impl<'a> ProcessSubmitResponse<'a> {
             submit_avail_class,
             enigma,
             redis,
+            prices,
         }
     }
 
@@ -236,12 +305,9 @@ impl<'a> ProcessSubmitResponse<'a> {
 
         let (data, encrypted_data) = self.process_data(account.encryption).await?;
 
-        let convertor = Convertor::new(
-            &self.submit_avail_class.client,
-            &self.submit_avail_class.account,
-        );
-
-        let credits_used = convertor.calculate_credit_utlisation(data.to_vec()).await;
+        let billed_unit = user.balance_unit()?;
+        let (credits_used, avail_usd_price, avail_per_credit) =
+            self.bill(&billed_unit, &data).await?;
 
         self.validate_balance(
             account.credit_selection,
@@ -254,12 +320,23 @@ impl<'a> ProcessSubmitResponse<'a> {
         self.validate_race_condition(&account, &credits_used, &user.credit_balance)
             .await?;
 
+        // Lets the fallback monitor find the extrinsic on chain if its outcome is lost
+        set_expected_data_hash(
+            self.connection,
+            &self.response.submission_id,
+            &data_hash(&data),
+        )
+        .await?;
+
         let result = self.submit_avail_class.submit_data(&data).await?;
 
         let params = TxParams {
             amount_data: format_size(data.len()),
             amount_data_billed: credits_used,
             fees: result.gas_fee,
+            billed_unit,
+            avail_usd_price,
+            avail_per_credit,
         };
 
         update_database_on_submission(
@@ -275,6 +352,75 @@ impl<'a> ProcessSubmitResponse<'a> {
         Ok(())
     }
 
+    /// Records a submission found on chain, billing `data` as if it had just been submitted
+    pub async fn record_inclusion(
+        &mut self,
+        data: &[u8],
+        result: TransactionInfo,
+    ) -> Result<(), String> {
+        let (account, user) = get_account_by_id(self.connection, &self.response.app_id).await?;
+
+        let billed_unit = user.balance_unit()?;
+        let (credits_used, avail_usd_price, avail_per_credit) =
+            self.bill(&billed_unit, data).await?;
+
+        let params = TxParams {
+            amount_data: format_size(data.len()),
+            amount_data_billed: credits_used,
+            fees: result.gas_fee,
+            billed_unit,
+            avail_usd_price,
+            avail_per_credit,
+        };
+
+        update_database_on_submission(
+            self.response.submission_id,
+            self.connection,
+            result,
+            &account,
+            params,
+            None,
+        )
+        .await
+    }
+
+    /// Amount `data` is billed in `billed_unit`, along with the AVAIL/USD price of a USD bill or the
+    /// AVAIL value of a credit of a bill in credits
+    ///
+    /// Balances held in USD are billed the fee of the data at the current AVAIL/USD price
+    async fn bill(
+        &self,
+        billed_unit: &BalanceUnit,
+        data: &[u8],
+    ) -> Result<(BigDecimal, Option<BigDecimal>, Option<BigDecimal>), String> {
+        let convertor = Convertor::new(
+            self.submit_avail_class.client,
+            self.submit_avail_class.account,
+        );
+
+        match billed_unit {
+            BalanceUnit::Credits => Ok((
+                convertor.calculate_credit_utlisation(data.to_vec()).await,
+                None,
+                convertor.credit_avail_value().await,
+            )),
+            BalanceUnit::Usd => {
+                let avail_price = self
+                    .prices
+                    .usd_price("avail")
+                    .await
+                    .map_err(|e| format!("Failed to fetch prices for avail: {}", e))?;
+                Ok((
+                    convertor
+                        .calculate_usd_utilisation(data.to_vec(), &avail_price.usd)
+                        .await,
+                    Some(avail_price.usd),
+                    None,
+                ))
+            }
+        }
+    }
+
     async fn process_data(
         &self,
         encryption: bool,
@@ -289,7 +435,13 @@ impl<'a> ProcessSubmitResponse<'a> {
                     turbo_da_app_id: self.response.app_id,
                 })
                 .await
-                .map_err(|e| e.to_string())?;
+                .map_err(|e| match e.status() {
+                    // Not retried, the same payload would be rejected again
+                    Some(status) if is_rejected_status(status.as_u16()) => {
+                        format!("{}: {}", ENCRYPTION_REJECTED, e)
+                    }
+                    _ => e.to_string(),
+                })?;
             Some(encrypt_response)
         } else {
             None
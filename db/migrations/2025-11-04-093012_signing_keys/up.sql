@@ -0,0 +1,11 @@
+-- Your SQL goes here
+CREATE TABLE signing_keys (
+    id UUID PRIMARY KEY,
+    app_id UUID NOT NULL,
+    user_id VARCHAR NOT NULL,
+    key_type VARCHAR(20) NOT NULL CHECK (key_type IN ('ed25519', 'secp256k1')),
+    public_key VARCHAR NOT NULL UNIQUE,
+    created_at TIMESTAMP WITHOUT TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE ON UPDATE CASCADE
+);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE apps
+DROP COLUMN IF EXISTS org_id;
+
+DROP TABLE IF EXISTS organisation_members;
+DROP TABLE IF EXISTS organisations;
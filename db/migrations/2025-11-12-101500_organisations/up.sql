@@ -0,0 +1,41 @@
+-- Your SQL goes here
+CREATE TABLE organisations (
+    id UUID PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    owner_id VARCHAR NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    CONSTRAINT fk_organisations_owner_id FOREIGN KEY (owner_id) REFERENCES users(id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE TABLE organisation_members (
+    org_id UUID NOT NULL,
+    user_id VARCHAR NOT NULL,
+    role VARCHAR(20) NOT NULL CHECK (role IN ('owner', 'admin', 'developer', 'billing-viewer')),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (org_id, user_id),
+    CONSTRAINT fk_organisation_members_org_id FOREIGN KEY (org_id) REFERENCES organisations(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    CONSTRAINT fk_organisation_members_user_id FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX idx_organisation_members_user_id ON organisation_members(user_id);
+
+-- Every existing user gets a personal organisation which owns their apps
+INSERT INTO organisations (id, name, owner_id, created_at)
+SELECT gen_random_uuid(), name, id, CURRENT_TIMESTAMP
+FROM users;
+
+INSERT INTO organisation_members (org_id, user_id, role)
+SELECT id, owner_id, 'owner'
+FROM organisations;
+
+ALTER TABLE apps
+ADD COLUMN org_id UUID,
+ADD CONSTRAINT fk_apps_org_id FOREIGN KEY (org_id) REFERENCES organisations(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+UPDATE apps
+SET org_id = organisations.id
+FROM organisations
+WHERE apps.user_id = organisations.owner_id;
+
+ALTER TABLE apps
+ALTER COLUMN org_id SET NOT NULL;
@@ -0,0 +1,27 @@
+-- Your SQL goes here
+CREATE TABLE audit_events (
+    id UUID PRIMARY KEY,
+    actor VARCHAR NOT NULL,
+    action VARCHAR(64) NOT NULL,
+    target_type VARCHAR(32) NOT NULL,
+    target_id VARCHAR NOT NULL,
+    org_id UUID,
+    before JSONB,
+    after JSONB,
+    ip_address VARCHAR,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_audit_events_org_id ON audit_events(org_id, created_at);
+CREATE INDEX idx_audit_events_actor ON audit_events(actor, created_at);
+
+-- Audit events are append-only
+CREATE FUNCTION reject_audit_event_mutation() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_events_append_only
+BEFORE UPDATE OR DELETE ON audit_events
+FOR EACH ROW EXECUTE FUNCTION reject_audit_event_mutation();
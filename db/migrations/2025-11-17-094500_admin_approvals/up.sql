@@ -0,0 +1,15 @@
+-- Your SQL goes here
+CREATE TABLE admin_approvals (
+    id UUID PRIMARY KEY,
+    action VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    requested_by VARCHAR NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    resolved_by VARCHAR,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    resolved_at TIMESTAMP,
+    -- Two-person rule, the requesting admin cannot resolve their own request
+    CHECK (resolved_by IS NULL OR resolved_by <> requested_by)
+);
+
+CREATE INDEX idx_admin_approvals_status ON admin_approvals(status, created_at);
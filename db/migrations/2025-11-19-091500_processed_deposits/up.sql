@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE processed_deposits (
+    id SERIAL PRIMARY KEY,
+    chain_id INTEGER NOT NULL,
+    tx_hash VARCHAR NOT NULL,
+    -- Position of the deposit within its transaction, the log index on EVM chains
+    log_index INTEGER NOT NULL,
+    block_number INTEGER NOT NULL,
+    block_hash VARCHAR NOT NULL,
+    credit_request_id INTEGER NOT NULL REFERENCES credit_requests(id),
+    amount_credit NUMERIC NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    UNIQUE (chain_id, tx_hash, log_index)
+);
+
+CREATE INDEX idx_processed_deposits_block ON processed_deposits(chain_id, block_number);
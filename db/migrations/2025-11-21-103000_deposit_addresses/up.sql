@@ -0,0 +1,12 @@
+-- Your SQL goes here
+-- Avail addresses derived from the deposit seed of the funds monitor, one per user
+CREATE TABLE deposit_addresses (
+    id SERIAL PRIMARY KEY,
+    address VARCHAR NOT NULL UNIQUE,
+    derivation_index INTEGER NOT NULL UNIQUE,
+    user_id VARCHAR UNIQUE REFERENCES users(id),
+    assigned_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_deposit_addresses_unassigned ON deposit_addresses(derivation_index) WHERE user_id IS NULL;
@@ -0,0 +1,24 @@
+-- Your SQL goes here
+CREATE TABLE unmatched_deposits (
+    id SERIAL PRIMARY KEY,
+    chain_id INTEGER NOT NULL,
+    tx_hash VARCHAR NOT NULL,
+    log_index INTEGER NOT NULL,
+    block_number INTEGER NOT NULL,
+    block_hash VARCHAR NOT NULL,
+    from_address VARCHAR NOT NULL,
+    token_address VARCHAR NOT NULL,
+    amount_paid NUMERIC NOT NULL,
+    -- Order reference carried by the deposit, if any
+    order_id VARCHAR,
+    reason VARCHAR NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'attributed', 'refunded')),
+    credit_request_id INTEGER REFERENCES credit_requests(id),
+    refund_tx_hash VARCHAR,
+    resolved_by VARCHAR,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    resolved_at TIMESTAMP,
+    UNIQUE (chain_id, tx_hash, log_index)
+);
+
+CREATE INDEX idx_unmatched_deposits_status ON unmatched_deposits(status, created_at);
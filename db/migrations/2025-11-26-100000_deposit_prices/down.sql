@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE processed_deposits
+    DROP COLUMN IF EXISTS token_usd_price,
+    DROP COLUMN IF EXISTS avail_usd_price,
+    DROP COLUMN IF EXISTS price_source,
+    DROP COLUMN IF EXISTS priced_at;
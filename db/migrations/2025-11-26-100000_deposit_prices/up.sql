@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- Prices the amount credited for a deposit was computed with, NULL for deposits credited before
+ALTER TABLE processed_deposits
+    ADD COLUMN token_usd_price NUMERIC,
+    ADD COLUMN avail_usd_price NUMERIC,
+    ADD COLUMN price_source VARCHAR,
+    ADD COLUMN priced_at TIMESTAMP;
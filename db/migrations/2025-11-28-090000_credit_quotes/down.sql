@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE credit_requests
+    DROP COLUMN IF EXISTS quote_token_address,
+    DROP COLUMN IF EXISTS quote_amount,
+    DROP COLUMN IF EXISTS quote_credits,
+    DROP COLUMN IF EXISTS quote_expires_at,
+    DROP COLUMN IF EXISTS quote_signature,
+    DROP COLUMN IF EXISTS quote_outcome,
+    DROP COLUMN IF EXISTS quote_difference;
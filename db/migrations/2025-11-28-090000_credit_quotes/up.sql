@@ -0,0 +1,13 @@
+-- Your SQL goes here
+-- Credits locked in when the credit request was registered, honoured for a deposit of the quoted
+-- token and amount made before the quote expires
+ALTER TABLE credit_requests
+    ADD COLUMN quote_token_address VARCHAR(255),
+    ADD COLUMN quote_amount NUMERIC,
+    ADD COLUMN quote_credits NUMERIC,
+    ADD COLUMN quote_expires_at TIMESTAMP,
+    ADD COLUMN quote_signature VARCHAR,
+    ADD COLUMN quote_outcome VARCHAR(16)
+        CHECK (quote_outcome IN ('honoured', 'expired', 'mismatched', 'invalid')),
+    -- Credits at the price of the deposit minus the quoted credits
+    ADD COLUMN quote_difference NUMERIC;
@@ -0,0 +1,26 @@
+-- Your SQL goes here
+-- Tokens deposits are accepted in, per chain. Native tokens use the zero address, AVAIL on the
+-- Avail chain is chain 0
+CREATE TABLE supported_tokens (
+    id SERIAL PRIMARY KEY,
+    chain_id INTEGER NOT NULL,
+    token_address VARCHAR(255) NOT NULL CHECK (token_address = LOWER(token_address)),
+    symbol VARCHAR(32) NOT NULL,
+    decimals INTEGER NOT NULL CHECK (decimals >= 0),
+    -- CoinGecko id the price of the token is looked up with
+    price_feed_id VARCHAR(64) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    UNIQUE (chain_id, token_address)
+);
+
+SELECT diesel_manage_updated_at('supported_tokens');
+
+-- Tokens previously hard-coded in the API
+INSERT INTO supported_tokens (chain_id, token_address, symbol, decimals, price_feed_id) VALUES
+    (0, '0x0000000000000000000000000000000000000000', 'AVAIL', 18, 'avail'),
+    (11155111, '0x8b42845d23c68b845e262dc3e5caa1c9ce9edb44', 'ETH', 18, 'ethereum'),
+    (11155111, '0x99a907545815c289fb6de86d55fe61d996063a94', 'AVAIL', 18, 'avail'),
+    (84532, '0x8b42845d23c68b845e262dc3e5caa1c9ce9edb44', 'ETH', 18, 'ethereum'),
+    (84532, '0x99a907545815c289fb6de86d55fe61d996063a94', 'AVAIL', 18, 'avail');
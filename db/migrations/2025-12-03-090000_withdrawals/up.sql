@@ -0,0 +1,31 @@
+-- Your SQL goes here
+-- Credits users withdraw back to chain. The credits are debited from the balance when the
+-- withdrawal is requested and held by the row until it is paid out or rejected. The id is the
+-- nonce the operator signs, the contract pays each nonce out once.
+CREATE TABLE withdrawals (
+    id SERIAL PRIMARY KEY,
+    user_id VARCHAR NOT NULL REFERENCES users(id),
+    chain_id INTEGER NOT NULL,
+    token_address VARCHAR(255) NOT NULL,
+    recipient VARCHAR(255) NOT NULL,
+    credits NUMERIC NOT NULL CHECK (credits > 0),
+    -- Amount paid out, in the smallest unit of the token
+    amount NUMERIC NOT NULL CHECK (amount > 0),
+    token_usd_price NUMERIC NOT NULL,
+    avail_usd_price NUMERIC NOT NULL,
+    price_source VARCHAR NOT NULL,
+    priced_at TIMESTAMP NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'signed', 'rejected', 'paid')),
+    signature VARCHAR,
+    resolved_by VARCHAR,
+    -- Position of the payout on chain, set once the funds monitor sees the Withdrawal event
+    tx_hash VARCHAR,
+    block_number INTEGER,
+    block_hash VARCHAR,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    resolved_at TIMESTAMP,
+    paid_at TIMESTAMP
+);
+
+CREATE INDEX idx_withdrawals_user_id ON withdrawals(user_id, created_at);
+CREATE INDEX idx_withdrawals_status ON withdrawals(status, created_at);
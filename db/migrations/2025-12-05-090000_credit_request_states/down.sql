@@ -0,0 +1,14 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_credit_requests_expiry;
+
+ALTER TABLE credit_requests
+    DROP COLUMN expires_at,
+    DROP COLUMN confirmations,
+    DROP CONSTRAINT credit_requests_request_status_check,
+    ALTER COLUMN request_status DROP DEFAULT;
+
+UPDATE credit_requests SET request_status = CASE
+    WHEN request_status = 'credited' THEN 'Processed'
+    WHEN request_status = 'tx_submitted' THEN 'INCLUDED'
+    ELSE 'PENDING'
+END;
@@ -0,0 +1,24 @@
+-- Your SQL goes here
+-- Typed lifecycle of credit requests, the free-form statuses are mapped to their state
+UPDATE credit_requests SET request_status = CASE
+    WHEN request_status = 'Processed' THEN 'credited'
+    WHEN request_status = 'INCLUDED' THEN 'tx_submitted'
+    WHEN request_status = 'Reorged' THEN 'tx_submitted'
+    WHEN request_status = 'PENDING' AND quote_signature IS NOT NULL THEN 'awaiting_payment'
+    WHEN request_status = 'PENDING' THEN 'created'
+    ELSE 'failed'
+END;
+
+ALTER TABLE credit_requests
+    ALTER COLUMN request_status SET DEFAULT 'created',
+    ADD CONSTRAINT credit_requests_request_status_check CHECK (request_status IN (
+        'created', 'awaiting_payment', 'tx_submitted', 'confirming', 'credited', 'expired', 'failed'
+    )),
+    -- Blocks on top of the deposit while it is confirming
+    ADD COLUMN confirmations INTEGER,
+    -- Requests still waiting for a payment are expired past this time
+    ADD COLUMN expires_at TIMESTAMP NOT NULL DEFAULT (CURRENT_TIMESTAMP + INTERVAL '1 day');
+
+UPDATE credit_requests SET expires_at = created_at + INTERVAL '1 day';
+
+CREATE INDEX idx_credit_requests_expiry ON credit_requests(request_status, expires_at);
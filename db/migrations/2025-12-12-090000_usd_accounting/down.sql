@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE customer_expenditures
+    DROP COLUMN avail_usd_price,
+    DROP COLUMN billed_unit;
+
+ALTER TABLE users DROP COLUMN balance_unit;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- Unit balances are held in, credits or USD. Balances of a user and of their apps share its unit
+ALTER TABLE users
+    ADD COLUMN balance_unit VARCHAR(16) NOT NULL DEFAULT 'credits'
+        CONSTRAINT users_balance_unit_check CHECK (balance_unit IN ('credits', 'usd'));
+
+-- Unit the submission was billed in, and the AVAIL/USD price it was priced at when billed in USD
+ALTER TABLE customer_expenditures
+    ADD COLUMN billed_unit VARCHAR(16) NOT NULL DEFAULT 'credits'
+        CONSTRAINT customer_expenditures_billed_unit_check CHECK (billed_unit IN ('credits', 'usd')),
+    ADD COLUMN avail_usd_price NUMERIC;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE fee_reconciliations;
+
+DROP INDEX idx_customer_expenditures_fee_unchecked;
+ALTER TABLE customer_expenditures DROP COLUMN fee_checked_at;
+ALTER TABLE customer_expenditures DROP COLUMN chain_fee;
@@ -0,0 +1,31 @@
+-- Your SQL goes here
+-- Fee the signer actually paid for a submission, read from the TransactionFeePaid event of its
+-- extrinsic by the funds monitor. `chain_fee` stays NULL when the event could not be found.
+ALTER TABLE customer_expenditures ADD COLUMN chain_fee NUMERIC;
+ALTER TABLE customer_expenditures ADD COLUMN fee_checked_at TIMESTAMP;
+
+CREATE INDEX idx_customer_expenditures_fee_unchecked ON customer_expenditures(created_at)
+    WHERE fee_checked_at IS NULL AND block_hash IS NOT NULL;
+
+-- AVAIL spent on the submissions of an app against what they were billed, per day. Fees are in
+-- the smallest unit of AVAIL, bills are valued in AVAIL to compute the margin.
+CREATE TABLE fee_reconciliations (
+    day DATE NOT NULL,
+    app_id UUID NOT NULL REFERENCES apps(id) ON DELETE CASCADE,
+    submissions INTEGER NOT NULL,
+    -- Submissions whose fee could not be read from chain
+    unverified_submissions INTEGER NOT NULL,
+    -- Submissions never billed, or whose recorded fee differs from the fee paid on chain or is
+    -- missing on either side
+    mismatched_submissions INTEGER NOT NULL,
+    -- Submissions billed less than the fee paid on chain
+    underbilled_submissions INTEGER NOT NULL,
+    avail_spent NUMERIC NOT NULL,
+    avail_recorded NUMERIC NOT NULL,
+    credits_billed NUMERIC NOT NULL,
+    usd_billed NUMERIC NOT NULL,
+    avail_billed NUMERIC NOT NULL,
+    margin NUMERIC NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (day, app_id)
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE customer_expenditures DROP COLUMN lock_expires_at;
+ALTER TABLE customer_expenditures DROP COLUMN locked_by;
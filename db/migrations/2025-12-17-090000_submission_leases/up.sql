@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- Lease on a submission, held by the worker submitting its payload until `lock_expires_at` so two
+-- workers never submit the same payload. Expired leases can be claimed by any worker.
+ALTER TABLE customer_expenditures ADD COLUMN locked_by VARCHAR;
+ALTER TABLE customer_expenditures ADD COLUMN lock_expires_at TIMESTAMP;
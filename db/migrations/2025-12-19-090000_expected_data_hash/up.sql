@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- Hash of the data last handed to the chain for a submission, recorded before it is submitted so
+-- the fallback monitor can recognise it on chain after a timeout instead of submitting it again.
+-- Encrypted payloads are encrypted anew on every attempt, only this hash identifies them.
+ALTER TABLE customer_expenditures ADD COLUMN expected_data_hash VARCHAR;
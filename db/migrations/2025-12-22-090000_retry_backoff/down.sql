@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE customer_expenditures DROP COLUMN failed_at;
+ALTER TABLE customer_expenditures DROP COLUMN next_retry_at;
+ALTER TABLE customer_expenditures DROP COLUMN error_class;
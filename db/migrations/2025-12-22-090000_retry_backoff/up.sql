@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- Class of the last error of a submission, `retryable`, `user_action` or `non_retryable`. Failed
+-- submissions are retried from `next_retry_at`, submissions that can't succeed are given up on at
+-- `failed_at`.
+ALTER TABLE customer_expenditures ADD COLUMN error_class VARCHAR(16);
+ALTER TABLE customer_expenditures ADD COLUMN next_retry_at TIMESTAMP;
+ALTER TABLE customer_expenditures ADD COLUMN failed_at TIMESTAMP;
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE admin_approvals DROP COLUMN expires_at;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- Pending requests can no longer be approved once they expire
+ALTER TABLE admin_approvals ADD COLUMN expires_at TIMESTAMP NOT NULL DEFAULT (CURRENT_TIMESTAMP + INTERVAL '1 day');
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE customer_expenditures DROP COLUMN avail_per_credit;
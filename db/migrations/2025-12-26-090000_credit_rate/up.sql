@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Value of a credit in the smallest unit of AVAIL when the submission was billed in credits, so
+-- its bill is reconciled at the rate of the day it was made
+ALTER TABLE customer_expenditures ADD COLUMN avail_per_credit NUMERIC;
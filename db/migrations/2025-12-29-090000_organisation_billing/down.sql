@@ -0,0 +1,21 @@
+-- This file should undo anything in `up.sql`
+UPDATE users
+SET credit_used = users.credit_used - moved.credit_used
+FROM (
+    SELECT organisations.owner_id, SUM(apps.credit_used) AS credit_used
+    FROM apps
+    JOIN organisations ON organisations.id = apps.org_id
+    WHERE apps.user_id <> organisations.owner_id
+    GROUP BY organisations.owner_id
+) AS moved
+WHERE users.id = moved.owner_id;
+
+UPDATE apps
+SET user_id = organisations.owner_id
+FROM organisations
+WHERE organisations.id = apps.org_id
+AND apps.user_id <> organisations.owner_id;
+
+ALTER TABLE organisations DROP COLUMN billing_id;
+
+DELETE FROM users WHERE id LIKE 'org:%';
@@ -0,0 +1,46 @@
+-- Your SQL goes here
+-- Every organisation is billed to an account of its own, so its members only ever reach its funds
+ALTER TABLE organisations ADD COLUMN billing_id VARCHAR;
+
+-- The personal organisation of a user, created on registration, is billed to the user
+UPDATE organisations
+SET billing_id = owner_id
+WHERE id IN (
+    SELECT DISTINCT ON (owner_id) id
+    FROM organisations
+    ORDER BY owner_id, created_at, id
+);
+
+INSERT INTO users (id, name, balance_unit)
+SELECT 'org:' || organisations.id, organisations.name, users.balance_unit
+FROM organisations
+JOIN users ON users.id = organisations.owner_id
+WHERE organisations.billing_id IS NULL;
+
+UPDATE organisations
+SET billing_id = 'org:' || id
+WHERE billing_id IS NULL;
+
+-- Apps move to the account of their organisation. The credits they spent were bought by the owner,
+-- so they stay counted against the owner's withdrawable credits
+UPDATE users
+SET credit_used = users.credit_used + moved.credit_used
+FROM (
+    SELECT apps.user_id, SUM(apps.credit_used) AS credit_used
+    FROM apps
+    JOIN organisations ON organisations.id = apps.org_id
+    WHERE apps.user_id <> organisations.billing_id
+    GROUP BY apps.user_id
+) AS moved
+WHERE users.id = moved.user_id;
+
+UPDATE apps
+SET user_id = organisations.billing_id
+FROM organisations
+WHERE organisations.id = apps.org_id
+AND apps.user_id <> organisations.billing_id;
+
+ALTER TABLE organisations
+    ALTER COLUMN billing_id SET NOT NULL,
+    ADD CONSTRAINT organisations_billing_id_key UNIQUE (billing_id),
+    ADD CONSTRAINT fk_organisations_billing_id FOREIGN KEY (billing_id) REFERENCES users(id) ON DELETE CASCADE ON UPDATE CASCADE;
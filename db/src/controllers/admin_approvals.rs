@@ -0,0 +1,202 @@
+use crate::{
+    models::{
+        admin_approvals::{AdminApproval, AdminApprovalCreate, ApprovalStatus},
+        audit_events::AuditAction,
+        user_model::User,
+    },
+    schema::{
+        admin_approvals::dsl as admin_approvals, audit_events::dsl as audit_events,
+        users::dsl as users,
+    },
+};
+use bigdecimal::BigDecimal;
+use diesel::{
+    dsl::{now, sql, IntervalDsl},
+    prelude::*,
+    sql_types::{Nullable, Numeric},
+};
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
+use uuid::Uuid;
+
+pub async fn create_admin_approval(
+    connection: &mut AsyncPgConnection,
+    approval: &AdminApprovalCreate,
+) -> Result<AdminApproval, String> {
+    diesel::insert_into(admin_approvals::admin_approvals)
+        .values(approval)
+        .returning(AdminApproval::as_returning())
+        .get_result::<AdminApproval>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub async fn get_admin_approval(
+    connection: &mut AsyncPgConnection,
+    id: &Uuid,
+) -> Result<AdminApproval, String> {
+    admin_approvals::admin_approvals
+        .filter(admin_approvals::id.eq(id))
+        .select(AdminApproval::as_select())
+        .first::<AdminApproval>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Outcome of a `fund_user` grant
+pub enum FundUserGrant {
+    /// The user was credited
+    Funded(User),
+    /// The grant exceeds the threshold and waits for the approval of a second admin
+    Queued(AdminApproval),
+}
+
+/// Credits `amount` to `user`, or queues `approval` if the grants to the user over the last
+/// `window_secs`, this one included, exceed `threshold`
+///
+/// Grants are summed from the `admin.fund_user` audit events, so the caller must record one for
+/// a funded grant in the same transaction. The user row is locked while summing, concurrent grants
+/// to the same user are counted one after the other.
+pub async fn grant_or_queue_fund_user(
+    connection: &mut AsyncPgConnection,
+    user: &String,
+    amount: &BigDecimal,
+    threshold: &BigDecimal,
+    window_secs: i64,
+    approval: &AdminApprovalCreate,
+) -> Result<FundUserGrant, String> {
+    connection
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                users::users
+                    .filter(users::id.eq(user))
+                    .select(users::id)
+                    .for_update()
+                    .first::<String>(conn)
+                    .await?;
+                let granted = audit_events::audit_events
+                    .filter(audit_events::action.eq(AuditAction::AdminFundUser.as_str()))
+                    .filter(audit_events::target_id.eq(user))
+                    .filter(audit_events::created_at.gt(now - window_secs.seconds()))
+                    .select(sql::<Nullable<Numeric>>("SUM((after->>'amount')::numeric)"))
+                    .get_result::<Option<BigDecimal>>(conn)
+                    .await?
+                    .unwrap_or_default();
+
+                if &(granted + amount) > threshold {
+                    let queued = diesel::insert_into(admin_approvals::admin_approvals)
+                        .values(approval)
+                        .returning(AdminApproval::as_returning())
+                        .get_result::<AdminApproval>(conn)
+                        .await?;
+                    return Ok(FundUserGrant::Queued(queued));
+                }
+                let funded = diesel::update(users::users.filter(users::id.eq(user)))
+                    .set(users::credit_balance.eq(users::credit_balance + amount))
+                    .returning(User::as_returning())
+                    .get_result::<User>(conn)
+                    .await?;
+                Ok(FundUserGrant::Funded(funded))
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Retrieves approval requests, newest first
+///
+/// Expired requests are left out of the pending ones.
+pub async fn get_admin_approvals(
+    connection: &mut AsyncPgConnection,
+    status: &Option<ApprovalStatus>,
+    limit: i64,
+) -> Result<Vec<AdminApproval>, String> {
+    let mut query = admin_approvals::admin_approvals
+        .select(AdminApproval::as_select())
+        .into_boxed();
+    if let Some(status) = status {
+        query = query.filter(admin_approvals::status.eq(status.as_str()));
+        if *status == ApprovalStatus::Pending {
+            query = query.filter(admin_approvals::expires_at.gt(now));
+        }
+    }
+    query
+        .order(admin_approvals::created_at.desc())
+        .limit(limit)
+        .load::<AdminApproval>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Moves a pending request to `status`.
+///
+/// Fails with `NotFound` if the request is not pending, has expired or was raised by the
+/// resolving admin.
+async fn resolve(
+    connection: &mut AsyncPgConnection,
+    id: &Uuid,
+    resolver: &String,
+    status: ApprovalStatus,
+) -> Result<AdminApproval, diesel::result::Error> {
+    diesel::update(
+        admin_approvals::admin_approvals
+            .filter(admin_approvals::id.eq(id))
+            .filter(admin_approvals::status.eq(ApprovalStatus::Pending.as_str()))
+            .filter(admin_approvals::expires_at.gt(now))
+            .filter(admin_approvals::requested_by.ne(resolver)),
+    )
+    .set((
+        admin_approvals::status.eq(status.as_str()),
+        admin_approvals::resolved_by.eq(resolver),
+        admin_approvals::resolved_at.eq(diesel::dsl::now),
+    ))
+    .returning(AdminApproval::as_returning())
+    .get_result::<AdminApproval>(connection)
+    .await
+}
+
+fn resolve_error(e: diesel::result::Error) -> String {
+    match e {
+        diesel::result::Error::NotFound => {
+            "Approval is not pending, has expired or was requested by the same admin".to_string()
+        }
+        e => e.to_string(),
+    }
+}
+
+pub async fn reject_admin_approval(
+    connection: &mut AsyncPgConnection,
+    id: &Uuid,
+    resolver: &String,
+) -> Result<AdminApproval, String> {
+    resolve(connection, id, resolver, ApprovalStatus::Rejected)
+        .await
+        .map_err(resolve_error)
+}
+
+/// Approves a pending `fund_user` request and credits the user in the same transaction
+pub async fn approve_fund_user(
+    connection: &mut AsyncPgConnection,
+    id: &Uuid,
+    resolver: &String,
+    user: &String,
+    amount: &BigDecimal,
+) -> Result<(AdminApproval, User), String> {
+    connection
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                let approval = resolve(conn, id, resolver, ApprovalStatus::Approved).await?;
+                let funded = diesel::update(users::users.filter(users::id.eq(user)))
+                    .set(users::credit_balance.eq(users::credit_balance + amount))
+                    .returning(User::as_returning())
+                    .get_result::<User>(conn)
+                    .await?;
+                Ok((approval, funded))
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(resolve_error)
+}
@@ -7,6 +7,7 @@ use diesel::ExpressionMethods;
 use diesel::QueryDsl;
 use diesel::SelectableHelper;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use uuid::Uuid;
 
 use super::misc::get_account_by_id;
 
@@ -28,10 +29,10 @@ pub async fn create_api_key(
 
 pub async fn get_api_keys(
     connection: &mut AsyncPgConnection,
-    user: &String,
+    apps: &Vec<Uuid>,
 ) -> Result<Vec<ApiKey>, String> {
     let result = api_keys
-        .filter(user_id.eq(user))
+        .filter(app_id.eq_any(apps))
         .select(ApiKey::as_select())
         .load(&mut *connection)
         .await
@@ -41,12 +42,12 @@ pub async fn get_api_keys(
 
 pub async fn delete_api_key(
     connection: &mut AsyncPgConnection,
-    user: &String,
+    apps: &Vec<Uuid>,
     ident: &String,
 ) -> Result<Vec<ApiKey>, String> {
     let deleted_keys = diesel::delete(
         api_keys
-            .filter(user_id.eq(user))
+            .filter(app_id.eq_any(apps))
             .filter(identifier.eq(ident)),
     )
     .returning(ApiKey::as_select())
@@ -0,0 +1,51 @@
+use crate::{
+    models::audit_events::{AuditEvent, AuditEventCreate},
+    schema::audit_events::dsl::*,
+};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use uuid::Uuid;
+
+pub async fn create_audit_event(
+    connection: &mut AsyncPgConnection,
+    event: &AuditEventCreate,
+) -> Result<(), String> {
+    diesel::insert_into(audit_events)
+        .values(event)
+        .execute(connection)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Retrieves audit events, newest first
+///
+/// # Arguments
+/// * `orgs` - Restricts events to these organisations when set
+/// * `actor_filter` - Restricts events to a single actor when set
+/// * `action_filter` - Restricts events to a single action when set
+/// * `limit` - Maximum number of events to return
+pub async fn get_audit_events(
+    connection: &mut AsyncPgConnection,
+    orgs: &Option<Vec<Uuid>>,
+    actor_filter: &Option<String>,
+    action_filter: &Option<String>,
+    limit: i64,
+) -> Result<Vec<AuditEvent>, String> {
+    let mut query = audit_events.select(AuditEvent::as_select()).into_boxed();
+    if let Some(orgs) = orgs {
+        query = query.filter(org_id.eq_any(orgs));
+    }
+    if let Some(actor_filter) = actor_filter {
+        query = query.filter(actor.eq(actor_filter));
+    }
+    if let Some(action_filter) = action_filter {
+        query = query.filter(action.eq(action_filter));
+    }
+    query
+        .order(created_at.desc())
+        .limit(limit)
+        .load::<AuditEvent>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
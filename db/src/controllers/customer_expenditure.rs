@@ -1,15 +1,25 @@
 use crate::{
+    controllers::users::TxParams,
+    errors::{retry_delay, ErrorClass},
     models::customer_expenditure::{
         CreateCustomerExpenditure, CustomerExpenditureGet, CustomerExpenditureGetWithPayload,
     },
     schema::customer_expenditures::dsl::*,
 };
 use bigdecimal::BigDecimal;
-use diesel::{prelude::*, result::Error};
-use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use chrono::{NaiveDateTime, Utc};
+use diesel::{
+    dsl::{now, IntervalDsl},
+    prelude::*,
+    result::Error,
+};
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
 use enigma::types::EncryptResponse;
 use log::{error, info};
 use serde_json::{json, Value};
+use std::{env, sync::OnceLock};
 use uuid::Uuid;
 
 use avail_utils::submit_data::TransactionInfo;
@@ -62,7 +72,7 @@ pub async fn handle_submission_info(
             let response = json!({
                 "submission": sub,
                 "id": sub.id,
-                "state": if sub.error.is_some() { "Error" } else if sub.block_hash.is_some() { "Finalized" } else { "Pending" },
+                "state": if sub.failed_at.is_some() { "Failed" } else if sub.error.is_some() { "Error" } else if sub.block_hash.is_some() { "Finalized" } else { "Pending" },
                 "error": sub.error,
                 "data": if sub.error.is_some() {
                     None
@@ -77,6 +87,8 @@ pub async fn handle_submission_info(
                         "data_hash": sub.data_hash.map(|h| format!("0x{}", h)),
                         "tx_index": sub.extrinsic_index,
                         "data_billed": sub.converted_fees.map(|f| f.to_string()),
+                        "billed_unit": sub.billed_unit,
+                        "avail_usd_price": sub.avail_usd_price.map(|p| p.to_string()),
                         "created_at": sub.created_at
                     }))
                 }
@@ -92,14 +104,17 @@ pub async fn update_customer_expenditure(
     result: TransactionInfo,
     encrypted_data: Option<EncryptResponse>,
     fees_as_bigdecimal: &BigDecimal,
-    fees_as_bigdecimal_in_avail: &BigDecimal,
+    tx_params: &TxParams,
     wallet_store: &Vec<u8>,
     submission_id: Uuid,
     connection: &mut AsyncPgConnection,
 ) -> Result<(), String> {
     let update_values = (
         fees.eq(fees_as_bigdecimal),
-        converted_fees.eq(fees_as_bigdecimal_in_avail),
+        converted_fees.eq(&tx_params.amount_data_billed),
+        billed_unit.eq(tx_params.billed_unit.as_str()),
+        avail_usd_price.eq(tx_params.avail_usd_price.as_ref()),
+        avail_per_credit.eq(tx_params.avail_per_credit.as_ref()),
         to_address.eq(Some(result.to_address)),
         block_hash.eq(Some(result.block_hash)),
         data_hash.eq(Some(result.data_hash)),
@@ -109,6 +124,8 @@ pub async fn update_customer_expenditure(
         wallet.eq(wallet_store),
         payload.eq(None::<Vec<u8>>),
         error.eq(None::<String>),
+        error_class.eq(None::<String>),
+        next_retry_at.eq(None::<NaiveDateTime>),
         ciphertext_hash.eq(encrypted_data.as_ref().map(|r| r.ciphertext_hash.clone())),
         plaintext_hash.eq(encrypted_data.as_ref().map(|r| r.plaintext_hash.clone())),
         signature_ciphertext_hash.eq(encrypted_data
@@ -171,28 +188,28 @@ pub async fn get_customer_expenditure_by_submission_id(
         .await
 }
 
-/// Retrieves all expenditure entries for a specific user
+/// Retrieves all expenditure entries for a set of apps
 ///
 /// # Arguments
 /// * `connection` - Database connection handle
-/// * `user` - User ID string
+/// * `apps` - App IDs to retrieve expenditure for
 /// * `final_limit` - Maximum number of entries to return
 ///
 /// # Returns
 /// * `HttpResponse` - JSON response containing list of expenditures or error message
 ///
 /// # Description
-/// Queries the database for all expenditure entries belonging to the specified user,
+/// Queries the database for all expenditure entries belonging to the specified apps,
 /// limited by the provided count. Returns:
 /// - 200 OK with list of expenditures if successful
 /// - 500 Internal Server Error if database query fails
 pub async fn handle_get_all_expenditure(
     connection: &mut AsyncPgConnection,
-    user: String,
+    apps: &Vec<Uuid>,
     final_limit: i64,
 ) -> Result<Value, Error> {
     match customer_expenditures
-        .filter(user_id.eq(user))
+        .filter(app_id.eq_any(apps))
         .limit(final_limit)
         .select(CustomerExpenditureGet::as_select())
         .load(connection)
@@ -205,13 +222,11 @@ pub async fn handle_get_all_expenditure(
 
 pub async fn handle_get_expenditure_by_time_range(
     connection: &mut AsyncPgConnection,
-    user: &String,
     start_date: chrono::NaiveDateTime,
     end_date: chrono::NaiveDateTime,
     app: &Uuid,
 ) -> Result<Vec<CustomerExpenditureGet>, Error> {
     customer_expenditures
-        .filter(user_id.eq(user))
         .filter(app_id.eq(app))
         .filter(created_at.ge(start_date))
         .filter(created_at.le(end_date))
@@ -229,9 +244,26 @@ pub async fn handle_get_expenditure_by_time_range(
 ///
 /// # Description
 /// Updates the error field of a customer expenditure entry with the provided error message.
+/// The error is classified with `ErrorClass`: retryable errors and errors awaiting the user are
+/// retried from `next_retry_at`, timed out entries once their extrinsic expired, other errors
+/// mark the entry as failed.
 /// Logs success or failure of the update operation.
 pub async fn add_error_entry(sub_id: &Uuid, e: String, connection: &mut AsyncPgConnection) {
-    let update_values = (error.eq(e.to_string()),);
+    // Retried after a backoff growing with its retries, or marked failed when it can't succeed
+    let class = ErrorClass::classify(&e);
+    let retries = customer_expenditures
+        .filter(id.eq(sub_id))
+        .select(retry_count)
+        .first::<i32>(connection)
+        .await
+        .unwrap_or(0);
+    let now_utc = Utc::now().naive_utc();
+    let update_values = (
+        error.eq(e.to_string()),
+        error_class.eq(Some(class.as_str())),
+        next_retry_at.eq(retry_delay(class, &e, retries).map(|delay| now_utc + delay)),
+        failed_at.eq((class == ErrorClass::NonRetryable).then_some(now_utc)),
+    );
 
     let tx = diesel::update(customer_expenditures.filter(id.eq(sub_id)))
         .set(update_values)
@@ -310,16 +342,126 @@ pub async fn get_did_fallback_resolved(
     }
 }
 
+/// Records the hash of the data about to be submitted, so the submission can be recognised on
+/// chain if its outcome is lost
+pub async fn set_expected_data_hash(
+    connection: &mut AsyncPgConnection,
+    submission_id: &Uuid,
+    hash: &str,
+) -> Result<(), String> {
+    diesel::update(customer_expenditures.filter(id.eq(submission_id)))
+        .set(expected_data_hash.eq(Some(hash)))
+        .execute(connection)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// How long a worker holds the submissions it claimed, outlasts the 120 seconds a submission is
+/// given so a lease never expires while its payload is being submitted
+pub const SUBMISSION_LEASE_SECS: i32 = 300;
+
+/// Identifies this process in the leases it takes, as `<hostname>:<pid>`
+///
+/// Without `HOSTNAME` a UUID drawn once per process is used instead, pids alone repeat across
+/// containers.
+pub fn worker_id() -> &'static str {
+    static WORKER_ID: OnceLock<String> = OnceLock::new();
+    WORKER_ID.get_or_init(|| match env::var("HOSTNAME") {
+        Ok(host) => format!("{}:{}", host, std::process::id()),
+        Err(_) => Uuid::new_v4().to_string(),
+    })
+}
+
+/// Claims a submission for `worker` for `SUBMISSION_LEASE_SECS`
+///
+/// # Returns
+/// * `Ok(true)` - The submission is leased to `worker`
+/// * `Ok(false)` - The submission is already included, or leased to another worker
+/// * `Err(String)` - Error message if the database operation fails
+///
+/// # Description
+/// The row is locked with `FOR UPDATE SKIP LOCKED`, a worker racing for the same submission skips
+/// it instead of waiting. A lease held by `worker` itself is renewed.
+pub async fn lease_submission(
+    connection: &mut AsyncPgConnection,
+    submission_id: &Uuid,
+    worker: &str,
+) -> Result<bool, String> {
+    connection
+        .transaction::<_, Error, _>(|conn| {
+            async move {
+                let claimable = customer_expenditures
+                    .filter(id.eq(submission_id))
+                    .filter(tx_hash.is_null())
+                    .filter(
+                        lock_expires_at
+                            .is_null()
+                            .or(lock_expires_at.lt(now))
+                            .or(locked_by.eq(worker)),
+                    )
+                    .select(id)
+                    .for_update()
+                    .skip_locked()
+                    .first::<Uuid>(conn)
+                    .await
+                    .optional()?;
+                if claimable.is_none() {
+                    return Ok(false);
+                }
+
+                diesel::update(customer_expenditures.filter(id.eq(submission_id)))
+                    .set((
+                        locked_by.eq(worker),
+                        lock_expires_at.eq((now + SUBMISSION_LEASE_SECS.seconds()).nullable()),
+                    ))
+                    .execute(conn)
+                    .await?;
+                Ok(true)
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Releases the lease `worker` holds on a submission, leases held by other workers are left as is
+pub async fn release_submission(
+    connection: &mut AsyncPgConnection,
+    submission_id: &Uuid,
+    worker: &str,
+) -> Result<(), String> {
+    diesel::update(
+        customer_expenditures
+            .filter(id.eq(submission_id))
+            .filter(locked_by.eq(worker)),
+    )
+    .set((
+        locked_by.eq(None::<String>),
+        lock_expires_at.eq(None::<chrono::NaiveDateTime>),
+    ))
+    .execute(connection)
+    .await
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
 pub async fn handle_reset_retry_count(
     connection: &mut AsyncPgConnection,
     app: &Option<Uuid>,
     retry: &i32,
     expenditure_id: &Option<Uuid>,
 ) -> Result<(), String> {
+    // Submissions given up on are retried again, without waiting for their backoff
+    let values = (
+        retry_count.eq(retry),
+        next_retry_at.eq(None::<NaiveDateTime>),
+        failed_at.eq(None::<NaiveDateTime>),
+    );
     let result;
     if let Some(expenditure_id) = expenditure_id {
         result = diesel::update(customer_expenditures.filter(id.eq(expenditure_id)))
-            .set(retry_count.eq(retry))
+            .set(values)
             .execute(connection)
             .await
             .map_err(|e| e.to_string());
@@ -327,13 +469,13 @@ pub async fn handle_reset_retry_count(
         result = match app {
             Some(app) => {
                 diesel::update(customer_expenditures.filter(app_id.eq(app)))
-                    .set(retry_count.eq(retry))
+                    .set(values)
                     .execute(connection)
                     .await
             }
             None => {
                 diesel::update(customer_expenditures)
-                    .set(retry_count.eq(retry))
+                    .set(values)
                     .execute(connection)
                     .await
             }
@@ -348,13 +490,12 @@ pub async fn handle_reset_retry_count(
 
 pub async fn handle_get_wallet_usage(
     connection: &mut AsyncPgConnection,
-    user: &String,
-
+    apps: &Vec<Uuid>,
     start_date: chrono::NaiveDateTime,
     end_date: chrono::NaiveDateTime,
 ) -> Result<Vec<CustomerExpenditureGet>, Error> {
     customer_expenditures
-        .filter(user_id.eq(user))
+        .filter(app_id.eq_any(apps))
         .filter(created_at.ge(start_date))
         .filter(created_at.le(end_date))
         .select(CustomerExpenditureGet::as_select())
@@ -0,0 +1,54 @@
+use crate::{
+    models::deposit_addresses::DepositAddress, schema::deposit_addresses::dsl as deposit_addresses,
+};
+use diesel::prelude::*;
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
+
+/// Returns the deposit address of a user, assigning the next free address of the pool if the
+/// user has none yet
+///
+/// The pool is filled by the funds monitor, which holds the seed the addresses are derived from.
+pub async fn assign_deposit_address(
+    connection: &mut AsyncPgConnection,
+    user: &String,
+) -> Result<DepositAddress, String> {
+    connection
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                let assigned = deposit_addresses::deposit_addresses
+                    .filter(deposit_addresses::user_id.eq(user))
+                    .select(DepositAddress::as_select())
+                    .first::<DepositAddress>(conn)
+                    .await
+                    .optional()?;
+                if let Some(address) = assigned {
+                    return Ok(address);
+                }
+
+                let free = deposit_addresses::deposit_addresses
+                    .filter(deposit_addresses::user_id.is_null())
+                    .order(deposit_addresses::derivation_index.asc())
+                    .select(deposit_addresses::id)
+                    .for_update()
+                    .skip_locked()
+                    .first::<i32>(conn)
+                    .await?;
+                diesel::update(deposit_addresses::deposit_addresses.find(free))
+                    .set((
+                        deposit_addresses::user_id.eq(user),
+                        deposit_addresses::assigned_at.eq(diesel::dsl::now),
+                    ))
+                    .returning(DepositAddress::as_returning())
+                    .get_result::<DepositAddress>(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => "No deposit address available".to_string(),
+            e => e.to_string(),
+        })
+}
@@ -0,0 +1,119 @@
+use crate::{
+    models::{
+        customer_expenditure::CustomerExpenditureGet,
+        fee_reconciliations::{DepositsReceived, FeeReconciliation},
+    },
+    schema::{
+        credit_requests::dsl as credit_requests,
+        customer_expenditures::dsl as customer_expenditures,
+        fee_reconciliations::dsl as fee_reconciliations,
+        processed_deposits::dsl as processed_deposits, users::dsl as users,
+    },
+};
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::{prelude::*, PgExpressionMethods};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Start of `start` and end of `end`, the bounds of a range of days
+fn day_bounds(start: NaiveDate, end: NaiveDate) -> (NaiveDateTime, NaiveDateTime) {
+    (
+        start.and_hms_opt(0, 0, 0).unwrap(),
+        end.succ_opt().unwrap_or(end).and_hms_opt(0, 0, 0).unwrap(),
+    )
+}
+
+/// Reconciliations of the days from `start` to `end`, newest first
+pub async fn get_fee_reconciliations(
+    connection: &mut AsyncPgConnection,
+    start: NaiveDate,
+    end: NaiveDate,
+    app: &Option<Uuid>,
+) -> Result<Vec<FeeReconciliation>, String> {
+    let mut query = fee_reconciliations::fee_reconciliations
+        .filter(fee_reconciliations::day.between(start, end))
+        .select(FeeReconciliation::as_select())
+        .into_boxed();
+    if let Some(app) = app {
+        query = query.filter(fee_reconciliations::app_id.eq(app));
+    }
+    query
+        .order((fee_reconciliations::day.desc(), fee_reconciliations::app_id))
+        .load::<FeeReconciliation>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Deposits credited from `start` to `end`, per day and unit of the balance credited
+pub async fn get_deposits_received(
+    connection: &mut AsyncPgConnection,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<DepositsReceived>, String> {
+    let (from, to) = day_bounds(start, end);
+    let deposits = processed_deposits::processed_deposits
+        .inner_join(credit_requests::credit_requests.inner_join(users::users))
+        .filter(processed_deposits::created_at.ge(from))
+        .filter(processed_deposits::created_at.lt(to))
+        .select((
+            processed_deposits::created_at,
+            users::balance_unit,
+            processed_deposits::amount_credit,
+        ))
+        .load::<(NaiveDateTime, String, BigDecimal)>(connection)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut received = BTreeMap::<(NaiveDate, String), (i64, BigDecimal)>::new();
+    for (created_at, unit, amount) in deposits {
+        let entry = received
+            .entry((created_at.date(), unit))
+            .or_insert((0, BigDecimal::from(0)));
+        entry.0 += 1;
+        entry.1 += amount;
+    }
+    Ok(received
+        .into_iter()
+        .rev()
+        .map(|((day, unit), (deposits, amount))| DepositsReceived {
+            day,
+            unit,
+            deposits,
+            amount,
+        })
+        .collect())
+}
+
+/// Reconciled submissions whose recorded fee differs from the fee paid on chain, including those
+/// whose fee could not be read from chain, or that were never billed
+pub async fn get_fee_mismatches(
+    connection: &mut AsyncPgConnection,
+    start: NaiveDate,
+    end: NaiveDate,
+    app: &Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<CustomerExpenditureGet>, String> {
+    let (from, to) = day_bounds(start, end);
+    let mut query = customer_expenditures::customer_expenditures
+        .filter(customer_expenditures::fee_checked_at.is_not_null())
+        .filter(customer_expenditures::created_at.ge(from))
+        .filter(customer_expenditures::created_at.lt(to))
+        .filter(
+            customer_expenditures::chain_fee
+                .is_distinct_from(customer_expenditures::fees)
+                .or(customer_expenditures::converted_fees.is_null()),
+        )
+        .select(CustomerExpenditureGet::as_select())
+        .into_boxed();
+    if let Some(app) = app {
+        query = query.filter(customer_expenditures::app_id.eq(app));
+    }
+    query
+        .order(customer_expenditures::created_at.desc())
+        .limit(limit)
+        .load::<CustomerExpenditureGet>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
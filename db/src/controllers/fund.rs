@@ -1,9 +1,24 @@
 use crate::{
-    models::credit_requests::{CreditRequestInfo, CreditRequestsGet},
-    schema::credit_requests::dsl::*,
+    models::{
+        credit_requests::{
+            CreditQuote, CreditRequestInfo, CreditRequestQuote, CreditRequestStatus,
+            CreditRequestsGet, DepositCredit,
+        },
+        indexer::IndexerBlockNumbers,
+        processed_deposits::ProcessedDepositCreate,
+        unmatched_deposits::UnmatchedDepositStatus,
+        user_model::BalanceUnit,
+    },
+    schema::{
+        credit_requests::dsl::*, indexer_block_numbers::dsl as indexer_block_numbers,
+        processed_deposits::dsl as processed_deposits,
+        unmatched_deposits::dsl as unmatched_deposits, users::dsl as users,
+    },
 };
 use diesel::prelude::*;
-use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
 use uuid::Uuid;
 
 pub async fn get_fund_status(
@@ -20,17 +35,20 @@ pub async fn get_fund_status(
         .map_err(|e| format!("Error loading fund status: {}", e))
 }
 
+/// Registers a credit request, it expires at `expiry` unless paid before
 pub async fn create_credit_request(
     user: String,
     chain: i32,
+    expiry: chrono::NaiveDateTime,
     connection: &mut AsyncPgConnection,
 ) -> Result<CreditRequestsGet, String> {
     let res = diesel::insert_into(credit_requests)
         .values((
             user_id.eq(user),
             chain_id.eq(chain),
-            request_status.eq("PENDING"),
+            request_status.eq(CreditRequestStatus::Created.as_str()),
             request_type.eq("DEPOSIT"),
+            expires_at.eq(expiry),
         ))
         .returning(CreditRequestsGet::as_returning())
         .get_result::<CreditRequestsGet>(connection)
@@ -39,23 +57,165 @@ pub async fn create_credit_request(
     Ok(res)
 }
 
+/// Stores the signed quote of a credit request, moving it to `awaiting_payment`
+pub async fn set_credit_request_quote(
+    request: i32,
+    quote: &CreditQuote,
+    connection: &mut AsyncPgConnection,
+) -> Result<CreditRequestsGet, String> {
+    let next = CreditRequestStatus::AwaitingPayment;
+    diesel::update(
+        credit_requests
+            .filter(id.eq(request))
+            .filter(request_status.eq_any(next.source_values())),
+    )
+    .set((quote, request_status.eq(next.as_str())))
+    .returning(CreditRequestsGet::as_returning())
+    .get_result::<CreditRequestsGet>(connection)
+    .await
+    .map_err(|e| format!("Error storing credit quote: {}", e))
+}
+
+/// Records the transaction the user submitted for a credit request, moving it to `next`:
+/// `tx_submitted` once verified on chain or `failed` if it reverted
+///
+/// Returns `None` if the request does not exist or cannot move to `next` from its state.
 pub async fn update_inclusion_details(
     user: String,
     order_id: i32,
     tx: String,
+    next: CreditRequestStatus,
     connection: &mut AsyncPgConnection,
-) -> Result<CreditRequestsGet, String> {
-    let res = diesel::update(
+) -> Result<Option<CreditRequestsGet>, String> {
+    diesel::update(
         credit_requests
             .filter(id.eq(order_id))
-            .filter(user_id.eq(user)),
+            .filter(user_id.eq(user))
+            .filter(request_status.eq_any(next.source_values())),
     )
-    .set((tx_hash.eq(tx), request_status.eq("INCLUDED")))
+    .set((tx_hash.eq(tx), request_status.eq(next.as_str())))
     .returning(CreditRequestsGet::as_returning())
     .get_result::<CreditRequestsGet>(connection)
     .await
-    .map_err(|e| format!("Error creating credit request: {}", e))?;
-    Ok(res)
+    .optional()
+    .map_err(|e| format!("Error updating credit request: {}", e))
+}
+
+/// Credit request of `user` with the quote it was registered with
+pub async fn get_credit_request_quote(
+    user: &String,
+    order_id: i32,
+    connection: &mut AsyncPgConnection,
+) -> Result<Option<CreditRequestQuote>, String> {
+    credit_requests
+        .filter(id.eq(order_id))
+        .filter(user_id.eq(user))
+        .select(CreditRequestQuote::as_select())
+        .first::<CreditRequestQuote>(connection)
+        .await
+        .optional()
+        .map_err(|e| format!("Error loading credit request: {}", e))
+}
+
+/// Last block of a chain scanned by the funds monitor
+pub async fn get_indexer_cursor(
+    chain: i32,
+    connection: &mut AsyncPgConnection,
+) -> Result<Option<IndexerBlockNumbers>, String> {
+    indexer_block_numbers::indexer_block_numbers
+        .filter(indexer_block_numbers::chain_id.eq(chain))
+        .select(IndexerBlockNumbers::as_select())
+        .first::<IndexerBlockNumbers>(connection)
+        .await
+        .optional()
+        .map_err(|e| format!("Error loading indexer cursor: {}", e))
+}
+
+/// Credits a deposit to its credit request in one transaction, the way the funds monitor does
+///
+/// The deposit is recorded in `processed_deposits` so it is never credited twice, and resolved if
+/// the funds monitor left it unmatched so it can't be refunded as well. Returns `None` if it was
+/// already recorded, or if the request cannot be credited from its state. Fails if the balance of
+/// the user is no longer held in the `unit` the deposit was priced in.
+pub async fn credit_deposit(
+    deposit: &ProcessedDepositCreate,
+    credit: &DepositCredit,
+    unit: BalanceUnit,
+    connection: &mut AsyncPgConnection,
+) -> Result<Option<CreditRequestsGet>, String> {
+    let result = connection
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                let inserted = diesel::insert_into(processed_deposits::processed_deposits)
+                    .values(deposit)
+                    .on_conflict_do_nothing()
+                    .execute(conn)
+                    .await?;
+                if inserted == 0 {
+                    return Err(diesel::result::Error::RollbackTransaction);
+                }
+
+                let row = diesel::update(
+                    credit_requests
+                        .filter(id.eq(deposit.credit_request_id))
+                        .filter(
+                            request_status.eq_any(CreditRequestStatus::Credited.source_values()),
+                        ),
+                )
+                .set((
+                    credit,
+                    request_status.eq(CreditRequestStatus::Credited.as_str()),
+                    request_type.eq("DEPOSIT"),
+                ))
+                .returning(CreditRequestsGet::as_returning())
+                .get_result::<CreditRequestsGet>(conn)
+                .await
+                .optional()?
+                .ok_or(diesel::result::Error::RollbackTransaction)?;
+
+                let credited = diesel::update(
+                    users::users
+                        .filter(users::id.eq(&row.user_id))
+                        .filter(users::balance_unit.eq(unit.as_str())),
+                )
+                .set(users::credit_balance.eq(users::credit_balance + &deposit.amount_credit))
+                .execute(conn)
+                .await?;
+                if credited == 0 {
+                    return Err(diesel::result::Error::NotFound);
+                }
+
+                diesel::update(
+                    unmatched_deposits::unmatched_deposits
+                        .filter(unmatched_deposits::chain_id.eq(deposit.chain_id))
+                        .filter(unmatched_deposits::tx_hash.eq(&deposit.tx_hash))
+                        .filter(unmatched_deposits::log_index.eq(deposit.log_index))
+                        .filter(
+                            unmatched_deposits::status.eq(UnmatchedDepositStatus::Pending.as_str()),
+                        ),
+                )
+                .set((
+                    unmatched_deposits::status.eq(UnmatchedDepositStatus::Attributed.as_str()),
+                    unmatched_deposits::credit_request_id.eq(row.id),
+                    unmatched_deposits::resolved_by.eq(&row.user_id),
+                    unmatched_deposits::resolved_at.eq(diesel::dsl::now),
+                ))
+                .execute(conn)
+                .await?;
+                Ok(row)
+            }
+            .scope_boxed()
+        })
+        .await;
+
+    match result {
+        Ok(row) => Ok(Some(row)),
+        Err(diesel::result::Error::RollbackTransaction) => Ok(None),
+        Err(diesel::result::Error::NotFound) => {
+            Err(format!("Balance of the user is no longer held in {}", unit))
+        }
+        Err(e) => Err(format!("Error crediting deposit: {}", e)),
+    }
 }
 
 pub async fn get_fund_list(
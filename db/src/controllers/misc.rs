@@ -3,7 +3,9 @@ use super::{
     users::{get_user, TxParams},
 };
 use crate::{
-    controllers::customer_expenditure::update_customer_expenditure,
+    controllers::customer_expenditure::{
+        error_log, update_customer_expenditure, SUBMISSION_LEASE_SECS,
+    },
     models::{
         apps::Apps, customer_expenditure::CustomerExpenditureGetWithPayload,
         indexer::IndexerBlockNumbers, user_model::User,
@@ -15,8 +17,14 @@ use crate::{
 };
 use avail_utils::submit_data::TransactionInfo;
 use bigdecimal::BigDecimal;
-use diesel::prelude::*;
-use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use chrono::Utc;
+use diesel::{
+    dsl::{now, IntervalDsl},
+    prelude::*,
+};
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
 use enigma::types::EncryptResponse;
 use uuid::Uuid;
 
@@ -58,77 +66,151 @@ pub async fn update_credit_balance(
     billed_from_credit: &BigDecimal,
     billed_from_fallback: &BigDecimal,
 ) -> Result<(), String> {
-    diesel::update(apps::apps.filter(apps::id.eq(&app.id)))
+    // The bill is in the unit of the balances when it was priced. The data is already submitted
+    // when the balances were converted in the meantime, the submission is left unbilled rather
+    // than debited in the wrong unit
+    let owners_in_unit = users::users
+        .filter(users::balance_unit.eq(tx_params.billed_unit.as_str()))
+        .select(users::id);
+    let updated = diesel::update(
+        apps::apps
+            .filter(apps::id.eq(&app.id))
+            .filter(apps::user_id.eq_any(owners_in_unit)),
+    )
+    .set((
+        apps::credit_balance.eq(apps::credit_balance - billed_from_credit),
+        apps::credit_used.eq(apps::credit_used + billed_from_credit),
+        apps::fallback_credit_used.eq(apps::fallback_credit_used + billed_from_fallback),
+    ))
+    .execute(connection)
+    .await
+    .map_err(|e| {
+        format!(
+            "Couldn't update app credit balance for app id {:?}, fee: {:?}. Error {:?}",
+            app.id, tx_params.fees, e
+        )
+    })?;
+    if updated == 0 {
+        error_log(&format!(
+            "Balances of app id {:?} are no longer held in {}, submission left unbilled",
+            app.id, tx_params.billed_unit
+        ));
+        return Ok(());
+    }
+
+    if billed_from_fallback > &BigDecimal::from(0) {
+        diesel::update(
+            users::users
+                .filter(users::id.eq(&app.user_id))
+                .filter(users::balance_unit.eq(tx_params.billed_unit.as_str())),
+        )
         .set((
-            apps::credit_balance.eq(apps::credit_balance - &billed_from_credit),
-            apps::credit_used.eq(apps::credit_used + &billed_from_credit),
-            apps::fallback_credit_used.eq(apps::fallback_credit_used + &billed_from_fallback),
+            users::credit_balance.eq(users::credit_balance - billed_from_fallback),
+            users::credit_used.eq(users::credit_used + billed_from_fallback),
         ))
         .execute(connection)
         .await
         .map_err(|e| {
             format!(
-                "Couldn't update app credit balance for app id {:?}, fee: {:?}. Error {:?}",
+                "Couldn't update user credit balance for app id {:?}, fee: {:?}. Error {:?}",
                 app.id, tx_params.fees, e
             )
         })?;
-
-    if billed_from_fallback > &BigDecimal::from(0) {
-        diesel::update(users::users.filter(users::id.eq(&app.user_id)))
-            .set((
-                users::credit_balance.eq(users::credit_balance - &billed_from_fallback),
-                users::credit_used.eq(users::credit_used + &billed_from_fallback),
-            ))
-            .execute(connection)
-            .await
-            .map_err(|e| {
-                format!(
-                    "Couldn't update user credit balance for app id {:?}, fee: {:?}. Error {:?}",
-                    app.id, tx_params.fees, e
-                )
-            })?;
     }
     Ok(())
 }
 
-/// Retrieves unresolved transactions from the database that have not exceeded retry limit
+/// Leases the unresolved transactions that have not exceeded retry limit to `worker`
 ///
 /// # Arguments
 /// * `connection` - Database connection handle
 /// * `retry` - Maximum number of retry attempts allowed
+/// * `limit` - Maximum number of transactions leased
+/// * `worker` - Worker the transactions are leased to, see `worker_id`
 ///
 /// # Returns
 /// * `Ok(Vec<CustomerExpenditureGetWithPayload>)` - List of unresolved transactions
 /// * `Err(String)` - Error message if database query fails
 ///
 /// # Description
-/// Queries the database for transactions that:
+/// Claims, with `FOR UPDATE SKIP LOCKED`, the transactions that:
 /// 1. Have an error or payload
 /// 2. Have not exceeded the maximum retry count
-/// 3. Are ordered by creation date descending
-pub async fn get_unresolved_transactions(
+/// 3. Have not failed, and whose backoff elapsed
+/// 4. Are not leased to another worker, or whose lease expired
+/// 5. Are ordered by creation date descending
+///
+/// Each of them is leased for `SUBMISSION_LEASE_SECS`, other fallback monitors and the data
+/// submission workers skip them until they are released.
+pub async fn lease_unresolved_transactions(
     connection: &mut AsyncPgConnection,
     retry: i32,
     limit: i64,
+    worker: &str,
 ) -> Result<Vec<(CustomerExpenditureGetWithPayload, Apps, User)>, String> {
-    customer_expenditures::customer_expenditures
-        .inner_join(apps::apps)
-        .inner_join(users::users)
-        .filter(customer_expenditures::error.is_not_null())
-        .or_filter(customer_expenditures::payload.is_not_null().and(
-            customer_expenditures::created_at.lt(diesel::dsl::sql::<diesel::sql_types::Timestamp>(
-                "NOW() - INTERVAL '15 minutes'",
-            )),
-        ))
-        .filter(customer_expenditures::retry_count.lt(retry))
-        .order(customer_expenditures::created_at.desc())
-        .limit(limit)
-        .select((
-            CustomerExpenditureGetWithPayload::as_select(),
-            Apps::as_select(),
-            User::as_select(),
-        ))
-        .load::<(CustomerExpenditureGetWithPayload, Apps, User)>(connection)
+    connection
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                let ids = customer_expenditures::customer_expenditures
+                    .filter(customer_expenditures::error.is_not_null())
+                    .or_filter(customer_expenditures::payload.is_not_null().and(
+                        customer_expenditures::created_at.lt(diesel::dsl::sql::<
+                            diesel::sql_types::Timestamp,
+                        >(
+                            "NOW() - INTERVAL '15 minutes'"
+                        )),
+                    ))
+                    .filter(customer_expenditures::retry_count.lt(retry))
+                    .filter(customer_expenditures::failed_at.is_null())
+                    .filter(
+                        customer_expenditures::next_retry_at
+                            .is_null()
+                            .or(customer_expenditures::next_retry_at.le(Utc::now().naive_utc())),
+                    )
+                    .filter(
+                        customer_expenditures::lock_expires_at
+                            .is_null()
+                            .or(customer_expenditures::lock_expires_at.lt(now))
+                            .or(customer_expenditures::locked_by.eq(worker)),
+                    )
+                    .order(customer_expenditures::created_at.desc())
+                    .limit(limit)
+                    .select(customer_expenditures::id)
+                    .for_update()
+                    .skip_locked()
+                    .load::<Uuid>(conn)
+                    .await?;
+                if ids.is_empty() {
+                    return Ok(vec![]);
+                }
+
+                diesel::update(
+                    customer_expenditures::customer_expenditures
+                        .filter(customer_expenditures::id.eq_any(&ids)),
+                )
+                .set((
+                    customer_expenditures::locked_by.eq(worker),
+                    customer_expenditures::lock_expires_at
+                        .eq((now + SUBMISSION_LEASE_SECS.seconds()).nullable()),
+                ))
+                .execute(conn)
+                .await?;
+
+                customer_expenditures::customer_expenditures
+                    .inner_join(apps::apps)
+                    .inner_join(users::users)
+                    .filter(customer_expenditures::id.eq_any(&ids))
+                    .order(customer_expenditures::created_at.desc())
+                    .select((
+                        CustomerExpenditureGetWithPayload::as_select(),
+                        Apps::as_select(),
+                        User::as_select(),
+                    ))
+                    .load::<(CustomerExpenditureGetWithPayload, Apps, User)>(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
         .await
         .map_err(|e| e.to_string())
 }
@@ -258,13 +340,14 @@ pub async fn update_database_on_submission(
         _ => (BigDecimal::from(0), tx_params.amount_data_billed.clone()),
     };
 
-    // Convert BigDecimal values to u64 and create wallet store
-    let fallback_u64 = billed_from_fallback
+    // Convert BigDecimal values to u64 and create wallet store, scaled to the smallest unit stored
+    let scale = BigDecimal::new(1.into(), -tx_params.billed_unit.wallet_exponent());
+    let fallback_u64 = (&billed_from_fallback * &scale)
         .round(0)
         .to_string()
         .parse::<i128>()
         .unwrap_or(0);
-    let credit_u64 = billed_from_credit
+    let credit_u64 = (&billed_from_credit * &scale)
         .round(0)
         .to_string()
         .parse::<i128>()
@@ -278,7 +361,7 @@ pub async fn update_database_on_submission(
         result,
         encrypted_data,
         &fees_as_bigdecimal,
-        &tx_params.amount_data_billed,
+        &tx_params,
         &wallet_store,
         submission_id,
         connection,
@@ -1,6 +1,17 @@
+pub mod admin_approvals;
 pub mod api_keys;
 pub mod apps;
+pub mod audit_events;
 pub mod customer_expenditure;
+pub mod deposit_addresses;
+pub mod fee_reconciliations;
 pub mod fund;
 pub mod misc;
+pub mod organisations;
+pub mod signing_keys;
+#[allow(clippy::module_inception)]
+mod test;
+pub mod supported_tokens;
+pub mod unmatched_deposits;
 pub mod users;
+pub mod withdrawals;
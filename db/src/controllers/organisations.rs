@@ -0,0 +1,214 @@
+use crate::{
+    models::{
+        apps::Apps,
+        organisations::{
+            OrgRole, Organisation, OrganisationCreate, OrganisationMember, OrganisationMemberCreate,
+        },
+    },
+    schema::{
+        apps::dsl as apps, organisation_members::dsl as organisation_members,
+        organisations::dsl as organisations, users::dsl as users,
+    },
+};
+use diesel::prelude::*;
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
+use uuid::Uuid;
+
+/// Inserts an organisation along with the owner membership of its billing account.
+pub async fn insert_organisation(
+    connection: &mut AsyncPgConnection,
+    organisation: &OrganisationCreate,
+) -> Result<Organisation, diesel::result::Error> {
+    let created = diesel::insert_into(organisations::organisations)
+        .values(organisation)
+        .returning(Organisation::as_returning())
+        .get_result::<Organisation>(connection)
+        .await?;
+
+    diesel::insert_into(organisation_members::organisation_members)
+        .values(&OrganisationMemberCreate {
+            org_id: created.id,
+            user_id: created.owner_id.clone(),
+            role: OrgRole::Owner.to_string(),
+        })
+        .execute(connection)
+        .await?;
+
+    Ok(created)
+}
+
+/// Creates an organisation along with its billing account, holding the balance in the unit the
+/// balance of its owner is held in.
+pub async fn create_organisation(
+    connection: &mut AsyncPgConnection,
+    organisation: &OrganisationCreate,
+) -> Result<Organisation, String> {
+    connection
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                let unit = users::users
+                    .find(&organisation.owner_id)
+                    .select(users::balance_unit)
+                    .first::<String>(conn)
+                    .await?;
+                diesel::insert_into(users::users)
+                    .values((
+                        users::id.eq(&organisation.billing_id),
+                        users::name.eq(&organisation.name),
+                        users::balance_unit.eq(unit),
+                    ))
+                    .execute(conn)
+                    .await?;
+                insert_organisation(conn, organisation).await
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Returns the organisation billed to the user, created on registration.
+pub async fn get_personal_organisation(
+    connection: &mut AsyncPgConnection,
+    user: &String,
+) -> Result<Organisation, String> {
+    organisations::organisations
+        .filter(organisations::billing_id.eq(user))
+        .select(Organisation::as_select())
+        .first::<Organisation>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub async fn get_organisation_membership(
+    connection: &mut AsyncPgConnection,
+    org: &Uuid,
+    user: &String,
+) -> Result<(Organisation, OrganisationMember), String> {
+    organisations::organisations
+        .inner_join(organisation_members::organisation_members)
+        .filter(organisations::id.eq(org))
+        .filter(organisation_members::user_id.eq(user))
+        .select((Organisation::as_select(), OrganisationMember::as_select()))
+        .first::<(Organisation, OrganisationMember)>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Retrieves an app together with the membership of the user in the organisation owning it.
+pub async fn get_app_membership(
+    connection: &mut AsyncPgConnection,
+    app: &Uuid,
+    user: &String,
+) -> Result<(Apps, OrganisationMember), String> {
+    apps::apps
+        .inner_join(
+            organisation_members::organisation_members
+                .on(organisation_members::org_id.eq(apps::org_id)),
+        )
+        .filter(apps::id.eq(app))
+        .filter(organisation_members::user_id.eq(user))
+        .select((Apps::as_select(), OrganisationMember::as_select()))
+        .first::<(Apps, OrganisationMember)>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub async fn get_user_organisations(
+    connection: &mut AsyncPgConnection,
+    user: &String,
+) -> Result<Vec<(Organisation, OrganisationMember)>, String> {
+    organisations::organisations
+        .inner_join(organisation_members::organisation_members)
+        .filter(organisation_members::user_id.eq(user))
+        .order(organisations::created_at.asc())
+        .select((Organisation::as_select(), OrganisationMember::as_select()))
+        .load::<(Organisation, OrganisationMember)>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Retrieves every app the user can access through organisation membership.
+///
+/// When `org` is set only apps of that organisation are returned.
+pub async fn get_member_apps(
+    connection: &mut AsyncPgConnection,
+    user: &String,
+    org: &Option<Uuid>,
+) -> Result<Vec<(Apps, OrganisationMember)>, String> {
+    let mut query = apps::apps
+        .inner_join(
+            organisation_members::organisation_members
+                .on(organisation_members::org_id.eq(apps::org_id)),
+        )
+        .filter(organisation_members::user_id.eq(user))
+        .select((Apps::as_select(), OrganisationMember::as_select()))
+        .into_boxed();
+    if let Some(org) = org {
+        query = query.filter(apps::org_id.eq(org));
+    }
+    query
+        .load::<(Apps, OrganisationMember)>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub async fn get_organisation_members(
+    connection: &mut AsyncPgConnection,
+    org: &Uuid,
+) -> Result<Vec<OrganisationMember>, String> {
+    organisation_members::organisation_members
+        .filter(organisation_members::org_id.eq(org))
+        .order(organisation_members::created_at.asc())
+        .select(OrganisationMember::as_select())
+        .load::<OrganisationMember>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub async fn add_organisation_member(
+    connection: &mut AsyncPgConnection,
+    member: &OrganisationMemberCreate,
+) -> Result<OrganisationMember, String> {
+    diesel::insert_into(organisation_members::organisation_members)
+        .values(member)
+        .returning(OrganisationMember::as_returning())
+        .get_result::<OrganisationMember>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub async fn update_organisation_member_role(
+    connection: &mut AsyncPgConnection,
+    org: &Uuid,
+    user: &String,
+    new_role: &OrgRole,
+) -> Result<OrganisationMember, String> {
+    diesel::update(
+        organisation_members::organisation_members
+            .filter(organisation_members::org_id.eq(org))
+            .filter(organisation_members::user_id.eq(user)),
+    )
+    .set(organisation_members::role.eq(new_role.as_str()))
+    .returning(OrganisationMember::as_returning())
+    .get_result::<OrganisationMember>(connection)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+pub async fn remove_organisation_member(
+    connection: &mut AsyncPgConnection,
+    org: &Uuid,
+    user: &String,
+) -> Result<usize, String> {
+    diesel::delete(
+        organisation_members::organisation_members
+            .filter(organisation_members::org_id.eq(org))
+            .filter(organisation_members::user_id.eq(user)),
+    )
+    .execute(connection)
+    .await
+    .map_err(|e| e.to_string())
+}
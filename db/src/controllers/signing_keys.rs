@@ -0,0 +1,56 @@
+use crate::{
+    models::signing_keys::{SigningKey, SigningKeyCreate},
+    schema::signing_keys::dsl::*,
+};
+
+use diesel::ExpressionMethods;
+use diesel::QueryDsl;
+use diesel::SelectableHelper;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use uuid::Uuid;
+
+use super::misc::get_account_by_id;
+
+pub async fn create_signing_key(
+    connection: &mut AsyncPgConnection,
+    key: &SigningKeyCreate,
+) -> Result<(), String> {
+    let account = get_account_by_id(connection, &key.app_id).await?;
+    if account.0.user_id != key.user_id {
+        return Err("Account does not belong to user".to_string());
+    }
+    diesel::insert_into(signing_keys)
+        .values(key)
+        .execute(&mut *connection)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub async fn get_signing_keys(
+    connection: &mut AsyncPgConnection,
+    apps: &Vec<Uuid>,
+) -> Result<Vec<SigningKey>, String> {
+    signing_keys
+        .filter(app_id.eq_any(apps))
+        .select(SigningKey::as_select())
+        .load(&mut *connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub async fn delete_signing_key(
+    connection: &mut AsyncPgConnection,
+    apps: &Vec<Uuid>,
+    key_id: &Uuid,
+) -> Result<Vec<SigningKey>, String> {
+    diesel::delete(
+        signing_keys
+            .filter(app_id.eq_any(apps))
+            .filter(id.eq(key_id)),
+    )
+    .returning(SigningKey::as_select())
+    .load(&mut *connection)
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,82 @@
+use crate::{
+    models::supported_tokens::{
+        normalize_token_address, SupportedToken, SupportedTokenCreate, SupportedTokenUpdate,
+    },
+    schema::supported_tokens::dsl as supported_tokens,
+};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// Retrieves the registered tokens, ordered by chain, including disabled tokens if `all` is set
+pub async fn get_supported_tokens(
+    connection: &mut AsyncPgConnection,
+    all: bool,
+) -> Result<Vec<SupportedToken>, String> {
+    let mut query = supported_tokens::supported_tokens
+        .select(SupportedToken::as_select())
+        .into_boxed();
+    if !all {
+        query = query.filter(supported_tokens::enabled.eq(true));
+    }
+    query
+        .order((supported_tokens::chain_id, supported_tokens::id))
+        .load::<SupportedToken>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Retrieves a token by chain and address, whether it is enabled or not
+pub async fn get_supported_token(
+    connection: &mut AsyncPgConnection,
+    chain: i32,
+    address: &str,
+) -> Result<Option<SupportedToken>, String> {
+    supported_tokens::supported_tokens
+        .filter(supported_tokens::chain_id.eq(chain))
+        .filter(supported_tokens::token_address.eq(normalize_token_address(address)))
+        .select(SupportedToken::as_select())
+        .first::<SupportedToken>(connection)
+        .await
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+pub async fn create_supported_token(
+    connection: &mut AsyncPgConnection,
+    token: &SupportedTokenCreate,
+) -> Result<SupportedToken, diesel::result::Error> {
+    diesel::insert_into(supported_tokens::supported_tokens)
+        .values(token)
+        .returning(SupportedToken::as_returning())
+        .get_result::<SupportedToken>(connection)
+        .await
+}
+
+/// Updates a token, returning it before and after the change
+pub async fn update_supported_token(
+    connection: &mut AsyncPgConnection,
+    id: i32,
+    changes: &SupportedTokenUpdate,
+) -> Result<(SupportedToken, SupportedToken), diesel::result::Error> {
+    let before = supported_tokens::supported_tokens
+        .find(id)
+        .select(SupportedToken::as_select())
+        .first::<SupportedToken>(connection)
+        .await?;
+    let after = diesel::update(supported_tokens::supported_tokens.find(id))
+        .set(changes)
+        .returning(SupportedToken::as_returning())
+        .get_result::<SupportedToken>(connection)
+        .await?;
+    Ok((before, after))
+}
+
+pub async fn delete_supported_token(
+    connection: &mut AsyncPgConnection,
+    id: i32,
+) -> Result<SupportedToken, diesel::result::Error> {
+    diesel::delete(supported_tokens::supported_tokens.find(id))
+        .returning(SupportedToken::as_returning())
+        .get_result::<SupportedToken>(connection)
+        .await
+}
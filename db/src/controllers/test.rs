@@ -0,0 +1,492 @@
+#[cfg(test)]
+pub mod test {
+    use crate::controllers::{
+        admin_approvals::{approve_fund_user, grant_or_queue_fund_user, FundUserGrant},
+        audit_events::create_audit_event,
+        customer_expenditure::{add_error_entry, lease_submission, release_submission},
+        fund::credit_deposit,
+        organisations::get_personal_organisation,
+        unmatched_deposits::{
+            attribute_unmatched_deposit, refund_unmatched_deposit, AttributeError,
+        },
+        users::{fund_user, get_user, register_new_user},
+        withdrawals::create_withdrawal,
+    };
+    use crate::models::{
+        admin_approvals::AdminApprovalCreate,
+        audit_events::{AuditAction, AuditEventCreate},
+        credit_requests::DepositCredit,
+        processed_deposits::{DepositPrice, ProcessedDepositCreate},
+        unmatched_deposits::UnmatchedDepositCreate,
+        user_model::{BalanceUnit, UserCreate},
+        withdrawals::WithdrawalCreate,
+    };
+    use crate::schema::{
+        credit_requests, customer_expenditures, processed_deposits, unmatched_deposits, users,
+    };
+    use crate::test_utils::TestDB;
+    use bigdecimal::BigDecimal;
+    use chrono::{Duration, NaiveDateTime, Utc};
+    use diesel::{ExpressionMethods, QueryDsl};
+    use diesel_async::{AsyncPgConnection, RunQueryDsl};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    const USER: &str = "user@example.com";
+    const ADMIN: &str = "admin@example.com";
+    const SECOND_ADMIN: &str = "second-admin@example.com";
+
+    async fn insert_user(connection: &mut AsyncPgConnection) {
+        register_new_user(
+            connection,
+            UserCreate {
+                id: USER.to_string(),
+                name: "user".to_string(),
+                sumsub_timestamp: None,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    fn approval(expires_at: chrono::NaiveDateTime) -> AdminApprovalCreate {
+        AdminApprovalCreate {
+            id: Uuid::new_v4(),
+            action: AuditAction::AdminFundUser.as_str().to_string(),
+            payload: json!({ "user_id": USER, "amount": "60" }),
+            requested_by: ADMIN.to_string(),
+            expires_at,
+        }
+    }
+
+    /// Grants `amount` and records its audit event, as the `fund_user` route does
+    async fn grant(connection: &mut AsyncPgConnection, amount: &BigDecimal) -> FundUserGrant {
+        let grant = grant_or_queue_fund_user(
+            connection,
+            &USER.to_string(),
+            amount,
+            &BigDecimal::from(100),
+            3600,
+            &approval((Utc::now() + Duration::hours(1)).naive_utc()),
+        )
+        .await
+        .unwrap();
+        if let FundUserGrant::Funded(_) = grant {
+            let event = AuditEventCreate {
+                after: Some(json!({ "amount": amount })),
+                ..AuditEventCreate::new(ADMIN, AuditAction::AdminFundUser, USER)
+            };
+            create_audit_event(connection, &event).await.unwrap();
+        }
+        grant
+    }
+
+    #[tokio::test]
+    async fn test_fund_user_grants_are_summed_against_threshold() {
+        let db = TestDB::init();
+        let mut connection = db.pool.get().await.unwrap();
+        insert_user(&mut connection).await;
+
+        let amount = BigDecimal::from(60);
+        assert!(matches!(
+            grant(&mut connection, &amount).await,
+            FundUserGrant::Funded(_)
+        ));
+        // Below the threshold on its own, above it with the previous grant
+        assert!(matches!(
+            grant(&mut connection, &amount).await,
+            FundUserGrant::Queued(_)
+        ));
+
+        let user = get_user(&mut connection, &USER.to_string()).await.unwrap();
+        assert_eq!(user.credit_balance, amount);
+    }
+
+    #[tokio::test]
+    async fn test_expired_approval_is_not_approved() {
+        let db = TestDB::init();
+        let mut connection = db.pool.get().await.unwrap();
+        insert_user(&mut connection).await;
+
+        let amount = BigDecimal::from(60);
+        for (expires_at, approved) in [
+            ((Utc::now() - Duration::minutes(1)).naive_utc(), false),
+            ((Utc::now() + Duration::hours(1)).naive_utc(), true),
+        ] {
+            let queued = approval(expires_at);
+            diesel::insert_into(crate::schema::admin_approvals::table)
+                .values(&queued)
+                .execute(&mut connection)
+                .await
+                .unwrap();
+
+            let result = approve_fund_user(
+                &mut connection,
+                &queued.id,
+                &SECOND_ADMIN.to_string(),
+                &USER.to_string(),
+                &amount,
+            )
+            .await;
+            assert_eq!(result.is_ok(), approved);
+        }
+
+        let user = get_user(&mut connection, &USER.to_string()).await.unwrap();
+        assert_eq!(user.credit_balance, amount);
+    }
+
+    async fn insert_credit_request(connection: &mut AsyncPgConnection, status: &str) -> i32 {
+        diesel::insert_into(credit_requests::table)
+            .values((
+                credit_requests::user_id.eq(USER),
+                credit_requests::request_status.eq(status),
+                credit_requests::request_type.eq("DEPOSIT"),
+            ))
+            .returning(credit_requests::id)
+            .get_result(connection)
+            .await
+            .unwrap()
+    }
+
+    async fn insert_unmatched_deposit(connection: &mut AsyncPgConnection, tx_hash: &str) -> i32 {
+        diesel::insert_into(unmatched_deposits::table)
+            .values(&UnmatchedDepositCreate {
+                chain_id: 1,
+                tx_hash: tx_hash.to_string(),
+                log_index: 0,
+                block_number: 10,
+                block_hash: "0xaa".to_string(),
+                from_address: "0xbb".to_string(),
+                token_address: "0xcc".to_string(),
+                amount_paid: BigDecimal::from(1),
+                order_id: None,
+                reason: "Credit request not found".to_string(),
+            })
+            .returning(unmatched_deposits::id)
+            .get_result(connection)
+            .await
+            .unwrap()
+    }
+
+    fn price() -> DepositPrice {
+        DepositPrice {
+            token_usd_price: BigDecimal::from(1),
+            avail_usd_price: BigDecimal::from(1),
+            price_source: "test".to_string(),
+            priced_at: Utc::now().naive_utc(),
+        }
+    }
+
+    /// Attributes the deposit `id` to `USER`, crediting 5
+    async fn attribute(
+        connection: &mut AsyncPgConnection,
+        id: i32,
+        order: Option<i32>,
+    ) -> Result<(), AttributeError> {
+        let user = get_user(connection, &USER.to_string()).await.unwrap();
+        attribute_unmatched_deposit(
+            connection,
+            id,
+            &ADMIN.to_string(),
+            &user,
+            order,
+            &BigDecimal::from(5),
+            &price(),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    #[tokio::test]
+    async fn test_unmatched_deposit_attribution_errors() {
+        let db = TestDB::init();
+        let mut connection = db.pool.get().await.unwrap();
+        insert_user(&mut connection).await;
+        let credited = insert_credit_request(&mut connection, "credited").await;
+        let pending = insert_credit_request(&mut connection, "awaiting_payment").await;
+        let first = insert_unmatched_deposit(&mut connection, "0x01").await;
+        let second = insert_unmatched_deposit(&mut connection, "0x02").await;
+
+        assert!(matches!(
+            attribute(&mut connection, first, Some(pending + 100)).await,
+            Err(AttributeError::RequestNotFound(_))
+        ));
+        assert!(matches!(
+            attribute(&mut connection, first, Some(credited)).await,
+            Err(AttributeError::RequestCredited(_))
+        ));
+        assert!(matches!(
+            attribute(&mut connection, first + 100, Some(pending)).await,
+            Err(AttributeError::DepositNotPending)
+        ));
+        assert!(attribute(&mut connection, first, Some(pending))
+            .await
+            .is_ok());
+        assert!(matches!(
+            attribute(&mut connection, first, None).await,
+            Err(AttributeError::DepositNotPending)
+        ));
+        assert!(attribute(&mut connection, second, None).await.is_ok());
+
+        let user = get_user(&mut connection, &USER.to_string()).await.unwrap();
+        assert_eq!(user.credit_balance, BigDecimal::from(10));
+    }
+
+    /// Deposit `tx_hash` of chain 1 crediting `amount` to `request`
+    fn processed_deposit(request: i32, tx_hash: &str, amount: i32) -> ProcessedDepositCreate {
+        ProcessedDepositCreate {
+            chain_id: 1,
+            tx_hash: tx_hash.to_string(),
+            log_index: 0,
+            block_number: 10,
+            block_hash: "0xaa".to_string(),
+            credit_request_id: request,
+            amount_credit: BigDecimal::from(amount),
+            price: price(),
+        }
+    }
+
+    /// Records the deposit `tx_hash` of chain 1 as credited `amount` against `request`
+    async fn insert_processed_deposit(
+        connection: &mut AsyncPgConnection,
+        request: i32,
+        tx_hash: &str,
+        amount: i32,
+    ) {
+        diesel::insert_into(processed_deposits::table)
+            .values(&processed_deposit(request, tx_hash, amount))
+            .execute(connection)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_credited_deposit_is_not_refunded() {
+        let db = TestDB::init();
+        let mut connection = db.pool.get().await.unwrap();
+        insert_user(&mut connection).await;
+        let request = insert_credit_request(&mut connection, "credited").await;
+        let credited = insert_unmatched_deposit(&mut connection, "0x01").await;
+        let unmatched = insert_unmatched_deposit(&mut connection, "0x02").await;
+        insert_processed_deposit(&mut connection, request, "0x01", 5).await;
+
+        let admin = ADMIN.to_string();
+        assert_eq!(
+            refund_unmatched_deposit(&mut connection, credited, &admin, &None)
+                .await
+                .unwrap_err(),
+            "Deposit is already credited"
+        );
+        assert!(
+            refund_unmatched_deposit(&mut connection, unmatched, &admin, &None)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_credited_deposit_resolves_unmatched_deposit() {
+        let db = TestDB::init();
+        let mut connection = db.pool.get().await.unwrap();
+        insert_user(&mut connection).await;
+        let request = insert_credit_request(&mut connection, "awaiting_payment").await;
+        let unmatched = insert_unmatched_deposit(&mut connection, "0x01").await;
+
+        let credit = DepositCredit {
+            amount_credit: BigDecimal::from(5),
+            chain_id: 1,
+            tx_hash: "0x01".to_string(),
+            token_address: "0xcc".to_string(),
+            amount_paid: BigDecimal::from(1),
+            quote_outcome: None,
+            quote_difference: None,
+        };
+        let deposit = processed_deposit(request, "0x01", 5);
+        assert!(
+            credit_deposit(&deposit, &credit, BalanceUnit::Credits, &mut connection)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        let (status, credit_request_id): (String, Option<i32>) = unmatched_deposits::table
+            .filter(unmatched_deposits::id.eq(unmatched))
+            .select((
+                unmatched_deposits::status,
+                unmatched_deposits::credit_request_id,
+            ))
+            .first(&mut connection)
+            .await
+            .unwrap();
+        assert_eq!(status, "attributed");
+        assert_eq!(credit_request_id, Some(request));
+        assert_eq!(
+            refund_unmatched_deposit(&mut connection, unmatched, &ADMIN.to_string(), &None)
+                .await
+                .unwrap_err(),
+            "Deposit is not pending"
+        );
+    }
+
+    fn withdrawal(credits: i32) -> WithdrawalCreate {
+        WithdrawalCreate {
+            user_id: USER.to_string(),
+            chain_id: 1,
+            token_address: "0xcc".to_string(),
+            recipient: "0xbb".to_string(),
+            credits: BigDecimal::from(credits),
+            amount: BigDecimal::from(credits),
+            token_usd_price: BigDecimal::from(1),
+            avail_usd_price: BigDecimal::from(1),
+            price_source: "test".to_string(),
+            priced_at: Utc::now().naive_utc(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spent_credits_are_not_refunded() {
+        let db = TestDB::init();
+        let mut connection = db.pool.get().await.unwrap();
+        insert_user(&mut connection).await;
+        let request = insert_credit_request(&mut connection, "credited").await;
+        // 100 credits bought and 100 granted
+        insert_processed_deposit(&mut connection, request, "0x01", 100).await;
+        fund_user(&mut connection, &USER.to_string(), &BigDecimal::from(200))
+            .await
+            .unwrap();
+
+        assert!(
+            create_withdrawal(&mut connection, &withdrawal(40), BalanceUnit::Credits)
+                .await
+                .is_ok()
+        );
+        diesel::update(users::table.find(USER))
+            .set((
+                users::credit_balance.eq(users::credit_balance - BigDecimal::from(60)),
+                users::credit_used.eq(BigDecimal::from(60)),
+            ))
+            .execute(&mut connection)
+            .await
+            .unwrap();
+
+        // The 100 credits left were granted
+        assert!(matches!(
+            create_withdrawal(&mut connection, &withdrawal(1), BalanceUnit::Credits).await,
+            Err(e) if e == "Credits exceed the purchased credits that can be refunded"
+        ));
+        let user = get_user(&mut connection, &USER.to_string()).await.unwrap();
+        assert_eq!(user.credit_balance, BigDecimal::from(100));
+    }
+
+    /// Inserts a pending submission of `USER`, along with an app of their personal organisation
+    async fn insert_submission(connection: &mut AsyncPgConnection) -> Uuid {
+        let submission = Uuid::new_v4();
+        let org = get_personal_organisation(connection, &USER.to_string())
+            .await
+            .unwrap();
+        for query in [
+            format!(
+                "INSERT INTO apps (id, user_id, app_id, org_id) VALUES \
+                 ('1c9c8a3e-8b7e-4a43-9a6d-0f3b1c2d4e5f', '{}', 1, '{}')",
+                USER, org.id
+            ),
+            format!(
+                "INSERT INTO customer_expenditures (id, user_id, amount_data, app_id, payload) \
+                 VALUES ('{}', '{}', '1 KB', '1c9c8a3e-8b7e-4a43-9a6d-0f3b1c2d4e5f', '\\x01')",
+                submission, USER
+            ),
+        ] {
+            diesel::sql_query(query).execute(connection).await.unwrap();
+        }
+        submission
+    }
+
+    #[tokio::test]
+    async fn test_submission_is_leased_to_one_worker() {
+        let db = TestDB::init();
+        let mut connection = db.pool.get().await.unwrap();
+        insert_user(&mut connection).await;
+        let submission = insert_submission(&mut connection).await;
+
+        assert!(lease_submission(&mut connection, &submission, "fallback")
+            .await
+            .unwrap());
+        // Skipped by every other worker, renewed for its holder
+        assert!(!lease_submission(&mut connection, &submission, "consumer")
+            .await
+            .unwrap());
+        assert!(lease_submission(&mut connection, &submission, "fallback")
+            .await
+            .unwrap());
+
+        // Only the holder can release it
+        release_submission(&mut connection, &submission, "consumer")
+            .await
+            .unwrap();
+        assert!(!lease_submission(&mut connection, &submission, "consumer")
+            .await
+            .unwrap());
+        release_submission(&mut connection, &submission, "fallback")
+            .await
+            .unwrap();
+        assert!(lease_submission(&mut connection, &submission, "consumer")
+            .await
+            .unwrap());
+    }
+
+    /// Records `e` on a submission and reads back its error class, next retry and failure
+    async fn record_error(
+        connection: &mut AsyncPgConnection,
+        submission: Uuid,
+        e: &str,
+    ) -> (Option<String>, Option<NaiveDateTime>, Option<NaiveDateTime>) {
+        add_error_entry(&submission, e.to_string(), connection).await;
+        customer_expenditures::table
+            .filter(customer_expenditures::id.eq(submission))
+            .select((
+                customer_expenditures::error_class,
+                customer_expenditures::next_retry_at,
+                customer_expenditures::failed_at,
+            ))
+            .first(connection)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_backed_off_or_failed() {
+        let db = TestDB::init();
+        let mut connection = db.pool.get().await.unwrap();
+        insert_user(&mut connection).await;
+        let submission = insert_submission(&mut connection).await;
+
+        let in_secs =
+            |at: Option<NaiveDateTime>| (at.unwrap() - Utc::now().naive_utc()).num_seconds();
+
+        let (class, next_retry_at, failed_at) =
+            record_error(&mut connection, submission, "RPC call timed out").await;
+        assert_eq!(class.as_deref(), Some("retryable"));
+        assert!((25..=30).contains(&in_secs(next_retry_at)));
+        assert!(failed_at.is_none());
+
+        let (class, next_retry_at, failed_at) = record_error(
+            &mut connection,
+            submission,
+            "Insufficient credits for user id",
+        )
+        .await;
+        assert_eq!(class.as_deref(), Some("user_action"));
+        assert!((895..=900).contains(&in_secs(next_retry_at)));
+        assert!(failed_at.is_none());
+
+        let (class, next_retry_at, failed_at) = record_error(
+            &mut connection,
+            submission,
+            "Transaction was executed but execution failed.",
+        )
+        .await;
+        assert_eq!(class.as_deref(), Some("non_retryable"));
+        assert!(next_retry_at.is_none());
+        assert!(failed_at.is_some());
+    }
+}
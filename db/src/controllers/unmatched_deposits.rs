@@ -0,0 +1,273 @@
+use crate::{
+    models::{
+        credit_requests::CreditRequestStatus,
+        processed_deposits::{DepositPrice, ProcessedDepositCreate},
+        unmatched_deposits::{UnmatchedDeposit, UnmatchedDepositStatus},
+        user_model::{BalanceUnit, User},
+    },
+    schema::{
+        credit_requests::dsl as credit_requests, processed_deposits::dsl as processed_deposits,
+        unmatched_deposits::dsl as unmatched_deposits, users::dsl as users,
+    },
+};
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
+use std::fmt;
+
+pub async fn get_unmatched_deposit(
+    connection: &mut AsyncPgConnection,
+    id: i32,
+) -> Result<UnmatchedDeposit, String> {
+    unmatched_deposits::unmatched_deposits
+        .find(id)
+        .select(UnmatchedDeposit::as_select())
+        .first::<UnmatchedDeposit>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Retrieves unmatched deposits, newest first
+pub async fn get_unmatched_deposits(
+    connection: &mut AsyncPgConnection,
+    status: &Option<UnmatchedDepositStatus>,
+    limit: i64,
+) -> Result<Vec<UnmatchedDeposit>, String> {
+    let mut query = unmatched_deposits::unmatched_deposits
+        .select(UnmatchedDeposit::as_select())
+        .into_boxed();
+    if let Some(status) = status {
+        query = query.filter(unmatched_deposits::status.eq(status.as_str()));
+    }
+    query
+        .order(unmatched_deposits::created_at.desc())
+        .limit(limit)
+        .load::<UnmatchedDeposit>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn resolve_error(e: diesel::result::Error) -> String {
+    match e {
+        diesel::result::Error::NotFound => "Deposit is not pending".to_string(),
+        e => e.to_string(),
+    }
+}
+
+/// Reasons an unmatched deposit is not attributed
+#[derive(Debug)]
+pub enum AttributeError {
+    /// The deposit does not exist or is already resolved
+    DepositNotPending,
+    /// The credit request does not exist or belongs to another user
+    RequestNotFound(i32),
+    /// The credit request is already credited
+    RequestCredited(i32),
+    /// The balance of the user is no longer held in the unit the deposit was priced in
+    UnitChanged(BalanceUnit),
+    Database(diesel::result::Error),
+}
+
+impl From<diesel::result::Error> for AttributeError {
+    fn from(e: diesel::result::Error) -> Self {
+        AttributeError::Database(e)
+    }
+}
+
+impl fmt::Display for AttributeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeError::DepositNotPending => write!(f, "Deposit is not pending"),
+            AttributeError::RequestNotFound(id) => write!(f, "Credit request {} not found", id),
+            AttributeError::RequestCredited(id) => {
+                write!(f, "Credit request {} is already processed", id)
+            }
+            AttributeError::UnitChanged(unit) => {
+                write!(f, "Balance of the user is no longer held in {}", unit)
+            }
+            AttributeError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Credits a pending unmatched deposit to `user`, the same way the funds monitor credits a
+/// matched deposit
+///
+/// The deposit is credited against `order`, which must be a credit request of `user` not credited
+/// yet, or against a new credit request when `order` is `None`, and recorded in
+/// `processed_deposits` so it is never credited twice. `amount_credit` is in the unit the balance
+/// of `user` is held in.
+pub async fn attribute_unmatched_deposit(
+    connection: &mut AsyncPgConnection,
+    id: i32,
+    resolver: &String,
+    user: &User,
+    order: Option<i32>,
+    amount_credit: &BigDecimal,
+    price: &DepositPrice,
+) -> Result<(UnmatchedDeposit, User), AttributeError> {
+    let unit = user.balance_unit().map_err(|e| {
+        AttributeError::Database(diesel::result::Error::DeserializationError(e.into()))
+    })?;
+    let user = &user.id;
+    connection
+        .transaction::<_, AttributeError, _>(|conn| {
+            async move {
+                let deposit = unmatched_deposits::unmatched_deposits
+                    .find(id)
+                    .filter(unmatched_deposits::status.eq(UnmatchedDepositStatus::Pending.as_str()))
+                    .select(UnmatchedDeposit::as_select())
+                    .for_update()
+                    .first::<UnmatchedDeposit>(conn)
+                    .await
+                    .optional()?
+                    .ok_or(AttributeError::DepositNotPending)?;
+
+                let request = match order {
+                    Some(order) => {
+                        let status = credit_requests::credit_requests
+                            .find(order)
+                            .filter(credit_requests::user_id.eq(user))
+                            .select(credit_requests::request_status)
+                            .for_update()
+                            .first::<String>(conn)
+                            .await
+                            .optional()?
+                            .ok_or(AttributeError::RequestNotFound(order))?;
+                        if !CreditRequestStatus::Credited
+                            .source_values()
+                            .contains(&status.as_str())
+                        {
+                            return Err(AttributeError::RequestCredited(order));
+                        }
+                        order
+                    }
+                    None => {
+                        diesel::insert_into(credit_requests::credit_requests)
+                            .values((
+                                credit_requests::user_id.eq(user),
+                                credit_requests::chain_id.eq(deposit.chain_id),
+                                credit_requests::request_status
+                                    .eq(CreditRequestStatus::Created.as_str()),
+                                credit_requests::request_type.eq("DEPOSIT"),
+                            ))
+                            .returning(credit_requests::id)
+                            .get_result::<i32>(conn)
+                            .await?
+                    }
+                };
+
+                diesel::insert_into(processed_deposits::processed_deposits)
+                    .values(&ProcessedDepositCreate {
+                        chain_id: deposit.chain_id,
+                        tx_hash: deposit.tx_hash.clone(),
+                        log_index: deposit.log_index,
+                        block_number: deposit.block_number,
+                        block_hash: deposit.block_hash.clone(),
+                        credit_request_id: request,
+                        amount_credit: amount_credit.clone(),
+                        price: price.clone(),
+                    })
+                    .execute(conn)
+                    .await?;
+                diesel::update(
+                    credit_requests::credit_requests
+                        .find(request)
+                        .filter(credit_requests::user_id.eq(user))
+                        .filter(
+                            credit_requests::request_status
+                                .eq_any(CreditRequestStatus::Credited.source_values()),
+                        ),
+                )
+                .set((
+                    credit_requests::amount_credit.eq(Some(amount_credit)),
+                    credit_requests::request_status.eq(CreditRequestStatus::Credited.as_str()),
+                    credit_requests::chain_id.eq(Some(deposit.chain_id)),
+                    credit_requests::tx_hash.eq(Some(&deposit.tx_hash)),
+                    credit_requests::request_type.eq("DEPOSIT"),
+                    credit_requests::token_address.eq(Some(&deposit.token_address)),
+                    credit_requests::amount_paid.eq(Some(&deposit.amount_paid)),
+                ))
+                .returning(credit_requests::id)
+                .get_result::<i32>(conn)
+                .await?;
+                let credited = diesel::update(
+                    users::users
+                        .filter(users::id.eq(user))
+                        .filter(users::balance_unit.eq(unit.as_str())),
+                )
+                .set(users::credit_balance.eq(users::credit_balance + amount_credit))
+                .returning(User::as_returning())
+                .get_result::<User>(conn)
+                .await
+                .optional()?
+                .ok_or(AttributeError::UnitChanged(unit))?;
+
+                let deposit = diesel::update(unmatched_deposits::unmatched_deposits.find(id))
+                    .set((
+                        unmatched_deposits::status.eq(UnmatchedDepositStatus::Attributed.as_str()),
+                        unmatched_deposits::credit_request_id.eq(request),
+                        unmatched_deposits::resolved_by.eq(resolver),
+                        unmatched_deposits::resolved_at.eq(diesel::dsl::now),
+                    ))
+                    .returning(UnmatchedDeposit::as_returning())
+                    .get_result::<UnmatchedDeposit>(conn)
+                    .await?;
+                Ok((deposit, credited))
+            }
+            .scope_boxed()
+        })
+        .await
+}
+
+/// Marks a pending unmatched deposit as refunded to its sender
+///
+/// A deposit credited since it was recorded, e.g. reported by its user, is never refunded.
+pub async fn refund_unmatched_deposit(
+    connection: &mut AsyncPgConnection,
+    id: i32,
+    resolver: &String,
+    refund_tx_hash: &Option<String>,
+) -> Result<UnmatchedDeposit, String> {
+    connection
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                let deposit = unmatched_deposits::unmatched_deposits
+                    .find(id)
+                    .filter(unmatched_deposits::status.eq(UnmatchedDepositStatus::Pending.as_str()))
+                    .select(UnmatchedDeposit::as_select())
+                    .for_update()
+                    .first::<UnmatchedDeposit>(conn)
+                    .await?;
+                let credited = diesel::select(diesel::dsl::exists(
+                    processed_deposits::processed_deposits
+                        .filter(processed_deposits::chain_id.eq(deposit.chain_id))
+                        .filter(processed_deposits::tx_hash.eq(&deposit.tx_hash))
+                        .filter(processed_deposits::log_index.eq(deposit.log_index)),
+                ))
+                .get_result::<bool>(conn)
+                .await?;
+                if credited {
+                    return Ok(None);
+                }
+
+                diesel::update(unmatched_deposits::unmatched_deposits.find(id))
+                    .set((
+                        unmatched_deposits::status.eq(UnmatchedDepositStatus::Refunded.as_str()),
+                        unmatched_deposits::refund_tx_hash.eq(refund_tx_hash),
+                        unmatched_deposits::resolved_by.eq(resolver),
+                        unmatched_deposits::resolved_at.eq(diesel::dsl::now),
+                    ))
+                    .returning(UnmatchedDeposit::as_returning())
+                    .get_result::<UnmatchedDeposit>(conn)
+                    .await
+                    .map(Some)
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(resolve_error)?
+        .ok_or_else(|| "Deposit is already credited".to_string())
+}
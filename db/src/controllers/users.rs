@@ -1,17 +1,39 @@
+use super::organisations::insert_organisation;
 use crate::{
-    models::user_model::{User, UserCreate},
-    schema::users::dsl::*,
+    models::{
+        credit_requests::CreditRequestStatus,
+        organisations::OrganisationCreate,
+        user_model::{BalanceUnit, User, UserCreate},
+    },
+    schema::{apps, credit_requests, processed_deposits, users::dsl::*, withdrawals},
 };
 use bigdecimal::BigDecimal;
 use diesel::prelude::*;
-use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
+
+diesel::define_sql_function!(fn round(x: diesel::sql_types::Numeric, s: diesel::sql_types::Integer) -> diesel::sql_types::Numeric);
+diesel::define_sql_function!(
+    #[sql_name = "round"]
+    fn round_nullable(
+        x: diesel::sql_types::Nullable<diesel::sql_types::Numeric>,
+        s: diesel::sql_types::Integer,
+    ) -> diesel::sql_types::Nullable<diesel::sql_types::Numeric>
+);
 
 /// Parameters for transaction details
 #[derive(Clone)]
 pub struct TxParams {
     pub amount_data: String,
+    /// Amount billed, in `billed_unit`
     pub amount_data_billed: BigDecimal,
     pub fees: u128,
+    pub billed_unit: BalanceUnit,
+    /// AVAIL/USD price the submission was priced at, for a USD bill
+    pub avail_usd_price: Option<BigDecimal>,
+    /// Value of a credit in the smallest unit of AVAIL, for a bill in credits
+    pub avail_per_credit: Option<BigDecimal>,
 }
 
 pub async fn get_all_users(
@@ -33,16 +55,25 @@ pub async fn get_all_users(
     Ok(result)
 }
 
+/// Registers a user together with the personal organisation acting as their billing account.
 pub async fn register_new_user(
     connection: &mut AsyncPgConnection,
     user: UserCreate,
 ) -> Result<(), String> {
-    diesel::insert_into(users)
-        .values(user)
-        .execute(&mut *connection)
+    connection
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                diesel::insert_into(users)
+                    .values(&user)
+                    .execute(&mut *conn)
+                    .await?;
+                insert_organisation(conn, &OrganisationCreate::personal(&user)).await?;
+                Ok(())
+            }
+            .scope_boxed()
+        })
         .await
-        .map_err(|e| e.to_string())?;
-    Ok(())
+        .map_err(|e| e.to_string())
 }
 
 pub async fn allocate_global_credit_balance(
@@ -93,3 +124,115 @@ pub async fn fund_user(
         .map_err(|e| e.to_string())?;
     Ok(result)
 }
+
+/// Converts the balances of a credit denominated user and of their apps to USD, at
+/// `usd_per_credit`, and holds them in USD from then on
+///
+/// The credits of their deposits, credit requests and withdrawals are converted along, so
+/// refunds and reverted deposits apply in USD. Quotes of requests not credited yet are in
+/// credits and are dropped, their deposits are priced in USD when they arrive. Returns the user
+/// before and after the conversion, fails if the balances are already held in USD.
+pub async fn convert_balances_to_usd(
+    connection: &mut AsyncPgConnection,
+    user: &String,
+    usd_per_credit: &BigDecimal,
+) -> Result<(User, User), String> {
+    let scale = BalanceUnit::Usd.scale() as i32;
+    let result = connection
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                let before = users
+                    .filter(id.eq(user))
+                    .filter(balance_unit.eq(BalanceUnit::Credits.as_str()))
+                    .select(User::as_select())
+                    .for_update()
+                    .first::<User>(conn)
+                    .await?;
+
+                let after = diesel::update(users.filter(id.eq(user)))
+                    .set((
+                        credit_balance.eq(round(credit_balance * usd_per_credit, scale)),
+                        credit_used.eq(round(credit_used * usd_per_credit, scale)),
+                        allocated_credit_balance
+                            .eq(round(allocated_credit_balance * usd_per_credit, scale)),
+                        balance_unit.eq(BalanceUnit::Usd.as_str()),
+                    ))
+                    .returning(User::as_returning())
+                    .get_result::<User>(conn)
+                    .await?;
+
+                diesel::update(apps::table.filter(apps::user_id.eq(user)))
+                    .set((
+                        apps::credit_balance
+                            .eq(round(apps::credit_balance * usd_per_credit, scale)),
+                        apps::credit_used.eq(round(apps::credit_used * usd_per_credit, scale)),
+                        apps::fallback_credit_used
+                            .eq(round(apps::fallback_credit_used * usd_per_credit, scale)),
+                    ))
+                    .execute(conn)
+                    .await?;
+
+                let requests = credit_requests::table
+                    .filter(credit_requests::user_id.eq(user))
+                    .select(credit_requests::id);
+                diesel::update(
+                    processed_deposits::table
+                        .filter(processed_deposits::credit_request_id.eq_any(requests)),
+                )
+                .set(processed_deposits::amount_credit.eq(round(
+                    processed_deposits::amount_credit * usd_per_credit,
+                    scale,
+                )))
+                .execute(conn)
+                .await?;
+                diesel::update(credit_requests::table.filter(credit_requests::user_id.eq(user)))
+                    .set((
+                        credit_requests::amount_credit.eq(round_nullable(
+                            credit_requests::amount_credit * usd_per_credit,
+                            scale,
+                        )),
+                        credit_requests::quote_difference.eq(round_nullable(
+                            credit_requests::quote_difference * usd_per_credit,
+                            scale,
+                        )),
+                    ))
+                    .execute(conn)
+                    .await?;
+                diesel::update(
+                    credit_requests::table
+                        .filter(credit_requests::user_id.eq(user))
+                        .filter(
+                            credit_requests::request_status
+                                .ne(CreditRequestStatus::Credited.as_str()),
+                        )
+                        .filter(credit_requests::quote_signature.is_not_null()),
+                )
+                .set((
+                    credit_requests::quote_token_address.eq(None::<String>),
+                    credit_requests::quote_amount.eq(None::<BigDecimal>),
+                    credit_requests::quote_credits.eq(None::<BigDecimal>),
+                    credit_requests::quote_expires_at.eq(None::<chrono::NaiveDateTime>),
+                    credit_requests::quote_signature.eq(None::<String>),
+                ))
+                .execute(conn)
+                .await?;
+                diesel::update(withdrawals::table.filter(withdrawals::user_id.eq(user)))
+                    .set(
+                        withdrawals::credits
+                            .eq(round(withdrawals::credits * usd_per_credit, scale)),
+                    )
+                    .execute(conn)
+                    .await?;
+
+                Ok((before, after))
+            }
+            .scope_boxed()
+        })
+        .await;
+
+    match result {
+        Ok(converted) => Ok(converted),
+        Err(diesel::result::Error::NotFound) => Err("Balances are already held in USD".to_string()),
+        Err(e) => Err(format!("Error converting balances to USD: {}", e)),
+    }
+}
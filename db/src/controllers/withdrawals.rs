@@ -0,0 +1,231 @@
+use crate::{
+    models::{
+        user_model::{BalanceUnit, User},
+        withdrawals::{Withdrawal, WithdrawalCreate, WithdrawalStatus},
+    },
+    schema::{
+        apps::dsl as apps, credit_requests::dsl as credit_requests,
+        processed_deposits::dsl as processed_deposits, users::dsl as users,
+        withdrawals::dsl as withdrawals,
+    },
+};
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
+
+enum WithdrawalError {
+    UnitChanged,
+    InsufficientBalance,
+    NotRefundable,
+    Database(diesel::result::Error),
+}
+
+impl From<diesel::result::Error> for WithdrawalError {
+    fn from(e: diesel::result::Error) -> Self {
+        WithdrawalError::Database(e)
+    }
+}
+
+/// Credits of `user` that can still be withdrawn: the credits bought with deposits less the
+/// credits of withdrawals that were not rejected and the credits spent by the user and their apps
+///
+/// Credits are spent from the purchased ones first, granted credits are only ever spent.
+async fn refundable_credits(
+    connection: &mut AsyncPgConnection,
+    user: &String,
+) -> Result<BigDecimal, diesel::result::Error> {
+    let deposited = processed_deposits::processed_deposits
+        .inner_join(credit_requests::credit_requests)
+        .filter(credit_requests::user_id.eq(user))
+        .select(diesel::dsl::sum(processed_deposits::amount_credit))
+        .first::<Option<BigDecimal>>(connection)
+        .await?
+        .unwrap_or_default();
+    let withdrawn = withdrawals::withdrawals
+        .filter(withdrawals::user_id.eq(user))
+        .filter(withdrawals::status.ne(WithdrawalStatus::Rejected.as_str()))
+        .select(diesel::dsl::sum(withdrawals::credits))
+        .first::<Option<BigDecimal>>(connection)
+        .await?
+        .unwrap_or_default();
+    // Fallback submissions are billed to the user, the others to their apps
+    let used_by_user = users::users
+        .find(user)
+        .select(users::credit_used)
+        .first::<BigDecimal>(connection)
+        .await?;
+    let used_by_apps = apps::apps
+        .filter(apps::user_id.eq(user))
+        .select(diesel::dsl::sum(apps::credit_used))
+        .first::<Option<BigDecimal>>(connection)
+        .await?
+        .unwrap_or_default();
+    Ok(deposited - withdrawn - used_by_user - used_by_apps)
+}
+
+/// Records a withdrawal and debits its credits from the balance of the user in one transaction
+///
+/// Fails if the balance is short of the credits, or if they exceed the credits bought with
+/// deposits that were neither withdrawn nor spent yet, granted credits are not refunded. The credits are in
+/// the `unit` the balance was held in when the withdrawal was priced.
+pub async fn create_withdrawal(
+    connection: &mut AsyncPgConnection,
+    withdrawal: &WithdrawalCreate,
+    unit: BalanceUnit,
+) -> Result<(Withdrawal, User), String> {
+    connection
+        .transaction::<_, WithdrawalError, _>(|conn| {
+            async move {
+                let owner = users::users
+                    .filter(users::id.eq(&withdrawal.user_id))
+                    .select(User::as_select())
+                    .for_update()
+                    .first::<User>(conn)
+                    .await?;
+                if owner.balance_unit != unit.as_str() {
+                    return Err(WithdrawalError::UnitChanged);
+                }
+
+                let user = diesel::update(
+                    users::users
+                        .filter(users::id.eq(&withdrawal.user_id))
+                        .filter(users::credit_balance.ge(&withdrawal.credits)),
+                )
+                .set(users::credit_balance.eq(users::credit_balance - &withdrawal.credits))
+                .returning(User::as_returning())
+                .get_result::<User>(conn)
+                .await
+                .optional()?
+                .ok_or(WithdrawalError::InsufficientBalance)?;
+
+                if refundable_credits(conn, &withdrawal.user_id).await? < withdrawal.credits {
+                    return Err(WithdrawalError::NotRefundable);
+                }
+
+                let created = diesel::insert_into(withdrawals::withdrawals)
+                    .values(withdrawal)
+                    .returning(Withdrawal::as_returning())
+                    .get_result::<Withdrawal>(conn)
+                    .await?;
+                Ok((created, user))
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|e| match e {
+            WithdrawalError::UnitChanged => {
+                format!("Balance is no longer held in {}", unit)
+            }
+            WithdrawalError::InsufficientBalance => "Insufficient credit balance".to_string(),
+            WithdrawalError::NotRefundable => {
+                "Credits exceed the purchased credits that can be refunded".to_string()
+            }
+            WithdrawalError::Database(e) => e.to_string(),
+        })
+}
+
+pub async fn get_withdrawal(
+    connection: &mut AsyncPgConnection,
+    id: i32,
+) -> Result<Withdrawal, String> {
+    withdrawals::withdrawals
+        .filter(withdrawals::id.eq(id))
+        .select(Withdrawal::as_select())
+        .first::<Withdrawal>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Retrieves withdrawals, newest first
+pub async fn get_withdrawals(
+    connection: &mut AsyncPgConnection,
+    user: &Option<String>,
+    status: &Option<WithdrawalStatus>,
+    limit: i64,
+) -> Result<Vec<Withdrawal>, String> {
+    let mut query = withdrawals::withdrawals
+        .select(Withdrawal::as_select())
+        .into_boxed();
+    if let Some(user) = user {
+        query = query.filter(withdrawals::user_id.eq(user));
+    }
+    if let Some(status) = status {
+        query = query.filter(withdrawals::status.eq(status.as_str()));
+    }
+    query
+        .order(withdrawals::created_at.desc())
+        .limit(limit)
+        .load::<Withdrawal>(connection)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn resolve_error(e: diesel::result::Error) -> String {
+    match e {
+        diesel::result::Error::NotFound => "Withdrawal is not pending".to_string(),
+        e => e.to_string(),
+    }
+}
+
+/// Stores the operator signature of a pending withdrawal, the payout can then be claimed on chain
+pub async fn sign_withdrawal(
+    connection: &mut AsyncPgConnection,
+    id: i32,
+    resolver: &String,
+    signature: &String,
+) -> Result<Withdrawal, String> {
+    diesel::update(
+        withdrawals::withdrawals
+            .filter(withdrawals::id.eq(id))
+            .filter(withdrawals::status.eq(WithdrawalStatus::Pending.as_str())),
+    )
+    .set((
+        withdrawals::status.eq(WithdrawalStatus::Signed.as_str()),
+        withdrawals::signature.eq(signature),
+        withdrawals::resolved_by.eq(resolver),
+        withdrawals::resolved_at.eq(diesel::dsl::now),
+    ))
+    .returning(Withdrawal::as_returning())
+    .get_result::<Withdrawal>(connection)
+    .await
+    .map_err(resolve_error)
+}
+
+/// Rejects a pending withdrawal and returns its credits to the user in the same transaction
+///
+/// A signed withdrawal can be claimed on chain at any time and is not rejected.
+pub async fn reject_withdrawal(
+    connection: &mut AsyncPgConnection,
+    id: i32,
+    resolver: &String,
+) -> Result<(Withdrawal, User), String> {
+    connection
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                let withdrawal = diesel::update(
+                    withdrawals::withdrawals
+                        .filter(withdrawals::id.eq(id))
+                        .filter(withdrawals::status.eq(WithdrawalStatus::Pending.as_str())),
+                )
+                .set((
+                    withdrawals::status.eq(WithdrawalStatus::Rejected.as_str()),
+                    withdrawals::resolved_by.eq(resolver),
+                    withdrawals::resolved_at.eq(diesel::dsl::now),
+                ))
+                .returning(Withdrawal::as_returning())
+                .get_result::<Withdrawal>(conn)
+                .await?;
+                let user = diesel::update(users::users.filter(users::id.eq(&withdrawal.user_id)))
+                    .set(users::credit_balance.eq(users::credit_balance + &withdrawal.credits))
+                    .returning(User::as_returning())
+                    .get_result::<User>(conn)
+                    .await?;
+                Ok((withdrawal, user))
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(resolve_error)
+}
@@ -1,4 +1,153 @@
+use avail_utils::submit_data::{BLOCK_TIME_SECS, MORTALITY_PERIOD};
+
 pub const ERROR_INSUFFICIENT_BALANCE: &str = "Insufficient balance";
 pub const INVALID_TOKEN_ID: &str = "Invalid token id";
 pub const TIMEOUT_ERROR: &str = "TIMEOUT";
 pub const INVALID_TOKEN_BALANCE: &str = "Invalid token balance";
+pub const ENCRYPTION_REJECTED: &str = "Encryption rejected";
+
+/// How a failed submission is retried, classified from its error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Transient failures, e.g. RPC errors and timeouts
+    Retryable,
+    /// Failures the user has to resolve, e.g. by topping up their balance
+    UserAction,
+    /// Failures retrying can't resolve, the submission is marked failed
+    NonRetryable,
+}
+
+/// Errors the user has to act on, matched case insensitively
+const USER_ACTION_ERRORS: [&str; 2] = ["insufficient", "invalid credit selection"];
+
+/// Errors no retry can resolve, matched case insensitively
+const NON_RETRYABLE_ERRORS: [&str; 7] = [
+    "invalidappid",
+    "invalid app id",
+    "datacannotbeempty",
+    "no payload found",
+    "execution failed",
+    "retry count exceeded",
+    // See `is_rejected_status`
+    "encryption rejected",
+];
+
+/// Whether a request answered with the HTTP `status` is rejected for good. Client errors are,
+/// except for timeouts and rate limits which are retried.
+pub fn is_rejected_status(status: u16) -> bool {
+    (400..500).contains(&status) && status != 408 && status != 429
+}
+
+impl ErrorClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorClass::Retryable => "retryable",
+            ErrorClass::UserAction => "user_action",
+            ErrorClass::NonRetryable => "non_retryable",
+        }
+    }
+
+    /// Classifies the error of a submission, unknown errors are assumed transient
+    pub fn classify(error: &str) -> Self {
+        let error = error.to_lowercase();
+        if NON_RETRYABLE_ERRORS.iter().any(|e| error.contains(e)) {
+            ErrorClass::NonRetryable
+        } else if USER_ACTION_ERRORS.iter().any(|e| error.contains(e)) {
+            ErrorClass::UserAction
+        } else {
+            ErrorClass::Retryable
+        }
+    }
+
+    /// Delay before retrying a submission that already went through `retries` retries, doubling
+    /// with every retry up to a cap. `None` when it is not retried.
+    pub fn backoff(&self, retries: i32) -> Option<chrono::Duration> {
+        let (base_secs, max_secs) = match self {
+            ErrorClass::Retryable => (30, 60 * 60),
+            ErrorClass::UserAction => (15 * 60, 24 * 60 * 60),
+            ErrorClass::NonRetryable => return None,
+        };
+        let secs = base_secs * 2i64.pow(retries.clamp(0, 16) as u32);
+        Some(chrono::Duration::seconds(secs.min(max_secs)))
+    }
+}
+
+/// Delay before retrying a submission that failed with `error`, see `ErrorClass::backoff`
+///
+/// A timed out submission may still be included until the mortality period of its extrinsic has
+/// passed, it is not searched for on chain and posted again before.
+pub fn retry_delay(class: ErrorClass, error: &str, retries: i32) -> Option<chrono::Duration> {
+    let delay = class.backoff(retries)?;
+    if error != TIMEOUT_ERROR {
+        return Some(delay);
+    }
+    let mortality = chrono::Duration::seconds((MORTALITY_PERIOD * BLOCK_TIME_SECS) as i64);
+    Some(delay.max(mortality))
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{is_rejected_status, retry_delay, ErrorClass, ENCRYPTION_REJECTED, TIMEOUT_ERROR};
+
+    #[test]
+    fn test_errors_are_classified() {
+        for (error, class) in [
+            ("RPC call timed out", ErrorClass::Retryable),
+            ("Record not found", ErrorClass::Retryable),
+            (
+                "HTTP status client error (429 Too Many Requests) for url (http://enigma)",
+                ErrorClass::Retryable,
+            ),
+            ("Insufficient credits for user id", ErrorClass::UserAction),
+            ("Invalid credit selection", ErrorClass::UserAction),
+            (
+                "Transaction was executed but execution failed.",
+                ErrorClass::NonRetryable,
+            ),
+            (
+                "Module error: DataAvailability::InvalidAppId",
+                ErrorClass::NonRetryable,
+            ),
+            (
+                &format!("{}: 400 Bad Request", ENCRYPTION_REJECTED),
+                ErrorClass::NonRetryable,
+            ),
+        ] {
+            assert_eq!(ErrorClass::classify(error), class, "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_client_errors_other_than_timeouts_and_rate_limits_are_rejected() {
+        assert!(is_rejected_status(400));
+        assert!(is_rejected_status(404));
+        assert!(!is_rejected_status(408));
+        assert!(!is_rejected_status(429));
+        assert!(!is_rejected_status(500));
+        assert!(!is_rejected_status(200));
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_a_cap() {
+        let secs = |class: ErrorClass, retries| class.backoff(retries).unwrap().num_seconds();
+        assert_eq!(secs(ErrorClass::Retryable, 0), 30);
+        assert_eq!(secs(ErrorClass::Retryable, 3), 240);
+        assert_eq!(secs(ErrorClass::Retryable, 20), 60 * 60);
+        assert_eq!(secs(ErrorClass::UserAction, 0), 15 * 60);
+        assert_eq!(secs(ErrorClass::UserAction, 10), 24 * 60 * 60);
+        assert_eq!(secs(ErrorClass::Retryable, -1), 30);
+        assert!(ErrorClass::NonRetryable.backoff(0).is_none());
+    }
+
+    #[test]
+    fn test_timed_out_submission_waits_for_its_mortality() {
+        let delay = |error: &str, retries| {
+            retry_delay(ErrorClass::classify(error), error, retries)
+                .unwrap()
+                .num_seconds()
+        };
+        assert_eq!(delay(TIMEOUT_ERROR, 0), 640);
+        assert_eq!(delay(TIMEOUT_ERROR, 5), 60 * 16);
+        assert_eq!(delay("RPC call timed out", 0), 30);
+    }
+}
@@ -2,3 +2,5 @@ pub mod controllers;
 pub mod errors;
 pub mod models;
 pub mod schema;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
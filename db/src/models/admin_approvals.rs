@@ -0,0 +1,50 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Sensitive admin action waiting for the approval of a second admin
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::admin_approvals)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AdminApproval {
+    pub id: Uuid,
+    pub action: String,
+    /// Parameters the action is executed with once approved
+    pub payload: Value,
+    pub requested_by: String,
+    pub status: String,
+    pub resolved_by: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub resolved_at: Option<chrono::NaiveDateTime>,
+    /// A pending request can't be resolved after this time
+    pub expires_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::admin_approvals)]
+pub struct AdminApprovalCreate {
+    pub id: Uuid,
+    pub action: String,
+    pub payload: Value,
+    pub requested_by: String,
+    pub expires_at: chrono::NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl ApprovalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalStatus::Pending => "pending",
+            ApprovalStatus::Approved => "approved",
+            ApprovalStatus::Rejected => "rejected",
+        }
+    }
+}
@@ -18,6 +18,7 @@ pub struct Apps {
     pub credit_selection: Option<i16>,
     pub fallback_credit_used: BigDecimal,
     pub encryption: bool,
+    pub org_id: uuid::Uuid,
 }
 
 #[derive(Insertable, Serialize, Deserialize, Debug)]
@@ -32,4 +33,5 @@ pub struct AppsCreate {
     pub app_name: Option<String>,
     pub app_description: Option<String>,
     pub app_logo: Option<String>,
+    pub org_id: uuid::Uuid,
 }
@@ -0,0 +1,144 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::audit_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub actor: String,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: String,
+    pub org_id: Option<Uuid>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub ip_address: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::audit_events)]
+pub struct AuditEventCreate {
+    pub id: Uuid,
+    pub actor: String,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: String,
+    pub org_id: Option<Uuid>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub ip_address: Option<String>,
+}
+
+/// Actions recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    AppCreate,
+    AppUpdate,
+    AppDelete,
+    AppUpdateAppId,
+    AppToggleEncryption,
+    ApiKeyCreate,
+    ApiKeyDelete,
+    SigningKeyCreate,
+    SigningKeyDelete,
+    CreditsAllocate,
+    CreditsReclaim,
+    CreditsWithdraw,
+    CreditsConvertToUsd,
+    MemberAdd,
+    MemberUpdate,
+    MemberRemove,
+    AdminFundUser,
+    AdminResetRetryCount,
+    AdminApprovalRequest,
+    AdminApprovalReject,
+    AdminDepositAttribute,
+    AdminDepositRefund,
+    AdminTokenCreate,
+    AdminTokenUpdate,
+    AdminTokenDelete,
+    AdminWithdrawalSign,
+    AdminWithdrawalReject,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::AppCreate => "app.create",
+            AuditAction::AppUpdate => "app.update",
+            AuditAction::AppDelete => "app.delete",
+            AuditAction::AppUpdateAppId => "app.update_app_id",
+            AuditAction::AppToggleEncryption => "app.toggle_encryption",
+            AuditAction::ApiKeyCreate => "api_key.create",
+            AuditAction::ApiKeyDelete => "api_key.delete",
+            AuditAction::SigningKeyCreate => "signing_key.create",
+            AuditAction::SigningKeyDelete => "signing_key.delete",
+            AuditAction::CreditsAllocate => "credits.allocate",
+            AuditAction::CreditsReclaim => "credits.reclaim",
+            AuditAction::CreditsWithdraw => "credits.withdraw",
+            AuditAction::CreditsConvertToUsd => "credits.convert_to_usd",
+            AuditAction::MemberAdd => "member.add",
+            AuditAction::MemberUpdate => "member.update",
+            AuditAction::MemberRemove => "member.remove",
+            AuditAction::AdminFundUser => "admin.fund_user",
+            AuditAction::AdminResetRetryCount => "admin.reset_retry_count",
+            AuditAction::AdminApprovalRequest => "admin.approval_request",
+            AuditAction::AdminApprovalReject => "admin.approval_reject",
+            AuditAction::AdminDepositAttribute => "admin.deposit_attribute",
+            AuditAction::AdminDepositRefund => "admin.deposit_refund",
+            AuditAction::AdminTokenCreate => "admin.token_create",
+            AuditAction::AdminTokenUpdate => "admin.token_update",
+            AuditAction::AdminTokenDelete => "admin.token_delete",
+            AuditAction::AdminWithdrawalSign => "admin.withdrawal_sign",
+            AuditAction::AdminWithdrawalReject => "admin.withdrawal_reject",
+        }
+    }
+
+    /// Type of the resource the action targets
+    pub fn target_type(&self) -> &'static str {
+        match self {
+            AuditAction::AppCreate
+            | AuditAction::AppUpdate
+            | AuditAction::AppDelete
+            | AuditAction::AppUpdateAppId
+            | AuditAction::AppToggleEncryption
+            | AuditAction::CreditsAllocate
+            | AuditAction::CreditsReclaim => "app",
+            AuditAction::ApiKeyCreate | AuditAction::ApiKeyDelete => "api_key",
+            AuditAction::SigningKeyCreate | AuditAction::SigningKeyDelete => "signing_key",
+            AuditAction::MemberAdd | AuditAction::MemberUpdate | AuditAction::MemberRemove => {
+                "member"
+            }
+            AuditAction::AdminFundUser | AuditAction::CreditsConvertToUsd => "user",
+            AuditAction::AdminResetRetryCount => "expenditure",
+            AuditAction::AdminApprovalRequest | AuditAction::AdminApprovalReject => "approval",
+            AuditAction::AdminDepositAttribute | AuditAction::AdminDepositRefund => "deposit",
+            AuditAction::AdminTokenCreate
+            | AuditAction::AdminTokenUpdate
+            | AuditAction::AdminTokenDelete => "token",
+            AuditAction::CreditsWithdraw
+            | AuditAction::AdminWithdrawalSign
+            | AuditAction::AdminWithdrawalReject => "withdrawal",
+        }
+    }
+}
+
+impl AuditEventCreate {
+    pub fn new(actor: &str, action: AuditAction, target_id: impl ToString) -> Self {
+        AuditEventCreate {
+            id: Uuid::new_v4(),
+            actor: actor.to_string(),
+            action: action.as_str().to_string(),
+            target_type: action.target_type().to_string(),
+            target_id: target_id.to_string(),
+            org_id: None,
+            before: None,
+            after: None,
+            ip_address: None,
+        }
+    }
+}
@@ -26,6 +26,8 @@ pub struct CreditRequestsGet {
     pub tx_hash: Option<String>,
     pub token_address: Option<String>,
     pub amount_paid: Option<BigDecimal>,
+    pub confirmations: Option<i32>,
+    pub expires_at: chrono::NaiveDateTime,
 }
 
 #[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
@@ -41,6 +43,14 @@ pub struct CreditRequestInfo {
     pub created_at: chrono::NaiveDateTime,
     pub token_address: Option<String>,
     pub amount_paid: Option<BigDecimal>,
+    pub quote_token_address: Option<String>,
+    pub quote_amount: Option<BigDecimal>,
+    pub quote_credits: Option<BigDecimal>,
+    pub quote_expires_at: Option<chrono::NaiveDateTime>,
+    pub quote_outcome: Option<String>,
+    pub quote_difference: Option<BigDecimal>,
+    pub confirmations: Option<i32>,
+    pub expires_at: chrono::NaiveDateTime,
 }
 
 #[derive(Queryable, Selectable)]
@@ -48,3 +58,240 @@ pub struct CreditRequestInfo {
 pub struct ChainIdInfo {
     pub chain_id: Option<i32>,
 }
+
+/// Credits locked in for a deposit of `amount` of `token_address` made before `expires_at`
+#[derive(AsChangeset, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[diesel(table_name = crate::schema::credit_requests)]
+pub struct CreditQuote {
+    #[diesel(column_name = quote_token_address)]
+    pub token_address: String,
+    #[diesel(column_name = quote_amount)]
+    pub amount: BigDecimal,
+    #[diesel(column_name = quote_credits)]
+    pub credits: BigDecimal,
+    #[diesel(column_name = quote_expires_at)]
+    pub expires_at: chrono::NaiveDateTime,
+    /// Hex encoded HMAC-SHA256 of the quote and the request it was issued for
+    #[diesel(column_name = quote_signature)]
+    pub signature: String,
+}
+
+/// Deposit a credit request is credited with
+#[derive(AsChangeset, Debug)]
+#[diesel(table_name = crate::schema::credit_requests)]
+pub struct DepositCredit {
+    pub amount_credit: BigDecimal,
+    pub chain_id: i32,
+    pub tx_hash: String,
+    pub token_address: String,
+    pub amount_paid: BigDecimal,
+    pub quote_outcome: Option<String>,
+    pub quote_difference: Option<BigDecimal>,
+}
+
+/// Credit request with the quote it was registered with, if any
+#[derive(Queryable, Selectable, Debug)]
+#[diesel(table_name = crate::schema::credit_requests)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct CreditRequestQuote {
+    pub id: i32,
+    pub user_id: String,
+    pub chain_id: Option<i32>,
+    pub request_status: String,
+    pub quote_token_address: Option<String>,
+    pub quote_amount: Option<BigDecimal>,
+    pub quote_credits: Option<BigDecimal>,
+    pub quote_expires_at: Option<chrono::NaiveDateTime>,
+    pub quote_signature: Option<String>,
+}
+
+impl CreditRequestQuote {
+    pub fn quote(&self) -> Option<CreditQuote> {
+        Some(CreditQuote {
+            token_address: self.quote_token_address.clone()?,
+            amount: self.quote_amount.clone()?,
+            credits: self.quote_credits.clone()?,
+            expires_at: self.quote_expires_at?,
+            signature: self.quote_signature.clone()?,
+        })
+    }
+}
+
+/// How the quote of a credit request was applied to its deposit
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteOutcome {
+    /// The quoted credits were credited
+    Honoured,
+    /// The deposit arrived after the quote expired and was repriced
+    Expired,
+    /// The deposit was not of the quoted token and amount and was repriced
+    Mismatched,
+    /// The signature did not match the quote, the deposit was repriced
+    Invalid,
+}
+
+impl QuoteOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuoteOutcome::Honoured => "honoured",
+            QuoteOutcome::Expired => "expired",
+            QuoteOutcome::Mismatched => "mismatched",
+            QuoteOutcome::Invalid => "invalid",
+        }
+    }
+}
+
+/// Lifecycle of a credit request
+///
+/// `created` or, once quoted, `awaiting_payment` until the user submits the deposit transaction
+/// (`tx_submitted`), the funds monitor sees it (`confirming`) and credits it (`credited`).
+/// Requests left unpaid past `expires_at` are `expired`, a submitted transaction that reverted
+/// is `failed`. A deposit is credited from any state but `credited`, so a late deposit still
+/// credits an expired request. Nothing leaves `credited` but a reorg of the deposit, which the
+/// funds monitor moves back to `tx_submitted`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CreditRequestStatus {
+    /// Registered without a quote
+    Created,
+    /// Quoted, waiting for the deposit
+    AwaitingPayment,
+    /// The user submitted the deposit transaction, verified on chain
+    TxSubmitted,
+    /// The deposit was seen on chain and is waiting for finality
+    Confirming,
+    /// The deposit was credited
+    Credited,
+    /// No payment was seen before the request expired
+    Expired,
+    /// The submitted transaction reverted
+    Failed,
+}
+
+impl CreditRequestStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CreditRequestStatus::Created => "created",
+            CreditRequestStatus::AwaitingPayment => "awaiting_payment",
+            CreditRequestStatus::TxSubmitted => "tx_submitted",
+            CreditRequestStatus::Confirming => "confirming",
+            CreditRequestStatus::Credited => "credited",
+            CreditRequestStatus::Expired => "expired",
+            CreditRequestStatus::Failed => "failed",
+        }
+    }
+
+    /// States a request may move to this state from
+    pub fn sources(&self) -> &'static [CreditRequestStatus] {
+        use CreditRequestStatus::*;
+        match self {
+            Created => &[],
+            AwaitingPayment => &[Created],
+            TxSubmitted => &[Created, AwaitingPayment, Expired, Failed],
+            Confirming => &[
+                Created,
+                AwaitingPayment,
+                TxSubmitted,
+                Confirming,
+                Expired,
+                Failed,
+            ],
+            Credited => &[
+                Created,
+                AwaitingPayment,
+                TxSubmitted,
+                Confirming,
+                Expired,
+                Failed,
+            ],
+            Expired => &[Created, AwaitingPayment],
+            Failed => &[Created, AwaitingPayment, TxSubmitted, Expired],
+        }
+    }
+
+    /// Values of `request_status` a request may move to this state from
+    pub fn source_values(&self) -> Vec<&'static str> {
+        self.sources().iter().map(|state| state.as_str()).collect()
+    }
+
+    pub fn can_transition_to(&self, next: CreditRequestStatus) -> bool {
+        next.sources().contains(self)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::CreditRequestStatus::{self, *};
+
+    const ALL: [CreditRequestStatus; 7] = [
+        Created,
+        AwaitingPayment,
+        TxSubmitted,
+        Confirming,
+        Credited,
+        Expired,
+        Failed,
+    ];
+
+    #[test]
+    fn test_credited_request_is_final() {
+        for next in ALL {
+            assert!(!Credited.can_transition_to(next), "credited -> {:?}", next);
+        }
+    }
+
+    #[test]
+    fn test_late_deposit_credits_an_expired_or_failed_request() {
+        assert!(Expired.can_transition_to(Credited));
+        assert!(Expired.can_transition_to(Confirming));
+        assert!(Expired.can_transition_to(TxSubmitted));
+        assert!(Failed.can_transition_to(Credited));
+    }
+
+    #[test]
+    fn test_allowed_transitions() {
+        for (from, next) in [
+            (Created, AwaitingPayment),
+            (Created, TxSubmitted),
+            (Created, Confirming),
+            (Created, Credited),
+            (Created, Expired),
+            (AwaitingPayment, TxSubmitted),
+            (AwaitingPayment, Expired),
+            (TxSubmitted, Confirming),
+            (TxSubmitted, Failed),
+            (Confirming, Confirming),
+            (Confirming, Credited),
+        ] {
+            assert!(from.can_transition_to(next), "{:?} -> {:?}", from, next);
+        }
+    }
+
+    #[test]
+    fn test_rejected_transitions() {
+        for (from, next) in [
+            (AwaitingPayment, Created),
+            (AwaitingPayment, AwaitingPayment),
+            (TxSubmitted, AwaitingPayment),
+            (TxSubmitted, Expired),
+            (Confirming, TxSubmitted),
+            (Confirming, Expired),
+            (Confirming, Failed),
+            (Expired, AwaitingPayment),
+            (Failed, Expired),
+        ] {
+            assert!(!from.can_transition_to(next), "{:?} -> {:?}", from, next);
+        }
+        // Nothing moves back to a new request
+        for from in ALL {
+            assert!(!from.can_transition_to(Created), "{:?} -> created", from);
+        }
+    }
+
+    #[test]
+    fn test_source_values_are_the_stored_states() {
+        assert_eq!(Expired.source_values(), vec!["created", "awaiting_payment"]);
+        assert!(!Credited.source_values().contains(&"credited"));
+    }
+}
@@ -31,6 +31,16 @@ pub struct CustomerExpenditureGet {
     pub updated_at: chrono::NaiveDateTime,
     pub app_id: Uuid,
     pub wallet: Option<Vec<u8>>,
+    pub billed_unit: String,
+    pub avail_usd_price: Option<BigDecimal>,
+    /// Fee paid on chain, set once the funds monitor reconciled the submission
+    pub chain_fee: Option<BigDecimal>,
+    /// Class of the last error, see `ErrorClass`
+    pub error_class: Option<String>,
+    /// When the failed submission is retried next
+    pub next_retry_at: Option<chrono::NaiveDateTime>,
+    /// When the submission was given up on, it is not retried anymore
+    pub failed_at: Option<chrono::NaiveDateTime>,
 }
 
 #[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
@@ -68,6 +78,8 @@ pub struct CustomerExpenditureGetWithPayload {
     pub signature_ciphertext_hash: Option<Vec<u8>>,
     pub signature_plaintext_hash: Option<Vec<u8>>,
     pub address: Option<Vec<u8>>,
+    /// Hash of the data last submitted, set before the submission is sent to the chain
+    pub expected_data_hash: Option<String>,
 }
 
 #[derive(Insertable, Selectable, Serialize, Deserialize, Debug)]
@@ -0,0 +1,22 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Avail address derived from the deposit seed, transfers to it are credited to its user
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::deposit_addresses)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DepositAddress {
+    pub id: i32,
+    pub address: String,
+    pub derivation_index: i32,
+    pub user_id: Option<String>,
+    pub assigned_at: Option<chrono::NaiveDateTime>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::deposit_addresses)]
+pub struct DepositAddressCreate {
+    pub address: String,
+    pub derivation_index: i32,
+}
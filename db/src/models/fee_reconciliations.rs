@@ -0,0 +1,59 @@
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// AVAIL spent on the submissions of an app against what they were billed, for one day
+///
+/// Fees are in the smallest unit of AVAIL. `avail_billed` values the bills in AVAIL, credits at
+/// the 1 KB fee of the reconciliation and USD at the AVAIL price of each submission.
+#[derive(Queryable, Selectable, Insertable, AsChangeset, Serialize, Deserialize, Debug, Clone)]
+#[diesel(table_name = crate::schema::fee_reconciliations)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct FeeReconciliation {
+    pub day: chrono::NaiveDate,
+    pub app_id: Uuid,
+    pub submissions: i32,
+    /// Submissions whose fee could not be read from chain, their recorded fee is counted as spent
+    pub unverified_submissions: i32,
+    /// Submissions never billed, or whose recorded fee differs from the chain or is missing on
+    /// either side
+    pub mismatched_submissions: i32,
+    /// Submissions billed less than the fee paid on chain
+    pub underbilled_submissions: i32,
+    pub avail_spent: BigDecimal,
+    pub avail_recorded: BigDecimal,
+    pub credits_billed: BigDecimal,
+    pub usd_billed: BigDecimal,
+    pub avail_billed: BigDecimal,
+    /// `avail_billed` less `avail_spent`
+    pub margin: BigDecimal,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+/// Fee records of a submission included on chain, as reconciled
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = crate::schema::customer_expenditures)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct FeeRecord {
+    pub id: Uuid,
+    pub app_id: Uuid,
+    pub created_at: chrono::NaiveDateTime,
+    /// Fee recorded when the submission was billed
+    pub fees: Option<BigDecimal>,
+    pub chain_fee: Option<BigDecimal>,
+    pub converted_fees: Option<BigDecimal>,
+    pub billed_unit: String,
+    pub avail_usd_price: Option<BigDecimal>,
+    /// Value of a credit the submission was billed at, for a bill in credits
+    pub avail_per_credit: Option<BigDecimal>,
+}
+
+/// Deposits credited on a day, in the unit of the balances they were credited to
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DepositsReceived {
+    pub day: chrono::NaiveDate,
+    pub unit: String,
+    pub deposits: i64,
+    pub amount: BigDecimal,
+}
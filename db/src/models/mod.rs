@@ -1,6 +1,16 @@
+pub mod admin_approvals;
 pub mod api;
 pub mod apps;
+pub mod audit_events;
 pub mod credit_requests;
 pub mod customer_expenditure;
+pub mod deposit_addresses;
+pub mod fee_reconciliations;
 pub mod indexer;
+pub mod organisations;
+pub mod processed_deposits;
+pub mod signing_keys;
+pub mod supported_tokens;
+pub mod unmatched_deposits;
 pub mod user_model;
+pub mod withdrawals;
@@ -0,0 +1,150 @@
+use crate::models::user_model::UserCreate;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(table_name = crate::schema::organisations)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Organisation {
+    pub id: Uuid,
+    pub name: String,
+    pub owner_id: String,
+    pub created_at: chrono::NaiveDateTime,
+    /// Billing account of the organisation. Balances, credit requests, deposit addresses and
+    /// withdrawals are held on this user, and no other organisation is billed to it.
+    pub billing_id: String,
+}
+
+#[derive(Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::organisations)]
+pub struct OrganisationCreate {
+    pub id: Uuid,
+    pub name: String,
+    pub owner_id: String,
+    pub billing_id: String,
+}
+
+impl OrganisationCreate {
+    /// Organisation billed to an account of its own, created along with it
+    pub fn new(name: String, owner_id: String) -> Self {
+        let id = Uuid::new_v4();
+        OrganisationCreate {
+            id,
+            name,
+            owner_id,
+            billing_id: format!("org:{}", id),
+        }
+    }
+
+    /// Personal organisation of a user, billed to the user
+    pub fn personal(user: &UserCreate) -> Self {
+        OrganisationCreate {
+            id: Uuid::new_v4(),
+            name: user.name.clone(),
+            owner_id: user.id.clone(),
+            billing_id: user.id.clone(),
+        }
+    }
+}
+
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(table_name = crate::schema::organisation_members)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OrganisationMember {
+    pub org_id: Uuid,
+    pub user_id: String,
+    pub role: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::organisation_members)]
+pub struct OrganisationMemberCreate {
+    pub org_id: Uuid,
+    pub user_id: String,
+    pub role: String,
+}
+
+/// Role of a user within an organisation
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OrgRole {
+    Owner,
+    Admin,
+    Developer,
+    BillingViewer,
+}
+
+/// Actions on organisation resources which are gated by role
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrgPermission {
+    /// Read apps and their usage
+    ViewApps,
+    /// Create, edit and delete apps
+    ManageApps,
+    /// Create and revoke API and signing keys
+    ManageKeys,
+    /// Read balances, credit requests and expenditure
+    ViewBilling,
+    /// Register credit requests and move credits between apps
+    ManageBilling,
+    /// Add, remove and change the role of members
+    ManageMembers,
+}
+
+impl OrgRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrgRole::Owner => "owner",
+            OrgRole::Admin => "admin",
+            OrgRole::Developer => "developer",
+            OrgRole::BillingViewer => "billing-viewer",
+        }
+    }
+
+    pub fn allows(&self, permission: OrgPermission) -> bool {
+        match self {
+            OrgRole::Owner | OrgRole::Admin => true,
+            OrgRole::Developer => matches!(
+                permission,
+                OrgPermission::ViewApps | OrgPermission::ManageKeys
+            ),
+            OrgRole::BillingViewer => matches!(
+                permission,
+                OrgPermission::ViewApps | OrgPermission::ViewBilling
+            ),
+        }
+    }
+}
+
+impl fmt::Display for OrgRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OrgRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "owner" => Ok(OrgRole::Owner),
+            "admin" => Ok(OrgRole::Admin),
+            "developer" => Ok(OrgRole::Developer),
+            "billing-viewer" => Ok(OrgRole::BillingViewer),
+            _ => Err(format!("Unknown organisation role {}", s)),
+        }
+    }
+}
+
+impl OrganisationMember {
+    pub fn role(&self) -> Result<OrgRole, String> {
+        self.role.parse()
+    }
+
+    pub fn allows(&self, permission: OrgPermission) -> bool {
+        self.role().is_ok_and(|role| role.allows(permission))
+    }
+}
@@ -0,0 +1,48 @@
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Deposit credited by the funds monitor, keyed on its position on chain so it is credited once
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::processed_deposits)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ProcessedDeposit {
+    pub id: i32,
+    pub chain_id: i32,
+    pub tx_hash: String,
+    pub log_index: i32,
+    pub block_number: i32,
+    pub block_hash: String,
+    pub credit_request_id: i32,
+    pub amount_credit: BigDecimal,
+    pub created_at: chrono::NaiveDateTime,
+    pub token_usd_price: Option<BigDecimal>,
+    pub avail_usd_price: Option<BigDecimal>,
+    pub price_source: Option<String>,
+    pub priced_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::processed_deposits)]
+pub struct ProcessedDepositCreate {
+    pub chain_id: i32,
+    pub tx_hash: String,
+    pub log_index: i32,
+    pub block_number: i32,
+    pub block_hash: String,
+    pub credit_request_id: i32,
+    pub amount_credit: BigDecimal,
+    #[diesel(embed)]
+    pub price: DepositPrice,
+}
+
+/// USD prices a deposit was converted to credits with, kept for audit
+#[derive(Insertable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(table_name = crate::schema::processed_deposits)]
+pub struct DepositPrice {
+    pub token_usd_price: BigDecimal,
+    pub avail_usd_price: BigDecimal,
+    /// Sources of the token and AVAIL prices, e.g. `coingecko` or `chainlink/coingecko`
+    pub price_source: String,
+    pub priced_at: chrono::NaiveDateTime,
+}
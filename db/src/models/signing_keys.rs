@@ -0,0 +1,25 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::signing_keys)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct SigningKey {
+    pub id: Uuid,
+    pub app_id: Uuid,
+    pub user_id: String,
+    pub key_type: String,
+    pub public_key: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::signing_keys)]
+pub struct SigningKeyCreate {
+    pub id: Uuid,
+    pub app_id: Uuid,
+    pub user_id: String,
+    pub key_type: String,
+    pub public_key: String,
+}
@@ -0,0 +1,54 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Token deposits are accepted in on a chain
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(table_name = crate::schema::supported_tokens)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct SupportedToken {
+    pub id: i32,
+    pub chain_id: i32,
+    /// Address of the token as normalized by `normalize_token_address`, the zero address for the
+    /// native token of the chain
+    pub token_address: String,
+    pub symbol: String,
+    pub decimals: i32,
+    /// CoinGecko id the price of the token is looked up with
+    pub price_feed_id: String,
+    pub enabled: bool,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::supported_tokens)]
+pub struct SupportedTokenCreate {
+    pub chain_id: i32,
+    pub token_address: String,
+    pub symbol: String,
+    pub decimals: i32,
+    pub price_feed_id: String,
+    pub enabled: bool,
+}
+
+/// Fields of a token an admin may change, `None` fields are left untouched
+#[derive(AsChangeset, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::supported_tokens)]
+pub struct SupportedTokenUpdate {
+    pub symbol: Option<String>,
+    pub decimals: Option<i32>,
+    pub price_feed_id: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// Form token addresses are registered and looked up in
+///
+/// Hex addresses are lowercased, other addresses such as Solana mints are base58 encoded and case
+/// sensitive, they are kept as they are.
+pub fn normalize_token_address(address: &str) -> String {
+    if address.starts_with("0x") || address.starts_with("0X") {
+        address.to_lowercase()
+    } else {
+        address.to_string()
+    }
+}
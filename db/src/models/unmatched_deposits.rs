@@ -0,0 +1,61 @@
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Deposit the funds monitor could not match to a credit request, kept until an admin attributes
+/// or refunds it
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::unmatched_deposits)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UnmatchedDeposit {
+    pub id: i32,
+    pub chain_id: i32,
+    pub tx_hash: String,
+    pub log_index: i32,
+    pub block_number: i32,
+    pub block_hash: String,
+    pub from_address: String,
+    pub token_address: String,
+    pub amount_paid: BigDecimal,
+    pub order_id: Option<String>,
+    pub reason: String,
+    pub status: String,
+    pub credit_request_id: Option<i32>,
+    pub refund_tx_hash: Option<String>,
+    pub resolved_by: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub resolved_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::unmatched_deposits)]
+pub struct UnmatchedDepositCreate {
+    pub chain_id: i32,
+    pub tx_hash: String,
+    pub log_index: i32,
+    pub block_number: i32,
+    pub block_hash: String,
+    pub from_address: String,
+    pub token_address: String,
+    pub amount_paid: BigDecimal,
+    pub order_id: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UnmatchedDepositStatus {
+    Pending,
+    Attributed,
+    Refunded,
+}
+
+impl UnmatchedDepositStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnmatchedDepositStatus::Pending => "pending",
+            UnmatchedDepositStatus::Attributed => "attributed",
+            UnmatchedDepositStatus::Refunded => "refunded",
+        }
+    }
+}
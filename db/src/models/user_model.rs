@@ -1,6 +1,7 @@
 use bigdecimal::BigDecimal;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
 
 #[derive(Queryable, Selectable, Serialize, Deserialize)]
 #[diesel(table_name = crate::schema::users)]
@@ -12,6 +13,7 @@ pub struct User {
     pub credit_used: BigDecimal,
     pub allocated_credit_balance: BigDecimal,
     pub sumsub_timestamp: Option<chrono::NaiveDateTime>,
+    pub balance_unit: String,
 }
 
 #[derive(Insertable, Selectable, Serialize, Deserialize)]
@@ -32,4 +34,66 @@ pub struct UserLogin {
     pub credit_used: BigDecimal,
     pub allocated_credit_balance: BigDecimal,
     pub sumsub_timestamp: Option<chrono::NaiveDateTime>,
+    pub balance_unit: String,
+}
+
+/// Unit the balances of a user and of their apps are held in
+///
+/// Credits are byte-equivalents priced from the AVAIL fee of 1 KB, USD balances are debited the
+/// AVAIL fee of each submission at the AVAIL/USD price of the time.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BalanceUnit {
+    Credits,
+    Usd,
+}
+
+impl BalanceUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BalanceUnit::Credits => "credits",
+            BalanceUnit::Usd => "usd",
+        }
+    }
+
+    /// Decimal places amounts credited or converted to the unit are rounded to
+    pub fn scale(&self) -> i64 {
+        match self {
+            BalanceUnit::Credits => 3,
+            BalanceUnit::Usd => 8,
+        }
+    }
+
+    /// Power of ten an amount is scaled by to be stored as an integer in the wallet split of an
+    /// expenditure, USD amounts are stored in micro USD
+    pub fn wallet_exponent(&self) -> i64 {
+        match self {
+            BalanceUnit::Credits => 0,
+            BalanceUnit::Usd => 6,
+        }
+    }
+}
+
+impl fmt::Display for BalanceUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for BalanceUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "credits" => Ok(BalanceUnit::Credits),
+            "usd" => Ok(BalanceUnit::Usd),
+            _ => Err(format!("Unknown balance unit {}", s)),
+        }
+    }
+}
+
+impl User {
+    pub fn balance_unit(&self) -> Result<BalanceUnit, String> {
+        self.balance_unit.parse()
+    }
 }
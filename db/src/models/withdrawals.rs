@@ -0,0 +1,72 @@
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Credits withdrawn back to chain, held from the balance of the user until paid out or rejected
+///
+/// The id is the nonce signed by the operator.
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::withdrawals)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Withdrawal {
+    pub id: i32,
+    pub user_id: String,
+    pub chain_id: i32,
+    pub token_address: String,
+    pub recipient: String,
+    pub credits: BigDecimal,
+    /// Amount paid out, in the smallest unit of the token
+    pub amount: BigDecimal,
+    pub token_usd_price: BigDecimal,
+    pub avail_usd_price: BigDecimal,
+    pub price_source: String,
+    pub priced_at: chrono::NaiveDateTime,
+    pub status: String,
+    pub signature: Option<String>,
+    pub resolved_by: Option<String>,
+    pub tx_hash: Option<String>,
+    pub block_number: Option<i32>,
+    pub block_hash: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub resolved_at: Option<chrono::NaiveDateTime>,
+    pub paid_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::withdrawals)]
+pub struct WithdrawalCreate {
+    pub user_id: String,
+    pub chain_id: i32,
+    pub token_address: String,
+    pub recipient: String,
+    pub credits: BigDecimal,
+    pub amount: BigDecimal,
+    pub token_usd_price: BigDecimal,
+    pub avail_usd_price: BigDecimal,
+    pub price_source: String,
+    pub priced_at: chrono::NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WithdrawalStatus {
+    /// Credits are held, waiting for an operator
+    Pending,
+    /// Signed by an operator, the payout can be claimed on chain
+    Signed,
+    /// Rejected by an operator, the credits were returned
+    Rejected,
+    /// Paid out by the contract
+    Paid,
+}
+
+impl WithdrawalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WithdrawalStatus::Pending => "pending",
+            WithdrawalStatus::Signed => "signed",
+            WithdrawalStatus::Rejected => "rejected",
+            WithdrawalStatus::Paid => "paid",
+        }
+    }
+}
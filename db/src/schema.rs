@@ -1,5 +1,21 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    admin_approvals (id) {
+        id -> Uuid,
+        #[max_length = 64]
+        action -> Varchar,
+        payload -> Jsonb,
+        requested_by -> Varchar,
+        #[max_length = 16]
+        status -> Varchar,
+        resolved_by -> Nullable<Varchar>,
+        created_at -> Timestamp,
+        resolved_at -> Nullable<Timestamp>,
+        expires_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     api_keys (api_key) {
         #[max_length = 255]
@@ -29,6 +45,24 @@ diesel::table! {
         updated_at -> Timestamptz,
         credit_selection -> Nullable<Int2>,
         encryption -> Bool,
+        org_id -> Uuid,
+    }
+}
+
+diesel::table! {
+    audit_events (id) {
+        id -> Uuid,
+        actor -> Varchar,
+        #[max_length = 64]
+        action -> Varchar,
+        #[max_length = 32]
+        target_type -> Varchar,
+        target_id -> Varchar,
+        org_id -> Nullable<Uuid>,
+        before -> Nullable<Jsonb>,
+        after -> Nullable<Jsonb>,
+        ip_address -> Nullable<Varchar>,
+        created_at -> Timestamp,
     }
 }
 
@@ -49,6 +83,17 @@ diesel::table! {
         #[max_length = 255]
         token_address -> Nullable<Varchar>,
         amount_paid -> Nullable<Numeric>,
+        #[max_length = 255]
+        quote_token_address -> Nullable<Varchar>,
+        quote_amount -> Nullable<Numeric>,
+        quote_credits -> Nullable<Numeric>,
+        quote_expires_at -> Nullable<Timestamp>,
+        quote_signature -> Nullable<Varchar>,
+        #[max_length = 16]
+        quote_outcome -> Nullable<Varchar>,
+        quote_difference -> Nullable<Numeric>,
+        confirmations -> Nullable<Int4>,
+        expires_at -> Timestamp,
     }
 }
 
@@ -78,6 +123,48 @@ diesel::table! {
         signature_plaintext_hash -> Nullable<Bytea>,
         address -> Nullable<Bytea>,
         ephemeral_pub_key -> Nullable<Bytea>,
+        #[max_length = 16]
+        billed_unit -> Varchar,
+        avail_usd_price -> Nullable<Numeric>,
+        chain_fee -> Nullable<Numeric>,
+        fee_checked_at -> Nullable<Timestamp>,
+        locked_by -> Nullable<Varchar>,
+        lock_expires_at -> Nullable<Timestamp>,
+        expected_data_hash -> Nullable<Varchar>,
+        #[max_length = 16]
+        error_class -> Nullable<Varchar>,
+        next_retry_at -> Nullable<Timestamp>,
+        failed_at -> Nullable<Timestamp>,
+        avail_per_credit -> Nullable<Numeric>,
+    }
+}
+
+diesel::table! {
+    deposit_addresses (id) {
+        id -> Int4,
+        address -> Varchar,
+        derivation_index -> Int4,
+        user_id -> Nullable<Varchar>,
+        assigned_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    fee_reconciliations (day, app_id) {
+        day -> Date,
+        app_id -> Uuid,
+        submissions -> Int4,
+        unverified_submissions -> Int4,
+        mismatched_submissions -> Int4,
+        underbilled_submissions -> Int4,
+        avail_spent -> Numeric,
+        avail_recorded -> Numeric,
+        credits_billed -> Numeric,
+        usd_billed -> Numeric,
+        avail_billed -> Numeric,
+        margin -> Numeric,
+        updated_at -> Timestamp,
     }
 }
 
@@ -91,6 +178,96 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    organisation_members (org_id, user_id) {
+        org_id -> Uuid,
+        user_id -> Varchar,
+        #[max_length = 20]
+        role -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    organisations (id) {
+        id -> Uuid,
+        name -> Varchar,
+        owner_id -> Varchar,
+        created_at -> Timestamp,
+        billing_id -> Varchar,
+    }
+}
+
+diesel::table! {
+    processed_deposits (id) {
+        id -> Int4,
+        chain_id -> Int4,
+        tx_hash -> Varchar,
+        log_index -> Int4,
+        block_number -> Int4,
+        block_hash -> Varchar,
+        credit_request_id -> Int4,
+        amount_credit -> Numeric,
+        created_at -> Timestamp,
+        token_usd_price -> Nullable<Numeric>,
+        avail_usd_price -> Nullable<Numeric>,
+        price_source -> Nullable<Varchar>,
+        priced_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    signing_keys (id) {
+        id -> Uuid,
+        app_id -> Uuid,
+        user_id -> Varchar,
+        #[max_length = 20]
+        key_type -> Varchar,
+        public_key -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    supported_tokens (id) {
+        id -> Int4,
+        chain_id -> Int4,
+        #[max_length = 255]
+        token_address -> Varchar,
+        #[max_length = 32]
+        symbol -> Varchar,
+        decimals -> Int4,
+        #[max_length = 64]
+        price_feed_id -> Varchar,
+        enabled -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    unmatched_deposits (id) {
+        id -> Int4,
+        chain_id -> Int4,
+        tx_hash -> Varchar,
+        log_index -> Int4,
+        block_number -> Int4,
+        block_hash -> Varchar,
+        from_address -> Varchar,
+        token_address -> Varchar,
+        amount_paid -> Numeric,
+        order_id -> Nullable<Varchar>,
+        reason -> Varchar,
+        #[max_length = 16]
+        status -> Varchar,
+        credit_request_id -> Nullable<Int4>,
+        refund_tx_hash -> Nullable<Varchar>,
+        resolved_by -> Nullable<Varchar>,
+        created_at -> Timestamp,
+        resolved_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Varchar,
@@ -99,22 +276,74 @@ diesel::table! {
         credit_used -> Numeric,
         allocated_credit_balance -> Numeric,
         sumsub_timestamp -> Nullable<Timestamp>,
+        #[max_length = 16]
+        balance_unit -> Varchar,
+    }
+}
+
+diesel::table! {
+    withdrawals (id) {
+        id -> Int4,
+        user_id -> Varchar,
+        chain_id -> Int4,
+        #[max_length = 255]
+        token_address -> Varchar,
+        #[max_length = 255]
+        recipient -> Varchar,
+        credits -> Numeric,
+        amount -> Numeric,
+        token_usd_price -> Numeric,
+        avail_usd_price -> Numeric,
+        price_source -> Varchar,
+        priced_at -> Timestamp,
+        #[max_length = 16]
+        status -> Varchar,
+        signature -> Nullable<Varchar>,
+        resolved_by -> Nullable<Varchar>,
+        tx_hash -> Nullable<Varchar>,
+        block_number -> Nullable<Int4>,
+        block_hash -> Nullable<Varchar>,
+        created_at -> Timestamp,
+        resolved_at -> Nullable<Timestamp>,
+        paid_at -> Nullable<Timestamp>,
     }
 }
 
 diesel::joinable!(api_keys -> apps (app_id));
 diesel::joinable!(api_keys -> users (user_id));
+diesel::joinable!(apps -> organisations (org_id));
 diesel::joinable!(apps -> users (user_id));
 diesel::joinable!(credit_requests -> apps (app_id));
 diesel::joinable!(credit_requests -> users (user_id));
 diesel::joinable!(customer_expenditures -> apps (app_id));
 diesel::joinable!(customer_expenditures -> users (user_id));
+diesel::joinable!(deposit_addresses -> users (user_id));
+diesel::joinable!(fee_reconciliations -> apps (app_id));
+diesel::joinable!(organisation_members -> organisations (org_id));
+diesel::joinable!(organisation_members -> users (user_id));
+diesel::joinable!(organisations -> users (owner_id));
+diesel::joinable!(processed_deposits -> credit_requests (credit_request_id));
+diesel::joinable!(signing_keys -> apps (app_id));
+diesel::joinable!(signing_keys -> users (user_id));
+diesel::joinable!(unmatched_deposits -> credit_requests (credit_request_id));
+diesel::joinable!(withdrawals -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    admin_approvals,
     api_keys,
     apps,
+    audit_events,
     credit_requests,
     customer_expenditures,
+    deposit_addresses,
+    fee_reconciliations,
     indexer_block_numbers,
+    organisation_members,
+    organisations,
+    processed_deposits,
+    signing_keys,
+    supported_tokens,
+    unmatched_deposits,
     users,
+    withdrawals,
 );
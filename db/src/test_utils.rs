@@ -0,0 +1,61 @@
+/// Fixtures shared by the database backed tests of the workspace, enabled with the `test-utils`
+/// feature
+use diesel::{Connection, PgConnection, RunQueryDsl};
+use diesel_async::{
+    pooled_connection::{deadpool::Pool, AsyncDieselConnectionManager},
+    AsyncPgConnection,
+};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use std::env;
+use uuid::Uuid;
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
+
+/// Database created from the migrations for a single test, dropped with it
+pub struct TestDB {
+    pub db_url: String,
+    db_name: String,
+    pub table_url: String,
+    pub pool: Pool<AsyncPgConnection>,
+}
+
+impl TestDB {
+    pub fn init() -> Self {
+        let db_url = env::var("DATABASE_URL_TEST").expect("DATABASE_URL_TEST must be set");
+        let mut conn = PgConnection::establish(&db_url).expect("Can't connect to database");
+        let db_name = "test_".to_string() + &Uuid::new_v4().to_string().replace('-', "_");
+        diesel::sql_query(format!("CREATE DATABASE {}", db_name))
+            .execute(&mut conn)
+            .unwrap_or_else(|_| panic!("Can't create test database {}", db_name));
+        let table_url = format!("{}/{}", &db_url, db_name);
+        let mut conn = PgConnection::establish(&table_url).expect("Can't connect to database");
+        conn.run_pending_migrations(MIGRATIONS)
+            .expect("Can't run migrations");
+
+        let pool = Pool::builder(AsyncDieselConnectionManager::new(table_url.clone()))
+            .max_size(8)
+            .build()
+            .expect("Failed to create pool");
+        Self {
+            db_url,
+            db_name,
+            table_url,
+            pool,
+        }
+    }
+
+    /// Blocking connection to the test database
+    pub fn connection(&self) -> PgConnection {
+        PgConnection::establish(&self.table_url).expect("Can't connect to database")
+    }
+}
+
+impl Drop for TestDB {
+    fn drop(&mut self) {
+        self.pool.close();
+        let mut conn = PgConnection::establish(&self.db_url).expect("Can't connect to database");
+        diesel::sql_query(format!("DROP DATABASE {} WITH (FORCE)", &self.db_name))
+            .execute(&mut conn)
+            .unwrap_or_else(|_| panic!("Can't drop test database {}", &self.db_name));
+    }
+}
@@ -97,7 +97,13 @@ impl EnigmaEncryptionService {
     ) -> Result<EncryptResponse, reqwest::Error> {
         let url = format!("{}/v1/encrypt", self.service_url.clone());
 
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .client
+            .post(&url)
+            .json(&payload)
+            .send()
+            .await?
+            .error_for_status()?;
 
         let response = response.json::<EncryptResponse>().await?;
         Ok(response)
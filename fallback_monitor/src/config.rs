@@ -6,6 +6,8 @@ use serde::{Deserialize, Serialize};
 use std::{env, error::Error, fs, io};
 use toml;
 use turbo_da_core::logger::{error, info, warn};
+use turbo_da_core::price_oracle::PriceOracleConfig;
+use turbo_da_core::treasury::TreasuryConfig;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppConfig {
@@ -15,9 +17,22 @@ pub struct AppConfig {
     pub avail_rpc_endpoint: Vec<String>,
     pub coingecko_api_url: String,
     pub coingecko_api_key: String,
+    /// Caching and sanity checks of the AVAIL/USD price balances held in USD are billed at
+    #[serde(default)]
+    pub price_oracle: PriceOracleConfig,
+    /// Balance monitoring and top ups of the signers
+    #[serde(default)]
+    pub treasury: TreasuryConfig,
     pub limit: i64,
     pub enigma_url: String,
     pub redis_url: String,
+    /// Finalized blocks searched for a prior inclusion before a submission is posted again
+    #[serde(default = "default_inclusion_lookback_blocks")]
+    pub inclusion_lookback_blocks: u32,
+}
+
+fn default_inclusion_lookback_blocks() -> u32 {
+    100
 }
 
 impl Default for AppConfig {
@@ -29,9 +44,12 @@ impl Default for AppConfig {
             avail_rpc_endpoint: vec![],
             coingecko_api_url: String::new(),
             coingecko_api_key: String::new(),
+            price_oracle: PriceOracleConfig::default(),
+            treasury: TreasuryConfig::default(),
             limit: 10,
             enigma_url: String::new(),
             redis_url: String::new(),
+            inclusion_lookback_blocks: default_inclusion_lookback_blocks(),
         }
     }
 }
@@ -102,6 +120,10 @@ impl AppConfig {
         }
         let enigma_url = env::var("ENIGMA_URL")?;
         let redis_url = env::var("REDIS_URL")?;
+        let inclusion_lookback_blocks = env::var("INCLUSION_LOOKBACK_BLOCKS")
+            .ok()
+            .and_then(|s| s.parse::<u32>().ok())
+            .unwrap_or_else(default_inclusion_lookback_blocks);
         info(&format!("Config loaded from environment variables"));
 
         Ok(AppConfig {
@@ -111,9 +133,12 @@ impl AppConfig {
             avail_rpc_endpoint,
             coingecko_api_url,
             coingecko_api_key,
+            price_oracle: PriceOracleConfig::from_env(),
+            treasury: TreasuryConfig::from_env(),
             limit,
             enigma_url,
             redis_url,
+            inclusion_lookback_blocks,
         })
     }
 }
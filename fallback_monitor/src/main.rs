@@ -1,39 +1,35 @@
-use avail_rust::Client;
 use chrono::Utc;
 use config::AppConfig;
 use cron::Schedule;
-use data_submission::redis::Redis;
-use enigma::EnigmaEncryptionService;
-use monitor::monitor::monitor_failed_transactions;
+use monitor::service::{generate_avail_sdk, FallbackService};
 use observability::{init_meter, init_tracer};
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::{
     self,
-    time::{self, sleep, Duration},
+    time::{self, Duration},
 };
 use turbo_da_core::{
     logger::{error, info},
+    price_oracle::PriceFeed,
+    treasury::{Signers, Treasury, TreasuryConfig},
     utils::generate_keygen_list,
 };
 
 mod config;
 mod monitor;
 
-const WAIT_TIME: u64 = 5;
-
 /// Main entry point for the fallback monitor service
 ///
 /// # Description
 /// Initializes the service by:
 /// 1. Loading configuration
 /// 2. Setting up a cron schedule to run every 10 seconds
-/// 3. Establishing database connection
-/// 4. Creating an Avail SDK instance
-/// 5. Running an infinite loop to monitor failed transactions
+/// 3. Creating the service holding the database pool and the Avail SDK instance
+/// 4. Running an infinite loop to monitor failed transactions
 ///
 /// The service will continuously check for failed transactions at the scheduled intervals
-/// and attempt to process them using the Avail network.
+/// and attempt to process them using the Avail network, after checking its connections.
 #[tokio::main]
 async fn main() {
     init_meter("fallback_service");
@@ -53,7 +49,45 @@ async fn main() {
 
     let mut interval = schedule.upcoming(Utc);
 
-    let keypair = generate_keygen_list(app_config.limit as i32, &app_config.private_keys).await;
+    let keypair = generate_keygen_list(
+        app_config.private_keys.len() as i32,
+        &app_config.private_keys,
+    )
+    .await;
+    let signers = Arc::new(Signers::new(keypair));
+
+    // Balances are checked to route resubmissions to funded signers only. Signers are topped up
+    // by the data submission service alone, both would otherwise send every top up
+    let treasury = Treasury::new(
+        generate_avail_sdk(&app_config.avail_rpc_endpoint).await,
+        signers.clone(),
+        TreasuryConfig {
+            top_up_amount_avail: 0,
+            private_key: None,
+            ..app_config.treasury.clone()
+        },
+    );
+    tokio::spawn(treasury.run());
+
+    let prices = match PriceFeed::from_config(
+        &app_config.coingecko_api_url,
+        &app_config.coingecko_api_key,
+        &app_config.price_oracle,
+    ) {
+        Ok(prices) => prices,
+        Err(e) => {
+            error(&format!("Error creating price feed: {}", e));
+            return;
+        }
+    };
+
+    let mut service = match FallbackService::new(app_config, signers, prices).await {
+        Ok(service) => service,
+        Err(e) => {
+            error(&format!("Error creating fallback service: {}", e));
+            return;
+        }
+    };
 
     while let Some(next_time) = interval.next() {
         let now = Utc::now();
@@ -68,66 +102,10 @@ async fn main() {
             Utc::now()
         ));
 
-        let sdk = generate_avail_sdk(&Arc::new(app_config.avail_rpc_endpoint.clone())).await;
-
-        let enigma = EnigmaEncryptionService::new(app_config.enigma_url.clone());
-
-        let redis = Arc::new(Redis::new(app_config.redis_url.as_str()));
-
-        monitor_failed_transactions(
-            &app_config.database_url,
-            &sdk,
-            &keypair,
-            redis,
-            app_config.retry_count,
-            app_config.limit,
-            &enigma,
-        )
-        .await;
-    }
-}
-
-/// Attempts to establish a connection to an Avail network endpoint
-///
-/// # Arguments
-/// * `endpoints` - A vector of Avail RPC endpoint URLs to try connecting to
-///
-/// # Returns
-/// Returns an SDK instance connected to a working endpoint
-///
-/// # Description
-/// This function implements a retry mechanism that:
-/// 1. Tries each endpoint in the provided list
-/// 2. If all endpoints fail, waits for WAIT_TIME seconds before retrying
-/// 3. Continues until a successful connection is established
-///
-/// The function cycles through the endpoints indefinitely until a connection succeeds.
-async fn generate_avail_sdk(endpoints: &Arc<Vec<String>>) -> Client {
-    let mut attempts = 0;
-
-    loop {
-        if attempts >= endpoints.len() {
-            attempts = 0;
-        }
-        let endpoint = &endpoints[attempts];
-        info(&format!("Attempting to connect endpoint: {:?}", endpoint));
-        match Client::new(endpoint).await {
-            Ok(sdk) => {
-                info(&format!("Connected successfully to endpoint: {}", endpoint));
-                return sdk;
-            }
-            Err(e) => {
-                error(&format!(
-                    "Failed to connect to endpoint {}: {:?}",
-                    endpoint, e
-                ));
-                attempts += 1;
-            }
+        if !service.health_check().await {
+            continue;
         }
 
-        info(&format!(
-            "All endpoints failed. Waiting 5 seconds before next retry...."
-        ));
-        sleep(Duration::from_secs(WAIT_TIME)).await;
+        service.monitor_failed_transactions().await;
     }
 }
@@ -1 +1,3 @@
 pub mod monitor;
+pub mod scheduler;
+pub mod service;
@@ -1,183 +1,382 @@
-use std::sync::Arc;
+use std::collections::HashMap;
 
-use avail_rust::{Client, Keypair};
-use avail_utils::submit_data::SubmitDataAvail;
+use super::service::FallbackService;
+use avail_rust::KeypairExt;
+use avail_utils::{
+    inclusion::{data_hash, find_submitted_data, get_submitted_data, SubmittedData},
+    submit_data::SubmitDataAvail,
+};
 use data_submission::{ProcessSubmitResponse, Response};
 use db::models::{customer_expenditure::CustomerExpenditureGetWithPayload, user_model::User};
 /// This file contains logic to monitor the failing transactions.
 /// If there are failed transactions it picks them and tries to resubmit it
 /// If successful updates the state of the data to "Resolved".
 use db::{
-    controllers::{customer_expenditure::increase_retry_count, misc::get_unresolved_transactions},
+    controllers::{
+        customer_expenditure::{
+            add_error_entry, increase_retry_count, lease_submission, release_submission, worker_id,
+        },
+        misc::lease_unresolved_transactions,
+    },
+    errors::TIMEOUT_ERROR,
     models::apps::Apps,
 };
 
-use data_submission::redis::Redis;
-use diesel_async::{
-    pooled_connection::{deadpool::Pool, AsyncDieselConnectionManager},
-    AsyncConnection, AsyncPgConnection,
-};
-use enigma::EnigmaEncryptionService;
+use diesel_async::AsyncPgConnection;
 use observability::{log_fallback_txn_error, log_retry_count};
+use std::sync::Arc;
+use tokio::time::{timeout, Duration};
 use turbo_da_core::logger::{error, info};
 
-/// Monitors and processes failed transactions from the database
-///
-/// # Arguments
-/// * `connection` - Database connection handle
-/// * `config` - Application configuration
-/// * `client` - Avail SDK client instance
-/// * `account` - Keypair for transaction signing
-///
-/// # Description
-/// Fetches unresolved transactions from the database and processes them
-/// by attempting to resubmit them to the Avail network
-pub async fn monitor_failed_transactions(
-    connection: &String,
-    client: &Client,
-    account: &Vec<Keypair>,
-    redis: Arc<Redis>,
-    retry_count: i32,
-    limit: i64,
-    enigma: &EnigmaEncryptionService,
-) {
-    let mut connection_client = AsyncPgConnection::establish(connection)
+impl FallbackService {
+    /// Monitors and processes failed transactions from the database
+    ///
+    /// # Description
+    /// Leases unresolved transactions from the database and processes them
+    /// by attempting to resubmit them to the Avail network. Transactions leased
+    /// to another fallback monitor or a data submission worker are skipped, and
+    /// transactions already included on chain are recorded instead of resubmitted.
+    pub async fn monitor_failed_transactions(&mut self) {
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                error(&format!("Couldn't get a db connection: {}", e));
+                return;
+            }
+        };
+        let unresolved_transactions = lease_unresolved_transactions(
+            &mut connection,
+            self.config.retry_count,
+            self.config.limit,
+            worker_id(),
+        )
+        .await;
+
+        let failed_transactions_list = match unresolved_transactions {
+            Ok(failed_transactions_list) => failed_transactions_list,
+            Err(e) => {
+                error(&format!(
+                    "Couldn't fetch unresolved transactions from db: {}",
+                    e
+                ));
+                return;
+            }
+        };
+        if failed_transactions_list.is_empty() {
+            info(&"No unresolved transactions found".to_string());
+            return;
+        }
+
+        // A timed out submission may still have landed, nothing is resubmitted unless the
+        // chain could be searched for it
+        let accounts: Vec<_> = (0..self.signers.len())
+            .map(|index| self.signers.keypair(index).account_id())
+            .collect();
+        let submitted = match find_submitted_data(
+            &self.client,
+            &accounts,
+            self.config.inclusion_lookback_blocks,
+            &mut self.blocks,
+        )
         .await
-        .expect("Failed to connect to db");
-    let unresolved_transactions =
-        get_unresolved_transactions(&mut connection_client, retry_count, limit).await;
-
-    match unresolved_transactions {
-        Ok(failed_transactions_list) => {
-            if failed_transactions_list.is_empty() {
-                info(&format!("No unresolved transactions found"));
+        {
+            Ok(submitted) => submitted,
+            Err(e) => {
+                error(&format!(
+                    "Couldn't search the chain for prior inclusions: {}",
+                    e
+                ));
+                for (details, _, _) in &failed_transactions_list {
+                    let _ = release_submission(&mut connection, &details.id, worker_id()).await;
+                }
                 return;
             }
-            process_failed_transactions(
-                connection,
-                client,
-                redis,
-                account,
-                retry_count,
-                failed_transactions_list,
-                enigma,
-            )
+        };
+        // Each transaction of the round takes its own connection from the pool
+        drop(connection);
+
+        let failed_transactions_list = match_inclusions(failed_transactions_list, submitted);
+        self.process_failed_transactions(failed_transactions_list)
             .await;
-        }
-        Err(e) => {
-            error(&format!("Couldn't fetch unresolved transactions from db: {}", e));
-        }
     }
-}
 
-/// Processes a list of failed transactions by attempting to resubmit them
-///
-/// # Arguments
-/// * `connection` - Database connection handle
-/// * `config` - Application configuration
-/// * `client` - Avail SDK client instance
-/// * `account` - Keypair for transaction signing
-/// * `failed_transactions_list` - List of failed transactions to process
-///
-/// # Description
-/// For each failed transaction:
-/// 1. Retrieves the associated app ID
-/// 2. Attempts to resubmit the transaction data
-/// 3. If successful, calculates fees and updates the transaction status
-async fn process_failed_transactions(
-    connection: &String,
-    client: &Client,
-    redis: Arc<Redis>,
-    account: &Vec<Keypair>,
-    retry_count: i32,
-    failed_transactions_list: Vec<(CustomerExpenditureGetWithPayload, Apps, User)>,
-    enigma: &EnigmaEncryptionService,
-) {
-    let db_config = AsyncDieselConnectionManager::<AsyncPgConnection>::new(connection);
-
-    let pool: Pool<AsyncPgConnection> = Pool::builder(db_config)
-        .max_size(failed_transactions_list.len())
-        .build()
-        .expect("Failed to create pool");
-
-    let pool_ref = &pool;
-
-    let futures = failed_transactions_list.into_iter().enumerate().map(
-        |(index, (customer_expenditure_details, account_details, _))| {
-            let redis = Arc::clone(&redis);
-            async move {
-                let mut connection = pool_ref.get().await.unwrap();
-                info(&format!(
-                    "Processing failed transaction submission id: {:?} ",
-                    customer_expenditure_details.id
-                ));
-                let result =
-                    increase_retry_count(customer_expenditure_details.id, &mut connection).await;
-                if result.is_err() {
+    /// Processes a list of failed transactions by attempting to resubmit them
+    ///
+    /// # Arguments
+    /// * `failed_transactions_list` - List of failed transactions to process
+    ///
+    /// # Description
+    /// For each failed transaction:
+    /// 1. Retrieves the associated app ID
+    /// 2. Records it if it was found on chain, otherwise attempts to resubmit the transaction data
+    ///    with a signer taken from the scheduler
+    /// 3. If successful, calculates fees and updates the transaction status
+    /// 4. Releases its lease, so it can be retried by any worker
+    async fn process_failed_transactions(
+        &self,
+        failed_transactions_list: Vec<(
+            CustomerExpenditureGetWithPayload,
+            Apps,
+            Option<SubmittedData>,
+        )>,
+    ) {
+        let futures = failed_transactions_list.into_iter().map(
+            |(customer_expenditure_details, account_details, inclusion)| async move {
+                let submission_id = customer_expenditure_details.id;
+                // The lease expires when no connection can be had
+                let mut connection = match self.pool.get().await {
+                    Ok(connection) => connection,
+                    Err(e) => {
+                        log_error(
+                            &submission_id.to_string(),
+                            &format!("Couldn't get a db connection: {}", e),
+                        );
+                        return;
+                    }
+                };
+
+                match inclusion {
+                    Some(submitted) => {
+                        self.record_inclusion(
+                            &mut connection,
+                            customer_expenditure_details,
+                            account_details,
+                            submitted,
+                        )
+                        .await
+                    }
+                    None => {
+                        self.resubmit(
+                            &mut connection,
+                            customer_expenditure_details,
+                            account_details,
+                        )
+                        .await
+                    }
+                }
+
+                if let Err(e) =
+                    release_submission(&mut connection, &submission_id, worker_id()).await
+                {
                     log_error(
-                        &customer_expenditure_details.id.to_string(),
-                        "Failed to increase retry count",
+                        &submission_id.to_string(),
+                        &format!("Failed to release lease: {}", e),
                     );
-                    return;
                 }
+            },
+        );
+
+        futures::future::join_all(futures).await;
+    }
+
+    /// Records a transaction found on chain, billed as if it was submitted now
+    async fn record_inclusion(
+        &self,
+        connection: &mut AsyncPgConnection,
+        customer_expenditure_details: CustomerExpenditureGetWithPayload,
+        account_details: Apps,
+        submitted: SubmittedData,
+    ) {
+        // The extrinsic was found among the extrinsics of our signers
+        let Some(index) = self.signers.position(&submitted.signer) else {
+            log_error(
+                &customer_expenditure_details.id.to_string(),
+                "Prior inclusion was signed by an unknown signer",
+            );
+            return;
+        };
+        let response = Response {
+            raw_payload: Default::default(),
+            submission_id: customer_expenditure_details.id,
+            thread_id: 0,
+            app_id: account_details.id,
+            avail_app_id: account_details.app_id,
+        };
+        let mut process_response = ProcessSubmitResponse::new(
+            &response,
+            connection,
+            SubmitDataAvail::new(
+                &self.client,
+                self.signers.keypair(index),
+                account_details.app_id,
+            ),
+            &self.enigma,
+            Arc::clone(&self.redis),
+            &self.prices,
+        );
+        // Left for a later round when it can't be read back, it is resubmitted
+        // once it falls out of the blocks searched
+        let result = match get_submitted_data(&self.client, &submitted).await {
+            Ok((data, info)) => process_response.record_inclusion(&data, info).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(_) => info(&format!(
+                "Recorded prior inclusion of submission id: {:?} in block {}",
+                customer_expenditure_details.id, submitted.block_number
+            )),
+            Err(e) => log_error(
+                &customer_expenditure_details.id.to_string(),
+                &format!("Failed to record prior inclusion: {}", e),
+            ),
+        }
+    }
 
-                log_retry_count(
+    /// Submits the payload of a transaction again
+    async fn resubmit(
+        &self,
+        connection: &mut AsyncPgConnection,
+        customer_expenditure_details: CustomerExpenditureGetWithPayload,
+        account_details: Apps,
+    ) {
+        // Left for a later round, without counting a retry
+        let Some(signer) = self.scheduler.acquire().await else {
+            log_error(
+                &customer_expenditure_details.id.to_string(),
+                "Signer is underfunded",
+            );
+            return;
+        };
+        // The lease may have expired while waiting for a signer, and the transaction been taken
+        // by another worker
+        match lease_submission(connection, &customer_expenditure_details.id, worker_id()).await {
+            Ok(true) => {}
+            Ok(false) => {
+                info(&format!(
+                    "Submission id: {:?} was leased by another worker, skipping",
+                    customer_expenditure_details.id
+                ));
+                return;
+            }
+            Err(e) => {
+                log_error(
                     &customer_expenditure_details.id.to_string(),
-                    customer_expenditure_details.retry_count as usize,
+                    &format!("Failed to renew lease: {}", e),
                 );
+                return;
+            }
+        }
+        info(&format!(
+            "Processing failed transaction submission id: {:?} with signer {}",
+            customer_expenditure_details.id,
+            signer.index()
+        ));
+        let result = increase_retry_count(customer_expenditure_details.id, connection).await;
+        if result.is_err() {
+            log_error(
+                &customer_expenditure_details.id.to_string(),
+                "Failed to increase retry count",
+            );
+            return;
+        }
 
-                if customer_expenditure_details.retry_count > retry_count {
-                    log_error(
-                        &customer_expenditure_details.id.to_string(),
-                        "Retry count exceeded",
-                    );
-                    return;
-                }
+        log_retry_count(
+            &customer_expenditure_details.id.to_string(),
+            customer_expenditure_details.retry_count as usize,
+        );
 
-                let Some(data) = customer_expenditure_details.payload else {
-                    log_error(
-                        &customer_expenditure_details.id.to_string(),
-                        "No payload found for transaction id",
-                    );
-                    return;
-                };
+        if customer_expenditure_details.retry_count > self.config.retry_count {
+            log_error(
+                &customer_expenditure_details.id.to_string(),
+                "Retry count exceeded",
+            );
+            add_error_entry(
+                &customer_expenditure_details.id,
+                "Retry count exceeded".to_string(),
+                connection,
+            )
+            .await;
+            return;
+        }
 
-                let submit_data_class =
-                    SubmitDataAvail::new(&client, &account[index], account_details.app_id);
+        let Some(data) = customer_expenditure_details.payload else {
+            log_error(
+                &customer_expenditure_details.id.to_string(),
+                "No payload found for transaction id",
+            );
+            add_error_entry(
+                &customer_expenditure_details.id,
+                "No payload found for transaction id".to_string(),
+                connection,
+            )
+            .await;
+            return;
+        };
 
-                let response = Response {
-                    raw_payload: data.into(),
-                    submission_id: customer_expenditure_details.id,
-                    thread_id: 0,
-                    app_id: account_details.id,
-                    avail_app_id: account_details.app_id,
-                };
+        let submit_data_class =
+            SubmitDataAvail::new(&self.client, signer.keypair(), account_details.app_id);
 
-                let mut process_response = ProcessSubmitResponse::new(
-                    &response,
-                    &mut connection,
-                    submit_data_class,
-                    enigma,
-                    redis,
-                );
+        let response = Response {
+            raw_payload: data.into(),
+            submission_id: customer_expenditure_details.id,
+            thread_id: 0,
+            app_id: account_details.id,
+            avail_app_id: account_details.app_id,
+        };
 
-                let result = process_response.process_response().await;
-                match result {
-                    Ok(_) => {
-                        info(&format!(
-                            "Successfully processed response for submission id: {:?}",
-                            customer_expenditure_details.id
-                        ));
-                    }
-                    Err(e) => {
-                        log_error(&customer_expenditure_details.id.to_string(), &e);
-                    }
-                }
+        let mut process_response = ProcessSubmitResponse::new(
+            &response,
+            connection,
+            submit_data_class,
+            &self.enigma,
+            Arc::clone(&self.redis),
+            &self.prices,
+        );
+
+        // Bounded like the data submission workers, so the lease outlasts the submission
+        let result = timeout(
+            Duration::from_secs(120),
+            process_response.process_response(),
+        )
+        .await;
+        let err = match result {
+            Ok(Ok(_)) => {
+                info(&format!(
+                    "Successfully processed response for submission id: {:?}",
+                    customer_expenditure_details.id
+                ));
+                return;
             }
-        },
-    );
+            Ok(Err(e)) => e,
+            Err(_) => TIMEOUT_ERROR.to_string(),
+        };
+        // Classified to schedule the next retry, or to give up on the submission
+        log_error(&customer_expenditure_details.id.to_string(), &err);
+        add_error_entry(&customer_expenditure_details.id, err, connection).await;
+    }
+}
 
-    futures::future::join_all(futures).await;
+/// Pairs each transaction with its extrinsic found on chain, if any
+///
+/// Transactions are matched on the hash of the data last submitted for them, or of their payload
+/// when it is submitted unencrypted, and on their app id. An extrinsic is matched only once.
+fn match_inclusions(
+    failed_transactions_list: Vec<(CustomerExpenditureGetWithPayload, Apps, User)>,
+    mut submitted: HashMap<String, SubmittedData>,
+) -> Vec<(
+    CustomerExpenditureGetWithPayload,
+    Apps,
+    Option<SubmittedData>,
+)> {
+    failed_transactions_list
+        .into_iter()
+        .map(|(details, account, _)| {
+            let payload_hash = match (&details.payload, account.encryption) {
+                (Some(payload), false) => Some(data_hash(payload)),
+                _ => None,
+            };
+            let inclusion = [details.expected_data_hash.clone(), payload_hash]
+                .into_iter()
+                .flatten()
+                .find(|hash| {
+                    submitted
+                        .get(hash)
+                        .is_some_and(|found| found.app_id == account.app_id as u32)
+                })
+                .and_then(|hash| submitted.remove(&hash));
+            (details, account, inclusion)
+        })
+        .collect()
 }
 
 fn log_error(id: &str, message: &str) {
@@ -0,0 +1,85 @@
+/// Scheduling of the signers over the submissions of a round
+/// - A signer signs one submission at a time, so concurrent submissions never race for its nonce
+/// - Idle signers are handed out in turn, a round may hold more submissions than there are
+///   signers, the extra submissions wait for a signer to be released
+#[cfg(test)]
+mod test;
+
+use avail_rust::Keypair;
+use std::{
+    collections::VecDeque,
+    pin::pin,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::Notify;
+use turbo_da_core::treasury::Signers;
+
+/// Hands out the funded signers to submissions, one submission per signer at a time
+pub struct KeypairScheduler {
+    signers: Arc<Signers>,
+    idle: Mutex<VecDeque<usize>>,
+    released: Notify,
+}
+
+impl KeypairScheduler {
+    pub fn new(signers: Arc<Signers>) -> Self {
+        let idle = (0..signers.len()).collect();
+        KeypairScheduler {
+            signers,
+            idle: Mutex::new(idle),
+            released: Notify::new(),
+        }
+    }
+
+    /// Waits for an idle funded signer, `None` when every signer is underfunded
+    ///
+    /// The signer is returned to the scheduler when the lease is dropped.
+    pub async fn acquire(&self) -> Option<SignerLease<'_>> {
+        loop {
+            // Registered before looking for a signer, so a release in between isn't missed
+            let mut released = pin!(self.released.notified());
+            released.as_mut().enable();
+
+            // Nothing to wait for when every signer is underfunded
+            self.signers.pick_funded()?;
+            {
+                let mut idle = self.idle.lock().unwrap();
+                let funded = idle.iter().position(|index| self.signers.is_funded(*index));
+                if let Some(index) = funded.and_then(|position| idle.remove(position)) {
+                    return Some(SignerLease {
+                        scheduler: self,
+                        index,
+                    });
+                }
+            }
+            released.await;
+        }
+    }
+
+    fn release(&self, index: usize) {
+        self.idle.lock().unwrap().push_back(index);
+        self.released.notify_one();
+    }
+}
+
+/// A signer leased to a single submission
+pub struct SignerLease<'a> {
+    scheduler: &'a KeypairScheduler,
+    index: usize,
+}
+
+impl SignerLease<'_> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn keypair(&self) -> &Keypair {
+        self.scheduler.signers.keypair(self.index)
+    }
+}
+
+impl Drop for SignerLease<'_> {
+    fn drop(&mut self) {
+        self.scheduler.release(self.index);
+    }
+}
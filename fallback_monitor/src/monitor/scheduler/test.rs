@@ -0,0 +1,49 @@
+use super::KeypairScheduler;
+use avail_rust::constants::dev_accounts;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+use tokio::time::{sleep, Duration};
+use turbo_da_core::treasury::Signers;
+
+#[tokio::test]
+async fn test_signers_are_leased_to_one_submission_at_a_time() {
+    let signers = Arc::new(Signers::new(vec![
+        dev_accounts::alice(),
+        dev_accounts::bob(),
+    ]));
+    let scheduler = KeypairScheduler::new(signers);
+    let in_use = Mutex::new(HashSet::new());
+
+    // More submissions than signers, each of them eventually gets one
+    let submissions = (0..6).map(|_| async {
+        let signer = scheduler.acquire().await.expect("signers are funded");
+        assert!(in_use.lock().unwrap().insert(signer.index()));
+        sleep(Duration::from_millis(10)).await;
+        in_use.lock().unwrap().remove(&signer.index());
+    });
+    futures::future::join_all(submissions).await;
+
+    assert!(in_use.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_only_funded_signers_are_leased() {
+    let signers = Arc::new(Signers::new(vec![
+        dev_accounts::alice(),
+        dev_accounts::bob(),
+    ]));
+    let scheduler = KeypairScheduler::new(signers.clone());
+
+    signers.set_funded(0, false);
+    for _ in 0..3 {
+        assert_eq!(
+            scheduler.acquire().await.map(|signer| signer.index()),
+            Some(1)
+        );
+    }
+
+    signers.set_funded(1, false);
+    assert!(scheduler.acquire().await.is_none());
+}
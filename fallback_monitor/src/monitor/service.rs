@@ -0,0 +1,136 @@
+/// Long lived state of the fallback monitor
+/// - The database pool, Avail client, Enigma client and Redis pool are built once and shared by
+///   every round, instead of being rebuilt each time failed transactions are checked
+/// - Blocks searched for prior inclusions are kept, each round only fetches the new ones
+/// - Before each round the Avail client is health checked and reconnected if its endpoint stopped
+///   responding, the database and Redis pools replace broken connections on their own
+use super::scheduler::KeypairScheduler;
+use crate::config::AppConfig;
+use avail_rust::Client;
+use avail_utils::inclusion::BlockCache;
+use data_submission::redis::Redis;
+use diesel_async::{
+    pooled_connection::{deadpool::Pool, AsyncDieselConnectionManager},
+    AsyncPgConnection,
+};
+use enigma::EnigmaEncryptionService;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+use turbo_da_core::{
+    logger::{error, info},
+    price_oracle::PriceFeed,
+    treasury::Signers,
+};
+
+const WAIT_TIME: u64 = 5;
+
+pub struct FallbackService {
+    pub(super) config: AppConfig,
+    pub(super) pool: Pool<AsyncPgConnection>,
+    pub(super) client: Client,
+    pub(super) signers: Arc<Signers>,
+    pub(super) scheduler: KeypairScheduler,
+    pub(super) enigma: EnigmaEncryptionService,
+    pub(super) redis: Arc<Redis>,
+    pub(super) prices: Arc<PriceFeed>,
+    /// Blocks searched for prior inclusions in previous rounds
+    pub(super) blocks: BlockCache,
+}
+
+impl FallbackService {
+    /// Connects to Avail and builds the pools shared by every round
+    ///
+    /// The database pool holds up to `limit` connections, one per transaction of a round.
+    pub async fn new(
+        config: AppConfig,
+        signers: Arc<Signers>,
+        prices: Arc<PriceFeed>,
+    ) -> Result<Self, String> {
+        let db_config =
+            AsyncDieselConnectionManager::<AsyncPgConnection>::new(&config.database_url);
+        let pool = Pool::builder(db_config)
+            .max_size(config.limit.max(1) as usize)
+            .build()
+            .map_err(|e| format!("Failed to create pool: {}", e))?;
+
+        let client = generate_avail_sdk(&config.avail_rpc_endpoint).await;
+        let enigma = EnigmaEncryptionService::new(config.enigma_url.clone());
+        let redis = Arc::new(Redis::new(config.redis_url.as_str()));
+        let scheduler = KeypairScheduler::new(signers.clone());
+
+        Ok(FallbackService {
+            config,
+            pool,
+            client,
+            signers,
+            scheduler,
+            enigma,
+            redis,
+            prices,
+            blocks: BlockCache::default(),
+        })
+    }
+
+    /// Checks the connections a round relies on
+    ///
+    /// Reconnects to Avail when its endpoint stopped responding. Returns `false` when the
+    /// database can't be reached, the round is then skipped.
+    pub async fn health_check(&mut self) -> bool {
+        if let Err(e) = self.client.finalized().block_height().await {
+            error(&format!(
+                "Avail endpoint is unhealthy, reconnecting: {:?}",
+                e
+            ));
+            self.client = generate_avail_sdk(&self.config.avail_rpc_endpoint).await;
+        }
+
+        if let Err(e) = self.pool.get().await {
+            error(&format!("Couldn't connect to db, skipping round: {}", e));
+            return false;
+        }
+        true
+    }
+}
+
+/// Attempts to establish a connection to an Avail network endpoint
+///
+/// # Arguments
+/// * `endpoints` - A vector of Avail RPC endpoint URLs to try connecting to
+///
+/// # Returns
+/// Returns an SDK instance connected to a working endpoint
+///
+/// # Description
+/// This function implements a retry mechanism that:
+/// 1. Tries each endpoint in the provided list
+/// 2. If all endpoints fail, waits for WAIT_TIME seconds before retrying
+/// 3. Continues until a successful connection is established
+///
+/// The function cycles through the endpoints indefinitely until a connection succeeds.
+pub async fn generate_avail_sdk(endpoints: &[String]) -> Client {
+    let mut attempts = 0;
+
+    loop {
+        if attempts >= endpoints.len() {
+            attempts = 0;
+        }
+        let endpoint = &endpoints[attempts];
+        info(&format!("Attempting to connect endpoint: {:?}", endpoint));
+        match Client::new(endpoint).await {
+            Ok(sdk) => {
+                info(&format!("Connected successfully to endpoint: {}", endpoint));
+                return sdk;
+            }
+            Err(e) => {
+                error(&format!(
+                    "Failed to connect to endpoint {}: {:?}",
+                    endpoint, e
+                ));
+                attempts += 1;
+            }
+        }
+
+        info(&"All endpoints failed. Waiting 5 seconds before next retry....".to_string());
+        sleep(Duration::from_secs(WAIT_TIME)).await;
+    }
+}
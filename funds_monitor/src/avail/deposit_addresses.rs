@@ -0,0 +1,57 @@
+/// Per-user Avail deposit addresses
+/// - Addresses are derived from the deposit seed as `<seed>//turbo-da-deposit//<index>`, only the
+///   funds monitor holds the seed
+/// - A pool of unassigned addresses is kept in `deposit_addresses`, turbo-da-core hands them out
+///   to users without knowing the seed
+use avail_rust::prelude::*;
+use db::models::deposit_addresses::DepositAddressCreate;
+use diesel::PgConnection;
+use turbo_da_core::logger::info;
+
+use crate::utils::Utils;
+use turbo_da_core::deposit::avail::DepositAddresses;
+
+pub(crate) fn derive_keypair(seed: &str, index: i32) -> Result<Keypair, String> {
+    Keypair::from_str(&format!("{}//turbo-da-deposit//{}", seed, index))
+        .map_err(|e| format!("Failed to derive deposit address {}: {:?}", index, e))
+}
+
+/// Derives new addresses until `pool_size` of them are unassigned
+pub(crate) fn fill_pool(
+    seed: &str,
+    pool_size: i64,
+    utils: &Utils,
+    connection: &mut PgConnection,
+) -> Result<(), String> {
+    let (free, next_index) = utils.get_deposit_address_pool(connection)?;
+    if free >= pool_size {
+        return Ok(());
+    }
+
+    let mut addresses = Vec::new();
+    for index in next_index..next_index + (pool_size - free) as i32 {
+        addresses.push(DepositAddressCreate {
+            address: derive_keypair(seed, index)?.account_id().to_string(),
+            derivation_index: index,
+        });
+    }
+    let inserted = utils.insert_deposit_addresses(&addresses, connection)?;
+    info(&format!("Added {} deposit addresses to the pool", inserted));
+    Ok(())
+}
+
+/// Treasury and the deposit addresses assigned to users
+pub(crate) fn load_deposit_addresses(
+    treasury: &AccountId,
+    utils: &Utils,
+    connection: &mut PgConnection,
+) -> Result<DepositAddresses, String> {
+    let mut addresses = DepositAddresses::new(treasury.clone());
+    for row in utils.get_assigned_deposit_addresses(connection)? {
+        let (Ok(account), Some(user)) = (AccountId::from_str(&row.address), row.user_id) else {
+            continue;
+        };
+        addresses.derived.insert(account.0, user);
+    }
+    Ok(addresses)
+}
@@ -0,0 +1,198 @@
+mod deposit_addresses;
+mod sweeper;
+#[allow(clippy::module_inception)]
+mod test;
+
+use avail_rust::block_api::BlockExtOptionsExpanded;
+use avail_rust::prelude::*;
+use deposit_addresses::{fill_pool, load_deposit_addresses};
+use std::sync::Arc;
+use std::time::Duration;
+use turbo_da_core::chain::AVAIL_CHAIN_ID;
+use turbo_da_core::deposit::avail::{
+    block_timestamp, read_deposit, AvailDeposit, DepositAddresses, DepositReference,
+};
+use turbo_da_core::logger::{debug, error, info};
+use turbo_da_core::price_oracle::PriceFeed;
+
+use crate::config::Config;
+use crate::source::{Beneficiary, ChainEvent, DepositSource, Scanner};
+use crate::utils::{Deposit, DepositLocation, Utils};
+
+/// Delay before resuming from the persisted cursor after an error
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+/// Interval between two checks of the finalized head, about one block
+const POLL_INTERVAL: Duration = Duration::from_secs(20);
+
+// Remark is the order id in hex format
+pub async fn run(cfg: Arc<Config>, prices: Arc<PriceFeed>) -> Result<(), String> {
+    debug(&format!("Starting Avail Chain Monitor"));
+    let sdk = Client::new(cfg.avail_rpc_url.as_str()).await;
+    let sdk = sdk.map_err(|e| e.to_string())?;
+    let utils = Utils::new(
+        prices,
+        cfg.quote_signing_key.clone(),
+        cfg.database_url.clone(),
+        cfg.avail_rpc_url.clone(),
+    );
+
+    debug(&format!("SDK initialized with local endpoint"));
+
+    let treasury = AccountId::from_str(&cfg.avail_deposit_address)
+        .map_err(|e| format!("Invalid Avail deposit address: {:?}", e))?;
+
+    // Per-user deposit addresses are only handed out when the seed they are derived from is set
+    if let Some(seed) = cfg.avail_deposit_seed.clone() {
+        let mut connection = utils.establish_connection()?;
+        fill_pool(
+            &seed,
+            cfg.deposit_address_pool_size,
+            &utils,
+            &mut connection,
+        )?;
+        tokio::spawn(sweeper::run(sdk.clone(), utils.clone(), cfg.clone(), seed));
+    }
+
+    let source = AvailSource {
+        client: sdk,
+        utils: utils.clone(),
+        treasury,
+    };
+    let mut scanner = Scanner::new(source, utils)?;
+    loop {
+        if let Err(e) = scanner.run(POLL_INTERVAL).await {
+            error(&format!("Failed to follow finalized blocks: {}", e));
+        }
+        tokio::time::sleep(RETRY_DELAY).await;
+    }
+}
+
+/// Avail, deposits are transfers to the treasury with the order id in a remark, or transfers to
+/// a per-user deposit address
+///
+/// Only finalized blocks are scanned, one at a time so the cursor is persisted after each block.
+struct AvailSource {
+    client: Client,
+    utils: Utils,
+    treasury: AccountId,
+}
+
+impl DepositSource for AvailSource {
+    fn chain_id(&self) -> i32 {
+        AVAIL_CHAIN_ID
+    }
+
+    fn finality_depth(&self) -> u64 {
+        0
+    }
+
+    fn max_block_range(&self) -> u64 {
+        1
+    }
+
+    async fn final_block(&self) -> Result<u64, String> {
+        let height = self
+            .client
+            .finalized()
+            .block_height()
+            .await
+            .map_err(|e| format!("Failed to fetch finalized block height: {}", e))?;
+        Ok(height as u64)
+    }
+
+    async fn block_hash(&self, number: u64) -> Result<String, String> {
+        Ok(hex::encode(self.block_at(number).await?.0))
+    }
+
+    async fn events(&self, from: u64, to: u64) -> Result<Vec<ChainEvent>, String> {
+        let mut connection = self.utils.establish_connection()?;
+        let mut events = Vec::new();
+        for height in from..=to {
+            info(&std::format!("Fetched block height: {}", height));
+            let block_hash = self.block_at(height).await?;
+            // Reloaded for every block so addresses assigned in the meantime are picked up
+            let addresses = load_deposit_addresses(&self.treasury, &self.utils, &mut connection)?;
+            let deposits = block_deposits(&self.client, block_hash, height as u32, &addresses)
+                .await
+                .map_err(|e| format!("Failed to process block {}: {}", height, e))?;
+            events.extend(deposits);
+        }
+        Ok(events)
+    }
+}
+
+impl AvailSource {
+    async fn block_at(&self, height: u64) -> Result<H256, String> {
+        self.client
+            .chain()
+            .block_hash(Some(height as u32))
+            .await
+            .map_err(|e| format!("Failed to fetch block hash: {}", e))?
+            .ok_or(format!("Block {} not found", height))
+    }
+}
+
+async fn block_deposits(
+    client: &Client,
+    block_hash: H256,
+    block_height: u32,
+    addresses: &DepositAddresses,
+) -> Result<Vec<ChainEvent>, String> {
+    debug(&"Filtering deposits from block".to_string());
+
+    let block = BlockWithRawExt::new(client.clone(), block_hash);
+    let all = block
+        .all(BlockExtOptionsExpanded {
+            encode_as: Some(EncodeSelector::Extrinsic),
+            ..Default::default()
+        })
+        .await;
+    let all = all.map_err(|e| e.to_string())?;
+    let mut timestamp = None;
+
+    let mut events = Vec::new();
+    for ext in all {
+        let Some(deposit) = read_deposit(client, &ext, addresses).await? else {
+            continue;
+        };
+        let AvailDeposit {
+            transfer,
+            amount,
+            from,
+            tx_hash,
+            tx_index,
+        } = deposit;
+        info(&format!(
+            "Deposit at block height: {}, block hash: {}",
+            block_height, block_hash
+        ));
+
+        let block_timestamp = match timestamp {
+            Some(timestamp) => timestamp,
+            None => *timestamp.insert(block_timestamp(client, block_hash).await?),
+        };
+
+        let beneficiary = match transfer.reference {
+            Some(DepositReference::Order(order_id)) => Beneficiary::Order(order_id),
+            Some(DepositReference::User(user)) => Beneficiary::User(user),
+            None => Beneficiary::Unknown,
+        };
+        events.push(ChainEvent::Deposit {
+            beneficiary,
+            deposit: Deposit {
+                token_address: "0x0000000000000000000000000000000000000000".to_string(),
+                amount: amount.to_string(),
+                from,
+            },
+            location: DepositLocation {
+                tx_hash: hex::encode(tx_hash.0),
+                log_index: tx_index as i32,
+                block_number: block_height as i32,
+                block_hash: hex::encode(block_hash.0),
+                block_timestamp,
+            },
+        });
+    }
+
+    Ok(events)
+}
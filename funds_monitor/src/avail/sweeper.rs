@@ -0,0 +1,80 @@
+/// Sweeper consolidating the balance of the per-user deposit addresses into the treasury
+/// - Addresses holding at least `sweep_min_balance_avail` are emptied with `transfer_all`
+/// - The pool of unassigned addresses is topped up on every round
+use avail_rust::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+use turbo_da_core::logger::{error, info};
+
+use super::deposit_addresses::{derive_keypair, fill_pool};
+use crate::config::Config;
+use crate::utils::Utils;
+
+pub(crate) async fn run(client: Client, utils: Utils, cfg: Arc<Config>, seed: String) {
+    let treasury = match AccountId::from_str(&cfg.avail_deposit_address) {
+        Ok(treasury) => treasury,
+        Err(e) => {
+            error(&format!("Invalid Avail deposit address: {:?}", e));
+            return;
+        }
+    };
+    let min_balance = cfg.sweep_min_balance_avail as u128 * ONE_AVAIL;
+
+    let mut interval = tokio::time::interval(Duration::from_secs(cfg.sweep_interval_secs));
+    loop {
+        interval.tick().await;
+        if let Err(e) = sweep(&client, &utils, &cfg, &seed, &treasury, min_balance).await {
+            error(&format!("Failed to sweep deposit addresses: {}", e));
+        }
+    }
+}
+
+async fn sweep(
+    client: &Client,
+    utils: &Utils,
+    cfg: &Config,
+    seed: &str,
+    treasury: &AccountId,
+    min_balance: u128,
+) -> Result<(), String> {
+    let mut connection = utils.establish_connection()?;
+    fill_pool(seed, cfg.deposit_address_pool_size, utils, &mut connection)?;
+
+    // A failing address must not prevent the others from being swept
+    for row in utils.get_assigned_deposit_addresses(&mut connection)? {
+        let keypair = match derive_keypair(seed, row.derivation_index) {
+            Ok(keypair) => keypair,
+            Err(e) => {
+                error(&e);
+                continue;
+            }
+        };
+        let account = keypair.account_id();
+        let balance = match client.finalized().account_balance(account.clone()).await {
+            Ok(balance) => balance.free,
+            Err(e) => {
+                error(&format!("Failed to query balance of {}: {}", account, e));
+                continue;
+            }
+        };
+        if balance < min_balance {
+            continue;
+        }
+
+        match client
+            .tx()
+            .balances()
+            .transfer_all(treasury.clone(), false)
+            .sign_and_submit(&keypair, Options::default())
+            .await
+        {
+            Ok(submitted) => info(&format!(
+                "Swept {} from {} to the treasury, tx hash: {:?}",
+                balance, account, submitted.tx_hash
+            )),
+            Err(e) => error(&format!("Failed to sweep {}: {}", account, e)),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+#[cfg(test)]
+pub mod test {
+    use crate::avail::deposit_addresses::derive_keypair;
+    use avail_rust::prelude::*;
+
+    #[test]
+    fn test_deposit_addresses_are_derived_per_index() {
+        let first = derive_keypair("//Alice", 0).unwrap().account_id();
+
+        assert_eq!(derive_keypair("//Alice", 0).unwrap().account_id(), first);
+        assert_ne!(derive_keypair("//Alice", 1).unwrap().account_id(), first);
+        assert_ne!(derive_keypair("//Bob", 0).unwrap().account_id(), first);
+    }
+}
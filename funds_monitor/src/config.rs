@@ -1,26 +1,128 @@
+use alloy::primitives::Address;
 use dotenv::dotenv;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, env, error::Error, fs};
+use std::{collections::HashMap, env, error::Error, fs, str::FromStr};
 use toml;
 use turbo_da_core::logger::{error, info, warn};
+use turbo_da_core::price_oracle::PriceOracleConfig;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Network {
-    pub contract_address: String,
+    /// Address of the `TurboDAResolver` contract, validated when the configuration is loaded
+    pub contract_address: Address,
     pub url: String,
     pub ws_url: String,
     pub chain_id: i32,
     pub finalised_threshold: u64,
+    /// Maximum number of blocks requested in a single `eth_getLogs` call
+    #[serde(default = "default_max_block_range")]
+    pub max_block_range: u64,
+    /// Interval between two polls of the HTTP endpoint while the WebSocket is down
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// Interval between two comparisons of the token registry with the tokens valid on the contract
+    #[serde(default = "default_token_check_interval_secs")]
+    pub token_check_interval_secs: u64,
+}
+
+/// Solana network deposits in SPL tokens are monitored on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolanaNetwork {
+    pub url: String,
+    /// Identifier the deposits and the cursor of the network are recorded under
+    pub chain_id: i32,
+    /// Token accounts of the treasury deposits are transferred to, one per accepted mint
+    pub deposit_token_accounts: Vec<String>,
+    /// Maximum number of slots scanned before the cursor is persisted
+    #[serde(default = "default_max_slot_range")]
+    pub max_slot_range: u64,
+    /// Interval between two polls of the finalized slot
+    #[serde(default = "default_solana_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+}
+
+fn default_max_slot_range() -> u64 {
+    100_000
+}
+
+fn default_solana_poll_interval_secs() -> u64 {
+    30
+}
+
+fn default_max_block_range() -> u64 {
+    1000
+}
+
+fn default_poll_interval_secs() -> u64 {
+    12
+}
+
+fn default_token_check_interval_secs() -> u64 {
+    3600
+}
+
+fn default_deposit_address_pool_size() -> i64 {
+    100
+}
+
+fn default_credit_request_expiry_interval_secs() -> u64 {
+    300
+}
+
+fn default_fee_reconciliation_interval_secs() -> u64 {
+    3600
+}
+
+fn default_fee_reconciliation_batch_size() -> i64 {
+    1000
+}
+
+fn default_sweep_interval_secs() -> u64 {
+    3600
+}
+
+fn default_sweep_min_balance_avail() -> u64 {
+    1
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
     pub(crate) network: HashMap<String, Network>,
+    /// Solana networks, keyed by name
+    #[serde(default)]
+    pub(crate) solana: HashMap<String, SolanaNetwork>,
     pub(crate) database_url: String,
     pub(crate) coin_gecho_api_url: String,
     pub(crate) coin_gecho_api_key: String,
+    /// Caching and sanity checks of token prices, and the Chainlink fallback
+    #[serde(default)]
+    pub(crate) price_oracle: PriceOracleConfig,
+    /// Key credit quotes are signed with by the API, quotes are not honoured when unset
+    #[serde(default)]
+    pub(crate) quote_signing_key: Option<String>,
     pub(crate) avail_rpc_url: String,
     pub(crate) avail_deposit_address: String,
+    /// Seed the per-user deposit addresses are derived from, they are disabled when unset
+    #[serde(default)]
+    pub(crate) avail_deposit_seed: Option<String>,
+    /// Number of unassigned deposit addresses kept in the pool
+    #[serde(default = "default_deposit_address_pool_size")]
+    pub(crate) deposit_address_pool_size: i64,
+    /// Interval between two sweeps of the deposit addresses into the treasury
+    #[serde(default = "default_sweep_interval_secs")]
+    pub(crate) sweep_interval_secs: u64,
+    /// Balance, in AVAIL, from which a deposit address is swept
+    #[serde(default = "default_sweep_min_balance_avail")]
+    pub(crate) sweep_min_balance_avail: u64,
+    /// Interval between two expiries of the credit requests left unpaid
+    #[serde(default = "default_credit_request_expiry_interval_secs")]
+    pub(crate) credit_request_expiry_interval_secs: u64,
+    /// Interval between two fee reconciliations
+    #[serde(default = "default_fee_reconciliation_interval_secs")]
+    pub(crate) fee_reconciliation_interval_secs: u64,
+    /// Number of submissions whose fee is read from chain per reconciliation
+    #[serde(default = "default_fee_reconciliation_batch_size")]
+    pub(crate) fee_reconciliation_batch_size: i64,
 }
 
 impl Default for Config {
@@ -31,20 +133,33 @@ impl Default for Config {
         network.insert(
             "ethereum".to_string(),
             Network {
-                contract_address: String::new(),
+                contract_address: Address::ZERO,
                 url: String::new(),
                 ws_url: String::new(),
                 chain_id: 1,
                 finalised_threshold: 16,
+                max_block_range: default_max_block_range(),
+                poll_interval_secs: default_poll_interval_secs(),
+                token_check_interval_secs: default_token_check_interval_secs(),
             },
         );
         Self {
             network,
+            solana: HashMap::new(),
             database_url: String::new(),
             coin_gecho_api_url: String::new(),
             coin_gecho_api_key: String::new(),
+            price_oracle: PriceOracleConfig::default(),
+            quote_signing_key: None,
             avail_rpc_url: String::new(),
             avail_deposit_address: String::new(),
+            avail_deposit_seed: None,
+            deposit_address_pool_size: default_deposit_address_pool_size(),
+            sweep_interval_secs: default_sweep_interval_secs(),
+            sweep_min_balance_avail: default_sweep_min_balance_avail(),
+            credit_request_expiry_interval_secs: default_credit_request_expiry_interval_secs(),
+            fee_reconciliation_interval_secs: default_fee_reconciliation_interval_secs(),
+            fee_reconciliation_batch_size: default_fee_reconciliation_batch_size(),
         }
     }
 }
@@ -127,6 +242,33 @@ impl Config {
             e
         })?;
 
+        let quote_signing_key = env::var("QUOTE_SIGNING_KEY").ok().filter(|s| !s.is_empty());
+        let avail_deposit_seed = env::var("AVAIL_DEPOSIT_SEED").ok();
+        let deposit_address_pool_size = env::var("DEPOSIT_ADDRESS_POOL_SIZE")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .unwrap_or_else(default_deposit_address_pool_size);
+        let sweep_interval_secs = env::var("SWEEP_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or_else(default_sweep_interval_secs);
+        let sweep_min_balance_avail = env::var("SWEEP_MIN_BALANCE_AVAIL")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or_else(default_sweep_min_balance_avail);
+        let credit_request_expiry_interval_secs = env::var("CREDIT_REQUEST_EXPIRY_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or_else(default_credit_request_expiry_interval_secs);
+        let fee_reconciliation_interval_secs = env::var("FEE_RECONCILIATION_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or_else(default_fee_reconciliation_interval_secs);
+        let fee_reconciliation_batch_size = env::var("FEE_RECONCILIATION_BATCH_SIZE")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .unwrap_or_else(default_fee_reconciliation_batch_size);
+
         let mut network = HashMap::new();
 
         // Collect all environment variables
@@ -152,6 +294,12 @@ impl Config {
         // Build `network` map dynamically
         for (name, fields) in temp_networks {
             let contract_address = fields.get("contract_address").cloned().unwrap_or_default();
+            let contract_address = Address::from_str(&contract_address).map_err(|e| {
+                format!(
+                    "Invalid contract address {:?} of network {}: {}",
+                    contract_address, name, e
+                )
+            })?;
             let url = fields.get("url").cloned().unwrap_or_default();
             let ws_url = fields.get("ws_url").cloned().unwrap_or_default();
             let finalised_threshold = fields
@@ -162,6 +310,18 @@ impl Config {
                 .get("chain_id")
                 .and_then(|s| s.parse::<i32>().ok())
                 .unwrap_or_default();
+            let max_block_range = fields
+                .get("max_block_range")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or_else(default_max_block_range);
+            let poll_interval_secs = fields
+                .get("poll_interval_secs")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or_else(default_poll_interval_secs);
+            let token_check_interval_secs = fields
+                .get("token_check_interval_secs")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or_else(default_token_check_interval_secs);
 
             network.insert(
                 name,
@@ -171,17 +331,79 @@ impl Config {
                     ws_url,
                     chain_id,
                     finalised_threshold,
+                    max_block_range,
+                    poll_interval_secs,
+                    token_check_interval_secs,
                 },
             );
         }
 
+        let solana = solana_networks_from_env();
+
         Ok(Config {
             network,
+            solana,
             database_url,
             coin_gecho_api_url,
             coin_gecho_api_key,
+            price_oracle: PriceOracleConfig::from_env(),
+            quote_signing_key,
             avail_rpc_url,
             avail_deposit_address,
+            avail_deposit_seed,
+            deposit_address_pool_size,
+            sweep_interval_secs,
+            sweep_min_balance_avail,
+            credit_request_expiry_interval_secs,
+            fee_reconciliation_interval_secs,
+            fee_reconciliation_batch_size,
         })
     }
 }
+
+/// Reads the `SOLANA_<NETWORK_NAME>_` variables, deposit token accounts are comma separated
+fn solana_networks_from_env() -> HashMap<String, SolanaNetwork> {
+    let mut temp_networks: HashMap<String, HashMap<String, String>> = HashMap::new();
+    for (key, value) in env::vars() {
+        if let Some((network_name, field)) = key
+            .strip_prefix("SOLANA_")
+            .and_then(|suffix| suffix.split_once('_'))
+        {
+            temp_networks
+                .entry(network_name.to_lowercase())
+                .or_default()
+                .insert(field.to_lowercase(), value);
+        }
+    }
+
+    temp_networks
+        .into_iter()
+        .map(|(name, fields)| {
+            let network = SolanaNetwork {
+                url: fields.get("url").cloned().unwrap_or_default(),
+                chain_id: fields
+                    .get("chain_id")
+                    .and_then(|s| s.parse::<i32>().ok())
+                    .unwrap_or_default(),
+                deposit_token_accounts: fields
+                    .get("deposit_token_accounts")
+                    .map(|s| {
+                        s.split(',')
+                            .map(|account| account.trim().to_string())
+                            .filter(|account| !account.is_empty())
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                max_slot_range: fields
+                    .get("max_slot_range")
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .unwrap_or_else(default_max_slot_range),
+                poll_interval_secs: fields
+                    .get("poll_interval_secs")
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .unwrap_or_else(default_solana_poll_interval_secs),
+            };
+            (name, network)
+        })
+        .collect()
+}
@@ -3,112 +3,102 @@ use std::str::FromStr;
 use alloy::{
     primitives::Address,
     providers::{Provider, ProviderBuilder, RootProvider, WsConnect},
-    pubsub::PubSubFrontend,
-    rpc::types::{Filter, Log},
+    rpc::types::{BlockNumberOrTag, BlockTransactionsKind, Filter, Log},
     sol,
     sol_types::SolEvent,
+    transports::http::{Client, Http},
 };
 use serde_json::json;
 
-use crate::utils::{Deposit as EvmDeposit, Utils};
+use crate::config::Network;
+use crate::source::{Beneficiary, ChainEvent, DepositSource, Scanner};
+use crate::utils::{Deposit as EvmDeposit, DepositLocation, Payout, Utils};
 use crate::Config;
+use bigdecimal::BigDecimal;
+use chrono::DateTime;
 use futures_util::stream::StreamExt;
+use std::collections::HashMap;
 use std::sync::Arc;
-use turbo_da_core::logger::{debug, debug_json, error, info_json};
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+use turbo_da_core::deposit::Deposit;
+use turbo_da_core::logger::{debug, debug_json, error, info_json, warn, warn_json};
+use turbo_da_core::price_oracle::PriceFeed;
+use turbo_da_core::withdrawal::Withdrawal;
 
-sol! {
-    struct Encoder{bytes userID; address tokenAddress; uint256 amount; address recipient; uint256 nonce;}
-}
+/// Bounds of the delay between two attempts to reconnect the WebSocket
+pub(crate) const MIN_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+pub(crate) const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(300);
+/// A connection staying up this long resets the delay to `MIN_RECONNECT_BACKOFF`
+pub(crate) const HEALTHY_CONNECTION: Duration = Duration::from_secs(60);
 
 sol! {
-    event Deposit(
-        bytes32 indexed orderId,
-        address indexed tokenAddress,
-        uint256 amount,
-        address from
-    );
+    #[sol(rpc)]
+    interface TurboDAResolver {
+        function validTokenAddresses(address tokenAddress) external view returns (bool);
+    }
 }
 
-pub(crate) struct EVM {
-    provider: RootProvider<PubSubFrontend>,
+/// EVM chain deposits are made to the `TurboDAResolver` contract on, withdrawals are paid out by
+/// the same contract
+pub(crate) struct EvmSource {
+    /// HTTP provider used for queries, and to poll for new blocks while the WebSocket is down
+    provider: RootProvider<Http<Client>>,
     evm_chain_id: i32,
-    contract_address: String,
+    contract_address: Address,
     finalised_threshold: u64,
-    start_block: u64,
-    utils: Utils,
+    max_block_range: u64,
 }
 
-impl EVM {
-    pub async fn new(
-        contract_adress: String,
-        ws_url: String,
-        evm_chain_id: i32,
-        finalised_threshold: u64,
-        start_block: u64,
-        cfg: Arc<Config>,
-    ) -> Result<Self, String> {
-        let ws = WsConnect::new(ws_url);
-
-        let provider = ProviderBuilder::new()
-            .on_ws(ws)
-            .await
-            .map_err(|e| format!("Failed to connect to Turbo DA Contract: {:?}", e))?;
-
-        Ok(Self {
-            provider,
-            evm_chain_id,
-            contract_address: contract_adress,
-            finalised_threshold,
-            start_block,
-            utils: Utils::new(
-                cfg.coin_gecho_api_url.clone(),
-                cfg.coin_gecho_api_key.clone(),
-                cfg.database_url.clone(),
-                cfg.avail_rpc_url.clone(),
-            ),
-        })
+impl DepositSource for EvmSource {
+    fn chain_id(&self) -> i32 {
+        self.evm_chain_id
     }
 
-    pub async fn monitor_evm_chain(&mut self) {
-        info_json(json!({
-            "message": "Monitor service started",
-            "contract_address": self.contract_address,
-            "finalised_threshold": self.finalised_threshold,
-            "level": "info"
-        }));
+    fn finality_depth(&self) -> u64 {
+        self.finalised_threshold
+    }
 
-        let subscription = match self.provider.subscribe_blocks().await {
-            Ok(s) => s,
-            Err(e) => return error(&format!("{}", e.to_string())),
-        };
-        let mut _stream = subscription.into_stream();
+    fn max_block_range(&self) -> u64 {
+        self.max_block_range
+    }
 
-        while let Some(header) = _stream.next().await {
-            info_json(json!({
-                "header": header.number,
-                "level": "info"
-            }));
-            let finalised_block = header.inner.number - self.finalised_threshold;
+    async fn final_block(&self) -> Result<u64, String> {
+        let head = self
+            .provider
+            .get_block_number()
+            .await
+            .map_err(|e| format!("Failed to get block number: {}", e))?;
+        Ok(head.saturating_sub(self.finalised_threshold))
+    }
 
-            match self.check_deposits(finalised_block).await {
-                Ok(_) => debug(&format!("Deposits checked successfully")),
-                Err(e) => error(&format!("Failed to check deposits: {}", e)),
-            }
-        }
+    async fn block_hash(&self, number: u64) -> Result<String, String> {
+        let block = self
+            .provider
+            .get_block_by_number(
+                BlockNumberOrTag::Number(number),
+                BlockTransactionsKind::Hashes,
+            )
+            .await
+            .map_err(|e| format!("Failed to get block {}: {}", number, e))?
+            .ok_or(format!("Block {} not found", number))?;
+        Ok(block.header.hash.to_string())
     }
 
-    async fn check_deposits(&mut self, number: u64) -> Result<(), String> {
+    async fn events(&self, from: u64, to: u64) -> Result<Vec<ChainEvent>, String> {
         let filter = Filter::new()
-            .address(Address::from_str(&self.contract_address).unwrap())
-            .event("Deposit(bytes32,address,uint256,address)")
-            .from_block(self.start_block)
-            .to_block(number);
+            .address(self.contract_address)
+            .events([Deposit::SIGNATURE, Withdrawal::SIGNATURE])
+            .from_block(from)
+            .to_block(to);
         let logs = self
             .provider
             .get_logs(&filter)
             .await
             .map_err(|e| format!("Failed to get logs: {}", e))?;
-        self.start_block = number + 1;
+
+        let mut timestamps = HashMap::new();
+        let mut events = Vec::new();
         for log in logs {
             debug_json(json!({
                 "message": "Log from our contract",
@@ -119,79 +109,338 @@ impl EVM {
                 "log_index": log.log_index,
                 "level": "debug"
             }));
-            let receipt = match self.process_deposit_event(&log) {
-                Ok(receipt) => receipt,
+            let timestamp = self.block_timestamp(&log, &mut timestamps).await?;
+            let location = match log_location(&log, timestamp) {
+                Ok(location) => location,
                 Err(e) => {
-                    error(&format!("Failed to process deposit event: {}", e));
+                    error(&e);
                     continue;
                 }
             };
 
-            let mut connection = match self.utils.establish_connection() {
-                Ok(conn) => conn,
+            if log.topic0() == Some(&Withdrawal::SIGNATURE_HASH) {
+                match process_withdrawal_event(&log) {
+                    Ok(payout) => events.push(ChainEvent::Payout { payout, location }),
+                    Err(e) => error(&format!("Failed to process withdrawal event: {}", e)),
+                }
+                continue;
+            }
+
+            let receipt = match process_deposit_event(&log) {
+                Ok(receipt) => receipt,
                 Err(e) => {
-                    error(&format!("Failed to establish database connection: {}", e));
+                    error(&format!("Failed to process deposit event: {}", e));
                     continue;
                 }
             };
+            events.push(ChainEvent::Deposit {
+                beneficiary: Beneficiary::Order(receipt.orderId.to_string()),
+                deposit: EvmDeposit {
+                    token_address: receipt.tokenAddress.to_string(),
+                    amount: receipt.amount.to_string(),
+                    from: receipt.from.to_string(),
+                },
+                location,
+            });
+        }
+        Ok(events)
+    }
+}
 
-            let Some(number) = log.block_number else {
-                error(&format!("Block number not found"));
-                continue;
-            };
-            let Some(hash) = log.block_hash else {
-                error(&format!("Block hash not found"));
-                continue;
-            };
+impl EvmSource {
+    /// Time of the block of `log` in seconds, read from the block header unless the node returned
+    /// it with the log, headers already read are kept in `timestamps`
+    async fn block_timestamp(
+        &self,
+        log: &Log,
+        timestamps: &mut HashMap<u64, u64>,
+    ) -> Result<u64, String> {
+        if let Some(timestamp) = log.block_timestamp {
+            return Ok(timestamp);
+        }
+        let number = log.block_number.ok_or("Block number not found")?;
+        if let Some(timestamp) = timestamps.get(&number) {
+            return Ok(*timestamp);
+        }
+        let timestamp = self
+            .provider
+            .get_block_by_number(
+                BlockNumberOrTag::Number(number),
+                BlockTransactionsKind::Hashes,
+            )
+            .await
+            .map_err(|e| format!("Failed to get block {}: {}", number, e))?
+            .ok_or(format!("Block {} not found", number))?
+            .header
+            .timestamp;
+        timestamps.insert(number, timestamp);
+        Ok(timestamp)
+    }
+}
 
-            if let Err(e) = self
-                .utils
-                .update_finalised_block_number(
-                    number as i32,
-                    hash.to_string(),
-                    &mut connection,
-                    self.evm_chain_id,
-                )
+pub(crate) struct EVM {
+    ws_url: String,
+    poll_interval: Duration,
+    token_check_interval: Duration,
+    last_token_check: Option<Instant>,
+    scanner: Scanner<EvmSource>,
+}
+
+impl EVM {
+    pub fn new(network: Network, cfg: Arc<Config>, prices: Arc<PriceFeed>) -> Result<Self, String> {
+        let url = network
+            .url
+            .parse()
+            .map_err(|e| format!("Invalid RPC url {}: {:?}", network.url, e))?;
+        let provider = ProviderBuilder::new().on_http(url);
+        let source = EvmSource {
+            provider,
+            evm_chain_id: network.chain_id,
+            contract_address: network.contract_address,
+            finalised_threshold: network.finalised_threshold,
+            max_block_range: network.max_block_range.max(1),
+        };
+        let utils = Utils::new(
+            prices,
+            cfg.quote_signing_key.clone(),
+            cfg.database_url.clone(),
+            cfg.avail_rpc_url.clone(),
+        );
+
+        Ok(Self {
+            ws_url: network.ws_url,
+            poll_interval: Duration::from_secs(network.poll_interval_secs),
+            token_check_interval: Duration::from_secs(network.token_check_interval_secs),
+            last_token_check: None,
+            scanner: Scanner::new(source, utils)?,
+        })
+    }
+
+    /// Follows new blocks over the WebSocket, polling over HTTP while it is unavailable
+    ///
+    /// When the subscription fails or its stream ends, the chain is polled until the next
+    /// reconnection attempt. The delay between attempts doubles up to `MAX_RECONNECT_BACKOFF`, and
+    /// starts over once a subscription stayed up for `HEALTHY_CONNECTION`.
+    pub async fn monitor_evm_chain(&mut self) {
+        info_json(json!({
+            "message": "Monitor service started",
+            "contract_address": self.scanner.source.contract_address.to_string(),
+            "finalised_threshold": self.scanner.source.finalised_threshold,
+            "level": "info"
+        }));
+
+        let mut backoff = MIN_RECONNECT_BACKOFF;
+        loop {
+            let connected_at = Instant::now();
+            match self.follow_subscription().await {
+                Ok(_) => warn(&"Block subscription ended".to_string()),
+                Err(e) => error(&format!("Block subscription failed: {}", e)),
+            }
+            if connected_at.elapsed() >= HEALTHY_CONNECTION {
+                backoff = MIN_RECONNECT_BACKOFF;
+            }
+
+            info_json(json!({
+                "message": "Polling for new blocks until the WebSocket is reconnected",
+                "chain_id": self.scanner.source.evm_chain_id,
+                "reconnect_in_secs": backoff.as_secs(),
+                "level": "info"
+            }));
+            self.poll_until(Instant::now() + backoff).await;
+            backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+        }
+    }
+
+    async fn follow_subscription(&mut self) -> Result<(), String> {
+        let ws_provider = ProviderBuilder::new()
+            .on_ws(WsConnect::new(self.ws_url.clone()))
+            .await
+            .map_err(|e| format!("Failed to connect to Turbo DA Contract: {:?}", e))?;
+        let subscription = ws_provider
+            .subscribe_blocks()
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut stream = subscription.into_stream();
+
+        // Catch up on the blocks produced while the subscription was down
+        self.on_new_head(
+            self.scanner
+                .source
+                .provider
+                .get_block_number()
                 .await
-            {
-                error(&format!("Failed to update finalised block number: {}", e));
+                .map_err(|e| e.to_string())?,
+        )
+        .await;
+
+        while let Some(header) = stream.next().await {
+            info_json(json!({
+                "header": header.number,
+                "level": "info"
+            }));
+            self.on_new_head(header.inner.number).await;
+        }
+        Ok(())
+    }
+
+    async fn poll_until(&mut self, deadline: Instant) {
+        while Instant::now() < deadline {
+            match self.scanner.source.provider.get_block_number().await {
+                Ok(head) => self.on_new_head(head).await,
+                Err(e) => error(&format!("Failed to poll block number: {}", e)),
             }
+            sleep(
+                self.poll_interval
+                    .min(deadline.saturating_duration_since(Instant::now())),
+            )
+            .await;
+        }
+    }
 
-            let tx_hash = match log.transaction_hash {
-                Some(tx_hash) => tx_hash.to_string(),
-                None => {
-                    error(&format!("Transaction hash not found"));
-                    continue;
-                }
-            };
+    async fn on_new_head(&mut self, head: u64) {
+        if self
+            .last_token_check
+            .is_none_or(|at| at.elapsed() >= self.token_check_interval)
+        {
+            self.last_token_check = Some(Instant::now());
+            if let Err(e) = self.check_token_drift().await {
+                error(&format!("Failed to check supported tokens: {}", e));
+            }
+        }
 
-            let deposit = EvmDeposit {
-                token_address: receipt.tokenAddress.to_string(),
-                amount: receipt.amount.to_string(),
-                _from: receipt.from.to_string(),
-            };
-            let result = self
-                .utils
-                .update_database_on_deposit(
+        let finalised_block = head.saturating_sub(self.scanner.source.finalised_threshold);
+
+        match self.scanner.scan_to(finalised_block).await {
+            Ok(_) => debug(&"Deposits checked successfully".to_string()),
+            Err(e) => error(&format!("Failed to check deposits: {}", e)),
+        }
+
+        if let Err(e) = self.track_confirmations(head).await {
+            error(&format!("Failed to track confirmations: {}", e));
+        }
+    }
+
+    /// Moves the credit requests of the deposits after the cursor, in blocks that are not final
+    /// yet, to `confirming` with their number of confirmations
+    async fn track_confirmations(&self, head: u64) -> Result<(), String> {
+        let source = &self.scanner.source;
+        let from = (self.scanner.cursor() + 1).max(head.saturating_sub(source.max_block_range - 1));
+        if from > head {
+            return Ok(());
+        }
+        let filter = Filter::new()
+            .address(source.contract_address)
+            .event_signature(Deposit::SIGNATURE_HASH)
+            .from_block(from)
+            .to_block(head);
+        let logs = source
+            .provider
+            .get_logs(&filter)
+            .await
+            .map_err(|e| format!("Failed to get logs: {}", e))?;
+        if logs.is_empty() {
+            return Ok(());
+        }
+
+        let utils = &self.scanner.utils;
+        let mut connection = utils.establish_connection()?;
+        for log in logs {
+            let result = process_deposit_event(&log).and_then(|receipt| {
+                let block_number = log.block_number.ok_or("Block number not found")?;
+                let tx_hash = log.transaction_hash.ok_or("Transaction hash not found")?;
+                let confirmations = (head - block_number + 1) as i32;
+                utils.update_confirmations(
                     &receipt.orderId.to_string(),
-                    &deposit,
-                    &tx_hash,
+                    &tx_hash.to_string(),
+                    confirmations,
                     &mut connection,
-                    self.evm_chain_id,
-                    &"Processed".to_string(),
+                    source.evm_chain_id,
                 )
-                .await;
+            });
             if let Err(e) = result {
-                error(&format!("Failed to update database: {}", e));
+                warn(&format!("Failed to track confirmations of deposit: {}", e));
             }
         }
         Ok(())
     }
 
-    fn process_deposit_event(&self, log: &Log) -> Result<Deposit, String> {
-        let event_receipt = Deposit::decode_log_data(&log.data(), true)
-            .map_err(|e| format!("Failed to decode log data: {}", e))?;
+    /// Compares the tokens registered for the chain with the tokens valid on the contract
+    ///
+    /// A token enabled on one side only is reported: its deposits either revert or are left
+    /// unmatched. Native ETH is accepted by the contract without being listed and is not checked,
+    /// nor can tokens valid on the contract but missing from the registry be enumerated.
+    async fn check_token_drift(&self) -> Result<(), String> {
+        let source = &self.scanner.source;
+        let mut connection = self.scanner.utils.establish_connection()?;
+        let tokens = self
+            .scanner
+            .utils
+            .get_chain_tokens(source.evm_chain_id, &mut connection)?;
+        let contract = TurboDAResolver::new(source.contract_address, &source.provider);
 
-        Ok(event_receipt)
+        for token in tokens {
+            let token_address = Address::from_str(&token.token_address)
+                .map_err(|e| format!("Invalid token address {}: {}", token.token_address, e))?;
+            if token_address == Address::ZERO {
+                continue;
+            }
+            let valid = contract
+                .validTokenAddresses(token_address)
+                .call()
+                .await
+                .map_err(|e| format!("Failed to query token {}: {}", token.token_address, e))?
+                ._0;
+            if token.enabled != valid {
+                warn_json(json!({
+                    "message": "Supported token drift",
+                    "chain_id": source.evm_chain_id,
+                    "token_address": token.token_address,
+                    "symbol": token.symbol,
+                    "enabled_in_registry": token.enabled,
+                    "valid_on_contract": valid,
+                    "level": "warn"
+                }));
+            }
+        }
+        Ok(())
     }
 }
+
+fn process_deposit_event(log: &Log) -> Result<Deposit, String> {
+    let event_receipt = Deposit::decode_log_data(log.data(), true)
+        .map_err(|e| format!("Failed to decode log data: {}", e))?;
+
+    Ok(event_receipt)
+}
+
+fn process_withdrawal_event(log: &Log) -> Result<Payout, String> {
+    let event = Withdrawal::decode_log_data(log.data(), true)
+        .map_err(|e| format!("Failed to decode log data: {}", e))?;
+
+    Ok(Payout {
+        nonce: i32::try_from(event.nonce)
+            .map_err(|e| format!("Invalid nonce {}: {}", event.nonce, e))?,
+        token_address: event.tokenAddress.to_string(),
+        amount: BigDecimal::from_str(&event.amount.to_string())
+            .map_err(|e| format!("Invalid amount {}: {}", event.amount, e))?,
+        recipient: event.recipient.to_string(),
+    })
+}
+
+/// Position of a log on chain, in a block produced at `timestamp` seconds
+fn log_location(log: &Log, timestamp: u64) -> Result<DepositLocation, String> {
+    let block_number = log.block_number.ok_or("Block number not found")?;
+    let hash = log.block_hash.ok_or("Block hash not found")?;
+    let log_index = log.log_index.ok_or("Log index not found")?;
+    let tx_hash = log.transaction_hash.ok_or("Transaction hash not found")?;
+
+    Ok(DepositLocation {
+        tx_hash: tx_hash.to_string(),
+        log_index: log_index as i32,
+        block_number: block_number as i32,
+        block_hash: hash.to_string(),
+        block_timestamp: DateTime::from_timestamp(timestamp as i64, 0)
+            .ok_or(format!("Invalid block timestamp {}", timestamp))?
+            .naive_utc(),
+    })
+}
@@ -0,0 +1,22 @@
+/// Expiry of the credit requests left unpaid
+/// - Requests still `created` or `awaiting_payment` past their `expires_at` move to `expired`
+/// - A deposit made to an expired request is still credited
+use std::time::Duration;
+use turbo_da_core::logger::{error, info};
+
+use crate::utils::Utils;
+
+pub(crate) async fn run(utils: Utils, interval_secs: u64) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+    loop {
+        interval.tick().await;
+        let expired = utils
+            .establish_connection()
+            .and_then(|mut connection| utils.expire_credit_requests(&mut connection));
+        match expired {
+            Ok(0) => {}
+            Ok(count) => info(&format!("Expired {} credit requests", count)),
+            Err(e) => error(&format!("Failed to expire credit requests: {}", e)),
+        }
+    }
+}
@@ -1,6 +1,12 @@
 mod avail;
 mod config;
 mod evm;
+mod expiry;
+mod reconciliation;
+mod solana;
+mod source;
+#[allow(clippy::module_inception)]
+mod test;
 mod utils;
 
 use avail::run;
@@ -9,11 +15,14 @@ use config::Network;
 use db::{models::indexer::IndexerBlockNumbers, schema::indexer_block_numbers::dsl::*};
 use diesel::prelude::*;
 use diesel::PgConnection;
-use evm::EVM;
+use evm::{EVM, HEALTHY_CONNECTION, MAX_RECONNECT_BACKOFF, MIN_RECONNECT_BACKOFF};
 use observability::{init_meter, init_tracer};
 use serde_json::json;
 use std::sync::Arc;
+use tokio::time::Instant;
 use turbo_da_core::logger::{debug, debug_json, error, info};
+use turbo_da_core::price_oracle::PriceFeed;
+use utils::Utils;
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
@@ -28,15 +37,50 @@ async fn main() {
     init_tracer("funds_monitor");
     init_meter("funds_monitor");
 
+    let prices = match PriceFeed::from_config(
+        &cfg.coin_gecho_api_url,
+        &cfg.coin_gecho_api_key,
+        &cfg.price_oracle,
+    ) {
+        Ok(prices) => prices,
+        Err(e) => {
+            error(&format!("Error creating price feed: {}", e));
+            return;
+        }
+    };
+
     let cfg_ref = Arc::new(cfg);
     let cfg_ref_2 = cfg_ref.clone();
     let cfg_ref_3 = cfg_ref.clone();
 
     let mut handles = Vec::new();
+    handles.push(tokio::spawn(expiry::run(
+        Utils::new(
+            prices.clone(),
+            cfg_ref.quote_signing_key.clone(),
+            cfg_ref.database_url.clone(),
+            cfg_ref.avail_rpc_url.clone(),
+        ),
+        cfg_ref.credit_request_expiry_interval_secs,
+    )));
+
+    handles.push(tokio::spawn(reconciliation::run(
+        Utils::new(
+            prices.clone(),
+            cfg_ref.quote_signing_key.clone(),
+            cfg_ref.database_url.clone(),
+            cfg_ref.avail_rpc_url.clone(),
+        ),
+        cfg_ref.avail_rpc_url.clone(),
+        cfg_ref.fee_reconciliation_interval_secs,
+        cfg_ref.fee_reconciliation_batch_size,
+    )));
+
+    let avail_prices = prices.clone();
     handles.push(tokio::spawn(async move {
         info(&format!("Starting Avail Chain Monitor"));
 
-        let result = run(cfg_ref.clone()).await;
+        let result = run(cfg_ref.clone(), avail_prices).await;
         if let Err(e) = result {
             error(&format!("Error running Avail Chain Monitor: {:?}", e));
         }
@@ -47,6 +91,7 @@ async fn main() {
         let network_config = network_config.clone();
 
         let cfg_ref_4 = cfg_ref_3.clone();
+        let prices = prices.clone();
         debug_json(json!({
             "message": "Task for network",
             "network_name": network_name,
@@ -56,9 +101,53 @@ async fn main() {
         handles.push(tokio::spawn(async move {
             debug(&format!("Spawning new task"));
 
-            match monitor(network_config, cfg_ref_4).await {
-                Ok(_) => info(&format!("Monitor task completed successfully")),
-                Err(e) => error(&format!("Error running monitor task: {}", e)),
+            // The chain keeps being monitored whenever the task exits, until shutdown
+            let mut backoff = MIN_RECONNECT_BACKOFF;
+            loop {
+                let started_at = Instant::now();
+                match monitor(network_config.clone(), cfg_ref_4.clone(), prices.clone()).await {
+                    Ok(_) => info(&"Monitor task completed successfully".to_string()),
+                    Err(e) => error(&format!("Error running monitor task: {}", e)),
+                }
+                if started_at.elapsed() >= HEALTHY_CONNECTION {
+                    backoff = MIN_RECONNECT_BACKOFF;
+                }
+                info(&format!(
+                    "Restarting monitor task for {} in {}s",
+                    network_name,
+                    backoff.as_secs()
+                ));
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+            }
+        }));
+    }
+
+    for (network_name, network_config) in &cfg_ref_2.solana {
+        let network_name = network_name.clone();
+        let network_config = network_config.clone();
+        let cfg = cfg_ref_3.clone();
+        let prices = prices.clone();
+
+        handles.push(tokio::spawn(async move {
+            info(&format!("Starting Solana monitor for {}", network_name));
+
+            let mut backoff = MIN_RECONNECT_BACKOFF;
+            loop {
+                let started_at = Instant::now();
+                if let Err(e) =
+                    solana::run(network_config.clone(), cfg.clone(), prices.clone()).await
+                {
+                    error(&format!(
+                        "Error running Solana monitor for {}: {}",
+                        network_name, e
+                    ));
+                }
+                if started_at.elapsed() >= HEALTHY_CONNECTION {
+                    backoff = MIN_RECONNECT_BACKOFF;
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
             }
         }));
     }
@@ -71,28 +160,13 @@ async fn main() {
     }
 }
 
-async fn monitor(network_config: Network, cfg: Arc<Config>) -> Result<(), String> {
-    let mut connection = PgConnection::establish(&cfg.database_url)
-        .map_err(|e| format!("Error connecting to {}: {}", cfg.database_url, e))?;
-
-    let finalised_block_number =
-        query_finalised_block_number(network_config.chain_id, &mut connection);
-    info(&format!(
-        "Finalised block number: {}, chain id: {}",
-        finalised_block_number.block_number, network_config.chain_id
-    ));
-    drop(connection);
-
-    let mut evm = EVM::new(
-        network_config.contract_address,
-        network_config.ws_url,
-        network_config.chain_id,
-        network_config.finalised_threshold,
-        finalised_block_number.block_number as u64,
-        cfg.clone(),
-    )
-    .await
-    .map_err(|e| format!("Error creating EVM connection: {}", e))?;
+async fn monitor(
+    network_config: Network,
+    cfg: Arc<Config>,
+    prices: Arc<PriceFeed>,
+) -> Result<(), String> {
+    let mut evm = EVM::new(network_config, cfg.clone(), prices)
+        .map_err(|e| format!("Error creating EVM connection: {}", e))?;
 
     evm.monitor_evm_chain().await;
 
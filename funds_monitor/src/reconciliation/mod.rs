@@ -0,0 +1,186 @@
+/// Reconciliation of the AVAIL our signers spend on submissions against what they are billed
+/// - The fee each submission actually paid is read from the `TransactionFeePaid` event of its
+///   extrinsic, once
+/// - The submissions of the days touched are then summed up per app into `fee_reconciliations`
+#[cfg(test)]
+mod test;
+
+use avail_rust::{codec::Decode, prelude::*};
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, Utc};
+use db::models::{
+    fee_reconciliations::{FeeReconciliation, FeeRecord},
+    user_model::BalanceUnit,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+use turbo_da_core::logger::{error, info, warn};
+use turbo_da_core::utils::get_credit_avail_value;
+use uuid::Uuid;
+
+use crate::utils::Utils;
+
+/// `TransactionFeePaid { who, actual_fee, tip }`, only the fee is kept as it includes the tip
+#[derive(Debug, Clone)]
+pub(crate) struct TransactionFeePaid {
+    pub actual_fee: u128,
+}
+
+impl HasHeader for TransactionFeePaid {
+    // TransactionPayment pallet
+    const HEADER_INDEX: (u8, u8) = (7, 0);
+}
+
+impl Decode for TransactionFeePaid {
+    fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+        let _who = AccountId::decode(input)?;
+        let actual_fee = u128::decode(input)?;
+        let _tip = u128::decode(input)?;
+        Ok(Self { actual_fee })
+    }
+}
+
+pub(crate) async fn run(utils: Utils, avail_rpc_url: String, interval_secs: u64, batch_size: i64) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+    loop {
+        interval.tick().await;
+        if let Err(e) = reconcile_fees(&utils, &avail_rpc_url, batch_size).await {
+            error(&format!("Failed to reconcile fees: {}", e));
+        }
+    }
+}
+
+async fn reconcile_fees(utils: &Utils, avail_rpc_url: &str, batch_size: i64) -> Result<(), String> {
+    let client = Client::new(avail_rpc_url)
+        .await
+        .map_err(|e| format!("Failed to create SDK client: {:?}", e))?;
+    let mut connection = utils.establish_connection()?;
+
+    // Today is rebuilt on every round so new submissions show up
+    let mut days = BTreeSet::from([Utc::now().date_naive()]);
+    for (submission, block_hash, tx_index, created_at) in
+        utils.get_unchecked_fees(batch_size, &mut connection)?
+    {
+        // Submissions whose fee can't be read now are retried on the next round
+        let fee = match chain_fee(&client, &block_hash, tx_index as u32).await {
+            Ok(fee) => fee,
+            Err(e) => {
+                error(&format!(
+                    "Failed to read the fee of submission {}: {}",
+                    submission, e
+                ));
+                continue;
+            }
+        };
+        if fee.is_none() {
+            warn(&format!(
+                "Fee of submission {} not found in block {}",
+                submission, block_hash
+            ));
+        }
+        utils.store_chain_fee(submission, fee.map(BigDecimal::from), &mut connection)?;
+        days.insert(created_at.date());
+    }
+
+    let avail_per_credit = get_credit_avail_value(avail_rpc_url).await?;
+    for day in days {
+        let records = utils.get_fee_records(day, &mut connection)?;
+        let reconciliations = reconcile(day, &records, &avail_per_credit);
+        utils.store_fee_reconciliations(&reconciliations, &mut connection)?;
+        info(&format!(
+            "Reconciled the fees of {} submissions of {}",
+            records.len(),
+            day
+        ));
+    }
+    Ok(())
+}
+
+/// Fee paid by the extrinsic `tx_index` of block `block_hash`, `None` when no fee event is found
+async fn chain_fee(
+    client: &Client,
+    block_hash: &str,
+    tx_index: u32,
+) -> Result<Option<u128>, String> {
+    let hash = H256::from_str(block_hash).map_err(|e| format!("Invalid block hash: {}", e))?;
+    let events = BlockEvents::new(client.clone(), hash)
+        .ext(tx_index)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(events
+        .and_then(|events| events.first::<TransactionFeePaid>())
+        .map(|event| event.actual_fee))
+}
+
+/// Sums up the fee records of the submissions of `day` per app
+///
+/// Submissions whose fee could not be read from chain are counted at their recorded fee. Bills
+/// are valued in AVAIL at the rate the submission was billed at, the value of a credit or the
+/// AVAIL/USD price. Credits billed before their value was recorded are valued at
+/// `avail_per_credit`, the current value of a credit.
+pub(crate) fn reconcile(
+    day: NaiveDate,
+    records: &[FeeRecord],
+    avail_per_credit: &BigDecimal,
+) -> Vec<FeeReconciliation> {
+    let zero = BigDecimal::from(0);
+    let one_avail = BigDecimal::from(ONE_AVAIL);
+    let updated_at = Utc::now().naive_utc();
+
+    let mut apps = BTreeMap::<Uuid, FeeReconciliation>::new();
+    for record in records {
+        let row = apps
+            .entry(record.app_id)
+            .or_insert_with(|| FeeReconciliation {
+                day,
+                app_id: record.app_id,
+                submissions: 0,
+                unverified_submissions: 0,
+                mismatched_submissions: 0,
+                underbilled_submissions: 0,
+                avail_spent: zero.clone(),
+                avail_recorded: zero.clone(),
+                credits_billed: zero.clone(),
+                usd_billed: zero.clone(),
+                avail_billed: zero.clone(),
+                margin: zero.clone(),
+                updated_at,
+            });
+
+        let recorded = record.fees.clone().unwrap_or(zero.clone());
+        let spent = record.chain_fee.clone().unwrap_or(recorded.clone());
+        let billed = record.converted_fees.clone().unwrap_or(zero.clone());
+        let billed_avail = if record.billed_unit == BalanceUnit::Usd.as_str() {
+            row.usd_billed += &billed;
+            match &record.avail_usd_price {
+                Some(price) if price > &zero => &billed * &one_avail / price,
+                _ => zero.clone(),
+            }
+        } else {
+            row.credits_billed += &billed;
+            &billed * record.avail_per_credit.as_ref().unwrap_or(avail_per_credit)
+        };
+
+        row.submissions += 1;
+        if record.chain_fee.is_none() {
+            row.unverified_submissions += 1;
+        }
+        if record.chain_fee != record.fees || record.converted_fees.is_none() {
+            row.mismatched_submissions += 1;
+        }
+        if record.chain_fee.is_some() && billed_avail < spent {
+            row.underbilled_submissions += 1;
+        }
+        row.avail_spent += spent;
+        row.avail_recorded += recorded;
+        row.avail_billed += billed_avail;
+    }
+
+    apps.into_values()
+        .map(|mut row| {
+            row.avail_billed = row.avail_billed.round(0);
+            row.margin = &row.avail_billed - &row.avail_spent;
+            row
+        })
+        .collect()
+}
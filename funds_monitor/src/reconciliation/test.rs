@@ -0,0 +1,109 @@
+use super::reconcile;
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use db::models::fee_reconciliations::FeeRecord;
+use std::str::FromStr;
+use uuid::Uuid;
+
+fn record(
+    app_id: Uuid,
+    fees: Option<&str>,
+    chain_fee: Option<&str>,
+    billed: Option<&str>,
+    unit: &str,
+    rate: Option<&str>,
+) -> FeeRecord {
+    let decimal = |value: Option<&str>| value.map(|v| BigDecimal::from_str(v).unwrap());
+    FeeRecord {
+        id: Uuid::new_v4(),
+        app_id,
+        created_at: NaiveDate::from_ymd_opt(2025, 12, 15)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap(),
+        fees: decimal(fees),
+        chain_fee: decimal(chain_fee),
+        converted_fees: decimal(billed),
+        billed_unit: unit.to_string(),
+        avail_usd_price: decimal(rate.filter(|_| unit == "usd")),
+        avail_per_credit: decimal(rate.filter(|_| unit == "credits")),
+    }
+}
+
+#[test]
+fn test_reconcile_sums_up_per_app() {
+    let day = NaiveDate::from_ymd_opt(2025, 12, 15).unwrap();
+    let app = Uuid::new_v4();
+    let other = Uuid::new_v4();
+    // A credit is worth 1000 of the smallest unit of AVAIL
+    let avail_per_credit = BigDecimal::from(1000);
+    let records = vec![
+        // Billed 2000, spent 1500
+        record(app, Some("1500"), Some("1500"), Some("2"), "credits", None),
+        // Recorded fee differs from the chain, billed 1000 against 1200 spent
+        record(app, Some("1000"), Some("1200"), Some("1"), "credits", None),
+        // 0.000000000000003 USD at 1 USD per AVAIL is 3000, spent 2500
+        record(
+            other,
+            Some("2500"),
+            Some("2500"),
+            Some("0.000000000000003"),
+            "usd",
+            Some("1"),
+        ),
+        // Fee not found on chain and never billed
+        record(other, Some("700"), None, None, "credits", None),
+    ];
+
+    let reconciliations = reconcile(day, &records, &avail_per_credit);
+    assert_eq!(reconciliations.len(), 2);
+    let row = reconciliations.iter().find(|r| r.app_id == app).unwrap();
+    assert_eq!(row.day, day);
+    assert_eq!(row.submissions, 2);
+    assert_eq!(row.unverified_submissions, 0);
+    assert_eq!(row.mismatched_submissions, 1);
+    assert_eq!(row.underbilled_submissions, 1);
+    assert_eq!(row.avail_spent, BigDecimal::from(2700));
+    assert_eq!(row.avail_recorded, BigDecimal::from(2500));
+    assert_eq!(row.credits_billed, BigDecimal::from(3));
+    assert_eq!(row.avail_billed, BigDecimal::from(3000));
+    assert_eq!(row.margin, BigDecimal::from(300));
+
+    let row = reconciliations.iter().find(|r| r.app_id == other).unwrap();
+    assert_eq!(row.submissions, 2);
+    assert_eq!(row.unverified_submissions, 1);
+    assert_eq!(row.mismatched_submissions, 1);
+    assert_eq!(row.underbilled_submissions, 0);
+    // The unverified submission is counted at its recorded fee
+    assert_eq!(row.avail_spent, BigDecimal::from(3200));
+    assert_eq!(
+        row.usd_billed,
+        BigDecimal::from_str("0.000000000000003").unwrap()
+    );
+    assert_eq!(row.avail_billed, BigDecimal::from(3000));
+    assert_eq!(row.margin, BigDecimal::from(-200));
+}
+
+#[test]
+fn test_credits_are_valued_at_the_rate_they_were_billed_at() {
+    let day = NaiveDate::from_ymd_opt(2025, 12, 15).unwrap();
+    let app = Uuid::new_v4();
+    // The value of a credit doubled since the day was billed
+    let avail_per_credit = BigDecimal::from(2000);
+    let records = vec![
+        record(
+            app,
+            Some("1500"),
+            Some("1500"),
+            Some("2"),
+            "credits",
+            Some("1000"),
+        ),
+        // Billed before the value of a credit was recorded
+        record(app, Some("1000"), Some("1000"), Some("1"), "credits", None),
+    ];
+
+    let reconciliations = reconcile(day, &records, &avail_per_credit);
+    assert_eq!(reconciliations[0].avail_billed, BigDecimal::from(4000));
+    assert_eq!(reconciliations[0].margin, BigDecimal::from(1500));
+}
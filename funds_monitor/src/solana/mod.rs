@@ -0,0 +1,381 @@
+/// Solana, deposits are SPL token transfers to a token account of the treasury with the order id
+/// in a memo
+/// - Transactions of the deposit token accounts are listed with `getSignaturesForAddress` and read
+///   with `getTransaction`, at the `finalized` commitment so no reorg is ever scanned
+/// - Slots are the block numbers of the cursor, the hash of a skipped slot is recorded empty
+/// - Only transfers in top-level instructions are deposits, transfers made by another program are
+///   not detected
+/// - The mint is the token address of a deposit, base58 encoded and registered as it is in
+///   `supported_tokens`, unlike hex addresses it is not lowercased
+#[cfg(test)]
+mod test;
+
+use chrono::{DateTime, NaiveDateTime};
+use reqwest::Client as HttpClient;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use turbo_da_core::logger::debug;
+use turbo_da_core::price_oracle::PriceFeed;
+
+use crate::config::{Config, SolanaNetwork};
+use crate::source::{Beneficiary, ChainEvent, DepositSource, Scanner};
+use crate::utils::{Deposit, DepositLocation, Utils};
+
+/// Signatures listed per `getSignaturesForAddress` call, the maximum allowed by the RPC
+const SIGNATURES_PAGE_SIZE: usize = 1000;
+/// Errors of `getBlock` for a slot that has no block
+const SLOT_SKIPPED_ERRORS: [i64; 2] = [-32007, -32009];
+
+pub async fn run(
+    network: SolanaNetwork,
+    cfg: Arc<Config>,
+    prices: Arc<PriceFeed>,
+) -> Result<(), String> {
+    let utils = Utils::new(
+        prices,
+        cfg.quote_signing_key.clone(),
+        cfg.database_url.clone(),
+        cfg.avail_rpc_url.clone(),
+    );
+    let interval = Duration::from_secs(network.poll_interval_secs);
+    let source = SolanaSource {
+        client: HttpClient::new(),
+        url: network.url,
+        chain_id: network.chain_id,
+        deposit_token_accounts: network.deposit_token_accounts,
+        max_slot_range: network.max_slot_range,
+    };
+
+    Scanner::new(source, utils)?.run(interval).await
+}
+
+struct SolanaSource {
+    client: HttpClient,
+    url: String,
+    chain_id: i32,
+    deposit_token_accounts: Vec<String>,
+    max_slot_range: u64,
+}
+
+impl DepositSource for SolanaSource {
+    fn chain_id(&self) -> i32 {
+        self.chain_id
+    }
+
+    fn finality_depth(&self) -> u64 {
+        0
+    }
+
+    fn max_block_range(&self) -> u64 {
+        self.max_slot_range
+    }
+
+    async fn final_block(&self) -> Result<u64, String> {
+        let slot = rpc_result(
+            self.call("getSlot", json!([{ "commitment": "finalized" }]))
+                .await?,
+        )?;
+        slot.as_u64().ok_or(format!("Invalid slot {}", slot))
+    }
+
+    async fn block_hash(&self, number: u64) -> Result<String, String> {
+        let response = self
+            .call(
+                "getBlock",
+                json!([number, {
+                    "commitment": "finalized",
+                    "transactionDetails": "none",
+                    "rewards": false,
+                    "maxSupportedTransactionVersion": 0,
+                }]),
+            )
+            .await?;
+        parse_block_hash(response)
+    }
+
+    async fn events(&self, from: u64, to: u64) -> Result<Vec<ChainEvent>, String> {
+        let mut signatures: Vec<(u64, String)> = Vec::new();
+        for account in &self.deposit_token_accounts {
+            let mut found = self.signatures(account, from, to).await?;
+            // Listed newest first
+            found.reverse();
+            signatures.extend(found);
+        }
+        signatures.sort_by_key(|(slot, _)| *slot);
+        // A transaction can pay to several deposit accounts
+        let mut seen = HashSet::new();
+        signatures.retain(|(_, signature)| seen.insert(signature.clone()));
+
+        let mut block_hashes: HashMap<u64, String> = HashMap::new();
+        let mut events = Vec::new();
+        for (slot, signature) in signatures {
+            let response = self
+                .call(
+                    "getTransaction",
+                    json!([signature, {
+                        "commitment": "finalized",
+                        "encoding": "jsonParsed",
+                        "maxSupportedTransactionVersion": 0,
+                    }]),
+                )
+                .await?;
+            // A transaction that cannot be read stops the scan, its deposits would be lost otherwise
+            let transfers = rpc_result(response)
+                .and_then(|tx| parse_transfers(&tx, &self.deposit_token_accounts))
+                .map_err(|e| format!("Failed to read transaction {}: {}", signature, e))?;
+            if transfers.is_empty() {
+                continue;
+            }
+
+            let block_hash = match block_hashes.get(&slot) {
+                Some(hash) => hash.clone(),
+                None => {
+                    let hash = self.block_hash(slot).await?;
+                    block_hashes.insert(slot, hash.clone());
+                    hash
+                }
+            };
+            for transfer in transfers {
+                events.push(transfer.into_event(block_hash.clone())?);
+            }
+        }
+        Ok(events)
+    }
+}
+
+impl SolanaSource {
+    async fn call(&self, method: &str, params: Value) -> Result<Value, String> {
+        debug(&format!("Calling {} on {}", method, self.url));
+        self.client
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to call {}: {}", method, e))?
+            .json::<Value>()
+            .await
+            .map_err(|e| format!("Invalid response to {}: {}", method, e))
+    }
+
+    /// Successful transactions of `account` in the slots `from..=to`, newest first
+    async fn signatures(
+        &self,
+        account: &str,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<(u64, String)>, String> {
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+        loop {
+            let mut options = json!({
+                "commitment": "finalized",
+                "limit": SIGNATURES_PAGE_SIZE,
+            });
+            if let Some(before) = &before {
+                options["before"] = json!(before);
+            }
+            let page = rpc_result(
+                self.call("getSignaturesForAddress", json!([account, options]))
+                    .await?,
+            )?;
+            let page = signatures_in_range(&page, from, to)?;
+            signatures.extend(page.signatures);
+            match page.next {
+                Some(next) => before = Some(next),
+                None => return Ok(signatures),
+            }
+        }
+    }
+}
+
+/// Result of a JSON-RPC response
+fn rpc_result(response: Value) -> Result<Value, String> {
+    match response.get("error") {
+        Some(error) => Err(format!("RPC error {}: {}", error["code"], error["message"])),
+        None => Ok(response["result"].clone()),
+    }
+}
+
+/// Hash of the block of a `getBlock` response, empty for a skipped slot
+fn parse_block_hash(response: Value) -> Result<String, String> {
+    if let Some(code) = response["error"]["code"].as_i64() {
+        if SLOT_SKIPPED_ERRORS.contains(&code) {
+            return Ok(String::new());
+        }
+    }
+    let block = rpc_result(response)?;
+    block["blockhash"]
+        .as_str()
+        .map(|hash| hash.to_string())
+        .ok_or("Block hash not found".to_string())
+}
+
+/// Page of a `getSignaturesForAddress` listing
+#[derive(Debug, PartialEq)]
+struct SignaturePage {
+    /// Successful transactions of the page in the slot range, with their slot
+    signatures: Vec<(u64, String)>,
+    /// Signature to list the next page before, `None` once the range is fully listed
+    next: Option<String>,
+}
+
+fn signatures_in_range(page: &Value, from: u64, to: u64) -> Result<SignaturePage, String> {
+    let entries = page
+        .as_array()
+        .ok_or(format!("Invalid signatures {}", page))?;
+    let mut signatures = Vec::new();
+    for entry in entries {
+        let slot = entry["slot"].as_u64().ok_or("Signature without slot")?;
+        if slot < from {
+            return Ok(SignaturePage {
+                signatures,
+                next: None,
+            });
+        }
+        let signature = entry["signature"].as_str().ok_or("Signature not found")?;
+        if slot <= to && entry["err"].is_null() {
+            signatures.push((slot, signature.to_string()));
+        }
+    }
+
+    let next = match entries.last() {
+        Some(last) if entries.len() == SIGNATURES_PAGE_SIZE => {
+            last["signature"].as_str().map(|s| s.to_string())
+        }
+        _ => None,
+    };
+    Ok(SignaturePage { signatures, next })
+}
+
+/// SPL token transfer to a deposit token account
+#[derive(Debug, PartialEq)]
+struct SolanaTransfer {
+    signature: String,
+    slot: u64,
+    /// Time the block of the slot was produced at
+    block_time: NaiveDateTime,
+    /// Index of the transfer among the instructions of the transaction
+    instruction_index: usize,
+    mint: String,
+    /// Amount in the smallest unit of the token
+    amount: String,
+    authority: String,
+    /// Memo of the transaction, the hex encoded order id
+    memo: Option<String>,
+}
+
+impl SolanaTransfer {
+    fn into_event(self, block_hash: String) -> Result<ChainEvent, String> {
+        // Block numbers are recorded as 32 bit integers
+        let block_number = i32::try_from(self.slot)
+            .map_err(|_| format!("Slot {} does not fit a block number", self.slot))?;
+        Ok(ChainEvent::Deposit {
+            beneficiary: match self.memo {
+                Some(memo) => Beneficiary::Order(memo),
+                None => Beneficiary::Unknown,
+            },
+            deposit: Deposit {
+                token_address: self.mint,
+                amount: self.amount,
+                from: self.authority,
+            },
+            location: DepositLocation {
+                tx_hash: self.signature,
+                log_index: self.instruction_index as i32,
+                block_number,
+                block_hash,
+                block_timestamp: self.block_time,
+            },
+        })
+    }
+}
+
+/// Transfers to one of the `accounts` made by a `jsonParsed` transaction, none if it failed
+fn parse_transfers(tx: &Value, accounts: &[String]) -> Result<Vec<SolanaTransfer>, String> {
+    if !tx["meta"]["err"].is_null() {
+        return Ok(vec![]);
+    }
+    let signature = tx["transaction"]["signatures"][0]
+        .as_str()
+        .ok_or("Transaction without signature")?;
+    let slot = tx["slot"].as_u64().ok_or("Transaction without slot")?;
+    let block_time = tx["blockTime"]
+        .as_i64()
+        .and_then(|time| DateTime::from_timestamp(time, 0))
+        .ok_or("Transaction without block time")?
+        .naive_utc();
+    let instructions = tx["transaction"]["message"]["instructions"]
+        .as_array()
+        .ok_or("Transaction without instructions")?;
+    let memo = instructions
+        .iter()
+        .find(|instruction| instruction["program"] == "spl-memo")
+        .and_then(|instruction| instruction["parsed"].as_str())
+        .map(|memo| memo.trim().to_string());
+
+    let mut transfers = Vec::new();
+    for (index, instruction) in instructions.iter().enumerate() {
+        if !matches!(
+            instruction["program"].as_str(),
+            Some("spl-token" | "spl-token-2022")
+        ) {
+            continue;
+        }
+        let info = &instruction["parsed"]["info"];
+        let amount = match instruction["parsed"]["type"].as_str() {
+            Some("transferChecked") => &info["tokenAmount"]["amount"],
+            Some("transfer") => &info["amount"],
+            _ => continue,
+        };
+        let Some(destination) = info["destination"].as_str() else {
+            continue;
+        };
+        if !accounts.iter().any(|account| account == destination) {
+            continue;
+        }
+
+        // Only checked transfers name their mint
+        let mint = match info["mint"].as_str() {
+            Some(mint) => mint.to_string(),
+            None => token_account_mint(tx, destination)
+                .ok_or(format!("Mint of token account {} not found", destination))?,
+        };
+        transfers.push(SolanaTransfer {
+            signature: signature.to_string(),
+            slot,
+            block_time,
+            instruction_index: index,
+            mint,
+            amount: amount
+                .as_str()
+                .ok_or("Transfer without amount")?
+                .to_string(),
+            authority: info["authority"]
+                .as_str()
+                .or(info["multisigAuthority"].as_str())
+                .unwrap_or_default()
+                .to_string(),
+            memo: memo.clone(),
+        });
+    }
+    Ok(transfers)
+}
+
+/// Mint of a token account, from the token balances of a transaction it is part of
+fn token_account_mint(tx: &Value, account: &str) -> Option<String> {
+    let keys = tx["transaction"]["message"]["accountKeys"].as_array()?;
+    let index = keys.iter().position(|key| key["pubkey"] == account)?;
+    tx["meta"]["postTokenBalances"]
+        .as_array()?
+        .iter()
+        .find(|balance| balance["accountIndex"] == index)
+        .and_then(|balance| balance["mint"].as_str())
+        .map(|mint| mint.to_string())
+}
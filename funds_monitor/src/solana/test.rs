@@ -0,0 +1,92 @@
+use super::{
+    parse_block_hash, parse_transfers, rpc_result, signatures_in_range, SignaturePage,
+    SolanaTransfer,
+};
+use chrono::DateTime;
+use serde_json::Value;
+
+const DEPOSIT_ACCOUNT: &str = "3KmqAWQjms7WfYf2ZUdQgwcyqUSC4w7u8RA7BZvY9rRf";
+const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const PAYER: &str = "ZJdCR54e4C4vBKJy5ag7wueiik47wgWsizoNNajmVB9w";
+
+fn fixture(response: &str) -> Value {
+    rpc_result(serde_json::from_str(response).unwrap()).unwrap()
+}
+
+fn deposit_accounts() -> Vec<String> {
+    vec![DEPOSIT_ACCOUNT.to_string()]
+}
+
+#[test]
+fn test_transfer_with_memo_is_a_deposit_of_the_order() {
+    let tx = fixture(include_str!("fixtures/transfer_checked_with_memo.json"));
+
+    assert_eq!(
+        parse_transfers(&tx, &deposit_accounts()),
+        Ok(vec![SolanaTransfer {
+            signature: "JXmJvis5XnNAMF1oEXhAKdjVLoXVLqpcYwcgUoUaud5EgmhouAfp2i7tyhm6zrziFDDgsota4HBYWTL5NWVmJY98".to_string(),
+            slot: 310000120,
+            block_time: DateTime::from_timestamp(1734614211, 0).unwrap().naive_utc(),
+            instruction_index: 1,
+            mint: USDC.to_string(),
+            amount: "25000000".to_string(),
+            authority: PAYER.to_string(),
+            memo: Some("0x2a".to_string()),
+        }])
+    );
+}
+
+#[test]
+fn test_transfers_to_other_accounts_are_ignored() {
+    let tx = fixture(include_str!("fixtures/transfer_checked_with_memo.json"));
+    let accounts = vec!["SHfeTtMSP4CbCsuDnp4jFLFPDrERuRF4WZ4722bxgwZy".to_string()];
+
+    assert_eq!(parse_transfers(&tx, &accounts), Ok(vec![]));
+}
+
+#[test]
+fn test_transfer_without_memo_reads_the_mint_from_the_token_balances() {
+    let tx = fixture(include_str!("fixtures/transfer_without_memo.json"));
+
+    let transfers = parse_transfers(&tx, &deposit_accounts()).unwrap();
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].instruction_index, 0);
+    assert_eq!(transfers[0].mint, USDC);
+    assert_eq!(transfers[0].amount, "10000000");
+    assert_eq!(transfers[0].memo, None);
+}
+
+#[test]
+fn test_failed_transactions_are_ignored() {
+    let tx = fixture(include_str!("fixtures/failed_transfer.json"));
+
+    assert_eq!(parse_transfers(&tx, &deposit_accounts()), Ok(vec![]));
+}
+
+#[test]
+fn test_signatures_are_selected_in_the_slot_range() {
+    let page = fixture(include_str!("fixtures/signatures.json"));
+
+    // The failed transaction is skipped, the listing stops below the range
+    assert_eq!(
+        signatures_in_range(&page, 310000100, 310000200),
+        Ok(SignaturePage {
+            signatures: vec![(
+                310000120,
+                "JXmJvis5XnNAMF1oEXhAKdjVLoXVLqpcYwcgUoUaud5EgmhouAfp2i7tyhm6zrziFDDgsota4HBYWTL5NWVmJY98".to_string()
+            )],
+            next: None,
+        })
+    );
+}
+
+#[test]
+fn test_skipped_slot_has_an_empty_hash() {
+    let response = serde_json::from_str(include_str!("fixtures/skipped_slot.json")).unwrap();
+
+    assert_eq!(parse_block_hash(response), Ok(String::new()));
+    assert!(
+        rpc_result(serde_json::from_str(include_str!("fixtures/skipped_slot.json")).unwrap())
+            .is_err()
+    );
+}
@@ -0,0 +1,258 @@
+/// Chains deposits are monitored on
+/// - `DepositSource` is implemented by every chain, it reports the deposits and payouts of a range
+///   of blocks
+/// - `Scanner` follows a source from the cursor persisted in `indexer_block_numbers`, credits the
+///   deposits it reports and rewinds the cursor when a block it scanned is no longer canonical
+use diesel::PgConnection;
+use serde_json::json;
+use std::time::Duration;
+use turbo_da_core::logger::{info, warn_json};
+
+use crate::query_finalised_block_number;
+use crate::utils::{Deposit, DepositLocation, Payout, Utils};
+
+/// Who a deposit is credited to
+pub(crate) enum Beneficiary {
+    /// Credit request referenced by its hex encoded order id
+    Order(String),
+    /// Owner of the deposit address, a credit request is opened for the deposit
+    User(String),
+    /// Nothing references the deposit, it is recorded in `unmatched_deposits`
+    Unknown,
+}
+
+/// Event of a chain the funds monitor acts on
+pub(crate) enum ChainEvent {
+    Deposit {
+        beneficiary: Beneficiary,
+        deposit: Deposit,
+        location: DepositLocation,
+    },
+    Payout {
+        payout: Payout,
+        location: DepositLocation,
+    },
+}
+
+pub(crate) trait DepositSource {
+    /// Chain identifier the cursor and the deposits are recorded under
+    fn chain_id(&self) -> i32;
+
+    /// Number of blocks below the cursor a reorg can reach, 0 when only final blocks are scanned
+    fn finality_depth(&self) -> u64;
+
+    /// Largest number of blocks scanned at once, the cursor is persisted after each range
+    fn max_block_range(&self) -> u64;
+
+    /// Latest block that can be scanned
+    async fn final_block(&self) -> Result<u64, String>;
+
+    /// Hash of the canonical block at `number`, in the format deposits are recorded with
+    async fn block_hash(&self, number: u64) -> Result<String, String>;
+
+    /// Deposits and payouts made in the blocks `from..=to`, in chain order
+    async fn events(&self, from: u64, to: u64) -> Result<Vec<ChainEvent>, String>;
+}
+
+pub(crate) struct Scanner<S> {
+    pub(crate) source: S,
+    pub(crate) utils: Utils,
+    /// Last block scanned and its hash, as persisted in `indexer_block_numbers`
+    cursor: u64,
+    cursor_hash: String,
+}
+
+impl<S: DepositSource> Scanner<S> {
+    /// Resumes from the cursor persisted for the chain of `source`
+    pub fn new(source: S, utils: Utils) -> Result<Self, String> {
+        let mut connection = utils.establish_connection()?;
+        let cursor = query_finalised_block_number(source.chain_id(), &mut connection);
+        info(&format!(
+            "Finalised block number: {}, chain id: {}",
+            cursor.block_number,
+            source.chain_id()
+        ));
+
+        Ok(Self {
+            source,
+            utils,
+            cursor: cursor.block_number as u64,
+            cursor_hash: cursor.block_hash,
+        })
+    }
+
+    pub fn cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    /// Scans up to the final block of the source every `interval`
+    pub async fn run(&mut self, interval: Duration) -> Result<(), String> {
+        loop {
+            let number = self.source.final_block().await?;
+            self.scan_to(number).await?;
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Scans the blocks after the cursor up to `number`, in chunks of at most `max_block_range`
+    pub async fn scan_to(&mut self, number: u64) -> Result<(), String> {
+        let mut connection = self.utils.establish_connection()?;
+        self.handle_reorg(&mut connection).await?;
+
+        let max_block_range = self.source.max_block_range().max(1);
+        while self.cursor < number {
+            let to = number.min(self.cursor + max_block_range);
+            self.scan_range(self.cursor + 1, to, &mut connection)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn scan_range(
+        &mut self,
+        from: u64,
+        to: u64,
+        connection: &mut PgConnection,
+    ) -> Result<(), String> {
+        for event in self.source.events(from, to).await? {
+            self.process_event(event, connection).await?;
+        }
+
+        // Persist the cursor even when the range had no deposits, so a restart resumes from here
+        let hash = self.source.block_hash(to).await?;
+        self.utils.update_finalised_block_number(
+            to as i32,
+            hash.clone(),
+            connection,
+            self.source.chain_id(),
+        )?;
+        self.cursor = to;
+        self.cursor_hash = hash;
+        Ok(())
+    }
+
+    /// Credits a deposit or records a payout
+    ///
+    /// Deposits and payouts that match nothing are recorded or logged by `Utils`, an error is a
+    /// failure to record the event: it is returned so the cursor stays before its block and the
+    /// range is scanned again.
+    async fn process_event(
+        &self,
+        event: ChainEvent,
+        connection: &mut PgConnection,
+    ) -> Result<(), String> {
+        let chain_id = self.source.chain_id();
+        let (beneficiary, deposit, location) = match event {
+            ChainEvent::Payout { payout, location } => {
+                return self
+                    .utils
+                    .update_database_on_payout(&payout, &location, connection, chain_id)
+                    .map_err(|e| format!("Failed to process withdrawal event: {}", e));
+            }
+            ChainEvent::Deposit {
+                beneficiary,
+                deposit,
+                location,
+            } => (beneficiary, deposit, location),
+        };
+
+        let order_id = match beneficiary {
+            Beneficiary::Order(order_id) => order_id,
+            // Deposits to a per-user address come without an order, one is opened for them
+            Beneficiary::User(user) => {
+                return self
+                    .utils
+                    .update_database_on_user_deposit(
+                        &user, &deposit, &location, connection, chain_id,
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to update database on deposit: {}", e));
+            }
+            Beneficiary::Unknown => {
+                return self
+                    .utils
+                    .record_unmatched_deposit(
+                        None,
+                        &deposit,
+                        &location,
+                        connection,
+                        chain_id,
+                        "Deposit without order id",
+                    )
+                    .map_err(|e| format!("Failed to record unmatched deposit: {}", e));
+            }
+        };
+
+        self.utils
+            .update_database_on_deposit(&order_id, &deposit, &location, connection, chain_id)
+            .await
+            .map_err(|e| format!("Failed to update database on deposit: {}", e))
+    }
+
+    /// Detects a reorg below the finality threshold by re-verifying the hash of the cursor block
+    ///
+    /// On a reorg the deposits credited or left unmatched and the withdrawals paid in the last
+    /// `finality_depth` blocks before the cursor are checked against the canonical chain, the ones
+    /// whose block changed are reverted and the cursor is rewound so the range is scanned again.
+    async fn handle_reorg(&mut self, connection: &mut PgConnection) -> Result<(), String> {
+        let depth = self.source.finality_depth();
+        if depth == 0 {
+            return Ok(());
+        }
+        let canonical_hash = self.source.block_hash(self.cursor).await?;
+        if canonical_hash == self.cursor_hash {
+            return Ok(());
+        }
+
+        let chain_id = self.source.chain_id();
+        let rewind_to = self.cursor.saturating_sub(depth);
+        warn_json(json!({
+            "message": "Reorg detected below the finality threshold",
+            "chain_id": chain_id,
+            "block_number": self.cursor,
+            "stored_hash": self.cursor_hash,
+            "canonical_hash": canonical_hash,
+            "rewind_to": rewind_to,
+            "level": "warn"
+        }));
+
+        let deposits =
+            self.utils
+                .get_processed_deposits_since(rewind_to as i32, connection, chain_id)?;
+        for deposit in deposits {
+            if self.source.block_hash(deposit.block_number as u64).await? != deposit.block_hash {
+                self.utils.revert_deposit(&deposit, connection)?;
+            }
+        }
+        let unmatched =
+            self.utils
+                .get_unmatched_deposits_since(rewind_to as i32, connection, chain_id)?;
+        for deposit in unmatched {
+            if self.source.block_hash(deposit.block_number as u64).await? != deposit.block_hash {
+                self.utils.revert_unmatched_deposit(&deposit, connection)?;
+            }
+        }
+        let payouts =
+            self.utils
+                .get_paid_withdrawals_since(rewind_to as i32, connection, chain_id)?;
+        for withdrawal in payouts {
+            let Some(number) = withdrawal.block_number else {
+                continue;
+            };
+            if Some(self.source.block_hash(number as u64).await?) != withdrawal.block_hash {
+                self.utils.revert_payout(&withdrawal, connection)?;
+            }
+        }
+
+        let hash = self.source.block_hash(rewind_to).await?;
+        self.utils.update_finalised_block_number(
+            rewind_to as i32,
+            hash.clone(),
+            connection,
+            chain_id,
+        )?;
+        self.cursor = rewind_to;
+        self.cursor_hash = hash;
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+#[cfg(test)]
+pub mod test {
+    use crate::utils::{Deposit, DepositLocation, Utils};
+    use bigdecimal::BigDecimal;
+    use chrono::Utc;
+    use db::schema::{credit_requests, processed_deposits, unmatched_deposits, users};
+    use db::test_utils::TestDB;
+    use diesel::{prelude::*, PgConnection};
+    use futures::future::BoxFuture;
+    use std::sync::Arc;
+    use turbo_da_core::price_oracle::{PriceFeed, PriceOracle, PriceOracleConfig, PriceQuote};
+
+    const CHAIN_ID: i32 = 99;
+    const TOKEN: &str = "0x00000000000000000000000000000000000000aa";
+    const USER: &str = "user@example.com";
+
+    /// Prices every token at 1 USD
+    struct OneDollar;
+
+    impl PriceOracle for OneDollar {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn usd_price<'a>(&'a self, _token: &'a str) -> BoxFuture<'a, Result<PriceQuote, String>> {
+            Box::pin(async move {
+                Ok(PriceQuote {
+                    usd: BigDecimal::from(1),
+                    updated_at: Utc::now(),
+                    source: "test",
+                })
+            })
+        }
+    }
+
+    fn utils(db: &TestDB) -> Utils {
+        let prices = PriceFeed::new(vec![Box::new(OneDollar)], &PriceOracleConfig::default());
+        Utils::new(Arc::new(prices), None, db.table_url.clone(), String::new())
+    }
+
+    /// A USD denominated user with a credit request, returns the order id of the request
+    fn setup(connection: &mut PgConnection) -> String {
+        diesel::sql_query(format!(
+            "INSERT INTO users (id, name, balance_unit) VALUES ('{}', 'user', 'usd')",
+            USER
+        ))
+        .execute(connection)
+        .unwrap();
+        diesel::sql_query(format!(
+            "INSERT INTO supported_tokens (chain_id, token_address, symbol, decimals, price_feed_id) \
+             VALUES ({}, '{}', 'TST', 6, 'test')",
+            CHAIN_ID, TOKEN
+        ))
+        .execute(connection)
+        .unwrap();
+        let id = diesel::insert_into(credit_requests::table)
+            .values((
+                credit_requests::user_id.eq(USER),
+                credit_requests::request_status.eq("tx_submitted"),
+                credit_requests::request_type.eq("DEPOSIT"),
+            ))
+            .returning(credit_requests::id)
+            .get_result::<i32>(connection)
+            .unwrap();
+        format!("{:x}", id)
+    }
+
+    fn balance(connection: &mut PgConnection) -> BigDecimal {
+        users::table
+            .find(USER)
+            .select(users::credit_balance)
+            .first(connection)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reorged_deposit_is_reverted() {
+        let db = TestDB::init();
+        let mut connection = db.connection();
+        let order_id = setup(&mut connection);
+        let utils = utils(&db);
+
+        let deposit = Deposit {
+            token_address: TOKEN.to_string(),
+            amount: "2000000".to_string(),
+            from: "0x00000000000000000000000000000000000000bb".to_string(),
+        };
+        let location = DepositLocation {
+            tx_hash: "0x01".to_string(),
+            log_index: 0,
+            block_number: 10,
+            block_hash: "0xaa".to_string(),
+            block_timestamp: Utc::now().naive_utc(),
+        };
+        utils
+            .update_database_on_deposit(&order_id, &deposit, &location, &mut connection, CHAIN_ID)
+            .await
+            .unwrap();
+        assert_eq!(balance(&mut connection), BigDecimal::from(2));
+
+        let reorged = utils
+            .get_processed_deposits_since(10, &mut connection, CHAIN_ID)
+            .unwrap();
+        assert_eq!(reorged.len(), 1);
+        utils.revert_deposit(&reorged[0], &mut connection).unwrap();
+
+        assert_eq!(balance(&mut connection), BigDecimal::from(0));
+        let processed = processed_deposits::table
+            .count()
+            .get_result::<i64>(&mut connection)
+            .unwrap();
+        assert_eq!(processed, 0);
+        let status = credit_requests::table
+            .select(credit_requests::request_status)
+            .first::<String>(&mut connection)
+            .unwrap();
+        assert_eq!(status, "tx_submitted");
+
+        // The deposit is credited again once it is included in the new chain
+        utils
+            .update_database_on_deposit(&order_id, &deposit, &location, &mut connection, CHAIN_ID)
+            .await
+            .unwrap();
+        assert_eq!(balance(&mut connection), BigDecimal::from(2));
+    }
+
+    #[tokio::test]
+    async fn test_reorged_unmatched_deposit_is_forgotten() {
+        let db = TestDB::init();
+        let mut connection = db.connection();
+        setup(&mut connection);
+        let utils = utils(&db);
+
+        let deposit = Deposit {
+            token_address: TOKEN.to_string(),
+            amount: "2000000".to_string(),
+            from: "0x00000000000000000000000000000000000000bb".to_string(),
+        };
+        let location = DepositLocation {
+            tx_hash: "0x02".to_string(),
+            log_index: 0,
+            block_number: 10,
+            block_hash: "0xaa".to_string(),
+            block_timestamp: Utc::now().naive_utc(),
+        };
+        utils
+            .update_database_on_deposit(
+                &"zz".to_string(),
+                &deposit,
+                &location,
+                &mut connection,
+                CHAIN_ID,
+            )
+            .await
+            .unwrap();
+        let unmatched = || {
+            unmatched_deposits::table
+                .count()
+                .get_result::<i64>(&mut db.connection())
+                .unwrap()
+        };
+        assert_eq!(unmatched(), 1);
+
+        let reorged = utils
+            .get_unmatched_deposits_since(10, &mut connection, CHAIN_ID)
+            .unwrap();
+        assert_eq!(reorged.len(), 1);
+        utils
+            .revert_unmatched_deposit(&reorged[0], &mut connection)
+            .unwrap();
+        assert_eq!(unmatched(), 0);
+        assert_eq!(balance(&mut connection), BigDecimal::from(0));
+    }
+
+    #[tokio::test]
+    async fn test_deposit_to_user_address_opens_one_request() {
+        let db = TestDB::init();
+        let mut connection = db.connection();
+        setup(&mut connection);
+        let utils = utils(&db);
+        let requests = |connection: &mut PgConnection| {
+            credit_requests::table
+                .count()
+                .get_result::<i64>(connection)
+                .unwrap()
+        };
+
+        let unsupported = Deposit {
+            token_address: "0x00000000000000000000000000000000000000cc".to_string(),
+            amount: "2000000".to_string(),
+            from: "0x00000000000000000000000000000000000000bb".to_string(),
+        };
+        let location = |tx_hash: &str| DepositLocation {
+            tx_hash: tx_hash.to_string(),
+            log_index: 0,
+            block_number: 10,
+            block_hash: "0xaa".to_string(),
+            block_timestamp: Utc::now().naive_utc(),
+        };
+        // Left for an admin, without a request opened for it
+        utils
+            .update_database_on_user_deposit(
+                &USER.to_string(),
+                &unsupported,
+                &location("0x03"),
+                &mut connection,
+                CHAIN_ID,
+            )
+            .await
+            .unwrap();
+        assert_eq!(requests(&mut connection), 1);
+
+        let deposit = Deposit {
+            token_address: TOKEN.to_string(),
+            ..unsupported
+        };
+        for _ in 0..2 {
+            utils
+                .update_database_on_user_deposit(
+                    &USER.to_string(),
+                    &deposit,
+                    &location("0x04"),
+                    &mut connection,
+                    CHAIN_ID,
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(requests(&mut connection), 2);
+        assert_eq!(balance(&mut connection), BigDecimal::from(2));
+    }
+}
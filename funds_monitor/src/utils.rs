@@ -1,114 +1,348 @@
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
 
 use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, NaiveDateTime};
 use db::{
-    models::credit_requests::CreditRequestsGet,
-    schema::{credit_requests, indexer_block_numbers::dsl::*, users},
+    models::{
+        credit_requests::{
+            CreditRequestQuote, CreditRequestStatus, CreditRequestsGet, DepositCredit,
+        },
+        deposit_addresses::{DepositAddress, DepositAddressCreate},
+        fee_reconciliations::{FeeReconciliation, FeeRecord},
+        processed_deposits::{ProcessedDeposit, ProcessedDepositCreate},
+        supported_tokens::{normalize_token_address, SupportedToken},
+        unmatched_deposits::{UnmatchedDeposit, UnmatchedDepositCreate, UnmatchedDepositStatus},
+        user_model::BalanceUnit,
+        withdrawals::{Withdrawal, WithdrawalStatus},
+    },
+    schema::{
+        credit_requests, customer_expenditures, deposit_addresses, fee_reconciliations,
+        indexer_block_numbers::dsl::*, processed_deposits, supported_tokens, unmatched_deposits,
+        users, withdrawals,
+    },
 };
 use diesel::prelude::*;
 use serde_json::json;
-use turbo_da_core::logger::{debug_json, error, error_json, info};
+use turbo_da_core::deposit::parse_order_id;
+use turbo_da_core::logger::{debug_json, error_json, info, warn_json};
+use turbo_da_core::price_oracle::PriceFeed;
+use turbo_da_core::quote::apply_quote;
 use turbo_da_core::utils::get_amount_to_be_credited;
+use uuid::Uuid;
 
 pub struct Deposit {
     pub token_address: String,
     pub amount: String,
-    pub _from: String,
+    pub from: String,
 }
 
+/// Payout of a withdrawal seen on chain
+pub struct Payout {
+    pub nonce: i32,
+    pub token_address: String,
+    pub amount: BigDecimal,
+    pub recipient: String,
+}
+
+/// Position of a deposit on chain
+///
+/// `(chain_id, tx_hash, log_index)` identifies a deposit, a deposit seen again is not credited twice.
+pub struct DepositLocation {
+    pub tx_hash: String,
+    pub log_index: i32,
+    pub block_number: i32,
+    pub block_hash: String,
+    /// Time the block was produced at, quotes are checked for expiry against it
+    pub block_timestamp: NaiveDateTime,
+}
+
+#[derive(Clone)]
 pub struct Utils {
-    coin_gecho_api_url: String,
-    coin_gecho_api_key: String,
+    prices: Arc<PriceFeed>,
+    quote_signing_key: Option<String>,
     database_url: String,
     avail_rpc_url: String,
 }
 
 impl Utils {
     pub fn new(
-        coin_gecho_api_url: String,
-        coin_gecho_api_key: String,
+        prices: Arc<PriceFeed>,
+        quote_signing_key: Option<String>,
         database_url: String,
         avail_rpc_url: String,
     ) -> Self {
         Self {
-            coin_gecho_api_url,
-            coin_gecho_api_key,
+            prices,
+            quote_signing_key,
             database_url,
             avail_rpc_url,
         }
     }
 
+    /// Credits a deposit and moves the block cursor to its block in one transaction
+    ///
+    /// Deposits already recorded in `processed_deposits` are skipped, deposits whose order id does
+    /// not reference a credit request, or are made in a token that is not enabled in
+    /// `supported_tokens`, are recorded in `unmatched_deposits`, as are further deposits against a
+    /// request already credited. Deposits against a quoted request are credited as described in
+    /// `apply_quote`.
     pub async fn update_database_on_deposit(
         &self,
         order_id: &String,
         receipt: &Deposit,
-        transaction_hash: &String,
+        location: &DepositLocation,
         connection: &mut PgConnection,
         chain_identifier: i32, // 0 for Avail
-        status: &String,
     ) -> Result<(), String> {
-        let address = receipt.token_address.to_lowercase();
-        let amount = get_amount_to_be_credited(
-            &self.coin_gecho_api_url,
-            &self.coin_gecho_api_key,
-            &self.avail_rpc_url,
-            &(chain_identifier as u64),
-            &address,
-            &BigDecimal::from_str(&receipt.amount.to_string().as_str()).unwrap(),
-        )
-        .await
-        .map_err(|e| format!("Failed to get amount to be credited: {}", e))?;
+        if self.is_deposit_processed(location, connection, chain_identifier)? {
+            info(&format!(
+                "Deposit already processed, tx hash: {} log index: {}",
+                location.tx_hash, location.log_index
+            ));
+            return Ok(());
+        }
+
+        let request = match parse_order_id(order_id) {
+            Ok(parsed_id) => match self.get_credit_request_quote(parsed_id, connection)? {
+                Some(request) => request,
+                None => {
+                    return self.record_unmatched_deposit(
+                        Some(order_id),
+                        receipt,
+                        location,
+                        connection,
+                        chain_identifier,
+                        "Credit request not found",
+                    )
+                }
+            },
+            Err(e) => {
+                return self.record_unmatched_deposit(
+                    Some(order_id),
+                    receipt,
+                    location,
+                    connection,
+                    chain_identifier,
+                    &e,
+                )
+            }
+        };
 
-        let parsed_id = i32::from_str_radix(order_id.trim_start_matches("0x"), 16)
-            .map_err(|e| format!("Failed to parse order ID: {}", e))?;
+        if !CreditRequestStatus::Credited
+            .source_values()
+            .contains(&request.request_status.as_str())
+        {
+            return self.record_unmatched_deposit(
+                Some(order_id),
+                receipt,
+                location,
+                connection,
+                chain_identifier,
+                "Credit request already credited",
+            );
+        }
 
         debug_json(json!({
             "order_id": order_id,
             "level": "debug"
         }));
         debug_json(json!({
-            "parsed_id": parsed_id,
+            "parsed_id": request.id,
             "level": "debug"
         }));
 
-        let row = diesel::update(credit_requests::table)
-            .filter(credit_requests::id.eq(parsed_id))
-            .set((
-                credit_requests::amount_credit.eq(Some(amount.clone())),
-                credit_requests::request_status.eq(status.to_string()),
-                credit_requests::chain_id.eq(Some(chain_identifier)),
-                credit_requests::tx_hash.eq(Some(transaction_hash.clone())),
-                credit_requests::request_type.eq("DEPOSIT".to_string()),
-                credit_requests::token_address.eq(Some(address.clone())),
-                credit_requests::amount_paid.eq(Some(
-                    BigDecimal::from_str(&receipt.amount.to_string().as_str()).unwrap(),
-                )),
-            ))
-            .returning(CreditRequestsGet::as_returning())
-            .get_result::<CreditRequestsGet>(&mut *connection)
-            .map_err(|e| format!("Failed to store fund request: {}", e))?;
+        let user = request.user_id.clone();
+        self.credit_deposit(
+            Some(order_id),
+            &user,
+            Some(request),
+            receipt,
+            location,
+            connection,
+            chain_identifier,
+        )
+        .await
+    }
+
+    /// Credits a deposit made to the address of `user` as `update_database_on_deposit` does
+    ///
+    /// Its credit request is opened in the transaction crediting it, a deposit that can't be
+    /// credited yet leaves no request behind to be opened again on the next attempt.
+    pub async fn update_database_on_user_deposit(
+        &self,
+        user: &String,
+        receipt: &Deposit,
+        location: &DepositLocation,
+        connection: &mut PgConnection,
+        chain_identifier: i32,
+    ) -> Result<(), String> {
+        if self.is_deposit_processed(location, connection, chain_identifier)? {
+            info(&format!(
+                "Deposit already processed, tx hash: {} log index: {}",
+                location.tx_hash, location.log_index
+            ));
+            return Ok(());
+        }
+
+        self.credit_deposit(
+            None,
+            user,
+            None,
+            receipt,
+            location,
+            connection,
+            chain_identifier,
+        )
+        .await
+    }
+
+    /// Credits a deposit of `user` against `request`, or against a request opened for it when
+    /// there is none
+    #[allow(clippy::too_many_arguments)]
+    async fn credit_deposit(
+        &self,
+        order_id: Option<&String>,
+        user: &String,
+        request: Option<CreditRequestQuote>,
+        receipt: &Deposit,
+        location: &DepositLocation,
+        connection: &mut PgConnection,
+        chain_identifier: i32,
+    ) -> Result<(), String> {
+        let address = normalize_token_address(&receipt.token_address);
+        let token = match self.get_supported_token(chain_identifier, &address, connection)? {
+            Some(token) if token.enabled => token,
+            _ => {
+                return self.record_unmatched_deposit(
+                    order_id,
+                    receipt,
+                    location,
+                    connection,
+                    chain_identifier,
+                    "Token not supported",
+                )
+            }
+        };
+        let amount_paid = BigDecimal::from_str(&receipt.amount)
+            .map_err(|e| format!("Invalid deposit amount {}: {}", receipt.amount, e))?;
+        let unit = self.get_balance_unit(user, connection)?;
+        let (amount, price) = get_amount_to_be_credited(
+            &self.prices,
+            &self.avail_rpc_url,
+            &token,
+            &amount_paid,
+            unit,
+        )
+        .await
+        .map_err(|e| format!("Failed to get amount to be credited: {}", e))?;
+        let (amount, quote) = match &request {
+            Some(request) => apply_quote(
+                self.quote_signing_key.as_deref(),
+                request,
+                chain_identifier,
+                &address,
+                &amount_paid,
+                amount,
+                location.block_timestamp,
+            ),
+            None => (amount, None),
+        };
+        let credit = DepositCredit {
+            amount_credit: amount.clone(),
+            chain_id: chain_identifier,
+            tx_hash: location.tx_hash.clone(),
+            token_address: address.clone(),
+            amount_paid: amount_paid.clone(),
+            quote_outcome: quote
+                .as_ref()
+                .map(|(outcome, _)| outcome.as_str().to_string()),
+            quote_difference: quote.map(|(_, difference)| difference),
+        };
+
+        let credited = connection.transaction::<_, diesel::result::Error, _>(|conn| {
+            let parsed_id = match &request {
+                Some(request) => request.id,
+                None => diesel::insert_into(credit_requests::table)
+                    .values((
+                        credit_requests::user_id.eq(user),
+                        credit_requests::chain_id.eq(chain_identifier),
+                        credit_requests::request_status.eq(CreditRequestStatus::Created.as_str()),
+                        credit_requests::request_type.eq("DEPOSIT"),
+                    ))
+                    .returning(credit_requests::id)
+                    .get_result::<i32>(conn)?,
+            };
+            let inserted = diesel::insert_into(processed_deposits::table)
+                .values(&ProcessedDepositCreate {
+                    chain_id: chain_identifier,
+                    tx_hash: location.tx_hash.clone(),
+                    log_index: location.log_index,
+                    block_number: location.block_number,
+                    block_hash: location.block_hash.clone(),
+                    credit_request_id: parsed_id,
+                    amount_credit: amount.clone(),
+                    price: price.clone(),
+                })
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+            if inserted == 0 {
+                // Also drops the request opened for it
+                return Err(diesel::result::Error::RollbackTransaction);
+            }
+
+            let row = diesel::update(credit_requests::table)
+                .filter(credit_requests::id.eq(parsed_id))
+                .filter(
+                    credit_requests::request_status
+                        .eq_any(CreditRequestStatus::Credited.source_values()),
+                )
+                .set((
+                    &credit,
+                    credit_requests::request_status.eq(CreditRequestStatus::Credited.as_str()),
+                    credit_requests::request_type.eq("DEPOSIT"),
+                ))
+                .returning(CreditRequestsGet::as_returning())
+                .get_result::<CreditRequestsGet>(conn)?;
+
+            self.update_token_information_on_deposit(&amount, unit, &row.user_id, conn)?;
+            store_block_cursor(
+                conn,
+                chain_identifier,
+                location.block_number,
+                &location.block_hash,
+            )?;
+            Ok(row.id)
+        });
 
-        info(&format!("Success: {} status: {}", order_id, status));
-        self.update_token_information_on_deposit(&amount, &row.user_id, connection)
-            .await;
+        match credited {
+            Ok(request_id) => info(&format!(
+                "Success: {:x} status: {}",
+                request_id,
+                CreditRequestStatus::Credited.as_str()
+            )),
+            // Credited concurrently
+            Err(diesel::result::Error::RollbackTransaction) => {}
+            Err(e) => return Err(format!("Failed to store fund request: {}", e)),
+        }
 
         Ok(())
     }
 
-    pub async fn update_token_information_on_deposit(
+    /// Credits `amount` to the balance of a user, if the balance is still held in `unit`
+    pub fn update_token_information_on_deposit(
         &self,
         amount: &BigDecimal,
+        unit: BalanceUnit,
         user_id: &String,
         connection: &mut PgConnection,
-    ) {
-        let updated_rows_query = diesel::update(users::table.filter(users::id.eq(user_id)))
-            .set(users::credit_balance.eq(users::credit_balance + amount))
-            .execute(connection);
-
-        let updated_rows = updated_rows_query.unwrap_or_else(|_| {
-            error(&format!("Update token balances query failed"));
-            0
-        });
+    ) -> Result<(), diesel::result::Error> {
+        let updated_rows = diesel::update(
+            users::table
+                .filter(users::id.eq(user_id))
+                .filter(users::balance_unit.eq(unit.as_str())),
+        )
+        .set(users::credit_balance.eq(users::credit_balance + amount))
+        .execute(connection)?;
 
         if updated_rows > 0 {
             debug_json(json!({
@@ -117,26 +351,469 @@ impl Utils {
                 "amount": amount,
                 "level": "debug"
             }));
+            Ok(())
         } else {
             error_json(json!({
                 "message": "No rows updated for user ID",
                 "user_id": user_id,
+                "balance_unit": unit.as_str(),
                 "level": "error"
             }));
+            Err(diesel::result::Error::NotFound)
         }
     }
 
-    pub async fn update_finalised_block_number(
+    /// Unit the balance of a user is held in
+    fn get_balance_unit(
+        &self,
+        user_id: &String,
+        connection: &mut PgConnection,
+    ) -> Result<BalanceUnit, String> {
+        users::table
+            .filter(users::id.eq(user_id))
+            .select(users::balance_unit)
+            .first::<String>(connection)
+            .map_err(|e| format!("Failed to query user {}: {}", user_id, e))?
+            .parse()
+    }
+
+    fn get_supported_token(
+        &self,
+        chain_identifier: i32,
+        token_address: &str,
+        connection: &mut PgConnection,
+    ) -> Result<Option<SupportedToken>, String> {
+        supported_tokens::table
+            .filter(supported_tokens::chain_id.eq(chain_identifier))
+            .filter(supported_tokens::token_address.eq(token_address))
+            .select(SupportedToken::as_select())
+            .first::<SupportedToken>(connection)
+            .optional()
+            .map_err(|e| format!("Failed to query supported tokens: {}", e))
+    }
+
+    /// Tokens registered for a chain, enabled or not
+    pub fn get_chain_tokens(
+        &self,
+        chain_identifier: i32,
+        connection: &mut PgConnection,
+    ) -> Result<Vec<SupportedToken>, String> {
+        supported_tokens::table
+            .filter(supported_tokens::chain_id.eq(chain_identifier))
+            .select(SupportedToken::as_select())
+            .load::<SupportedToken>(connection)
+            .map_err(|e| format!("Failed to query supported tokens: {}", e))
+    }
+
+    fn get_credit_request_quote(
+        &self,
+        request_id: i32,
+        connection: &mut PgConnection,
+    ) -> Result<Option<CreditRequestQuote>, String> {
+        credit_requests::table
+            .find(request_id)
+            .select(CreditRequestQuote::as_select())
+            .first::<CreditRequestQuote>(connection)
+            .optional()
+            .map_err(|e| format!("Failed to query credit requests: {}", e))
+    }
+
+    /// Keeps a deposit that cannot be credited for an admin to attribute or refund
+    pub fn record_unmatched_deposit(
+        &self,
+        order_id: Option<&String>,
+        receipt: &Deposit,
+        location: &DepositLocation,
+        connection: &mut PgConnection,
+        chain_identifier: i32,
+        reason: &str,
+    ) -> Result<(), String> {
+        let amount_paid = BigDecimal::from_str(&receipt.amount)
+            .map_err(|e| format!("Failed to parse deposit amount: {}", e))?;
+        diesel::insert_into(unmatched_deposits::table)
+            .values(&UnmatchedDepositCreate {
+                chain_id: chain_identifier,
+                tx_hash: location.tx_hash.clone(),
+                log_index: location.log_index,
+                block_number: location.block_number,
+                block_hash: location.block_hash.clone(),
+                from_address: receipt.from.clone(),
+                token_address: normalize_token_address(&receipt.token_address),
+                amount_paid,
+                order_id: order_id.cloned(),
+                reason: reason.to_string(),
+            })
+            .on_conflict_do_nothing()
+            .execute(connection)
+            .map_err(|e| format!("Failed to store unmatched deposit: {}", e))?;
+
+        warn_json(json!({
+            "message": "Recorded unmatched deposit",
+            "chain_id": chain_identifier,
+            "tx_hash": location.tx_hash,
+            "log_index": location.log_index,
+            "order_id": order_id,
+            "reason": reason,
+            "level": "warn"
+        }));
+        Ok(())
+    }
+
+    pub fn is_deposit_processed(
+        &self,
+        location: &DepositLocation,
+        connection: &mut PgConnection,
+        chain_identifier: i32,
+    ) -> Result<bool, String> {
+        diesel::select(diesel::dsl::exists(
+            processed_deposits::table
+                .filter(processed_deposits::chain_id.eq(chain_identifier))
+                .filter(processed_deposits::tx_hash.eq(&location.tx_hash))
+                .filter(processed_deposits::log_index.eq(location.log_index)),
+        ))
+        .get_result::<bool>(connection)
+        .map_err(|e| format!("Failed to query processed deposits: {}", e))
+    }
+
+    /// Deposits credited at or above `from_block`, the ones that can still be reorged out
+    pub fn get_processed_deposits_since(
+        &self,
+        from_block: i32,
+        connection: &mut PgConnection,
+        chain_identifier: i32,
+    ) -> Result<Vec<ProcessedDeposit>, String> {
+        processed_deposits::table
+            .filter(processed_deposits::chain_id.eq(chain_identifier))
+            .filter(processed_deposits::block_number.ge(from_block))
+            .select(ProcessedDeposit::as_select())
+            .load::<ProcessedDeposit>(connection)
+            .map_err(|e| format!("Failed to query processed deposits: {}", e))
+    }
+
+    /// Pending unmatched deposits recorded at or above `from_block`
+    pub fn get_unmatched_deposits_since(
+        &self,
+        from_block: i32,
+        connection: &mut PgConnection,
+        chain_identifier: i32,
+    ) -> Result<Vec<UnmatchedDeposit>, String> {
+        unmatched_deposits::table
+            .filter(unmatched_deposits::chain_id.eq(chain_identifier))
+            .filter(unmatched_deposits::block_number.ge(from_block))
+            .filter(unmatched_deposits::status.eq(UnmatchedDepositStatus::Pending.as_str()))
+            .select(UnmatchedDeposit::as_select())
+            .load::<UnmatchedDeposit>(connection)
+            .map_err(|e| format!("Failed to query unmatched deposits: {}", e))
+    }
+
+    /// Forgets a pending unmatched deposit whose block was reorged out, it is recorded again if
+    /// it is included in the new chain
+    pub fn revert_unmatched_deposit(
+        &self,
+        deposit: &UnmatchedDeposit,
+        connection: &mut PgConnection,
+    ) -> Result<(), String> {
+        diesel::delete(
+            unmatched_deposits::table
+                .find(deposit.id)
+                .filter(unmatched_deposits::status.eq(UnmatchedDepositStatus::Pending.as_str())),
+        )
+        .execute(connection)
+        .map_err(|e| format!("Failed to revert unmatched deposit: {}", e))?;
+
+        warn_json(json!({
+            "message": "Reverted reorged unmatched deposit",
+            "chain_id": deposit.chain_id,
+            "tx_hash": deposit.tx_hash,
+            "log_index": deposit.log_index,
+            "block_number": deposit.block_number,
+            "block_hash": deposit.block_hash,
+            "level": "warn"
+        }));
+        Ok(())
+    }
+
+    /// Takes back the credit of a deposit whose block was reorged out
+    ///
+    /// The deposit is forgotten and its credit request moved back to `tx_submitted`, so it is
+    /// credited again if it is included in the new chain. A deposit an admin attributed is removed
+    /// from `unmatched_deposits` along, it is recorded again if it is included unmatched.
+    pub fn revert_deposit(
+        &self,
+        deposit: &ProcessedDeposit,
+        connection: &mut PgConnection,
+    ) -> Result<(), String> {
+        connection
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                diesel::delete(processed_deposits::table.find(deposit.id)).execute(conn)?;
+                diesel::delete(
+                    unmatched_deposits::table
+                        .filter(unmatched_deposits::chain_id.eq(deposit.chain_id))
+                        .filter(unmatched_deposits::tx_hash.eq(&deposit.tx_hash))
+                        .filter(unmatched_deposits::log_index.eq(deposit.log_index)),
+                )
+                .execute(conn)?;
+                diesel::update(
+                    credit_requests::table
+                        .find(deposit.credit_request_id)
+                        .filter(
+                            credit_requests::request_status
+                                .eq(CreditRequestStatus::Credited.as_str()),
+                        ),
+                )
+                .set((
+                    credit_requests::request_status.eq(CreditRequestStatus::TxSubmitted.as_str()),
+                    credit_requests::confirmations.eq(None::<i32>),
+                ))
+                .execute(conn)?;
+                let owner = credit_requests::table
+                    .find(deposit.credit_request_id)
+                    .select(credit_requests::user_id)
+                    .first::<String>(conn)?;
+                diesel::update(users::table.filter(users::id.eq(&owner)))
+                    .set(users::credit_balance.eq(users::credit_balance - &deposit.amount_credit))
+                    .execute(conn)?;
+                Ok(())
+            })
+            .map_err(|e| format!("Failed to revert deposit: {}", e))?;
+
+        warn_json(json!({
+            "message": "Reverted reorged deposit",
+            "chain_id": deposit.chain_id,
+            "tx_hash": deposit.tx_hash,
+            "log_index": deposit.log_index,
+            "block_number": deposit.block_number,
+            "block_hash": deposit.block_hash,
+            "amount": deposit.amount_credit,
+            "level": "warn"
+        }));
+        Ok(())
+    }
+
+    /// Marks a signed withdrawal paid from its payout on chain
+    ///
+    /// A payout seen again is skipped. A payout that does not match the signed withdrawal of its
+    /// nonce is reported and left alone, the contract only pays out payouts signed by an operator.
+    pub fn update_database_on_payout(
+        &self,
+        payout: &Payout,
+        location: &DepositLocation,
+        connection: &mut PgConnection,
+        chain_identifier: i32,
+    ) -> Result<(), String> {
+        let withdrawal = withdrawals::table
+            .find(payout.nonce)
+            .filter(withdrawals::chain_id.eq(chain_identifier))
+            .select(Withdrawal::as_select())
+            .first::<Withdrawal>(connection)
+            .optional()
+            .map_err(|e| format!("Failed to query withdrawal: {}", e))?;
+
+        let withdrawal = match withdrawal {
+            Some(withdrawal)
+                if withdrawal
+                    .token_address
+                    .eq_ignore_ascii_case(&payout.token_address)
+                    && withdrawal.recipient.eq_ignore_ascii_case(&payout.recipient)
+                    && withdrawal.amount == payout.amount =>
+            {
+                withdrawal
+            }
+            withdrawal => {
+                warn_json(json!({
+                    "message": "Payout does not match a withdrawal",
+                    "chain_id": chain_identifier,
+                    "nonce": payout.nonce,
+                    "token_address": payout.token_address,
+                    "amount": payout.amount,
+                    "recipient": payout.recipient,
+                    "tx_hash": location.tx_hash,
+                    "withdrawal": withdrawal,
+                    "level": "warn"
+                }));
+                return Ok(());
+            }
+        };
+        if withdrawal.tx_hash.as_ref() == Some(&location.tx_hash) {
+            info(&format!(
+                "Payout already processed, tx hash: {} nonce: {}",
+                location.tx_hash, payout.nonce
+            ));
+            return Ok(());
+        }
+
+        let updated = diesel::update(
+            withdrawals::table
+                .find(withdrawal.id)
+                .filter(withdrawals::status.eq(WithdrawalStatus::Signed.as_str())),
+        )
+        .set((
+            withdrawals::status.eq(WithdrawalStatus::Paid.as_str()),
+            withdrawals::tx_hash.eq(&location.tx_hash),
+            withdrawals::block_number.eq(location.block_number),
+            withdrawals::block_hash.eq(&location.block_hash),
+            withdrawals::paid_at.eq(diesel::dsl::now),
+        ))
+        .execute(connection)
+        .map_err(|e| format!("Failed to update withdrawal: {}", e))?;
+
+        if updated == 0 {
+            warn_json(json!({
+                "message": "Payout of a withdrawal that is not signed",
+                "chain_id": chain_identifier,
+                "nonce": payout.nonce,
+                "status": withdrawal.status,
+                "tx_hash": location.tx_hash,
+                "level": "warn"
+            }));
+        } else {
+            info(&format!(
+                "Withdrawal {} paid, tx hash: {}",
+                withdrawal.id, location.tx_hash
+            ));
+        }
+        Ok(())
+    }
+
+    /// Withdrawals paid out at or above `from_block`, the ones that can still be reorged out
+    pub fn get_paid_withdrawals_since(
+        &self,
+        from_block: i32,
+        connection: &mut PgConnection,
+        chain_identifier: i32,
+    ) -> Result<Vec<Withdrawal>, String> {
+        withdrawals::table
+            .filter(withdrawals::chain_id.eq(chain_identifier))
+            .filter(withdrawals::status.eq(WithdrawalStatus::Paid.as_str()))
+            .filter(withdrawals::block_number.ge(from_block))
+            .select(Withdrawal::as_select())
+            .load::<Withdrawal>(connection)
+            .map_err(|e| format!("Failed to query withdrawals: {}", e))
+    }
+
+    /// Moves a withdrawal whose payout was reorged out back to signed
+    ///
+    /// The signature stays valid, the payout is marked again when it is included in the new chain.
+    pub fn revert_payout(
+        &self,
+        withdrawal: &Withdrawal,
+        connection: &mut PgConnection,
+    ) -> Result<(), String> {
+        diesel::update(withdrawals::table.find(withdrawal.id))
+            .set((
+                withdrawals::status.eq(WithdrawalStatus::Signed.as_str()),
+                withdrawals::tx_hash.eq(None::<String>),
+                withdrawals::block_number.eq(None::<i32>),
+                withdrawals::block_hash.eq(None::<String>),
+                withdrawals::paid_at.eq(None::<chrono::NaiveDateTime>),
+            ))
+            .execute(connection)
+            .map_err(|e| format!("Failed to revert payout: {}", e))?;
+
+        warn_json(json!({
+            "message": "Reverted reorged payout",
+            "chain_id": withdrawal.chain_id,
+            "withdrawal_id": withdrawal.id,
+            "tx_hash": withdrawal.tx_hash,
+            "block_number": withdrawal.block_number,
+            "block_hash": withdrawal.block_hash,
+            "level": "warn"
+        }));
+        Ok(())
+    }
+
+    /// Deposit addresses that are assigned to a user
+    pub fn get_assigned_deposit_addresses(
+        &self,
+        connection: &mut PgConnection,
+    ) -> Result<Vec<DepositAddress>, String> {
+        deposit_addresses::table
+            .filter(deposit_addresses::user_id.is_not_null())
+            .select(DepositAddress::as_select())
+            .load::<DepositAddress>(connection)
+            .map_err(|e| format!("Failed to query deposit addresses: {}", e))
+    }
+
+    /// Number of unassigned addresses and the next derivation index of the pool
+    pub fn get_deposit_address_pool(
+        &self,
+        connection: &mut PgConnection,
+    ) -> Result<(i64, i32), String> {
+        let free = deposit_addresses::table
+            .filter(deposit_addresses::user_id.is_null())
+            .count()
+            .get_result::<i64>(connection)
+            .map_err(|e| format!("Failed to count deposit addresses: {}", e))?;
+        let last = deposit_addresses::table
+            .select(diesel::dsl::max(deposit_addresses::derivation_index))
+            .first::<Option<i32>>(connection)
+            .map_err(|e| format!("Failed to query deposit addresses: {}", e))?;
+        Ok((free, last.map_or(0, |index| index + 1)))
+    }
+
+    pub fn insert_deposit_addresses(
+        &self,
+        addresses: &Vec<DepositAddressCreate>,
+        connection: &mut PgConnection,
+    ) -> Result<usize, String> {
+        diesel::insert_into(deposit_addresses::table)
+            .values(addresses)
+            .on_conflict_do_nothing()
+            .execute(connection)
+            .map_err(|e| format!("Failed to insert deposit addresses: {}", e))
+    }
+
+    /// Moves the credit request of a deposit seen in a block that is not final yet to
+    /// `confirming`, with the number of blocks on top of it
+    ///
+    /// Returns whether the request was updated, requests already credited are left alone.
+    pub fn update_confirmations(
+        &self,
+        order_id: &str,
+        tx: &String,
+        confirmations: i32,
+        connection: &mut PgConnection,
+        chain_identifier: i32,
+    ) -> Result<bool, String> {
+        let request = parse_order_id(order_id)?;
+        let next = CreditRequestStatus::Confirming;
+        diesel::update(
+            credit_requests::table
+                .find(request)
+                .filter(credit_requests::chain_id.eq(chain_identifier))
+                .filter(credit_requests::request_status.eq_any(next.source_values())),
+        )
+        .set((
+            credit_requests::request_status.eq(next.as_str()),
+            credit_requests::confirmations.eq(Some(confirmations)),
+            credit_requests::tx_hash.eq(Some(tx)),
+        ))
+        .execute(connection)
+        .map(|rows| rows > 0)
+        .map_err(|e| format!("Failed to update confirmations: {}", e))
+    }
+
+    /// Moves the requests left unpaid past their `expires_at` to `expired`, returning their number
+    pub fn expire_credit_requests(&self, connection: &mut PgConnection) -> Result<usize, String> {
+        let next = CreditRequestStatus::Expired;
+        diesel::update(
+            credit_requests::table
+                .filter(credit_requests::request_status.eq_any(next.source_values()))
+                .filter(credit_requests::expires_at.lt(diesel::dsl::now)),
+        )
+        .set(credit_requests::request_status.eq(next.as_str()))
+        .execute(connection)
+        .map_err(|e| format!("Failed to expire credit requests: {}", e))
+    }
+
+    pub fn update_finalised_block_number(
         &self,
         number: i32,
         hash: String,
         connection: &mut PgConnection,
         chain_identifier: i32,
     ) -> Result<(), String> {
-        let row = diesel::update(indexer_block_numbers)
-            .filter(chain_id.eq(chain_identifier))
-            .set((block_number.eq(number), block_hash.eq(hash)))
-            .execute(connection);
+        let row = store_block_cursor(connection, chain_identifier, number, &hash);
 
         match row {
             Ok(row) => {
@@ -155,8 +832,97 @@ impl Utils {
         }
     }
 
+    /// Submissions included on chain whose fee was not read from chain yet, oldest first
+    pub fn get_unchecked_fees(
+        &self,
+        limit: i64,
+        connection: &mut PgConnection,
+    ) -> Result<Vec<(Uuid, String, i32, NaiveDateTime)>, String> {
+        customer_expenditures::table
+            .filter(customer_expenditures::fee_checked_at.is_null())
+            .filter(customer_expenditures::block_hash.is_not_null())
+            .filter(customer_expenditures::extrinsic_index.is_not_null())
+            .select((
+                customer_expenditures::id,
+                customer_expenditures::block_hash.assume_not_null(),
+                customer_expenditures::extrinsic_index.assume_not_null(),
+                customer_expenditures::created_at,
+            ))
+            .order(customer_expenditures::created_at)
+            .limit(limit)
+            .load::<(Uuid, String, i32, NaiveDateTime)>(connection)
+            .map_err(|e| format!("Failed to query unchecked fees: {}", e))
+    }
+
+    /// Stores the fee a submission paid on chain, `None` when it could not be found
+    pub fn store_chain_fee(
+        &self,
+        submission: Uuid,
+        fee: Option<BigDecimal>,
+        connection: &mut PgConnection,
+    ) -> Result<(), String> {
+        diesel::update(customer_expenditures::table.find(submission))
+            .set((
+                customer_expenditures::chain_fee.eq(fee),
+                customer_expenditures::fee_checked_at.eq(diesel::dsl::now),
+            ))
+            .execute(connection)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to store chain fee: {}", e))
+    }
+
+    /// Fee records of the submissions of `day` included on chain
+    pub fn get_fee_records(
+        &self,
+        day: NaiveDate,
+        connection: &mut PgConnection,
+    ) -> Result<Vec<FeeRecord>, String> {
+        let start = day.and_hms_opt(0, 0, 0).unwrap();
+        let end = day.succ_opt().unwrap_or(day).and_hms_opt(0, 0, 0).unwrap();
+        customer_expenditures::table
+            .filter(customer_expenditures::created_at.ge(start))
+            .filter(customer_expenditures::created_at.lt(end))
+            .filter(customer_expenditures::block_hash.is_not_null())
+            .select(FeeRecord::as_select())
+            .load::<FeeRecord>(connection)
+            .map_err(|e| format!("Failed to query fee records: {}", e))
+    }
+
+    /// Stores the reconciliations of a day, replacing those computed before
+    pub fn store_fee_reconciliations(
+        &self,
+        reconciliations: &Vec<FeeReconciliation>,
+        connection: &mut PgConnection,
+    ) -> Result<(), String> {
+        connection
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                for reconciliation in reconciliations {
+                    diesel::insert_into(fee_reconciliations::table)
+                        .values(reconciliation)
+                        .on_conflict((fee_reconciliations::day, fee_reconciliations::app_id))
+                        .do_update()
+                        .set(reconciliation)
+                        .execute(conn)?;
+                }
+                Ok(())
+            })
+            .map_err(|e| format!("Failed to store fee reconciliations: {}", e))
+    }
+
     pub fn establish_connection(&self) -> Result<PgConnection, String> {
         PgConnection::establish(&self.database_url)
             .map_err(|e| format!("Error connecting to {}: {}", self.database_url, e))
     }
 }
+
+fn store_block_cursor(
+    connection: &mut PgConnection,
+    chain_identifier: i32,
+    number: i32,
+    hash: &String,
+) -> QueryResult<usize> {
+    diesel::update(indexer_block_numbers)
+        .filter(chain_id.eq(chain_identifier))
+        .set((block_number.eq(number), block_hash.eq(hash)))
+        .execute(connection)
+}
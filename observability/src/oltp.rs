@@ -1,6 +1,7 @@
 use fmt::Layer;
 use opentelemetry::{
     global,
+    metrics::ObservableGauge,
     trace::{SamplingDecision, SamplingResult, TraceContextExt},
     KeyValue, Value,
 };
@@ -10,7 +11,13 @@ use opentelemetry_sdk::{
     trace::{BatchConfigBuilder, Config, ShouldSample},
     Resource,
 };
-use std::{env, io::stdout, time::Duration};
+use std::{
+    collections::HashMap,
+    env,
+    io::stdout,
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
 use tracing::Level;
 use tracing_subscriber::{
     fmt::{
@@ -160,3 +167,43 @@ pub fn log_fallback_txn_error(submission_id: &str, reason: &str) {
     ];
     log("turboDA.fallback_txn_error".into(), Some(&attributes))
 }
+
+/// Last free balance of each signer in AVAIL, keyed by address
+type SignerBalances = Arc<Mutex<HashMap<String, f64>>>;
+
+/// Signer balances and the observable gauge reporting them
+static SIGNER_BALANCES: OnceLock<(SignerBalances, ObservableGauge<f64>)> = OnceLock::new();
+
+pub fn record_signer_balance(address: &str, balance: f64) {
+    let (balances, _) = SIGNER_BALANCES.get_or_init(|| {
+        let balances = SignerBalances::default();
+        let observed = Arc::clone(&balances);
+        let gauge = global::meter("turbo_da")
+            .f64_observable_gauge("turboDA.signer_balance")
+            .with_callback(move |observer| {
+                for (address, balance) in observed.lock().unwrap().iter() {
+                    observer.observe(
+                        *balance,
+                        &[KeyValue::new(
+                            "address",
+                            Value::String(address.clone().into()),
+                        )],
+                    );
+                }
+            })
+            .init();
+        (balances, gauge)
+    });
+    balances
+        .lock()
+        .unwrap()
+        .insert(address.to_string(), balance);
+}
+
+pub fn log_signer_low_balance(address: &str, status: &str) {
+    let attributes = [
+        KeyValue::new("address", Value::String(address.to_string().into())),
+        KeyValue::new("status", Value::String(status.to_string().into())),
+    ];
+    log("turboDA.signer_low_balance".into(), Some(&attributes))
+}
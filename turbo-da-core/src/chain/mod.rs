@@ -0,0 +1,237 @@
+/// Chains deposits are made and withdrawals paid out on
+/// - `ChainConfig` holds the RPC endpoint and the `TurboDAResolver` contract of an EVM chain
+/// - `verify_submitted_tx` and `verify_avail_deposit` check a transaction reported by a user
+///   against chain data, before its credit request is shown as `tx_submitted`, and read the
+///   deposit it made for the request
+#[cfg(test)]
+mod test;
+
+use crate::deposit::{
+    avail::{block_timestamp, find_treasury_deposit, DepositReference},
+    find_evm_deposit, parse_order_id,
+};
+use alloy::{
+    consensus::Transaction,
+    primitives::{Address, B256},
+    providers::{Provider, ProviderBuilder},
+    rpc::types::{BlockNumberOrTag, BlockTransactionsKind},
+};
+use avail_rust::{AccountId, Client as AvailClient, H256};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, env, str::FromStr};
+
+/// Chain identifier of Avail in `indexer_block_numbers` and `processed_deposits`
+pub const AVAIL_CHAIN_ID: i32 = 0;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChainConfig {
+    /// RPC endpoint transactions are verified against
+    #[serde(default)]
+    pub rpc_url: String,
+    /// Address of the `TurboDAResolver` contract deposits are made to
+    #[serde(default)]
+    pub contract_address: String,
+}
+
+/// Reads `CHAIN_<CHAIN_ID>_RPC_URL` and `CHAIN_<CHAIN_ID>_CONTRACT_ADDRESS` variables, keyed by
+/// chain id
+pub fn chains_from_env() -> HashMap<String, ChainConfig> {
+    let mut chains: HashMap<String, ChainConfig> = HashMap::new();
+    for (key, value) in env::vars() {
+        let Some(rest) = key.strip_prefix("CHAIN_") else {
+            continue;
+        };
+        if let Some(chain) = rest.strip_suffix("_RPC_URL") {
+            chains.entry(chain.to_string()).or_default().rpc_url = value;
+        } else if let Some(chain) = rest.strip_suffix("_CONTRACT_ADDRESS") {
+            chains
+                .entry(chain.to_string())
+                .or_default()
+                .contract_address = value;
+        }
+    }
+    chains
+}
+
+/// What the chain knows of a transaction reported by a user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmittedTx {
+    /// Neither in a block nor in the mempool of the node
+    NotFound,
+    /// Sent to another address than the contract
+    WrongContract,
+    /// Sent to the contract, waiting to be included
+    Pending,
+    /// Included and succeeded
+    Included,
+    /// Included and reverted
+    Reverted,
+}
+
+fn parse_address(address: &str) -> Result<Address, String> {
+    Address::from_str(address).map_err(|e| format!("Invalid address {}: {}", address, e))
+}
+
+/// Outcome of a transaction sent to `to`, `success` is `None` until it is included
+pub fn check_submitted_tx(
+    contract: &str,
+    to: Option<Address>,
+    success: Option<bool>,
+) -> Result<SubmittedTx, String> {
+    if to != Some(parse_address(contract)?) {
+        return Ok(SubmittedTx::WrongContract);
+    }
+    Ok(match success {
+        None => SubmittedTx::Pending,
+        Some(true) => SubmittedTx::Included,
+        Some(false) => SubmittedTx::Reverted,
+    })
+}
+
+/// Deposit made for a credit request, positioned the way the funds monitor records it in
+/// `processed_deposits`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainDeposit {
+    pub token_address: String,
+    /// Amount in the smallest unit of the token
+    pub amount: BigDecimal,
+    pub from: String,
+    pub tx_hash: String,
+    pub log_index: i32,
+    pub block_number: i32,
+    pub block_hash: String,
+    /// Time the block was produced at, quotes are checked for expiry against it
+    pub block_timestamp: NaiveDateTime,
+}
+
+pub fn parse_tx_hash(tx_hash: &str) -> Result<B256, String> {
+    B256::from_str(tx_hash).map_err(|e| format!("Invalid transaction hash {}: {}", tx_hash, e))
+}
+
+/// Looks a transaction up by its receipt, or in the mempool when it is not included yet
+///
+/// An included transaction comes with the `Deposit` event it emitted for the credit request
+/// `order_id`, if any.
+pub async fn verify_submitted_tx(
+    chain: &ChainConfig,
+    hash: B256,
+    order_id: i32,
+) -> Result<(SubmittedTx, Option<ChainDeposit>), String> {
+    let url = chain
+        .rpc_url
+        .parse()
+        .map_err(|e| format!("Invalid RPC url {}: {:?}", chain.rpc_url, e))?;
+    let provider = ProviderBuilder::new().on_http(url);
+
+    let receipt = provider
+        .get_transaction_receipt(hash)
+        .await
+        .map_err(|e| format!("Failed to fetch transaction receipt: {}", e))?;
+    if let Some(receipt) = receipt {
+        let submitted =
+            check_submitted_tx(&chain.contract_address, receipt.to, Some(receipt.status()))?;
+        if submitted != SubmittedTx::Included {
+            return Ok((submitted, None));
+        }
+        let Some((log, event)) =
+            find_evm_deposit(receipt.inner.logs(), &chain.contract_address, order_id)?
+        else {
+            return Ok((submitted, None));
+        };
+        let block_number = log.block_number.ok_or("Block number not found")?;
+        let block_timestamp = match log.block_timestamp {
+            Some(timestamp) => timestamp,
+            None => {
+                provider
+                    .get_block_by_number(
+                        BlockNumberOrTag::Number(block_number),
+                        BlockTransactionsKind::Hashes,
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to fetch block {}: {}", block_number, e))?
+                    .ok_or(format!("Block {} not found", block_number))?
+                    .header
+                    .timestamp
+            }
+        };
+        let deposit = ChainDeposit {
+            token_address: event.tokenAddress.to_string(),
+            amount: BigDecimal::from_str(&event.amount.to_string())
+                .map_err(|e| format!("Invalid amount {}: {}", event.amount, e))?,
+            from: event.from.to_string(),
+            tx_hash: hash.to_string(),
+            log_index: log.log_index.ok_or("Log index not found")? as i32,
+            block_number: block_number as i32,
+            block_hash: log.block_hash.ok_or("Block hash not found")?.to_string(),
+            block_timestamp: DateTime::from_timestamp(block_timestamp as i64, 0)
+                .ok_or(format!("Invalid block timestamp {}", block_timestamp))?
+                .naive_utc(),
+        };
+        return Ok((submitted, Some(deposit)));
+    }
+
+    let submitted = match provider
+        .get_transaction_by_hash(hash)
+        .await
+        .map_err(|e| format!("Failed to fetch transaction: {}", e))?
+    {
+        Some(tx) => check_submitted_tx(&chain.contract_address, tx.to(), None)?,
+        None => SubmittedTx::NotFound,
+    };
+    Ok((submitted, None))
+}
+
+/// Reads the deposit made to the Avail `treasury` for the credit request `order_id` by the
+/// extrinsic `tx_hash` of block `block_hash`
+///
+/// Returns `None` if the extrinsic is not found, failed, is not a deposit for the request, or
+/// its block is not the canonical block at its height.
+pub async fn verify_avail_deposit(
+    client: &AvailClient,
+    treasury: &str,
+    block_hash: H256,
+    tx_hash: H256,
+    order_id: i32,
+) -> Result<Option<ChainDeposit>, String> {
+    let treasury = AccountId::from_str(treasury)
+        .map_err(|e| format!("Invalid Avail deposit address: {:?}", e))?;
+    let Some(deposit) = find_treasury_deposit(client, block_hash, tx_hash, &treasury).await? else {
+        return Ok(None);
+    };
+    let Some(DepositReference::Order(reference)) = &deposit.transfer.reference else {
+        return Ok(None);
+    };
+    if parse_order_id(reference).ok() != Some(order_id) {
+        return Ok(None);
+    }
+    let block_number = client
+        .chain()
+        .block_height(block_hash)
+        .await
+        .map_err(|e| format!("Failed to fetch block height: {}", e))?
+        .ok_or("Block not found")?;
+    // The block of a fork is known to the node, but was never scanned by the funds monitor
+    let canonical = client
+        .chain()
+        .block_hash(Some(block_number))
+        .await
+        .map_err(|e| format!("Failed to fetch block hash: {}", e))?;
+    if canonical != Some(block_hash) {
+        return Ok(None);
+    }
+    let block_timestamp = block_timestamp(client, block_hash).await?;
+
+    // Recorded the way the Avail monitor records it
+    Ok(Some(ChainDeposit {
+        token_address: "0x0000000000000000000000000000000000000000".to_string(),
+        amount: BigDecimal::from(deposit.amount),
+        from: deposit.from,
+        tx_hash: hex::encode(deposit.tx_hash.0),
+        log_index: deposit.tx_index as i32,
+        block_number: block_number as i32,
+        block_hash: hex::encode(block_hash.0),
+        block_timestamp,
+    }))
+}
@@ -0,0 +1,44 @@
+use super::{check_submitted_tx, SubmittedTx};
+use alloy::primitives::Address;
+use std::str::FromStr;
+
+const CONTRACT: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+const OTHER: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+
+fn address(value: &str) -> Option<Address> {
+    Some(Address::from_str(value).unwrap())
+}
+
+#[test]
+fn test_transaction_to_the_contract() {
+    assert_eq!(
+        check_submitted_tx(CONTRACT, address(CONTRACT), Some(true)),
+        Ok(SubmittedTx::Included)
+    );
+    assert_eq!(
+        check_submitted_tx(CONTRACT, address(CONTRACT), Some(false)),
+        Ok(SubmittedTx::Reverted)
+    );
+    assert_eq!(
+        check_submitted_tx(CONTRACT, address(CONTRACT), None),
+        Ok(SubmittedTx::Pending)
+    );
+}
+
+#[test]
+fn test_transaction_to_another_address() {
+    assert_eq!(
+        check_submitted_tx(CONTRACT, address(OTHER), Some(true)),
+        Ok(SubmittedTx::WrongContract)
+    );
+    // Contract creations have no recipient
+    assert_eq!(
+        check_submitted_tx(CONTRACT, None, Some(true)),
+        Ok(SubmittedTx::WrongContract)
+    );
+}
+
+#[test]
+fn test_invalid_contract_is_an_error() {
+    assert!(check_submitted_tx("not an address", address(CONTRACT), Some(true)).is_err());
+}
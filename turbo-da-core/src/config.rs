@@ -1,10 +1,17 @@
-use crate::logger::{error, info, warn};
+use crate::{
+    chain::{chains_from_env, ChainConfig},
+    logger::{error, info, warn},
+    price_oracle::PriceOracleConfig,
+    withdrawal::WithdrawalConfig,
+};
 /// Configuration setup
 /// Checks presence of `config.toml`
 /// Else checks environment variables to populate Application Configurations
+use bigdecimal::BigDecimal;
 use dotenv::dotenv;
+use jsonwebtoken::Algorithm;
 use serde::{Deserialize, Serialize};
-use std::{env, error::Error, fs, io, vec::Vec};
+use std::{collections::HashMap, env, error::Error, fs, io, net::IpAddr, str::FromStr, vec::Vec};
 use toml;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -16,10 +23,51 @@ pub struct AppConfig {
     pub avail_rpc_endpoint: Vec<String>,
     pub coingecko_api_url: String,
     pub coingecko_api_key: String,
+    /// Caching and sanity checks of token prices, and the Chainlink fallback
+    #[serde(default)]
+    pub price_oracle: PriceOracleConfig,
+    /// Key the credit quotes are signed with, credit requests are not quoted when empty
+    #[serde(default)]
+    pub quote_signing_key: String,
+    /// Time a credit quote is honoured for
+    #[serde(default = "default_quote_validity_secs")]
+    pub quote_validity_secs: i64,
+    /// Operator key withdrawals are signed with
+    #[serde(default)]
+    pub withdrawal: WithdrawalConfig,
+    /// RPC endpoint and contract of the EVM chains, keyed by chain id
+    #[serde(default)]
+    pub chains: HashMap<String, ChainConfig>,
+    /// Time an unpaid credit request is kept open before it expires
+    #[serde(default = "default_credit_request_ttl_secs")]
+    pub credit_request_ttl_secs: i64,
+    /// Avail address deposits are transferred to, Avail transactions are not verified when empty
+    #[serde(default)]
+    pub avail_deposit_address: String,
     pub total_users_query_limit: i64,
     pub rate_limit_window_size: u64,
     pub rate_limit_max_requests: u64,
+    /// Identity provider validating user tokens, `clerk` or `oidc`
+    #[serde(default = "default_identity_provider")]
+    pub identity_provider: String,
+    #[serde(default)]
     pub clerk_secret_key: String,
+    /// JWKS endpoint of the OIDC provider
+    #[serde(default)]
+    pub oidc_jwks_url: String,
+    #[serde(default)]
+    pub oidc_issuer: Option<String>,
+    #[serde(default)]
+    pub oidc_audience: Option<String>,
+    /// Algorithm of OIDC signing keys published without an `alg`
+    #[serde(default)]
+    pub oidc_algorithm: Option<Algorithm>,
+    /// Claim holding the user id, nested claims are separated by dots
+    #[serde(default = "default_user_id_claim")]
+    pub user_id_claim: String,
+    /// Claim holding the role, or list of roles, of the user
+    #[serde(default = "default_roles_claim")]
+    pub roles_claim: String,
     pub aws_access_key_id: String,
     pub aws_endpoint_url: String,
     pub aws_region: String,
@@ -28,6 +76,50 @@ pub struct AppConfig {
     pub sumsub_app_token: String,
     pub sumsub_secret_key: String,
     pub sumsub_base_url: String,
+    /// `fund_user` grants taking the total granted to a user over
+    /// `fund_user_approval_window_secs` above this amount require the approval of a second admin
+    #[serde(default = "default_fund_user_approval_threshold")]
+    pub fund_user_approval_threshold: BigDecimal,
+    #[serde(default = "default_fund_user_approval_window_secs")]
+    pub fund_user_approval_window_secs: i64,
+    /// Time after which a pending approval request can no longer be approved
+    #[serde(default = "default_fund_user_approval_ttl_secs")]
+    pub fund_user_approval_ttl_secs: i64,
+    /// Proxies whose forwarded headers are trusted for the client IP address
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+fn default_fund_user_approval_threshold() -> BigDecimal {
+    BigDecimal::from(1000)
+}
+
+fn default_fund_user_approval_window_secs() -> i64 {
+    86400
+}
+
+fn default_fund_user_approval_ttl_secs() -> i64 {
+    86400
+}
+
+fn default_quote_validity_secs() -> i64 {
+    900
+}
+
+fn default_credit_request_ttl_secs() -> i64 {
+    86400
+}
+
+fn default_identity_provider() -> String {
+    "clerk".to_string()
+}
+
+fn default_user_id_claim() -> String {
+    "user_email".to_string()
+}
+
+fn default_roles_claim() -> String {
+    "role".to_string()
 }
 
 impl Default for AppConfig {
@@ -39,11 +131,25 @@ impl Default for AppConfig {
             max_pool_size: 10,
             coingecko_api_url: String::new(),
             coingecko_api_key: String::new(),
+            price_oracle: PriceOracleConfig::default(),
+            quote_signing_key: String::new(),
+            quote_validity_secs: default_quote_validity_secs(),
+            withdrawal: WithdrawalConfig::default(),
+            chains: HashMap::new(),
+            credit_request_ttl_secs: default_credit_request_ttl_secs(),
+            avail_deposit_address: String::new(),
             total_users_query_limit: 100,
             rate_limit_window_size: 60,
             rate_limit_max_requests: 100,
             avail_rpc_endpoint: vec![],
+            identity_provider: default_identity_provider(),
             clerk_secret_key: String::new(),
+            oidc_jwks_url: String::new(),
+            oidc_issuer: None,
+            oidc_audience: None,
+            oidc_algorithm: None,
+            user_id_claim: default_user_id_claim(),
+            roles_claim: default_roles_claim(),
             aws_access_key_id: String::new(),
             aws_endpoint_url: String::new(),
             aws_region: String::new(),
@@ -52,11 +158,20 @@ impl Default for AppConfig {
             sumsub_app_token: String::new(),
             sumsub_secret_key: String::new(),
             sumsub_base_url: String::new(),
+            fund_user_approval_threshold: default_fund_user_approval_threshold(),
+            fund_user_approval_window_secs: default_fund_user_approval_window_secs(),
+            fund_user_approval_ttl_secs: default_fund_user_approval_ttl_secs(),
+            trusted_proxies: vec![],
         }
     }
 }
 
 impl AppConfig {
+    /// Configuration of an EVM chain
+    pub fn chain(&self, chain_id: i32) -> Option<&ChainConfig> {
+        self.chains.get(&chain_id.to_string())
+    }
+
     pub fn load_config(&self) -> Result<AppConfig, std::io::Error> {
         dotenv().ok();
         if let Ok(config) = self.load_from_toml() {
@@ -129,7 +244,26 @@ impl AppConfig {
                 e.to_string()
             })?;
 
-        let clerk_secret_key = env::var("CLERK_SECRET_KEY")?;
+        let identity_provider =
+            env::var("IDENTITY_PROVIDER").unwrap_or_else(|_| default_identity_provider());
+        let (clerk_secret_key, oidc_jwks_url) = match identity_provider.as_str() {
+            "clerk" => (env::var("CLERK_SECRET_KEY")?, String::new()),
+            "oidc" => (String::new(), env::var("OIDC_JWKS_URL")?),
+            _ => {
+                return Err(format!("Unknown identity provider {}", identity_provider).into());
+            }
+        };
+        let oidc_issuer = env::var("OIDC_ISSUER").ok().filter(|s| !s.is_empty());
+        let oidc_audience = env::var("OIDC_AUDIENCE").ok().filter(|s| !s.is_empty());
+        let oidc_algorithm = match env::var("OIDC_ALGORITHM").ok().filter(|s| !s.is_empty()) {
+            Some(alg) => Some(
+                alg.parse::<Algorithm>()
+                    .map_err(|_| format!("Unknown OIDC algorithm {}", alg))?,
+            ),
+            None => None,
+        };
+        let user_id_claim = env::var("USER_ID_CLAIM").unwrap_or_else(|_| default_user_id_claim());
+        let roles_claim = env::var("ROLES_CLAIM").unwrap_or_else(|_| default_roles_claim());
         let total_users_query_limit = env::var("TOTAL_USERS_QUERY_LIMIT")
             .map_err(|e| {
                 error(&format!(
@@ -185,6 +319,24 @@ impl AppConfig {
         let sumsub_secret_key = env::var("SUMSUB_SECRET_KEY")?;
         let sumsub_base_url = env::var("SUMSUB_BASE_URL")?;
 
+        let fund_user_approval_threshold = env::var("FUND_USER_APPROVAL_THRESHOLD")
+            .ok()
+            .and_then(|s| BigDecimal::from_str(&s).ok())
+            .unwrap_or_else(default_fund_user_approval_threshold);
+        let fund_user_approval_window_secs = env::var("FUND_USER_APPROVAL_WINDOW_SECS")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .unwrap_or_else(default_fund_user_approval_window_secs);
+        let fund_user_approval_ttl_secs = env::var("FUND_USER_APPROVAL_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .unwrap_or_else(default_fund_user_approval_ttl_secs);
+        let trusted_proxies = env::var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|ip| ip.trim().parse::<IpAddr>().ok())
+            .collect();
+
         let mut avail_rpc_endpoint = Vec::new();
         let mut index = 1;
         while let Ok(endpoint) = env::var(format!("AVAIL_RPC_ENDPOINT_{}", index)) {
@@ -194,6 +346,19 @@ impl AppConfig {
 
         let coingecko_api_url = env::var("COINGECKO_API_URL")?;
         let coingecko_api_key = env::var("COINGECKO_API_KEY")?;
+        let price_oracle = PriceOracleConfig::from_env();
+        let quote_signing_key = env::var("QUOTE_SIGNING_KEY").unwrap_or_default();
+        let quote_validity_secs = env::var("QUOTE_VALIDITY_SECS")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .unwrap_or_else(default_quote_validity_secs);
+        let withdrawal = WithdrawalConfig::from_env();
+        let chains = chains_from_env();
+        let credit_request_ttl_secs = env::var("CREDIT_REQUEST_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .unwrap_or_else(default_credit_request_ttl_secs);
+        let avail_deposit_address = env::var("AVAIL_DEPOSIT_ADDRESS").unwrap_or_default();
 
         let aws_access_key_id = env::var("AWS_ACCESS_KEY_ID")?;
         let aws_endpoint_url = env::var("AWS_ENDPOINT_URL")?;
@@ -206,9 +371,23 @@ impl AppConfig {
             database_url,
             redis_url,
             max_pool_size,
+            identity_provider,
             clerk_secret_key,
+            oidc_jwks_url,
+            oidc_issuer,
+            oidc_audience,
+            oidc_algorithm,
+            user_id_claim,
+            roles_claim,
             coingecko_api_url,
             coingecko_api_key,
+            price_oracle,
+            quote_signing_key,
+            quote_validity_secs,
+            withdrawal,
+            chains,
+            credit_request_ttl_secs,
+            avail_deposit_address,
             total_users_query_limit,
             rate_limit_window_size,
             rate_limit_max_requests,
@@ -221,6 +400,10 @@ impl AppConfig {
             sumsub_app_token,
             sumsub_secret_key,
             sumsub_base_url,
+            fund_user_approval_threshold,
+            fund_user_approval_window_secs,
+            fund_user_approval_ttl_secs,
+            trusted_proxies,
         })
     }
 }
@@ -0,0 +1,278 @@
+/// Two-person rule for sensitive admin actions
+/// An action queued by one admin is executed once a second admin approves it.
+use crate::{
+    config::AppConfig,
+    controllers::fund::FundUserParams,
+    identity::admin::{AdminPermission, RequirePermission},
+    utils::{audited, get_connection, retrieve_user_id_from_jwt},
+};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use db::{
+    controllers::{
+        admin_approvals::{
+            approve_fund_user, get_admin_approval, get_admin_approvals, reject_admin_approval,
+        },
+        users::user_exists,
+    },
+    models::{
+        admin_approvals::ApprovalStatus,
+        audit_events::{AuditAction, AuditEventCreate},
+    },
+};
+use diesel_async::{
+    pooled_connection::deadpool::Pool, scoped_futures::ScopedFutureExt, AsyncPgConnection,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+/// Query parameters for retrieving approval requests
+#[derive(Deserialize, Serialize)]
+struct GetApprovalsParams {
+    status: Option<ApprovalStatus>,
+    limit: Option<i64>,
+}
+
+/// Retrieves approval requests raised by admins
+///
+/// # Route
+/// `GET /v1/admin/get_approvals?status={pending|approved|rejected}&limit={limit}`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `finance-grant`)
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Approvals retrieved successfully",
+///   "data": [
+///     {
+///       "id": "uuid-string",
+///       "action": "admin.fund_user",
+///       "payload": { "user_id": "user@example.com", "amount": "5000" },
+///       "requested_by": "admin@example.com",
+///       "status": "pending",
+///       "resolved_by": null,
+///       "created_at": "2023-01-01T12:00:00",
+///       "resolved_at": null
+///     }
+///   ]
+/// }
+/// ```
+#[get(
+    "/get_approvals",
+    wrap = "RequirePermission::new(AdminPermission::FinanceGrant)"
+)]
+pub async fn get_approvals(
+    params: web::Query<GetApprovalsParams>,
+    config: web::Data<AppConfig>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    match get_admin_approvals(
+        &mut connection,
+        &params.status,
+        params.limit.unwrap_or(config.total_users_query_limit),
+    )
+    .await
+    {
+        Ok(approvals) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Approvals retrieved successfully",
+            "data": approvals,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Request payload for resolving an approval request
+#[derive(Deserialize, Serialize)]
+pub struct ResolveApprovalParams {
+    pub id: Uuid,
+}
+
+/// Approves a pending request and executes the queued action
+///
+/// # Description
+/// The approving admin must differ from the admin who raised the request.
+///
+/// # Route
+/// `POST /v1/admin/approve_action`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `finance-grant`)
+///
+/// # Request Body
+/// ```json
+/// {
+///   "id": "uuid-string"
+/// }
+/// ```
+///
+/// # Returns
+/// * 200 OK with the result of the executed action
+/// * 404 Not Found if the request does not exist
+/// * 409 Conflict if the request is resolved or was raised by the same admin
+#[post(
+    "/approve_action",
+    wrap = "RequirePermission::new(AdminPermission::FinanceGrant)"
+)]
+pub async fn approve_action(
+    payload: web::Json<ResolveApprovalParams>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    let admin = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let approval = match get_admin_approval(&mut connection, &payload.id).await {
+        Ok(approval) => approval,
+        Err(_) => {
+            return HttpResponse::NotFound().json(json!({
+                "state": "ERROR",
+                "error": "Approval not found",
+            }))
+        }
+    };
+
+    if approval.action != AuditAction::AdminFundUser.as_str() {
+        return HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": format!("Unsupported action {}", approval.action),
+        }));
+    }
+    let params = match serde_json::from_value::<FundUserParams>(approval.payload.clone()) {
+        Ok(params) => params,
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": format!("Invalid approval payload: {}", e),
+            }))
+        }
+    };
+    if !user_exists(&mut connection, &params.user_id)
+        .await
+        .is_ok_and(|exists| exists)
+    {
+        return HttpResponse::NotFound().json(json!({
+            "state": "ERROR",
+            "error": "User is not registered",
+        }));
+    }
+
+    match audited(
+        &mut connection,
+        &http_request,
+        |conn| {
+            approve_fund_user(conn, &approval.id, &admin, &params.user_id, &params.amount)
+                .scope_boxed()
+        },
+        |(approval, user)| {
+            Some(AuditEventCreate {
+                before: Some(json!({ "credit_balance": &user.credit_balance - &params.amount })),
+                after: Some(json!({
+                    "credit_balance": user.credit_balance,
+                    "amount": params.amount,
+                    "approval_id": approval.id,
+                    "requested_by": approval.requested_by,
+                })),
+                ..AuditEventCreate::new(&admin, AuditAction::AdminFundUser, &params.user_id)
+            })
+        },
+    )
+    .await
+    {
+        Ok((_, user)) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Funds Granted Successfully",
+            "data": user,
+        })),
+        Err(e) => HttpResponse::Conflict().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Rejects a pending request without executing it
+///
+/// # Route
+/// `POST /v1/admin/reject_action`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `finance-grant`)
+///
+/// # Request Body
+/// ```json
+/// {
+///   "id": "uuid-string"
+/// }
+/// ```
+#[post(
+    "/reject_action",
+    wrap = "RequirePermission::new(AdminPermission::FinanceGrant)"
+)]
+pub async fn reject_action(
+    payload: web::Json<ResolveApprovalParams>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    let admin = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    match audited(
+        &mut connection,
+        &http_request,
+        |conn| reject_admin_approval(conn, &payload.id, &admin).scope_boxed(),
+        |approval| {
+            Some(AuditEventCreate {
+                after: Some(json!(approval)),
+                ..AuditEventCreate::new(&admin, AuditAction::AdminApprovalReject, approval.id)
+            })
+        },
+    )
+    .await
+    {
+        Ok(approval) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Approval rejected",
+            "data": approval,
+        })),
+        Err(e) => HttpResponse::Conflict().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{
+    config::AppConfig,
+    identity::admin::{AdminPermission, RequirePermission},
+    utils::{authorize_organisation, get_connection, retrieve_user_id_from_jwt},
+};
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use db::{
+    controllers::{audit_events::get_audit_events, organisations::get_user_organisations},
+    models::organisations::OrgPermission,
+};
+use diesel_async::{pooled_connection::deadpool::Pool, AsyncPgConnection};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+/// Query parameters for retrieving the audit events of the user's organisations
+#[derive(Deserialize, Serialize)]
+struct GetAuditEventsParams {
+    org_id: Option<Uuid>,
+    action: Option<String>,
+    limit: Option<i64>,
+}
+
+/// Retrieves audit events for the organisations of the authenticated user
+///
+/// # Description
+/// Returns the audit trail of the organisations the user is a member of, newest first. Every
+/// event carries the actor, action, target, the state before and after the change and the IP
+/// address the request came from.
+///
+/// # Route
+/// `GET /v1/user/get_audit_events?org_id={org_id}&action={action}&limit={limit}`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+///
+/// # Query Parameters
+/// * `org_id` - Optional organisation to restrict events to
+/// * `action` - Optional action to filter by, e.g. `api_key.create`
+/// * `limit` - Optional limit on the number of events returned
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Audit events retrieved successfully",
+///   "data": [
+///     {
+///       "id": "uuid-string",
+///       "actor": "user@example.com",
+///       "action": "app.toggle_encryption",
+///       "target_type": "app",
+///       "target_id": "uuid-string",
+///       "org_id": "uuid-string",
+///       "before": { "encryption": false },
+///       "after": { "encryption": true },
+///       "ip_address": "203.0.113.7",
+///       "created_at": "2023-01-01T12:00:00"
+///     }
+///   ]
+/// }
+/// ```
+#[get("/get_audit_events")]
+pub async fn get_user_audit_events(
+    params: web::Query<GetAuditEventsParams>,
+    http_request: HttpRequest,
+    config: web::Data<AppConfig>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let orgs = if params.org_id.is_some() {
+        match authorize_organisation(
+            &mut connection,
+            &user,
+            &params.org_id,
+            OrgPermission::ViewApps,
+        )
+        .await
+        {
+            Ok(org) => vec![org.id],
+            Err(response) => return response,
+        }
+    } else {
+        match get_user_organisations(&mut connection, &user).await {
+            Ok(orgs) => orgs.into_iter().map(|(org, _)| org.id).collect(),
+            Err(e) => {
+                return HttpResponse::InternalServerError().json(json!({
+                    "state": "ERROR",
+                    "error": e,
+                }))
+            }
+        }
+    };
+
+    match get_audit_events(
+        &mut connection,
+        &Some(orgs),
+        &None,
+        &params.action,
+        params.limit.unwrap_or(config.total_users_query_limit),
+    )
+    .await
+    {
+        Ok(events) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Audit events retrieved successfully",
+            "data": events,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Query parameters for retrieving audit events across all users
+#[derive(Deserialize, Serialize)]
+struct GetAllAuditEventsParams {
+    actor: Option<String>,
+    org_id: Option<Uuid>,
+    action: Option<String>,
+    limit: Option<i64>,
+}
+
+/// Retrieves audit events across all users (admin only)
+///
+/// # Route
+/// `GET /v1/admin/get_all_audit_events?actor={actor}&org_id={org_id}&action={action}&limit={limit}`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires admin privileges)
+///
+/// # Query Parameters
+/// * `actor` - Optional user who performed the action
+/// * `org_id` - Optional organisation to restrict events to
+/// * `action` - Optional action to filter by, e.g. `admin.fund_user`
+/// * `limit` - Optional limit on the number of events returned
+#[get(
+    "/get_all_audit_events",
+    wrap = "RequirePermission::new(AdminPermission::SupportRead)"
+)]
+pub async fn get_all_audit_events(
+    params: web::Query<GetAllAuditEventsParams>,
+    config: web::Data<AppConfig>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    match get_audit_events(
+        &mut connection,
+        &params.org_id.map(|org| vec![org]),
+        &params.actor,
+        &params.action,
+        params.limit.unwrap_or(config.total_users_query_limit),
+    )
+    .await
+    {
+        Ok(events) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Audit events retrieved successfully",
+            "data": events,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
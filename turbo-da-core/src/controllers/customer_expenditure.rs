@@ -1,14 +1,23 @@
 use crate::{
     config::AppConfig,
-    utils::{get_connection, retrieve_user_id_from_jwt},
+    identity::admin::{AdminPermission, RequirePermission},
+    utils::{audited, authorize_app, authorized_apps, get_connection, retrieve_user_id_from_jwt},
 };
 use actix_web::{get, put, web, HttpRequest, HttpResponse, Responder};
 use chrono::{DateTime, Datelike, NaiveDateTime};
-use db::controllers::customer_expenditure::{
-    handle_get_all_expenditure, handle_get_expenditure_by_time_range, handle_get_wallet_usage,
-    handle_reset_retry_count,
+use db::{
+    controllers::customer_expenditure::{
+        handle_get_all_expenditure, handle_get_expenditure_by_time_range, handle_get_wallet_usage,
+        handle_reset_retry_count,
+    },
+    models::{
+        audit_events::{AuditAction, AuditEventCreate},
+        organisations::OrgPermission,
+    },
+};
+use diesel_async::{
+    pooled_connection::deadpool::Pool, scoped_futures::ScopedFutureExt, AsyncPgConnection,
 };
-use diesel_async::{pooled_connection::deadpool::Pool, AsyncPgConnection};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use uuid::Uuid;
@@ -18,6 +27,7 @@ use validator::Validate;
 #[derive(Deserialize, Serialize)]
 struct GetAllExpenditures {
     limit: Option<i64>,
+    org_id: Option<Uuid>,
 }
 
 /// Request payload for retrieving detailed token expenditure information
@@ -29,19 +39,20 @@ struct GetTokenExpenditure {
 /// Retrieves all expenditure records for an authenticated customer
 ///
 /// # Description
-/// This endpoint allows customers to view their transaction history and
-/// track how they've spent their credits on Avail data submissions.
+/// This endpoint allows customers to view the transaction history of the apps in their
+/// organisations and track how they've spent their credits on Avail data submissions.
 /// The results include transaction details such as data size, fees paid,
 /// and submission status.
 ///
 /// # Route
-/// `GET /v1/user/get_all_expenditure?limit={limit}`
+/// `GET /v1/user/get_all_expenditure?limit={limit}&org_id={org_id}`
 ///
 /// # Headers
 /// * `Authorization: Bearer <token>` - A Bearer token for authenticating the request
 ///
 /// # Query Parameters
 /// * `limit` - Optional parameter to limit the number of records returned
+/// * `org_id` - Optional parameter to restrict records to a single organisation
 ///
 /// # Returns
 /// * Success: JSON response with a list of expenditure records
@@ -100,7 +111,19 @@ pub async fn get_all_expenditure(
         None => config.total_users_query_limit,
     };
 
-    match handle_get_all_expenditure(&mut connection, user, final_limit).await {
+    let apps = match authorized_apps(
+        &mut connection,
+        &user,
+        &request_payload.org_id,
+        OrgPermission::ViewBilling,
+    )
+    .await
+    {
+        Ok(apps) => apps.into_iter().map(|app| app.id).collect(),
+        Err(response) => return response,
+    };
+
+    match handle_get_all_expenditure(&mut connection, &apps, final_limit).await {
         Ok(response) => HttpResponse::Ok().json(json!({"state": "SUCCESS", "message": "Expenditure retrieved successfully", "data": response})),
         Err(e) => HttpResponse::InternalServerError().json(json!({ "state": "ERROR", "error": e.to_string() })),
     }
@@ -132,7 +155,19 @@ pub async fn get_expenditure_by_time_range(
         Err(response) => return response,
     };
 
-    match handle_get_expenditure_by_time_range(&mut connection, &user, request_payload.start_date, request_payload.end_date, &request_payload.app_id).await {
+    let app = match authorize_app(
+        &mut connection,
+        &user,
+        &request_payload.app_id,
+        OrgPermission::ViewBilling,
+    )
+    .await
+    {
+        Ok(app) => app,
+        Err(response) => return response,
+    };
+
+    match handle_get_expenditure_by_time_range(&mut connection, request_payload.start_date, request_payload.end_date, &app.id).await {
         Ok(response) => HttpResponse::Ok().json(json!({"state": "SUCCESS", "message": "Expenditure retrieved successfully", "data": response})),
         Err(e) => HttpResponse::InternalServerError().json(json!({ "state": "ERROR", "error": e.to_string() })),
     }
@@ -150,6 +185,7 @@ struct ResetRetryCountParams {
 /// # Description
 /// This endpoint allows administrators to reset the retry count for all customer expenditures.
 /// This is useful when there is a need to reprocess all transactions that have failed due to temporary issues.
+/// Transactions marked failed, or waiting for their next retry, are retried on the next round.
 ///
 /// # Route
 /// `PUT /v1/user/reset_retry_count`
@@ -189,21 +225,56 @@ struct ResetRetryCountParams {
 ///   "message": "Retry count reset successfully"
 /// }
 /// ```
-#[put("/reset_retry_count")]
+#[put(
+    "/reset_retry_count",
+    wrap = "RequirePermission::new(AdminPermission::OpsRetry)"
+)]
 pub async fn reset_retry_count(
     payload: web::Json<ResetRetryCountParams>,
     injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
 ) -> impl Responder {
+    let admin = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
     let mut connection = match get_connection(&injected_dependency).await {
         Ok(conn) => conn,
         Err(response) => return response,
     };
 
-    match handle_reset_retry_count(
+    let target = payload
+        .expenditure_id
+        .or(payload.app_id)
+        .map_or("all".to_string(), |id| id.to_string());
+    match audited(
         &mut connection,
-        &payload.app_id,
-        &payload.retry_count,
-        &payload.expenditure_id,
+        &http_request,
+        |conn| {
+            handle_reset_retry_count(
+                conn,
+                &payload.app_id,
+                &payload.retry_count,
+                &payload.expenditure_id,
+            )
+            .scope_boxed()
+        },
+        |_| {
+            Some(AuditEventCreate {
+                after: Some(json!({
+                    "retry_count": payload.retry_count,
+                    "app_id": payload.app_id,
+                    "expenditure_id": payload.expenditure_id,
+                })),
+                ..AuditEventCreate::new(&admin, AuditAction::AdminResetRetryCount, target)
+            })
+        },
     )
     .await
     {
@@ -224,20 +295,24 @@ struct GetWalletUsageParams {
     pub start_date: i64,
     /// End date in UTC timestamp (seconds)
     pub end_date: i64,
+    /// Optional organisation to restrict usage to
+    pub org_id: Option<Uuid>,
 }
 
 /// Retrieves wallet usage statistics for a given time period
 ///
 /// # Description
 /// This endpoint returns monthly wallet usage statistics including fallback and credit usage
-/// for the specified application within the given date range.
+/// for the specified application within the given date range. Usage of submissions billed in
+/// USD is counted in micro USD.
 ///
 /// # Route
-/// `GET /v1/user/get_wallet_usage?start_date={start_date}&end_date={end_date}`
+/// `GET /v1/user/get_wallet_usage?start_date={start_date}&end_date={end_date}&org_id={org_id}`
 ///
 /// # Query Parameters
 /// * `start_date` - Start date as UTC timestamp in seconds
 /// * `end_date` - End date as UTC timestamp in seconds
+/// * `org_id` - Optional organisation to restrict usage to
 ///
 /// # Returns
 /// * Success: JSON response with monthly wallet usage statistics
@@ -301,7 +376,19 @@ pub async fn get_wallet_usage(
                 .json(json!({ "state": "ERROR", "error": "Invalid end date" }))
         }
     };
-    match handle_get_wallet_usage(&mut connection, &user, start_date, end_date).await {
+    let apps = match authorized_apps(
+        &mut connection,
+        &user,
+        &params.org_id,
+        OrgPermission::ViewBilling,
+    )
+    .await
+    {
+        Ok(apps) => apps.into_iter().map(|app| app.id).collect(),
+        Err(response) => return response,
+    };
+
+    match handle_get_wallet_usage(&mut connection, &apps, start_date, end_date).await {
         Ok(response) => {
             let wallet_usage: std::collections::HashMap<Uuid, Vec<(i128, i128)>> = response
                 .iter()
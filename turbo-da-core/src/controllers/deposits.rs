@@ -0,0 +1,397 @@
+/// Queue of deposits the funds monitor could not match to a credit request
+/// An admin attributes each of them to a user, crediting it like a matched deposit, or records
+/// that it was refunded to its sender.
+use crate::{
+    config::AppConfig,
+    identity::admin::{AdminPermission, RequirePermission},
+    price_oracle::PriceFeed,
+    utils::{audited, get_amount_to_be_credited, get_connection, retrieve_user_id_from_jwt},
+};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use db::{
+    controllers::{
+        fund::get_fund_status,
+        supported_tokens::get_supported_token,
+        unmatched_deposits::{
+            attribute_unmatched_deposit, get_unmatched_deposit, get_unmatched_deposits,
+            refund_unmatched_deposit, AttributeError,
+        },
+        users::get_user,
+    },
+    models::{
+        audit_events::{AuditAction, AuditEventCreate},
+        credit_requests::CreditRequestStatus,
+        unmatched_deposits::UnmatchedDepositStatus,
+    },
+};
+use diesel_async::{
+    pooled_connection::deadpool::Pool, scoped_futures::ScopedFutureExt, AsyncPgConnection,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Query parameters for retrieving unmatched deposits
+#[derive(Deserialize, Serialize)]
+struct GetUnmatchedDepositsParams {
+    status: Option<UnmatchedDepositStatus>,
+    limit: Option<i64>,
+}
+
+/// Retrieves deposits that could not be matched to a credit request
+///
+/// # Route
+/// `GET /v1/admin/get_unmatched_deposits?status={pending|attributed|refunded}&limit={limit}`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `support-read`)
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Unmatched deposits retrieved successfully",
+///   "data": [
+///     {
+///       "id": 1,
+///       "chain_id": 11155111,
+///       "tx_hash": "0x...",
+///       "log_index": 3,
+///       "block_number": 7000000,
+///       "block_hash": "0x...",
+///       "from_address": "0x...",
+///       "token_address": "0x...",
+///       "amount_paid": "1000000",
+///       "order_id": "0x2a",
+///       "reason": "Credit request not found",
+///       "status": "pending",
+///       "credit_request_id": null,
+///       "refund_tx_hash": null,
+///       "resolved_by": null,
+///       "created_at": "2023-01-01T12:00:00",
+///       "resolved_at": null
+///     }
+///   ]
+/// }
+/// ```
+#[get(
+    "/get_unmatched_deposits",
+    wrap = "RequirePermission::new(AdminPermission::SupportRead)"
+)]
+pub async fn get_unmatched_deposit_list(
+    params: web::Query<GetUnmatchedDepositsParams>,
+    config: web::Data<AppConfig>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    match get_unmatched_deposits(
+        &mut connection,
+        &params.status,
+        params.limit.unwrap_or(config.total_users_query_limit),
+    )
+    .await
+    {
+        Ok(deposits) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Unmatched deposits retrieved successfully",
+            "data": deposits,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Request payload for attributing an unmatched deposit
+#[derive(Deserialize, Serialize)]
+pub struct AttributeDepositParams {
+    pub id: i32,
+    /// User the deposit is credited to
+    pub user_id: String,
+    /// Credit request of the user the deposit pays for, a new one is opened when omitted
+    pub order_id: Option<i32>,
+}
+
+/// Credits an unmatched deposit to a user
+///
+/// # Description
+/// The amount credited is computed from the token and amount of the deposit at the current price,
+/// as for a deposit matched by the funds monitor. Deposits in a disabled token are priced as long as
+/// the token is registered.
+///
+/// # Route
+/// `POST /v1/admin/attribute_deposit`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `finance-grant`)
+///
+/// # Request Body
+/// ```json
+/// {
+///   "id": 1,
+///   "user_id": "user@example.com",
+///   "order_id": 42
+/// }
+/// ```
+///
+/// # Returns
+/// * 200 OK with the credited user
+/// * 404 Not Found if the deposit, the user or the credit request does not exist
+/// * 409 Conflict if the deposit is resolved or the credit request is already processed
+/// * 422 Unprocessable Entity if the token of the deposit is not registered
+#[post(
+    "/attribute_deposit",
+    wrap = "RequirePermission::new(AdminPermission::FinanceGrant)"
+)]
+pub async fn attribute_deposit(
+    payload: web::Json<AttributeDepositParams>,
+    config: web::Data<AppConfig>,
+    prices: web::Data<PriceFeed>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    let admin = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let deposit = match get_unmatched_deposit(&mut connection, payload.id).await {
+        Ok(deposit) => deposit,
+        Err(_) => {
+            return HttpResponse::NotFound().json(json!({
+                "state": "ERROR",
+                "error": "Deposit not found",
+            }))
+        }
+    };
+    if deposit.status != UnmatchedDepositStatus::Pending.as_str() {
+        return HttpResponse::Conflict().json(json!({
+            "state": "ERROR",
+            "error": "Deposit is not pending",
+        }));
+    }
+    let user = match get_user(&mut connection, &payload.user_id).await {
+        Ok(user) => user,
+        Err(_) => {
+            return HttpResponse::NotFound().json(json!({
+                "state": "ERROR",
+                "error": "User is not registered",
+            }))
+        }
+    };
+    if let Some(order_id) = payload.order_id {
+        let request =
+            match get_fund_status(payload.user_id.clone(), order_id, &mut connection).await {
+                Ok(requests) => requests.into_iter().next(),
+                Err(e) => {
+                    return HttpResponse::InternalServerError().json(json!({
+                        "state": "ERROR",
+                        "error": e,
+                    }))
+                }
+            };
+        match request {
+            None => {
+                return HttpResponse::NotFound().json(json!({
+                    "state": "ERROR",
+                    "error": "Credit request not found",
+                }))
+            }
+            Some(request) if request.request_status == CreditRequestStatus::Credited.as_str() => {
+                return HttpResponse::Conflict().json(json!({
+                    "state": "ERROR",
+                    "error": "Credit request is already processed",
+                }))
+            }
+            Some(_) => {}
+        }
+    }
+
+    let token = match get_supported_token(&mut connection, deposit.chain_id, &deposit.token_address)
+        .await
+    {
+        Ok(Some(token)) => token,
+        Ok(None) => {
+            return HttpResponse::UnprocessableEntity().json(json!({
+                "state": "ERROR",
+                "error": "Token is not registered",
+            }))
+        }
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": e,
+            }))
+        }
+    };
+
+    let unit = match user.balance_unit() {
+        Ok(unit) => unit,
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": e,
+            }))
+        }
+    };
+    let Some(avail_rpc_url) = config.avail_rpc_endpoint.first() else {
+        return HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": "No Avail RPC endpoint configured",
+        }));
+    };
+    let (amount, price) =
+        match get_amount_to_be_credited(&prices, avail_rpc_url, &token, &deposit.amount_paid, unit)
+            .await
+        {
+            Ok(amount) => amount,
+            Err(e) => {
+                return HttpResponse::InternalServerError().json(json!({
+                    "state": "ERROR",
+                    "error": e,
+                }))
+            }
+        };
+
+    match audited(
+        &mut connection,
+        &http_request,
+        |conn| {
+            attribute_unmatched_deposit(
+                conn,
+                deposit.id,
+                &admin,
+                &user,
+                payload.order_id,
+                &amount,
+                &price,
+            )
+            .scope_boxed()
+        },
+        |(deposit, user)| {
+            Some(AuditEventCreate {
+                before: Some(json!({ "credit_balance": &user.credit_balance - &amount })),
+                after: Some(json!({
+                    "credit_balance": user.credit_balance,
+                    "amount": amount,
+                    "user_id": payload.user_id,
+                    "credit_request_id": deposit.credit_request_id,
+                })),
+                ..AuditEventCreate::new(&admin, AuditAction::AdminDepositAttribute, deposit.id)
+            })
+        },
+    )
+    .await
+    {
+        Ok((_, user)) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Deposit attributed successfully",
+            "data": user,
+        })),
+        Err(e @ AttributeError::RequestNotFound(_)) => HttpResponse::NotFound().json(json!({
+            "state": "ERROR",
+            "error": e.to_string(),
+        })),
+        Err(e @ AttributeError::Database(_)) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e.to_string(),
+        })),
+        Err(e) => HttpResponse::Conflict().json(json!({
+            "state": "ERROR",
+            "error": e.to_string(),
+        })),
+    }
+}
+
+/// Request payload for marking an unmatched deposit as refunded
+#[derive(Deserialize, Serialize)]
+pub struct RefundDepositParams {
+    pub id: i32,
+    /// Transaction returning the funds to the sender
+    pub refund_tx_hash: Option<String>,
+}
+
+/// Marks an unmatched deposit as refunded to its sender
+///
+/// # Route
+/// `POST /v1/admin/refund_deposit`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `finance-grant`)
+///
+/// # Request Body
+/// ```json
+/// {
+///   "id": 1,
+///   "refund_tx_hash": "0x..."
+/// }
+/// ```
+///
+/// # Returns
+/// * 200 OK with the refunded deposit
+/// * 409 Conflict if the deposit does not exist, is resolved or was credited
+#[post(
+    "/refund_deposit",
+    wrap = "RequirePermission::new(AdminPermission::FinanceGrant)"
+)]
+pub async fn refund_deposit(
+    payload: web::Json<RefundDepositParams>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    let admin = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    match audited(
+        &mut connection,
+        &http_request,
+        |conn| {
+            refund_unmatched_deposit(conn, payload.id, &admin, &payload.refund_tx_hash)
+                .scope_boxed()
+        },
+        |deposit| {
+            Some(AuditEventCreate {
+                after: Some(json!(deposit)),
+                ..AuditEventCreate::new(&admin, AuditAction::AdminDepositRefund, deposit.id)
+            })
+        },
+    )
+    .await
+    {
+        Ok(deposit) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Deposit marked as refunded",
+            "data": deposit,
+        })),
+        Err(e) => HttpResponse::Conflict().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
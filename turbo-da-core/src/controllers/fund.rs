@@ -1,8 +1,16 @@
 use crate::{
+    chain::{
+        parse_tx_hash, verify_avail_deposit, verify_submitted_tx, ChainDeposit, SubmittedTx,
+        AVAIL_CHAIN_ID,
+    },
     config::AppConfig,
+    identity::admin::{AdminPermission, RequirePermission},
+    price_oracle::PriceFeed,
+    quote::{apply_quote, sign_quote, QuoteSubject},
     utils::{
-        generate_avail_sdk, get_amount_to_be_credited, get_connection, retrieve_user_id_from_jwt,
-        Convertor, TOKEN_MAP,
+        audited, authorize_organisation, generate_avail_sdk, get_amount_to_be_credited,
+        get_balance_unit, get_connection, get_credit_usd_value, get_enabled_token,
+        retrieve_user_id_from_jwt, token_map, Convertor,
     },
 };
 use actix_web::{
@@ -10,10 +18,35 @@ use actix_web::{
     web::{self, Bytes},
     HttpRequest, HttpResponse, Responder,
 };
-use avail_rust::constants::dev_accounts;
+use alloy::primitives::B256;
+use avail_rust::{constants::dev_accounts, Client as AvailClient, H256};
 use bigdecimal::BigDecimal;
-use db::controllers::fund::{create_credit_request, get_fund_status, update_inclusion_details};
-use diesel_async::{pooled_connection::deadpool::Pool, AsyncPgConnection};
+use db::{
+    controllers::{
+        admin_approvals::{grant_or_queue_fund_user, FundUserGrant},
+        deposit_addresses::assign_deposit_address,
+        fund::{
+            create_credit_request, credit_deposit, get_credit_request_quote, get_fund_status,
+            get_indexer_cursor, set_credit_request_quote, update_inclusion_details,
+        },
+        supported_tokens::get_supported_tokens,
+        users::convert_balances_to_usd,
+    },
+    models::{
+        admin_approvals::AdminApprovalCreate,
+        audit_events::{AuditAction, AuditEventCreate},
+        credit_requests::{
+            CreditQuote, CreditRequestQuote, CreditRequestStatus, CreditRequestsGet, DepositCredit,
+        },
+        organisations::OrgPermission,
+        processed_deposits::ProcessedDepositCreate,
+        supported_tokens::normalize_token_address,
+        user_model::BalanceUnit,
+    },
+};
+use diesel_async::{
+    pooled_connection::deadpool::Pool, scoped_futures::ScopedFutureExt, AsyncPgConnection,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::sync::Arc;
@@ -23,16 +56,29 @@ use uuid::Uuid;
 ///
 /// # Fields
 /// * `chain` - The chain ID for which the credit request is being registered
+/// * `org_id` - Optional organisation to credit. Defaults to the personal organisation of the user
+/// * `token_address` - Optional token the deposit will be made in, quoted together with `amount`
+/// * `amount` - Optional amount of the deposit, in the smallest unit of the token
 #[derive(Deserialize, Serialize, Clone)]
 struct RegisterCreditRequestParams {
     pub chain: i32,
+    pub org_id: Option<Uuid>,
+    pub token_address: Option<String>,
+    pub amount: Option<BigDecimal>,
 }
 
 /// Register a new credit request for a user
 ///
 /// # Description
 /// This endpoint allows a user to register a new credit request for a specific blockchain.
-/// The request is stored in the database with a "pending" status for later processing.
+/// The request is stored in the database with a "created" status for later processing, against the
+/// billing account of the organisation. Requires a role allowed to manage billing. A request that
+/// is not paid within `credit_request_ttl_secs` expires, a deposit made later is still credited.
+///
+/// When a token and amount are given, the credits they buy at the current price are locked in by a
+/// signed quote stored on the request, which then awaits payment. A deposit of the quoted token and amount credited before the
+/// quote expires is credited the quoted credits, any other deposit is credited at the price of the
+/// day and the difference to the quote is recorded.
 ///
 /// # Route
 /// `POST /v1/user/register_credit_request`
@@ -42,13 +88,44 @@ struct RegisterCreditRequestParams {
 ///
 /// # Request Body
 /// * `chain` - The chain ID for which the credit request is being registered
+/// * `org_id` - Optional organisation to credit
+/// * `token_address` - Optional token to quote
+/// * `amount` - Optional amount to quote
 ///
 /// # Returns
-/// * Success: JSON response with status "success" and the transaction data
+/// * Success: JSON response with status "success" and the transaction data, with its quote if any
+/// * 400 Bad Request if only one of `token_address` and `amount` is given, or the token is not
+///   supported
+/// * 503 Service Unavailable if a quote is requested but quotes are not configured
 /// * Error: Internal server error with appropriate error message
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Credit request created successfully",
+///   "data": {
+///     "id": 42,
+///     "user_id": "user@example.com",
+///     "chain_id": 1,
+///     "request_status": "awaiting_payment",
+///     "confirmations": null,
+///     "expires_at": "2023-01-02T12:00:00",
+///     "quote": {
+///       "token_address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+///       "amount": "1000000",
+///       "credits": "153.125",
+///       "expires_at": "2023-01-01T12:15:00",
+///       "signature": "hex-string"
+///     }
+///   }
+/// }
+/// ```
 #[post("/register_credit_request")]
 async fn register_credit_request(
     payload: web::Json<RegisterCreditRequestParams>,
+    config: web::Data<AppConfig>,
+    prices: web::Data<PriceFeed>,
     injected_dependency: web::Data<Pool<AsyncPgConnection>>,
     http_request: HttpRequest,
 ) -> impl Responder {
@@ -69,29 +146,145 @@ async fn register_credit_request(
         Err(response) => return response,
     };
 
-    // Create credit request in the database
-    let tx = create_credit_request(user, payload.0.chain, &mut connection).await;
-    match tx {
-        Ok(tx) => HttpResponse::Ok().json(json!({"state": "SUCCESS", "message": "Credit request created successfully", "data": tx})),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "state": "ERROR", "message": e})),
+    let org = match authorize_organisation(
+        &mut connection,
+        &user,
+        &payload.org_id,
+        OrgPermission::ManageBilling,
+    )
+    .await
+    {
+        Ok(org) => org,
+        Err(response) => return response,
+    };
+
+    let quoted = match (&payload.token_address, &payload.amount) {
+        (Some(token_address), Some(amount)) => {
+            Some((normalize_token_address(token_address), amount))
+        }
+        (None, None) => None,
+        _ => {
+            return HttpResponse::BadRequest().json(json!({
+                "state": "ERROR",
+                "error": "Both token_address and amount are required for a quote",
+            }))
+        }
+    };
+    if quoted.is_some() && config.quote_signing_key.is_empty() {
+        return HttpResponse::ServiceUnavailable().json(json!({
+            "state": "ERROR",
+            "error": "Quotes are not configured",
+        }));
     }
+
+    // Price the quote before registering the request, a failure leaves nothing behind
+    let quoted = match quoted {
+        Some((token_address, amount)) => {
+            let token =
+                match get_enabled_token(&mut connection, payload.chain, &token_address).await {
+                    Ok(token) => token,
+                    Err(response) => return response,
+                };
+            let unit = match get_balance_unit(&mut connection, &org.billing_id).await {
+                Ok(unit) => unit,
+                Err(response) => return response,
+            };
+            let Some(avail_rpc_url) = config.avail_rpc_endpoint.first() else {
+                return HttpResponse::InternalServerError().json(json!({
+                    "state": "ERROR",
+                    "error": "No Avail RPC endpoint configured",
+                }));
+            };
+            match get_amount_to_be_credited(&prices, avail_rpc_url, &token, amount, unit).await {
+                Ok((credits, _)) => Some((token_address, amount.clone(), credits)),
+                Err(e) => {
+                    return HttpResponse::InternalServerError()
+                        .json(json!({ "state": "ERROR", "message": e}))
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Create credit request in the database
+    let expiry = (chrono::Utc::now() + chrono::Duration::seconds(config.credit_request_ttl_secs))
+        .naive_utc();
+    let tx = match create_credit_request(org.billing_id, payload.0.chain, expiry, &mut connection)
+        .await
+    {
+        Ok(tx) => tx,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(json!({ "state": "ERROR", "message": e}))
+        }
+    };
+
+    let (tx, quote) = match quoted {
+        Some((token_address, amount, credits)) => {
+            let mut quote = CreditQuote {
+                token_address,
+                amount,
+                credits,
+                expires_at: (chrono::Utc::now()
+                    + chrono::Duration::seconds(config.quote_validity_secs))
+                .naive_utc(),
+                signature: String::new(),
+            };
+            let subject = QuoteSubject {
+                request_id: tx.id,
+                user_id: &tx.user_id,
+                chain_id: payload.chain,
+            };
+            let stored = match sign_quote(&config.quote_signing_key, &subject, &quote) {
+                Ok(signature) => {
+                    quote.signature = signature;
+                    set_credit_request_quote(tx.id, &quote, &mut connection).await
+                }
+                Err(e) => Err(e),
+            };
+            match stored {
+                Ok(tx) => (tx, Some(quote)),
+                Err(e) => {
+                    return HttpResponse::InternalServerError()
+                        .json(json!({ "state": "ERROR", "message": e}))
+                }
+            }
+        }
+        None => (tx, None),
+    };
+
+    let mut data = json!(tx);
+    data["quote"] = json!(quote);
+    HttpResponse::Ok().json(
+        json!({"state": "SUCCESS", "message": "Credit request created successfully", "data": data}),
+    )
 }
 /// Parameters for adding inclusion details to a transaction
 ///
 /// # Fields
 /// * `order_id` - The ID of the order to update
 /// * `tx_hash` - The transaction hash to associate with the order
+/// * `block_hash` - Block hash of the transaction, required on Avail
+/// * `org_id` - Optional organisation the order belongs to
 #[derive(Deserialize, Serialize, Clone)]
 struct AddInclusionDetailsParams {
     pub order_id: i32,
     pub tx_hash: String,
+    pub block_hash: Option<String>,
+    pub org_id: Option<Uuid>,
 }
 
 /// Add inclusion details to a transaction
 ///
 /// # Description
 /// This endpoint allows a user to add inclusion details (transaction hash) to an existing order.
-/// The details are updated in the database for the specified order.
+/// The transaction is looked up on the chain of the order: on EVM chains it must be sent to the
+/// contract of the chain, and may still be waiting in the mempool. Included transactions must make
+/// the deposit of the order, a `Deposit` event on EVM chains or a transfer to the deposit address
+/// on Avail. The order then moves to "tx_submitted", or to "failed" if the transaction reverted.
+///
+/// Deposits in blocks the funds monitor has already scanned past are credited right away, through
+/// the same path as the monitor, so that a deposit it missed is not lost.
 ///
 /// # Route
 /// `POST /v1/user/add_inclusion_details`
@@ -102,13 +295,22 @@ struct AddInclusionDetailsParams {
 /// # Request Body
 /// * `order_id` - The ID of the order to update
 /// * `tx_hash` - The transaction hash to associate with the order
+/// * `block_hash` - Block hash of the transaction, required on Avail
+/// * `org_id` - Optional organisation the order belongs to
 ///
 /// # Returns
 /// * Success: JSON response with status "success" and the updated transaction data
+/// * 400 Bad Request if the transaction is invalid, not found on chain, not sent to the contract,
+///   does not deposit to the order, or the chain of the order cannot be verified
+/// * 404 Not Found if the order does not exist
+/// * 409 Conflict if the order cannot take a transaction in its current state
+/// * 502 Bad Gateway if the chain cannot be reached
 /// * Error: Internal server error with appropriate error message
 #[post("/add_inclusion_details")]
 pub async fn add_inclusion_details(
     payload: web::Json<AddInclusionDetailsParams>,
+    config: web::Data<AppConfig>,
+    prices: web::Data<PriceFeed>,
     injected_dependency: web::Data<Pool<AsyncPgConnection>>,
     http_request: HttpRequest,
 ) -> impl Responder {
@@ -127,27 +329,304 @@ pub async fn add_inclusion_details(
         Err(response) => return response,
     };
 
-    let tx = update_inclusion_details(user, payload.0.order_id, payload.0.tx_hash, &mut connection)
-        .await;
+    let org = match authorize_organisation(
+        &mut connection,
+        &user,
+        &payload.org_id,
+        OrgPermission::ManageBilling,
+    )
+    .await
+    {
+        Ok(org) => org,
+        Err(response) => return response,
+    };
+
+    let hash = match parse_tx_hash(&payload.tx_hash) {
+        Ok(hash) => hash,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "state": "ERROR",
+                "error": e,
+            }))
+        }
+    };
+
+    let request =
+        match get_credit_request_quote(&org.billing_id, payload.order_id, &mut connection).await {
+            Ok(request) => request,
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .json(json!({ "state": "ERROR", "message": e}))
+            }
+        };
+    let Some(request) = request else {
+        return HttpResponse::NotFound().json(json!({
+            "state": "ERROR",
+            "error": "Credit request not found",
+        }));
+    };
+    if request.request_status == CreditRequestStatus::Credited.as_str() {
+        return HttpResponse::Conflict().json(json!({
+            "state": "ERROR",
+            "error": "Credit request is already credited",
+        }));
+    }
+    let Some(chain_id) = request.chain_id else {
+        return HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": "Transactions cannot be verified on the chain of this credit request",
+        }));
+    };
+
+    let verified = if chain_id == AVAIL_CHAIN_ID {
+        verify_avail_inclusion(&config, payload.block_hash.as_deref(), hash, request.id).await
+    } else {
+        verify_evm_inclusion(&config, chain_id, hash, request.id).await
+    };
+    let (next, deposit) = match verified {
+        Ok(verified) => verified,
+        Err(response) => return response,
+    };
+
+    if let Some(deposit) = &deposit {
+        match credit_missed_deposit(
+            &mut connection,
+            &config,
+            &prices,
+            &request,
+            chain_id,
+            deposit,
+        )
+        .await
+        {
+            Ok(Some(tx)) => {
+                return HttpResponse::Ok()
+                    .json(json!({"state": "SUCCESS", "message": "Deposit credited", "data": tx}))
+            }
+            Ok(None) => {}
+            Err(response) => return response,
+        }
+    }
+
+    let tx = update_inclusion_details(
+        org.billing_id,
+        payload.0.order_id,
+        payload.0.tx_hash,
+        next,
+        &mut connection,
+    )
+    .await;
 
     match tx {
-        Ok(tx) => HttpResponse::Ok().json(json!({"state": "SUCCESS", "message": "Inclusion details added successfully", "data": tx})),
+        Ok(Some(tx)) if next == CreditRequestStatus::Failed => HttpResponse::Ok().json(json!({"state": "SUCCESS", "message": "Transaction reverted, the credit request failed", "data": tx})),
+        Ok(Some(tx)) => HttpResponse::Ok().json(json!({"state": "SUCCESS", "message": "Inclusion details added successfully", "data": tx})),
+        Ok(None) => HttpResponse::Conflict().json(json!({
+            "state": "ERROR",
+            "error": format!("Credit request in state {} cannot take a transaction", request.request_status),
+        })),
         Err(e) => HttpResponse::InternalServerError().json(json!({ "state": "ERROR", "message": e})),
     }
 }
 
+fn not_a_deposit() -> HttpResponse {
+    HttpResponse::BadRequest().json(json!({
+        "state": "ERROR",
+        "error": "Transaction does not deposit to this credit request",
+    }))
+}
+
+/// Looks the transaction up on the EVM chain of the credit request
+async fn verify_evm_inclusion(
+    config: &AppConfig,
+    chain_id: i32,
+    hash: B256,
+    order_id: i32,
+) -> Result<(CreditRequestStatus, Option<ChainDeposit>), HttpResponse> {
+    let chain = match config.chain(chain_id) {
+        Some(chain) if !chain.rpc_url.is_empty() && !chain.contract_address.is_empty() => chain,
+        _ => {
+            return Err(HttpResponse::BadRequest().json(json!({
+                "state": "ERROR",
+                "error": "Transactions cannot be verified on the chain of this credit request",
+            })))
+        }
+    };
+
+    match verify_submitted_tx(chain, hash, order_id).await {
+        Ok((SubmittedTx::Pending, _)) => Ok((CreditRequestStatus::TxSubmitted, None)),
+        Ok((SubmittedTx::Included, Some(deposit))) => {
+            Ok((CreditRequestStatus::TxSubmitted, Some(deposit)))
+        }
+        Ok((SubmittedTx::Included, None)) => Err(not_a_deposit()),
+        Ok((SubmittedTx::Reverted, _)) => Ok((CreditRequestStatus::Failed, None)),
+        Ok((SubmittedTx::NotFound, _)) => Err(HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": "Transaction not found on chain",
+        }))),
+        Ok((SubmittedTx::WrongContract, _)) => Err(HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": "Transaction was not sent to the deposit contract",
+        }))),
+        Err(e) => Err(HttpResponse::BadGateway().json(json!({
+            "state": "ERROR",
+            "error": e,
+        }))),
+    }
+}
+
+/// Looks the extrinsic up in its block on Avail, which has no mempool lookup by hash
+async fn verify_avail_inclusion(
+    config: &AppConfig,
+    block_hash: Option<&str>,
+    hash: B256,
+    order_id: i32,
+) -> Result<(CreditRequestStatus, Option<ChainDeposit>), HttpResponse> {
+    if config.avail_deposit_address.is_empty() {
+        return Err(HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": "Transactions cannot be verified on the chain of this credit request",
+        })));
+    }
+    let Some(block_hash) = block_hash else {
+        return Err(HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": "Block hash is required for Avail transactions",
+        })));
+    };
+    let block_hash = parse_tx_hash(block_hash).map_err(|e| {
+        HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": e,
+        }))
+    })?;
+
+    let Some(avail_rpc_url) = config.avail_rpc_endpoint.first() else {
+        return Err(HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": "No Avail RPC endpoint configured",
+        })));
+    };
+    // A single attempt, the request fails rather than waiting for the endpoint to come back
+    let client = AvailClient::new(avail_rpc_url).await.map_err(|e| {
+        HttpResponse::BadGateway().json(json!({
+            "state": "ERROR",
+            "error": format!("Failed to connect to Avail: {:?}", e),
+        }))
+    })?;
+    match verify_avail_deposit(
+        &client,
+        &config.avail_deposit_address,
+        H256(block_hash.0),
+        H256(hash.0),
+        order_id,
+    )
+    .await
+    {
+        Ok(Some(deposit)) => Ok((CreditRequestStatus::TxSubmitted, Some(deposit))),
+        Ok(None) => Err(not_a_deposit()),
+        Err(e) => Err(HttpResponse::BadGateway().json(json!({
+            "state": "ERROR",
+            "error": e,
+        }))),
+    }
+}
+
+/// Credits a deposit in a block the funds monitor has already scanned past
+///
+/// Returns `None` when the block is not scanned yet, the deposit is then left to the monitor, or
+/// when the deposit was processed in the meantime.
+async fn credit_missed_deposit(
+    connection: &mut AsyncPgConnection,
+    config: &AppConfig,
+    prices: &PriceFeed,
+    request: &CreditRequestQuote,
+    chain_id: i32,
+    deposit: &ChainDeposit,
+) -> Result<Option<CreditRequestsGet>, HttpResponse> {
+    let internal_error = |e: String| {
+        HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        }))
+    };
+
+    let cursor = get_indexer_cursor(chain_id, connection)
+        .await
+        .map_err(internal_error)?;
+    if cursor.is_none_or(|cursor| deposit.block_number > cursor.block_number) {
+        return Ok(None);
+    }
+
+    let token_address = normalize_token_address(&deposit.token_address);
+    let token = get_enabled_token(connection, chain_id, &token_address).await?;
+    let Some(avail_rpc_url) = config.avail_rpc_endpoint.first() else {
+        return Err(internal_error(
+            "No Avail RPC endpoint configured".to_string(),
+        ));
+    };
+    let unit = get_balance_unit(connection, &request.user_id).await?;
+    let (credits, price) =
+        get_amount_to_be_credited(prices, avail_rpc_url, &token, &deposit.amount, unit)
+            .await
+            .map_err(internal_error)?;
+    let (amount_credit, quote) = apply_quote(
+        Some(config.quote_signing_key.as_str()).filter(|key| !key.is_empty()),
+        request,
+        chain_id,
+        &token_address,
+        &deposit.amount,
+        credits,
+        deposit.block_timestamp,
+    );
+
+    let processed = ProcessedDepositCreate {
+        chain_id,
+        tx_hash: deposit.tx_hash.clone(),
+        log_index: deposit.log_index,
+        block_number: deposit.block_number,
+        block_hash: deposit.block_hash.clone(),
+        credit_request_id: request.id,
+        amount_credit: amount_credit.clone(),
+        price,
+    };
+    let credit = DepositCredit {
+        amount_credit,
+        chain_id,
+        tx_hash: deposit.tx_hash.clone(),
+        token_address,
+        amount_paid: deposit.amount.clone(),
+        quote_outcome: quote
+            .as_ref()
+            .map(|(outcome, _)| outcome.as_str().to_string()),
+        quote_difference: quote.map(|(_, difference)| difference),
+    };
+    credit_deposit(&processed, &credit, unit, connection)
+        .await
+        .map_err(internal_error)
+}
+
+/// Query parameters for retrieving the fund list of an organisation
+#[derive(Deserialize, Serialize)]
+struct GetFundListParams {
+    /// Optional organisation. Defaults to the personal organisation of the user
+    org_id: Option<Uuid>,
+}
+
 /// Retrieve a list of all fund transactions for a user
 ///
 /// # Description
-/// This endpoint retrieves all fund transactions associated with the authenticated user.
+/// This endpoint retrieves all fund transactions on the billing account of the organisation.
 /// The transactions are fetched from the database and returned in a structured format.
 ///
 /// # Route
-/// `GET /v1/user/get_fund_list`
+/// `GET /v1/user/get_fund_list?org_id={org_id}`
 ///
 /// # Headers
 /// * `Authorization: Bearer <token>` - JWT token for authentication
 ///
+/// # Query Parameters
+/// * `org_id` - Optional organisation to retrieve fund transactions for
+///
 /// # Returns
 /// * 200 OK with a list of fund transactions if successful
 /// * 500 Internal Server Error if user authentication fails or database errors occur
@@ -163,7 +642,7 @@ pub async fn add_inclusion_details(
 ///       "user_id": "user@example.com",
 ///       "chain_id": 1,
 ///       "amount_credit": "100000000000000000000",
-///       "request_status": "completed",
+///       "request_status": "credited",
 ///       "request_type": "credit",
 ///       "tx_hash": "0x123abc456def789ghi",
 ///       "created_at": "2023-01-01T12:00:00Z"
@@ -173,6 +652,7 @@ pub async fn add_inclusion_details(
 /// ```
 #[get("/get_fund_list")]
 pub async fn get_fund_list(
+    params: web::Query<GetFundListParams>,
     injected_dependency: web::Data<Pool<AsyncPgConnection>>,
     http_request: HttpRequest,
 ) -> impl Responder {
@@ -190,7 +670,19 @@ pub async fn get_fund_list(
         Err(response) => return response,
     };
 
-    let tx = db::controllers::fund::get_fund_list(user, &mut connection).await;
+    let org = match authorize_organisation(
+        &mut connection,
+        &user,
+        &params.org_id,
+        OrgPermission::ViewBilling,
+    )
+    .await
+    {
+        Ok(org) => org,
+        Err(response) => return response,
+    };
+
+    let tx = db::controllers::fund::get_fund_list(org.billing_id, &mut connection).await;
     match tx {
         Ok(tx) => HttpResponse::Ok().json(
             json!({"state": "SUCCESS", "message": "Fund list retrieved successfully", "data": tx}),
@@ -201,6 +693,85 @@ pub async fn get_fund_list(
     }
 }
 
+/// Retrieve the Avail deposit address of an organisation
+///
+/// # Description
+/// Returns the Avail address dedicated to the billing account of the organisation, assigning one
+/// on first use. Any transfer to this address is credited to the billing account without a
+/// credit request or remark.
+///
+/// # Route
+/// `GET /v1/user/get_deposit_address?org_id={org_id}`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+///
+/// # Query Parameters
+/// * `org_id` - Optional organisation to retrieve the deposit address for
+///
+/// # Returns
+/// * 200 OK with the deposit address
+/// * 503 Service Unavailable if no deposit address can be assigned
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Deposit address retrieved successfully",
+///   "data": {
+///     "address": "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty",
+///     "assigned_at": "2023-01-01T12:00:00"
+///   }
+/// }
+/// ```
+#[get("/get_deposit_address")]
+pub async fn get_deposit_address(
+    params: web::Query<GetFundListParams>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let org = match authorize_organisation(
+        &mut connection,
+        &user,
+        &params.org_id,
+        OrgPermission::ManageBilling,
+    )
+    .await
+    {
+        Ok(org) => org,
+        Err(response) => return response,
+    };
+
+    match assign_deposit_address(&mut connection, &org.billing_id).await {
+        Ok(address) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Deposit address retrieved successfully",
+            "data": {
+                "address": address.address,
+                "assigned_at": address.assigned_at,
+            },
+        })),
+        Err(e) => HttpResponse::ServiceUnavailable().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
 /// Query parameters for retrieving fund requests with optional filters
 #[derive(Deserialize, Serialize)]
 struct GetAllFundRequestsParams {
@@ -250,7 +821,7 @@ struct GetAllFundRequestsParams {
 ///       "user_id": "user@example.com",
 ///       "chain_id": 1,
 ///       "amount_credit": "100000000000000000000",
-///       "request_status": "completed",
+///       "request_status": "credited",
 ///       "request_type": "credit",
 ///       "tx_hash": "0x123abc456def789ghi",
 ///       "created_at": "2023-01-01T12:00:00Z"
@@ -258,7 +829,10 @@ struct GetAllFundRequestsParams {
 ///   ]
 /// }
 /// ```
-#[get("/get_all_fund_requests")]
+#[get(
+    "/get_all_fund_requests",
+    wrap = "RequirePermission::new(AdminPermission::SupportRead)"
+)]
 pub async fn get_all_fund_requests(
     payload: web::Query<GetAllFundRequestsParams>,
     injected_dependency: web::Data<Pool<AsyncPgConnection>>,
@@ -310,13 +884,16 @@ pub struct FundUserParams {
 /// Fund a user's account with credits
 ///
 /// # Description
-/// This endpoint allows admins to add credits to a user's account
+/// This endpoint allows admins to add credits to a user's account. Grants taking the total
+/// granted to the user over `fund_user_approval_window_secs` above
+/// `fund_user_approval_threshold` are not executed, they are queued until a second admin
+/// approves them through `/v1/admin/approve_action`, within `fund_user_approval_ttl_secs`.
 ///
 /// # Route
 /// `POST /v1/admin/fund_user`
 ///
 /// # Headers
-/// * `Authorization: Bearer <token>` - JWT token for authentication (requires admin privileges)
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `finance-grant`)
 /// * `Content-Type: application/json`
 ///
 /// # Request Body
@@ -324,7 +901,8 @@ pub struct FundUserParams {
 /// * `amount` - The amount of credits to add
 ///
 /// # Returns
-/// JSON response indicating success or failure
+/// * 200 OK with the funded user
+/// * 202 Accepted with the approval request if the total granted is above the threshold
 ///
 /// # Example Response
 /// ```json
@@ -337,21 +915,79 @@ pub struct FundUserParams {
 ///   }
 /// }
 /// ```
-#[post("/fund_user")]
+#[post(
+    "/fund_user",
+    wrap = "RequirePermission::new(AdminPermission::FinanceGrant)"
+)]
 pub async fn fund_user(
     payload: web::Json<FundUserParams>,
     injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    config: web::Data<AppConfig>,
+    http_request: HttpRequest,
 ) -> impl Responder {
+    let admin = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
     let mut connection = match get_connection(&injected_dependency).await {
         Ok(conn) => conn,
         Err(response) => return response,
     };
 
-    let tx =
-        db::controllers::users::fund_user(&mut connection, &payload.user_id, &payload.amount).await;
+    let approval = AdminApprovalCreate {
+        id: Uuid::new_v4(),
+        action: AuditAction::AdminFundUser.as_str().to_string(),
+        payload: json!(payload.0),
+        requested_by: admin.clone(),
+        expires_at: (chrono::Utc::now()
+            + chrono::Duration::seconds(config.fund_user_approval_ttl_secs))
+        .naive_utc(),
+    };
+    let tx = audited(
+        &mut connection,
+        &http_request,
+        |conn| {
+            grant_or_queue_fund_user(
+                conn,
+                &payload.user_id,
+                &payload.amount,
+                &config.fund_user_approval_threshold,
+                config.fund_user_approval_window_secs,
+                &approval,
+            )
+            .scope_boxed()
+        },
+        |grant| match grant {
+            FundUserGrant::Funded(user) => Some(AuditEventCreate {
+                before: Some(json!({ "credit_balance": &user.credit_balance - &payload.amount })),
+                after: Some(json!({
+                    "credit_balance": user.credit_balance,
+                    "amount": payload.amount,
+                })),
+                ..AuditEventCreate::new(&admin, AuditAction::AdminFundUser, &payload.user_id)
+            }),
+            FundUserGrant::Queued(approval) => Some(AuditEventCreate {
+                after: Some(json!(approval)),
+                ..AuditEventCreate::new(&admin, AuditAction::AdminApprovalRequest, approval.id)
+            }),
+        },
+    )
+    .await;
     match tx {
-        Ok(tx) => HttpResponse::Ok()
-            .json(json!({"state": "SUCCESS", "message": "Funds Granted Successfully", "data": tx})),
+        Ok(FundUserGrant::Funded(user)) => HttpResponse::Ok().json(
+            json!({"state": "SUCCESS", "message": "Funds Granted Successfully", "data": user}),
+        ),
+        Ok(FundUserGrant::Queued(approval)) => HttpResponse::Accepted().json(json!({
+            "state": "SUCCESS",
+            "message": "Funding above the threshold requires approval of a second admin",
+            "data": approval,
+        })),
         Err(e) => {
             HttpResponse::InternalServerError().json(json!({ "state": "ERROR", "message": e}))
         }
@@ -361,6 +997,7 @@ pub async fn fund_user(
 #[derive(Deserialize, Serialize, Clone)]
 struct RequestFundsStatusParams {
     pub id: i32,
+    pub org_id: Option<Uuid>,
 }
 
 /// Retrieve the status and details of a user's fund request.
@@ -386,7 +1023,8 @@ struct RequestFundsStatusParams {
 ///   "data": [{
 ///     "amount_credit": "100000000000000000000", // scaled to 18 decimal places
 ///     "chain_id": 1,
-///     "request_status": "pending",
+///     "request_status": "confirming",
+///     "confirmations": 3,
 ///     "request_type": "credit",
 ///     "tx_hash": "0x123abc456def789ghi"
 ///   }]
@@ -413,7 +1051,19 @@ pub async fn request_funds_status(
         Err(response) => return response,
     };
 
-    let tx = get_fund_status(user, query.0.id, &mut connection).await;
+    let org = match authorize_organisation(
+        &mut connection,
+        &user,
+        &query.org_id,
+        OrgPermission::ViewBilling,
+    )
+    .await
+    {
+        Ok(org) => org,
+        Err(response) => return response,
+    };
+
+    let tx = get_fund_status(org.billing_id, query.0.id, &mut connection).await;
     match tx {
         Ok(tx) => HttpResponse::Ok().json(json!({"state": "SUCCESS", "message": "Fund request status retrieved successfully", "data": tx})),
         Err(e) => HttpResponse::InternalServerError().json(json!({ "state": "ERROR", "message": e})),
@@ -608,6 +1258,7 @@ pub struct EstimateCreditsToken {
     pub amount: BigDecimal,
     pub token_address: String,
     pub chain_id: u64,
+    pub unit: Option<BalanceUnit>,
 }
 
 /// Estimate the credits equivalent for a given token amount.
@@ -616,15 +1267,17 @@ pub struct EstimateCreditsToken {
 /// This endpoint calculates how many credits can be obtained for a specified amount of a particular token.
 ///
 /// # Route
-/// `GET /v1/user/estimate_credits_against_token?amount={amount}&token_address={address}&chain_id={chain_id}`
+/// `GET /v1/user/estimate_credits_against_token?amount={amount}&token_address={address}&chain_id={chain_id}&unit={unit}`
 ///
 /// # Query Parameters
 /// * `amount` - The amount of the token to convert to credits.
 /// * `token_address` - The blockchain address of the token to convert from.
 /// * `chain_id` - The blockchain ID of the token to convert from.
+/// * `unit` - Optional unit of the balance credited, `credits` (default) or `usd`.
 ///
 /// # Returns
-/// A JSON object containing the estimated credit equivalent for the specified token amount.
+/// A JSON object containing the estimated credit equivalent for the specified token amount, or 400 if
+/// the token is not supported.
 ///
 /// # Example Response
 ///
@@ -640,33 +1293,201 @@ pub struct EstimateCreditsToken {
 pub async fn estimate_credits_against_token(
     query: web::Query<EstimateCreditsToken>,
     config: web::Data<AppConfig>,
+    prices: web::Data<PriceFeed>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
 ) -> impl Responder {
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+    let token =
+        match get_enabled_token(&mut connection, query.chain_id as i32, &query.token_address).await
+        {
+            Ok(token) => token,
+            Err(response) => return response,
+        };
+
+    let Some(avail_rpc_url) = config.avail_rpc_endpoint.first() else {
+        return HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": "No Avail RPC endpoint configured",
+        }));
+    };
     let amount = get_amount_to_be_credited(
-        &config.coingecko_api_url,
-        &config.coingecko_api_key,
-        &config.avail_rpc_endpoint.first().unwrap(),
-        &query.0.chain_id,
-        &query.0.token_address,
+        &prices,
+        avail_rpc_url,
+        &token,
         &query.0.amount,
+        query.unit.unwrap_or(BalanceUnit::Credits),
     )
     .await;
 
     match amount {
-        Ok(amount) => HttpResponse::Ok().json(json!({"state": "SUCCESS", "message": "Credit cost calculated successfully", "data": amount})),
+        Ok((amount, _)) => HttpResponse::Ok().json(json!({"state": "SUCCESS", "message": "Credit cost calculated successfully", "data": amount})),
         Err(e) => HttpResponse::InternalServerError().json(json!({"state": "ERROR", "message": e})),
     }
 }
 
+/// Parameters for converting the balances of an organisation to USD
+///
+/// # Fields
+/// * `org_id` - Optional organisation. Defaults to the personal organisation of the user
+#[derive(Deserialize, Serialize)]
+struct ConvertBalanceParams {
+    pub org_id: Option<Uuid>,
+}
+
+/// Converts the credit balances of an organisation to USD
+///
+/// # Description
+/// Balances held in credits are converted once, at the USD value of a credit priced from the
+/// current AVAIL fee of 1 KB and the AVAIL/USD price. The balances of the billing account and of
+/// its apps are held in USD from then on: deposits are credited at their USD value and each
+/// submission is debited its AVAIL fee at the AVAIL/USD price of the time. Credits of past
+/// deposits and withdrawals are converted along, quotes of requests not credited yet are
+/// dropped. Requires a role allowed to manage billing.
+///
+/// # Route
+/// `POST /v1/user/convert_balance_to_usd`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+///
+/// # Request Body
+/// ```json
+/// {
+///   "org_id": "uuid-string"
+/// }
+/// ```
+///
+/// # Returns
+/// * 200 OK with the converted billing account
+/// * 409 Conflict if the balances are already held in USD
+/// * 500 Internal Server Error if the credit cannot be priced
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Balances converted to USD",
+///   "data": {
+///     "user": {
+///       "id": "user-id",
+///       "name": "user",
+///       "credit_balance": "12.34567891",
+///       "credit_used": "0.5",
+///       "allocated_credit_balance": "0",
+///       "sumsub_timestamp": null,
+///       "balance_unit": "usd"
+///     },
+///     "usd_per_credit": "0.0000120563"
+///   }
+/// }
+/// ```
+#[post("/convert_balance_to_usd")]
+pub async fn convert_balance_to_usd(
+    payload: web::Json<ConvertBalanceParams>,
+    config: web::Data<AppConfig>,
+    prices: web::Data<PriceFeed>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let org = match authorize_organisation(
+        &mut connection,
+        &user,
+        &payload.org_id,
+        OrgPermission::ManageBilling,
+    )
+    .await
+    {
+        Ok(org) => org,
+        Err(response) => return response,
+    };
+
+    let Some(avail_rpc_url) = config.avail_rpc_endpoint.first() else {
+        return HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": "No Avail RPC endpoint configured",
+        }));
+    };
+    let (usd_per_credit, avail_price) = match get_credit_usd_value(&prices, avail_rpc_url).await {
+        Ok(value) => value,
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": e,
+            }))
+        }
+    };
+
+    match audited(
+        &mut connection,
+        &http_request,
+        |conn| convert_balances_to_usd(conn, &org.billing_id, &usd_per_credit).scope_boxed(),
+        |(before, after)| {
+            Some(AuditEventCreate {
+                org_id: Some(org.id),
+                before: Some(json!({
+                    "credit_balance": before.credit_balance,
+                    "credit_used": before.credit_used,
+                    "balance_unit": before.balance_unit,
+                })),
+                after: Some(json!({
+                    "credit_balance": after.credit_balance,
+                    "credit_used": after.credit_used,
+                    "balance_unit": after.balance_unit,
+                    "usd_per_credit": usd_per_credit,
+                    "avail_usd_price": avail_price.usd,
+                    "price_source": avail_price.source,
+                })),
+                ..AuditEventCreate::new(&user, AuditAction::CreditsConvertToUsd, &org.billing_id)
+            })
+        },
+    )
+    .await
+    {
+        Ok((_, after)) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Balances converted to USD",
+            "data": {
+                "user": after,
+                "usd_per_credit": usd_per_credit,
+            },
+        })),
+        Err(e) => HttpResponse::Conflict().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
 /// Retrieve the list of supported tokens and their corresponding addresses.
 ///
 /// # Description
 /// This endpoint provides a list of supported tokens along with their addresses. It helps clients understand which tokens are available for interactions and their associated addresses on the blockchain.
 ///
+/// Tokens are read from the `supported_tokens` registry, disabled tokens are left out.
+///
 /// # Route
-/// `GET /v1/user/token_map`
+/// `GET /v1/token_map`
 ///
 /// # Returns
-/// A JSON object containing a mapping of token names to their addresses.
+/// A JSON object mapping chain ids to the tokens of the chain, keyed by their price feed id.
 ///
 /// # Example Response
 ///
@@ -678,31 +1499,30 @@ pub async fn estimate_credits_against_token(
 ///     "11155111": {
 ///       "ethereum": {
 ///         "token_address": "0xc...",
-///         "other_properties": "..."
+///         "token_decimals": 18,
+///         "symbol": "ETH"
 ///       },
 ///       "avail": {
 ///         "token_address": "0xd...",
-///         "other_properties": "..."
-///       }
-///     },
-///     "84532": {
-///       "ethereum": {
-///         "token_address": "0xc...",
-///         "other_properties": "..."
-///       },
-///       "avail": {
-///         "token_address": "0xd...",
-///         "other_properties": "..."
+///         "token_decimals": 18,
+///         "symbol": "AVAIL"
 ///       }
 ///     }
 ///   }
-///     }
-///   }
-///   }
 /// }
 /// ```
 
 #[get("/token_map")]
-pub async fn get_token_map() -> impl Responder {
-    HttpResponse::Ok().json(json!({"state": "SUCCESS", "message": "Token map retrieved successfully", "data": &*TOKEN_MAP}))
+pub async fn get_token_map(
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    match get_supported_tokens(&mut connection, false).await {
+        Ok(tokens) => HttpResponse::Ok().json(json!({"state": "SUCCESS", "message": "Token map retrieved successfully", "data": token_map(tokens)})),
+        Err(e) => HttpResponse::InternalServerError().json(json!({"state": "ERROR", "message": e})),
+    }
 }
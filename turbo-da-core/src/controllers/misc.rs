@@ -1,4 +1,7 @@
-use crate::utils::get_connection;
+use crate::{
+    identity::admin::{AdminPermission, RequirePermission},
+    utils::get_connection,
+};
 use actix_web::{
     get,
     web::{self},
@@ -39,7 +42,10 @@ use serde_json::json;
 ///   }
 /// }
 /// ```
-#[get("/indexer_status")]
+#[get(
+    "/indexer_status",
+    wrap = "RequirePermission::new(AdminPermission::SupportRead)"
+)]
 pub async fn indexer_status(
     injected_dependency: web::Data<Pool<AsyncPgConnection>>,
 ) -> impl Responder {
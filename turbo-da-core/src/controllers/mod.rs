@@ -1,9 +1,16 @@
 /// This entire module contains controllers which take data and do DB operations accordingly.
 /// Scope covers all listed tables: customer_expenditure, users, fund, token_balances ( excludes failed_transactions )
+pub mod approvals;
+pub mod audit;
 pub mod customer_expenditure;
+pub mod deposits;
 pub mod file;
 pub mod fund;
 pub mod kyc;
 pub mod misc;
+pub mod organisations;
+pub mod reconciliation;
 mod test;
+pub mod tokens;
 pub mod users;
+pub mod withdrawals;
@@ -0,0 +1,599 @@
+use crate::{
+    logger::info,
+    utils::{audited, authorize_organisation, get_connection, retrieve_user_id_from_jwt},
+};
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use db::{
+    controllers::{
+        organisations::{
+            add_organisation_member, create_organisation, get_organisation_members,
+            get_organisation_membership, get_user_organisations, remove_organisation_member,
+            update_organisation_member_role,
+        },
+        users::user_exists,
+    },
+    models::{
+        audit_events::{AuditAction, AuditEventCreate},
+        organisations::{
+            OrgPermission, OrgRole, Organisation, OrganisationCreate, OrganisationMemberCreate,
+        },
+    },
+};
+use diesel_async::{
+    pooled_connection::deadpool::Pool, scoped_futures::ScopedFutureExt, AsyncPgConnection,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Request payload for creating an organisation
+#[derive(Deserialize, Serialize, Validate)]
+pub struct CreateOrganisation {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+}
+
+/// Create a new organisation
+///
+/// # Description
+/// Creates an organisation owned by the authenticated user, along with a billing account of its
+/// own holding the balance its apps draw from. Funds of the owner or of their other organisations
+/// are never reachable from it.
+///
+/// # Route
+/// `POST /v1/user/create_organisation`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+///
+/// # Request Body
+/// ```json
+/// {
+///   "name": "Acme"
+/// }
+/// ```
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Organisation created successfully",
+///   "data": {
+///     "id": "uuid-string",
+///     "name": "Acme",
+///     "owner_id": "user@example.com",
+///     "created_at": "2023-01-01T12:00:00",
+///     "billing_id": "org:uuid-string"
+///   }
+/// }
+/// ```
+#[post("/create_organisation")]
+pub async fn create_new_organisation(
+    payload: web::Json<CreateOrganisation>,
+    http_request: HttpRequest,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    if let Err(errors) = payload.validate() {
+        return HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": errors,
+        }));
+    }
+
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let tx = create_organisation(
+        &mut connection,
+        &OrganisationCreate::new(payload.name.clone(), user),
+    )
+    .await;
+
+    match tx {
+        Ok(org) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Organisation created successfully",
+            "data": org,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Retrieve the organisations of the authenticated user
+///
+/// # Route
+/// `GET /v1/user/get_organisations`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Organisations retrieved successfully",
+///   "data": [
+///     {
+///       "organisation": {
+///         "id": "uuid-string",
+///         "name": "Acme",
+///         "owner_id": "owner@example.com",
+///         "created_at": "2023-01-01T12:00:00",
+///         "billing_id": "org:uuid-string"
+///       },
+///       "role": "developer"
+///     }
+///   ]
+/// }
+/// ```
+#[get("/get_organisations")]
+pub async fn get_organisations(
+    http_request: HttpRequest,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    match get_user_organisations(&mut connection, &user).await {
+        Ok(list) => {
+            let data: Vec<serde_json::Value> = list
+                .into_iter()
+                .map(|(organisation, member)| {
+                    json!({
+                        "organisation": organisation,
+                        "role": member.role,
+                    })
+                })
+                .collect();
+            HttpResponse::Ok().json(json!({
+                "state": "SUCCESS",
+                "message": "Organisations retrieved successfully",
+                "data": data,
+            }))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Query parameters for retrieving organisation members
+#[derive(Deserialize, Serialize)]
+pub struct GetOrganisationMembersParams {
+    pub org_id: Uuid,
+}
+
+/// Retrieve the members of an organisation
+///
+/// # Route
+/// `GET /v1/user/get_organisation_members?org_id={org_id}`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Organisation members retrieved successfully",
+///   "data": [
+///     {
+///       "org_id": "uuid-string",
+///       "user_id": "user@example.com",
+///       "role": "owner",
+///       "created_at": "2023-01-01T12:00:00"
+///     }
+///   ]
+/// }
+/// ```
+#[get("/get_organisation_members")]
+pub async fn get_members(
+    params: web::Query<GetOrganisationMembersParams>,
+    http_request: HttpRequest,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let org = match authorize_organisation(
+        &mut connection,
+        &user,
+        &Some(params.org_id),
+        OrgPermission::ViewApps,
+    )
+    .await
+    {
+        Ok(org) => org,
+        Err(response) => return response,
+    };
+
+    match get_organisation_members(&mut connection, &org.id).await {
+        Ok(members) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Organisation members retrieved successfully",
+            "data": members,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Request payload for adding a member or changing the role of a member
+#[derive(Deserialize, Serialize, Validate)]
+pub struct OrganisationMemberParams {
+    pub org_id: Uuid,
+    pub user_id: String,
+    /// One of `admin`, `developer` or `billing-viewer`
+    pub role: OrgRole,
+}
+
+/// Ownership is fixed to the billing account of the organisation and cannot be granted.
+fn check_assignable_role(role: &OrgRole) -> Result<(), HttpResponse> {
+    if *role == OrgRole::Owner {
+        return Err(HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": "The owner role cannot be assigned",
+        })));
+    }
+    Ok(())
+}
+
+fn check_not_owner(org: &Organisation, member: &String) -> Result<(), HttpResponse> {
+    if org.owner_id == *member {
+        return Err(HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": "The organisation owner cannot be changed or removed",
+        })));
+    }
+    Ok(())
+}
+
+/// Add a user to an organisation
+///
+/// # Description
+/// Adds an existing user to the organisation with the given role. Requires a role allowed to
+/// manage members.
+///
+/// # Route
+/// `POST /v1/user/add_organisation_member`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+///
+/// # Request Body
+/// ```json
+/// {
+///   "org_id": "uuid-string",
+///   "user_id": "teammate@example.com",
+///   "role": "developer"
+/// }
+/// ```
+///
+/// # Returns
+/// * 200 OK with the membership if the user was added
+/// * 400 Bad Request if the owner role is requested
+/// * 403 Forbidden if the role of the caller does not allow managing members
+/// * 404 Not Found if the user is not registered
+#[post("/add_organisation_member")]
+pub async fn add_member(
+    payload: web::Json<OrganisationMemberParams>,
+    http_request: HttpRequest,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    if let Err(response) = check_assignable_role(&payload.role) {
+        return response;
+    }
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let org = match authorize_organisation(
+        &mut connection,
+        &user,
+        &Some(payload.org_id),
+        OrgPermission::ManageMembers,
+    )
+    .await
+    {
+        Ok(org) => org,
+        Err(response) => return response,
+    };
+
+    if !user_exists(&mut connection, &payload.user_id)
+        .await
+        .is_ok_and(|exists| exists)
+    {
+        return HttpResponse::NotFound().json(json!({
+            "state": "ERROR",
+            "error": "User is not registered",
+        }));
+    }
+
+    let member = OrganisationMemberCreate {
+        org_id: org.id,
+        user_id: payload.user_id.clone(),
+        role: payload.role.to_string(),
+    };
+    let tx = audited(
+        &mut connection,
+        &http_request,
+        |conn| add_organisation_member(conn, &member).scope_boxed(),
+        |member| {
+            Some(AuditEventCreate {
+                org_id: Some(org.id),
+                after: Some(json!({ "role": member.role })),
+                ..AuditEventCreate::new(&user, AuditAction::MemberAdd, &member.user_id)
+            })
+        },
+    )
+    .await;
+
+    match tx {
+        Ok(member) => {
+            info(&format!(
+                "{} added {} to organisation {} as {}",
+                user, member.user_id, org.id, member.role
+            ));
+            HttpResponse::Ok().json(json!({
+                "state": "SUCCESS",
+                "message": "Member added successfully",
+                "data": member,
+            }))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Change the role of an organisation member
+///
+/// # Route
+/// `PUT /v1/user/update_organisation_member`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+///
+/// # Request Body
+/// ```json
+/// {
+///   "org_id": "uuid-string",
+///   "user_id": "teammate@example.com",
+///   "role": "admin"
+/// }
+/// ```
+#[put("/update_organisation_member")]
+pub async fn update_member(
+    payload: web::Json<OrganisationMemberParams>,
+    http_request: HttpRequest,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    if let Err(response) = check_assignable_role(&payload.role) {
+        return response;
+    }
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let org = match authorize_organisation(
+        &mut connection,
+        &user,
+        &Some(payload.org_id),
+        OrgPermission::ManageMembers,
+    )
+    .await
+    {
+        Ok(org) => org,
+        Err(response) => return response,
+    };
+
+    if let Err(response) = check_not_owner(&org, &payload.user_id) {
+        return response;
+    }
+
+    let previous_role = get_organisation_membership(&mut connection, &org.id, &payload.user_id)
+        .await
+        .ok()
+        .map(|(_, member)| member.role);
+
+    match audited(
+        &mut connection,
+        &http_request,
+        |conn| {
+            update_organisation_member_role(conn, &org.id, &payload.user_id, &payload.role)
+                .scope_boxed()
+        },
+        |member| {
+            Some(AuditEventCreate {
+                org_id: Some(org.id),
+                before: Some(json!({ "role": previous_role })),
+                after: Some(json!({ "role": member.role })),
+                ..AuditEventCreate::new(&user, AuditAction::MemberUpdate, &member.user_id)
+            })
+        },
+    )
+    .await
+    {
+        Ok(member) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Member updated successfully",
+            "data": member,
+        })),
+        Err(e) => HttpResponse::NotFound().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Request payload for removing an organisation member
+#[derive(Deserialize, Serialize, Validate)]
+pub struct RemoveOrganisationMember {
+    pub org_id: Uuid,
+    pub user_id: String,
+}
+
+/// Remove a member from an organisation
+///
+/// # Description
+/// Removes the member from the organisation. Members may always remove themselves, removing
+/// other members requires a role allowed to manage members. The owner cannot be removed.
+///
+/// # Route
+/// `DELETE /v1/user/remove_organisation_member`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+///
+/// # Request Body
+/// ```json
+/// {
+///   "org_id": "uuid-string",
+///   "user_id": "teammate@example.com"
+/// }
+/// ```
+#[delete("/remove_organisation_member")]
+pub async fn remove_member(
+    payload: web::Json<RemoveOrganisationMember>,
+    http_request: HttpRequest,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let permission = if payload.user_id == user {
+        OrgPermission::ViewApps
+    } else {
+        OrgPermission::ManageMembers
+    };
+    let org =
+        match authorize_organisation(&mut connection, &user, &Some(payload.org_id), permission)
+            .await
+        {
+            Ok(org) => org,
+            Err(response) => return response,
+        };
+
+    if let Err(response) = check_not_owner(&org, &payload.user_id) {
+        return response;
+    }
+
+    let previous_role = get_organisation_membership(&mut connection, &org.id, &payload.user_id)
+        .await
+        .ok()
+        .map(|(_, member)| member.role);
+
+    match audited(
+        &mut connection,
+        &http_request,
+        |conn| remove_organisation_member(conn, &org.id, &payload.user_id).scope_boxed(),
+        |removed| {
+            (*removed > 0).then(|| AuditEventCreate {
+                org_id: Some(org.id),
+                before: Some(json!({ "role": previous_role })),
+                ..AuditEventCreate::new(&user, AuditAction::MemberRemove, &payload.user_id)
+            })
+        },
+    )
+    .await
+    {
+        Ok(0) => HttpResponse::NotFound().json(json!({
+            "state": "ERROR",
+            "error": "Member not found",
+        })),
+        Ok(_) => {
+            info(&format!(
+                "{} removed {} from organisation {}",
+                user, payload.user_id, org.id
+            ));
+            HttpResponse::Ok().json(json!({
+                "state": "SUCCESS",
+                "message": "Member removed successfully",
+            }))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
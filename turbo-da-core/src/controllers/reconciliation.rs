@@ -0,0 +1,196 @@
+use crate::{
+    config::AppConfig,
+    identity::admin::{AdminPermission, RequirePermission},
+    utils::get_connection,
+};
+use actix_web::{get, web, HttpResponse, Responder};
+use chrono::{Duration, NaiveDate, Utc};
+use db::controllers::fee_reconciliations::{
+    get_deposits_received, get_fee_mismatches, get_fee_reconciliations,
+};
+use diesel_async::{pooled_connection::deadpool::Pool, AsyncPgConnection};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+/// Days covered when no `start_date` is given
+const DEFAULT_REPORT_DAYS: i64 = 30;
+
+/// Query parameters for the fee reconciliation report
+#[derive(Deserialize, Serialize)]
+struct FeeReconciliationParams {
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+    app_id: Option<Uuid>,
+    limit: Option<i64>,
+}
+
+impl FeeReconciliationParams {
+    /// Inclusive range of days to report on, the last 30 days by default
+    fn range(&self) -> Result<(NaiveDate, NaiveDate), String> {
+        let end = self.end_date.unwrap_or_else(|| Utc::now().date_naive());
+        let start = self
+            .start_date
+            .unwrap_or(end - Duration::days(DEFAULT_REPORT_DAYS - 1));
+        if start > end {
+            return Err("start_date must not be after end_date".to_string());
+        }
+        Ok((start, end))
+    }
+}
+
+/// Retrieves the daily reconciliation of the AVAIL spent on submissions against what was billed
+/// (admin only)
+///
+/// # Description
+/// Rows are built per app and day by the funds monitor from the fee each submission paid on
+/// chain. Spent, recorded and billed amounts are in the smallest unit of AVAIL, a negative
+/// `margin` means the app was billed less than its submissions cost. Deposits received over the
+/// same days are returned alongside, per balance unit.
+///
+/// # Route
+/// `GET /v1/admin/fee_reconciliation?start_date={start_date}&end_date={end_date}&app_id={app_id}`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires admin privileges)
+///
+/// # Query Parameters
+/// * `start_date` - Optional first day, `YYYY-MM-DD`, defaults to 30 days before `end_date`
+/// * `end_date` - Optional last day, `YYYY-MM-DD`, defaults to today
+/// * `app_id` - Optional app to restrict the report to
+///
+/// # Example Response
+/// ```json
+/// {
+///     "state": "SUCCESS",
+///     "message": "Fee reconciliation retrieved successfully",
+///     "data": {
+///         "reconciliations": [{
+///             "day": "2025-12-15",
+///             "app_id": "0a9b8e4e-6a4e-4f4e-9a53-2cb2f1c3d6a1",
+///             "submissions": 120,
+///             "unverified_submissions": 0,
+///             "mismatched_submissions": 2,
+///             "underbilled_submissions": 1,
+///             "avail_spent": "1240000000000000000",
+///             "avail_recorded": "1238000000000000000",
+///             "credits_billed": "10200",
+///             "usd_billed": "0",
+///             "avail_billed": "1300000000000000000",
+///             "margin": "60000000000000000",
+///             "updated_at": "2025-12-15T13:00:00"
+///         }],
+///         "deposits": [{
+///             "day": "2025-12-15",
+///             "unit": "credits",
+///             "deposits": 3,
+///             "amount": "25000"
+///         }]
+///     }
+/// }
+/// ```
+#[get(
+    "/fee_reconciliation",
+    wrap = "RequirePermission::new(AdminPermission::SupportRead)"
+)]
+pub async fn get_fee_reconciliation(
+    params: web::Query<FeeReconciliationParams>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let (start, end) = match params.range() {
+        Ok(range) => range,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "state": "ERROR",
+                "error": e,
+            }))
+        }
+    };
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let reconciliations =
+        match get_fee_reconciliations(&mut connection, start, end, &params.app_id).await {
+            Ok(reconciliations) => reconciliations,
+            Err(e) => {
+                return HttpResponse::InternalServerError().json(json!({
+                    "state": "ERROR",
+                    "error": e,
+                }))
+            }
+        };
+    match get_deposits_received(&mut connection, start, end).await {
+        Ok(deposits) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Fee reconciliation retrieved successfully",
+            "data": {
+                "reconciliations": reconciliations,
+                "deposits": deposits,
+            },
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Retrieves the submissions whose fee paid on chain differs from the recorded fee, or that were
+/// never billed (admin only)
+///
+/// # Route
+/// `GET /v1/admin/fee_mismatches?start_date={start_date}&end_date={end_date}&app_id={app_id}&limit={limit}`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires admin privileges)
+///
+/// # Query Parameters
+/// * `start_date` - Optional first day, `YYYY-MM-DD`, defaults to 30 days before `end_date`
+/// * `end_date` - Optional last day, `YYYY-MM-DD`, defaults to today
+/// * `app_id` - Optional app to restrict the submissions to
+/// * `limit` - Optional limit on the number of submissions returned
+#[get(
+    "/fee_mismatches",
+    wrap = "RequirePermission::new(AdminPermission::SupportRead)"
+)]
+pub async fn get_fee_mismatch_list(
+    params: web::Query<FeeReconciliationParams>,
+    config: web::Data<AppConfig>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let (start, end) = match params.range() {
+        Ok(range) => range,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "state": "ERROR",
+                "error": e,
+            }))
+        }
+    };
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    match get_fee_mismatches(
+        &mut connection,
+        start,
+        end,
+        &params.app_id,
+        params.limit.unwrap_or(config.total_users_query_limit),
+    )
+    .await
+    {
+        Ok(submissions) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Fee mismatches retrieved successfully",
+            "data": submissions,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
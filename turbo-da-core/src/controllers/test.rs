@@ -1,78 +1,33 @@
 #[cfg(test)]
 pub mod test {
     use crate::config::AppConfig;
-    use crate::controllers::users::{get_all_users, get_user, register_new_user, RegisterUser};
-    use actix_http::Request;
+    use crate::controllers::{
+        fund::{get_fund_list, request_funds_status},
+        users::{
+            allocate_credit, generate_app_account, get_all_users, get_user, register_new_user,
+            AllocateCreditBalance, RegisterAccount, RegisterUser,
+        },
+    };
+    use crate::identity::Identity;
+    use actix_http::{HttpMessage, Request};
     use actix_web::{dev::ServiceResponse, test, web, App};
-    use db::models::user_model::User;
-    use serde::Deserialize;
-
-    use std::env;
-
-    use diesel::{Connection, PgConnection};
-    use diesel_async::{
-        pooled_connection::{deadpool::Pool, AsyncDieselConnectionManager},
-        AsyncPgConnection,
+    use bigdecimal::BigDecimal;
+    use db::{
+        controllers::{
+            fund::create_credit_request,
+            organisations::{add_organisation_member, create_organisation, get_member_apps},
+            users::allocate_global_credit_balance,
+            withdrawals::create_withdrawal,
+        },
+        models::{
+            organisations::{OrgRole, OrganisationCreate, OrganisationMemberCreate},
+            user_model::{BalanceUnit, User, UserCreate},
+            withdrawals::WithdrawalCreate,
+        },
+        test_utils::TestDB,
     };
-    use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
-
-    const MIGRATIONS: EmbeddedMigrations = embed_migrations!("../db/migrations");
-    #[derive(Clone)]
-    pub struct DatabaseConnections {
-        pub postgres: Pool<AsyncPgConnection>,
-    }
-
-    impl DatabaseConnections {
-        pub fn postgres_pool(db_url: String) -> Pool<AsyncPgConnection> {
-            let config = AsyncDieselConnectionManager::new(db_url);
-            Pool::builder(config)
-                .max_size(8)
-                .build()
-                .expect("Failed to create pool")
-        }
-    }
-
-    pub struct TestDB {
-        db_url: String,
-        db_name: String,
-        pub dbc: DatabaseConnections,
-    }
-
-    impl TestDB {
-        pub fn init() -> Self {
-            let db_url = env::var("DATABASE_URL_TEST").expect("DATABASE_URL_TEST must be set");
-            let mut conn = PgConnection::establish(&db_url).expect("Can't connect to database");
-            let db_name = "test_".to_string() + &uuid::Uuid::new_v4().to_string().replace('-', "_");
-            let query = diesel::sql_query(format!("CREATE DATABASE {}", db_name).as_str());
-            diesel::RunQueryDsl::execute(query, &mut conn)
-                .expect(format!("Can't create test database {}", db_name).as_str());
-            let table_url = format!("{}/{}", &db_url, db_name);
-            let mut conn = PgConnection::establish(&table_url).expect("Can't connect to database");
-            conn.run_pending_migrations(MIGRATIONS)
-                .expect("Can't run migrations");
-
-            let dbc = DatabaseConnections {
-                postgres: DatabaseConnections::postgres_pool(table_url),
-            };
-
-            Self {
-                db_url,
-                db_name,
-                dbc,
-            }
-        }
-    }
-
-    impl Drop for TestDB {
-        fn drop(&mut self) {
-            self.dbc.postgres.close();
-            let mut conn =
-                PgConnection::establish(&self.db_url).expect("Can't connect to database");
-            let query = diesel::sql_query(&format!("DROP DATABASE {} WITH (FORCE)", &self.db_name));
-            diesel::RunQueryDsl::execute(query, &mut conn)
-                .expect(&format!("Can't drop test database {}", &self.db_name));
-        }
-    }
+    use serde::Deserialize;
+    use serde_json::Value;
 
     #[test]
     async fn test_user_registration_fails_without_injected_user_id() {
@@ -89,7 +44,7 @@ pub mod test {
         let app = test::init_service(
             App::new()
                 .app_data(web::Data::new(app_config.clone()))
-                .app_data(web::Data::new(db.dbc.postgres.clone()))
+                .app_data(web::Data::new(db.pool.clone()))
                 .service(register_new_user),
         )
         .await;
@@ -120,7 +75,7 @@ pub mod test {
         let app = test::init_service(
             App::new()
                 .app_data(web::Data::new(app_config.clone()))
-                .app_data(web::Data::new(db.dbc.postgres.clone()))
+                .app_data(web::Data::new(db.pool.clone()))
                 .service(register_new_user),
         )
         .await;
@@ -168,7 +123,7 @@ pub mod test {
         let app = test::init_service(
             App::new()
                 .app_data(web::Data::new(app_config.clone()))
-                .app_data(web::Data::new(db.dbc.postgres.clone()))
+                .app_data(web::Data::new(db.pool.clone()))
                 .service(register_new_user),
         )
         .await;
@@ -200,7 +155,7 @@ pub mod test {
         let app = test::init_service(
             App::new()
                 .app_data(web::Data::new(app_config.clone()))
-                .app_data(web::Data::new(db.dbc.postgres.clone()))
+                .app_data(web::Data::new(db.pool.clone()))
                 .service(register_new_user)
                 .service(get_user),
         )
@@ -243,7 +198,7 @@ pub mod test {
         let app = test::init_service(
             App::new()
                 .app_data(web::Data::new(app_config.clone()))
-                .app_data(web::Data::new(db.dbc.postgres.clone()))
+                .app_data(web::Data::new(db.pool.clone()))
                 .service(register_new_user)
                 .service(get_all_users),
         )
@@ -276,6 +231,150 @@ pub mod test {
         assert!(!user.results[0].id.is_empty());
     }
 
+    const OWNER: &str = "owner@example.com";
+    const MEMBER: &str = "member@example.com";
+
+    fn as_user(req: &mut Request, user: &str) {
+        req.extensions_mut().insert(Identity {
+            user_id: user.to_string(),
+            roles: vec![],
+        });
+    }
+
+    #[test]
+    async fn test_organisation_member_cannot_reach_funds_of_the_owner_or_other_organisations() {
+        let db = TestDB::init();
+        let mut connection = db.pool.get().await.unwrap();
+        for user in [OWNER, MEMBER] {
+            db::controllers::users::register_new_user(
+                &mut connection,
+                UserCreate {
+                    id: user.to_string(),
+                    name: user.to_string(),
+                    sumsub_timestamp: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+        let org_a = create_organisation(
+            &mut connection,
+            &OrganisationCreate::new("A".to_string(), OWNER.to_string()),
+        )
+        .await
+        .unwrap();
+        let org_b = create_organisation(
+            &mut connection,
+            &OrganisationCreate::new("B".to_string(), OWNER.to_string()),
+        )
+        .await
+        .unwrap();
+        add_organisation_member(
+            &mut connection,
+            &OrganisationMemberCreate {
+                org_id: org_b.id,
+                user_id: MEMBER.to_string(),
+                role: OrgRole::Admin.to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        let funded = [OWNER.to_string(), org_a.billing_id.clone()];
+        for account in &funded {
+            allocate_global_credit_balance(&mut connection, account, &BigDecimal::from(100))
+                .await
+                .unwrap();
+        }
+        let request = create_credit_request(
+            OWNER.to_string(),
+            1,
+            chrono::Utc::now().naive_utc(),
+            &mut connection,
+        )
+        .await
+        .unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(db.pool.clone()))
+                .service(generate_app_account)
+                .service(allocate_credit)
+                .service(get_fund_list)
+                .service(request_funds_status),
+        )
+        .await;
+
+        let mut req = test::TestRequest::post()
+            .uri("/generate_app_account")
+            .set_json(&RegisterAccount {
+                org_id: Some(org_b.id),
+                avail_app_id: None,
+                credit_selection: None,
+                app_name: None,
+                app_description: None,
+                app_logo: None,
+            })
+            .to_request();
+        as_user(&mut req, MEMBER);
+        assert_eq!(test::call_service(&app, req).await.status(), 200);
+        let (account, _) = get_member_apps(&mut connection, &MEMBER.to_string(), &Some(org_b.id))
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!(account.user_id, org_b.billing_id);
+
+        // Organisation B holds no credits of its own
+        let mut req = test::TestRequest::post()
+            .uri("/allocate_credit_balance")
+            .set_json(&AllocateCreditBalance {
+                amount: BigDecimal::from(10),
+                app_id: account.id,
+            })
+            .to_request();
+        as_user(&mut req, MEMBER);
+        assert_eq!(test::call_service(&app, req).await.status(), 500);
+
+        let mut req = test::TestRequest::get()
+            .uri(&format!("/get_fund_list?org_id={}", org_a.id))
+            .to_request();
+        as_user(&mut req, MEMBER);
+        assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+        for uri in [
+            format!("/get_fund_list?org_id={}", org_b.id),
+            format!("/request_fund_status?id={}&org_id={}", request.id, org_b.id),
+        ] {
+            let mut req = test::TestRequest::get().uri(&uri).to_request();
+            as_user(&mut req, MEMBER);
+            let body: Value = test::call_and_read_body_json(&app, req).await;
+            assert_eq!(body["data"], serde_json::json!([]));
+        }
+
+        let withdrawal = WithdrawalCreate {
+            user_id: org_b.billing_id.clone(),
+            chain_id: 1,
+            token_address: "0xcc".to_string(),
+            recipient: "0xbb".to_string(),
+            credits: BigDecimal::from(10),
+            amount: BigDecimal::from(10),
+            token_usd_price: BigDecimal::from(1),
+            avail_usd_price: BigDecimal::from(1),
+            price_source: "test".to_string(),
+            priced_at: chrono::Utc::now().naive_utc(),
+        };
+        assert!(matches!(
+            create_withdrawal(&mut connection, &withdrawal, BalanceUnit::Credits).await,
+            Err(e) if e == "Insufficient credit balance"
+        ));
+
+        for account in &funded {
+            let user = db::controllers::users::get_user(&mut connection, account)
+                .await
+                .unwrap();
+            assert_eq!(user.credit_balance, BigDecimal::from(100));
+        }
+    }
+
     fn insert_user_email(req: &mut Request) {
         let headers = req.headers_mut();
 
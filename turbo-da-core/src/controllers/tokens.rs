@@ -0,0 +1,389 @@
+/// Registry of the tokens deposits are accepted in
+/// Pricing, the token map and the funds monitor read the `supported_tokens` table. The contracts
+/// keep their own list of valid tokens, the funds monitor reports drift between the two.
+use crate::{
+    identity::admin::{AdminPermission, RequirePermission},
+    utils::{audited, get_connection, is_valid_token_address, retrieve_user_id_from_jwt},
+};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use db::{
+    controllers::supported_tokens::{
+        create_supported_token, delete_supported_token, get_supported_tokens,
+        update_supported_token,
+    },
+    models::{
+        audit_events::{AuditAction, AuditEventCreate},
+        supported_tokens::{normalize_token_address, SupportedTokenCreate, SupportedTokenUpdate},
+    },
+};
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel_async::{
+    pooled_connection::deadpool::Pool, scoped_futures::ScopedFutureExt, AsyncPgConnection,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use validator::Validate;
+
+/// Retrieves every registered token, including disabled tokens
+///
+/// # Route
+/// `GET /v1/admin/get_supported_tokens`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `support-read`)
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Supported tokens retrieved successfully",
+///   "data": [
+///     {
+///       "id": 1,
+///       "chain_id": 11155111,
+///       "token_address": "0x99a907545815c289fb6de86d55fe61d996063a94",
+///       "symbol": "AVAIL",
+///       "decimals": 18,
+///       "price_feed_id": "avail",
+///       "enabled": true,
+///       "created_at": "2023-01-01T12:00:00",
+///       "updated_at": "2023-01-01T12:00:00"
+///     }
+///   ]
+/// }
+/// ```
+#[get(
+    "/get_supported_tokens",
+    wrap = "RequirePermission::new(AdminPermission::SupportRead)"
+)]
+pub async fn get_supported_token_list(
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    match get_supported_tokens(&mut connection, true).await {
+        Ok(tokens) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Supported tokens retrieved successfully",
+            "data": tokens,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Request payload for registering a token
+#[derive(Deserialize, Serialize, Validate)]
+pub struct AddSupportedTokenParams {
+    pub chain_id: i32,
+    /// Address of the token, the zero address for the native token of the chain
+    #[validate(custom = "is_valid_token_address")]
+    pub token_address: String,
+    #[validate(length(min = 1, max = 32))]
+    pub symbol: String,
+    #[validate(range(min = 0, max = 77))]
+    pub decimals: i32,
+    /// CoinGecko id the price of the token is looked up with
+    #[validate(length(min = 1, max = 64))]
+    pub price_feed_id: String,
+    pub enabled: Option<bool>,
+}
+
+/// Registers a token deposits are accepted in
+///
+/// # Description
+/// The token should also be marked valid on the contract of the chain, the funds monitor reports
+/// tokens enabled on one side only.
+///
+/// # Route
+/// `POST /v1/admin/add_supported_token`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `finance-grant`)
+///
+/// # Request Body
+/// ```json
+/// {
+///   "chain_id": 8453,
+///   "token_address": "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913",
+///   "symbol": "USDC",
+///   "decimals": 6,
+///   "price_feed_id": "usd-coin",
+///   "enabled": true
+/// }
+/// ```
+///
+/// # Returns
+/// * 200 OK with the registered token
+/// * 400 Bad Request if a field is invalid
+/// * 409 Conflict if the token is already registered on the chain
+#[post(
+    "/add_supported_token",
+    wrap = "RequirePermission::new(AdminPermission::FinanceGrant)"
+)]
+pub async fn add_supported_token(
+    payload: web::Json<AddSupportedTokenParams>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    let admin = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+    if let Err(errors) = payload.validate() {
+        return HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": errors,
+        }));
+    }
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let payload = payload.into_inner();
+    let token = SupportedTokenCreate {
+        chain_id: payload.chain_id,
+        token_address: normalize_token_address(&payload.token_address),
+        symbol: payload.symbol,
+        decimals: payload.decimals,
+        price_feed_id: payload.price_feed_id,
+        enabled: payload.enabled.unwrap_or(true),
+    };
+    match audited(
+        &mut connection,
+        &http_request,
+        |conn| create_supported_token(conn, &token).scope_boxed(),
+        |token| {
+            Some(AuditEventCreate {
+                after: Some(json!(token)),
+                ..AuditEventCreate::new(&admin, AuditAction::AdminTokenCreate, token.id)
+            })
+        },
+    )
+    .await
+    {
+        Ok(token) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Token registered successfully",
+            "data": token,
+        })),
+        Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+            HttpResponse::Conflict().json(json!({
+                "state": "ERROR",
+                "error": "Token is already registered on this chain",
+            }))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e.to_string(),
+        })),
+    }
+}
+
+/// Request payload for updating a registered token
+#[derive(Deserialize, Serialize, Validate)]
+pub struct UpdateSupportedTokenParams {
+    pub id: i32,
+    #[validate(length(min = 1, max = 32))]
+    pub symbol: Option<String>,
+    #[validate(range(min = 0, max = 77))]
+    pub decimals: Option<i32>,
+    #[validate(length(min = 1, max = 64))]
+    pub price_feed_id: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// Updates a registered token, e.g. to disable deposits in it
+///
+/// # Description
+/// Omitted fields are left untouched. Deposits in a disabled token are recorded as unmatched by the
+/// funds monitor.
+///
+/// # Route
+/// `POST /v1/admin/update_supported_token`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `finance-grant`)
+///
+/// # Request Body
+/// ```json
+/// {
+///   "id": 1,
+///   "enabled": false
+/// }
+/// ```
+///
+/// # Returns
+/// * 200 OK with the updated token
+/// * 400 Bad Request if a field is invalid or no field is given
+/// * 404 Not Found if the token does not exist
+#[post(
+    "/update_supported_token",
+    wrap = "RequirePermission::new(AdminPermission::FinanceGrant)"
+)]
+pub async fn update_supported_token_details(
+    payload: web::Json<UpdateSupportedTokenParams>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    let admin = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+    if let Err(errors) = payload.validate() {
+        return HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": errors,
+        }));
+    }
+
+    let payload = payload.into_inner();
+    let changes = SupportedTokenUpdate {
+        symbol: payload.symbol,
+        decimals: payload.decimals,
+        price_feed_id: payload.price_feed_id,
+        enabled: payload.enabled,
+    };
+    if changes.symbol.is_none()
+        && changes.decimals.is_none()
+        && changes.price_feed_id.is_none()
+        && changes.enabled.is_none()
+    {
+        return HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": "No field to update",
+        }));
+    }
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    match audited(
+        &mut connection,
+        &http_request,
+        |conn| update_supported_token(conn, payload.id, &changes).scope_boxed(),
+        |(before, after)| {
+            Some(AuditEventCreate {
+                before: Some(json!(before)),
+                after: Some(json!(after)),
+                ..AuditEventCreate::new(&admin, AuditAction::AdminTokenUpdate, after.id)
+            })
+        },
+    )
+    .await
+    {
+        Ok((_, after)) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Token updated successfully",
+            "data": after,
+        })),
+        Err(DieselError::NotFound) => HttpResponse::NotFound().json(json!({
+            "state": "ERROR",
+            "error": "Token not found",
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e.to_string(),
+        })),
+    }
+}
+
+/// Request payload for removing a registered token
+#[derive(Deserialize, Serialize)]
+pub struct DeleteSupportedTokenParams {
+    pub id: i32,
+}
+
+/// Removes a token from the registry
+///
+/// # Description
+/// Deposits in the token are no longer priced, including unmatched deposits awaiting attribution.
+/// Disable the token instead to keep them priceable.
+///
+/// # Route
+/// `POST /v1/admin/delete_supported_token`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `finance-grant`)
+///
+/// # Request Body
+/// ```json
+/// {
+///   "id": 1
+/// }
+/// ```
+///
+/// # Returns
+/// * 200 OK with the removed token
+/// * 404 Not Found if the token does not exist
+#[post(
+    "/delete_supported_token",
+    wrap = "RequirePermission::new(AdminPermission::FinanceGrant)"
+)]
+pub async fn remove_supported_token(
+    payload: web::Json<DeleteSupportedTokenParams>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    let admin = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    match audited(
+        &mut connection,
+        &http_request,
+        |conn| delete_supported_token(conn, payload.id).scope_boxed(),
+        |token| {
+            Some(AuditEventCreate {
+                before: Some(json!(token)),
+                ..AuditEventCreate::new(&admin, AuditAction::AdminTokenDelete, token.id)
+            })
+        },
+    )
+    .await
+    {
+        Ok(token) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Token removed successfully",
+            "data": token,
+        })),
+        Err(DieselError::NotFound) => HttpResponse::NotFound().json(json!({
+            "state": "ERROR",
+            "error": "Token not found",
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e.to_string(),
+        })),
+    }
+}
@@ -3,7 +3,11 @@ use crate::logger::{error, info};
 /// Core dependencies for user management functionality
 use crate::{
     config::AppConfig,
-    utils::{get_connection, retrieve_user_id_from_jwt},
+    identity::admin::{AdminPermission, RequirePermission},
+    utils::{
+        audited, authorize_app, authorize_organisation, authorized_apps, get_connection,
+        retrieve_user_id_from_jwt,
+    },
 };
 /// Web framework dependencies for handling HTTP requests and responses
 use actix_web::{
@@ -18,10 +22,19 @@ use db::{
         apps::{create_account, delete_account_by_id},
         users::user_exists,
     },
-    models::{api::ApiKeyCreate, apps::AppsCreate, user_model::UserCreate},
+    models::{
+        api::ApiKeyCreate,
+        apps::AppsCreate,
+        audit_events::{AuditAction, AuditEventCreate},
+        organisations::OrgPermission,
+        signing_keys::SigningKeyCreate,
+        user_model::UserCreate,
+    },
 };
 /// Database and async connection handling
-use diesel_async::{pooled_connection::deadpool::Pool, AsyncPgConnection};
+use diesel_async::{
+    pooled_connection::deadpool::Pool, scoped_futures::ScopedFutureExt, AsyncPgConnection,
+};
 /// Redis caching functionality
 use redis::Commands;
 /// Serialization/deserialization
@@ -51,6 +64,8 @@ pub(crate) struct RegisterUser {
 /// Request payload for user registration
 #[derive(Deserialize, Serialize, Validate)]
 pub(crate) struct RegisterAccount {
+    /// Organisation to create the app in. Defaults to the personal organisation of the user
+    pub org_id: Option<Uuid>,
     pub avail_app_id: Option<i32>,
     pub credit_selection: Option<i16>,
     pub app_name: Option<String>,
@@ -116,7 +131,10 @@ pub(crate) struct UpdateAppID {
 ///   ]
 /// }
 /// ```
-#[get("/get_all_users")]
+#[get(
+    "/get_all_users",
+    wrap = "RequirePermission::new(AdminPermission::SupportRead)"
+)]
 pub async fn get_all_users(
     payload: web::Query<GetAllUsersParams>,
     injected_dependency: web::Data<Pool<AsyncPgConnection>>,
@@ -188,7 +206,10 @@ struct GetAllAppsParams {
 /// }
 /// ```
 
-#[get("/get_all_apps")]
+#[get(
+    "/get_all_apps",
+    wrap = "RequirePermission::new(AdminPermission::SupportRead)"
+)]
 pub async fn get_all_apps(
     payload: web::Query<GetAllAppsParams>,
     injected_dependency: web::Data<Pool<AsyncPgConnection>>,
@@ -378,8 +399,9 @@ pub async fn register_new_user(
 /// Generate an app account for a user
 ///
 /// # Description
-/// Creates a new account with fallback settings and optional app details. The user ID is extracted from
-/// the authentication token.
+/// Creates a new account with fallback settings and optional app details. The app is created in the
+/// given organisation, or in the personal organisation of the user extracted from the authentication
+/// token. Requires a role allowed to manage apps.
 ///
 /// # Route
 /// `POST /v1/user/generate_app_account`
@@ -390,6 +412,7 @@ pub async fn register_new_user(
 /// # Request Body
 /// ```json
 /// {
+///   "org_id": "optional-uuid",
 ///   "avail_app_id": 1001,
 ///   "fallback_enabled": true,
 ///   "app_name": "My Application",
@@ -401,6 +424,7 @@ pub async fn register_new_user(
 /// # Returns
 /// * 200 OK with account details if creation succeeds
 /// * 400 Bad Request if validation fails
+/// * 403 Forbidden if the role of the user does not allow managing apps
 /// * 406 Not Acceptable if account creation fails
 /// * 500 Internal Server Error if user info cannot be retrieved
 ///
@@ -451,11 +475,23 @@ pub async fn generate_app_account(
         }
     };
 
+    let org = match authorize_organisation(
+        &mut connection,
+        &user,
+        &payload.org_id,
+        OrgPermission::ManageApps,
+    )
+    .await
+    {
+        Ok(org) => org,
+        Err(response) => return response,
+    };
+
     let app_id = Uuid::new_v4();
     let avail_app_id = payload.avail_app_id.unwrap_or(0);
     let account = AppsCreate {
         id: app_id,
-        user_id: user,
+        user_id: org.billing_id,
         app_id: avail_app_id,
         credit_balance: BigDecimal::from(0),
         credit_used: BigDecimal::from(0),
@@ -463,9 +499,22 @@ pub async fn generate_app_account(
         app_name: payload.app_name.clone(),
         app_description: payload.app_description.clone(),
         app_logo: payload.app_logo.clone(),
+        org_id: org.id,
     };
 
-    let tx = create_account(&mut connection, &account).await;
+    let tx = audited(
+        &mut connection,
+        &http_request,
+        |conn| create_account(conn, &account).scope_boxed(),
+        |_| {
+            Some(AuditEventCreate {
+                org_id: Some(account.org_id),
+                after: Some(json!(account)),
+                ..AuditEventCreate::new(&user, AuditAction::AppCreate, account.id)
+            })
+        },
+    )
+    .await;
 
     match tx {
         Ok(_) => HttpResponse::Ok().json(json!({
@@ -534,22 +583,49 @@ pub async fn edit_app_account(
         }
     };
 
-    let app_result =
-        db::controllers::apps::get_app_by_id(&mut connection, &user, &payload.app_id).await;
-    if app_result.is_err() {
-        return HttpResponse::InternalServerError().json(json!({
-            "state": "ERROR",
-            "error": app_result.err().unwrap().to_string(),
-        }));
-    }
-    let mut account = app_result.unwrap();
+    let mut account = match authorize_app(
+        &mut connection,
+        &user,
+        &payload.app_id,
+        OrgPermission::ManageApps,
+    )
+    .await
+    {
+        Ok(app) => app,
+        Err(response) => return response,
+    };
+
+    let before = json!({
+        "app_name": account.app_name,
+        "app_description": account.app_description,
+        "app_logo": account.app_logo,
+        "credit_selection": account.credit_selection,
+    });
 
     account.app_name = payload.app_name.clone();
     account.app_description = payload.app_description.clone();
     account.app_logo = payload.app_logo.clone();
     account.credit_selection = payload.credit_selection.clone();
 
-    let tx = db::controllers::apps::update_app_account(&mut connection, &account).await;
+    let tx = audited(
+        &mut connection,
+        &http_request,
+        |conn| db::controllers::apps::update_app_account(conn, &account).scope_boxed(),
+        |_| {
+            Some(AuditEventCreate {
+                org_id: Some(account.org_id),
+                before: Some(before),
+                after: Some(json!({
+                    "app_name": account.app_name,
+                    "app_description": account.app_description,
+                    "app_logo": account.app_logo,
+                    "credit_selection": account.credit_selection,
+                })),
+                ..AuditEventCreate::new(&user, AuditAction::AppUpdate, account.id)
+            })
+        },
+    )
+    .await;
 
     match tx {
         Ok(_) => HttpResponse::Ok().json(json!({
@@ -562,17 +638,27 @@ pub async fn edit_app_account(
         })),
     }
 }
+/// Query parameters for retrieving apps
+#[derive(Deserialize, Serialize)]
+pub struct GetAppsParams {
+    /// Optional organisation to filter apps by
+    pub org_id: Option<Uuid>,
+}
+
 /// Retrieves all apps for the authenticated user
 ///
 /// # Description
-/// Gets a list of all applications associated with the authenticated user.
+/// Gets a list of all applications in the organisations the authenticated user is a member of.
 ///
 /// # Route
-/// `GET /v1/user/get_apps`
+/// `GET /v1/user/get_apps?org_id={org_id}`
 ///
 /// # Headers
 /// * `Authorization: Bearer <token>` - JWT token for authentication
 ///
+/// # Query Parameters
+/// * `org_id` - Optional organisation to filter apps by
+///
 /// # Returns
 /// JSON response containing the list of apps or an appropriate error message
 ///
@@ -588,7 +674,8 @@ pub async fn edit_app_account(
 ///       "app_id": 1001,
 ///       "credit_balance": "25.00",
 ///       "credit_used": "5.50",
-///       "fallback_enabled": true
+///       "fallback_enabled": true,
+///       "org_id": "uuid-string"
 ///     }
 ///   ]
 /// }
@@ -596,6 +683,7 @@ pub async fn edit_app_account(
 
 #[get("/get_apps")]
 pub async fn get_apps(
+    params: web::Query<GetAppsParams>,
     http_request: HttpRequest,
     injected_dependency: web::Data<Pool<AsyncPgConnection>>,
 ) -> impl Responder {
@@ -609,18 +697,20 @@ pub async fn get_apps(
         Err(response) => return response,
     };
 
-    let apps = db::controllers::apps::get_apps(&mut connection, &user).await;
-
-    match apps {
+    match authorized_apps(
+        &mut connection,
+        &user,
+        &params.org_id,
+        OrgPermission::ViewApps,
+    )
+    .await
+    {
         Ok(apps) => HttpResponse::Ok().json(json!({
             "state": "SUCCESS",
             "message": "Apps retrieved successfully",
             "data": apps,
         })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({
-            "state": "ERROR",
-            "error": e.to_string(),
-        })),
+        Err(response) => response,
     }
 }
 
@@ -632,7 +722,7 @@ pub struct DeleteAccount {
 /// Delete an account for the authenticated user
 ///
 /// # Description
-/// Deletes the specified account associated with the authenticated user.
+/// Deletes the specified account. Requires a role allowed to manage apps in the owning organisation.
 ///
 /// # Route
 /// `DELETE /v1/user/delete_account`
@@ -676,8 +766,34 @@ pub async fn delete_account(
         None => return HttpResponse::InternalServerError().body("User Id not retrieved"),
     };
 
+    let app = match authorize_app(
+        &mut connection,
+        &user,
+        &payload.app_id,
+        OrgPermission::ManageApps,
+    )
+    .await
+    {
+        Ok(app) => app,
+        Err(response) => return response,
+    };
+
+    let before = json!(app);
+
     // Delete the account
-    let tx = delete_account_by_id(&mut connection, user.clone(), payload.app_id).await;
+    let tx = audited(
+        &mut connection,
+        &http_request,
+        |conn| delete_account_by_id(conn, app.user_id, app.id).scope_boxed(),
+        |_| {
+            Some(AuditEventCreate {
+                org_id: Some(app.org_id),
+                before: Some(before),
+                ..AuditEventCreate::new(&user, AuditAction::AppDelete, app.id)
+            })
+        },
+    )
+    .await;
 
     match tx {
         Ok(_) => HttpResponse::Ok().json(json!({
@@ -700,7 +816,8 @@ pub struct AllocateCreditBalance {
 /// Allocate credit balance to a user account
 ///
 /// # Description
-/// Allocates the specified amount of credits to the user's account.
+/// Allocates the specified amount of credits from the organisation's billing account to the app.
+/// Requires a role allowed to manage billing.
 ///
 /// # Route
 /// `POST /v1/user/allocate_credit_balance`
@@ -744,11 +861,41 @@ pub async fn allocate_credit(
         None => return HttpResponse::InternalServerError().body("User Id not retrieved"),
     };
 
-    let tx = db::controllers::misc::allocate_credit_balance(
+    let app = match authorize_app(
         &mut connection,
-        &payload.app_id,
         &user,
-        &payload.amount,
+        &payload.app_id,
+        OrgPermission::ManageBilling,
+    )
+    .await
+    {
+        Ok(app) => app,
+        Err(response) => return response,
+    };
+
+    let tx = audited(
+        &mut connection,
+        &http_request,
+        |conn| {
+            db::controllers::misc::allocate_credit_balance(
+                conn,
+                &app.id,
+                &app.user_id,
+                &payload.amount,
+            )
+            .scope_boxed()
+        },
+        |_| {
+            Some(AuditEventCreate {
+                org_id: Some(app.org_id),
+                before: Some(json!({ "credit_balance": app.credit_balance })),
+                after: Some(json!({
+                    "credit_balance": &app.credit_balance + &payload.amount,
+                    "amount": payload.amount,
+                })),
+                ..AuditEventCreate::new(&user, AuditAction::CreditsAllocate, app.id)
+            })
+        },
     )
     .await;
 
@@ -786,11 +933,36 @@ pub async fn reclaim_credits(
         None => return HttpResponse::InternalServerError().body("User Id not retrieved"),
     };
 
-    let tx = db::controllers::misc::reclaim_credits(
+    let app = match authorize_app(
         &mut connection,
-        &payload.app_id,
         &user,
-        &payload.amount,
+        &payload.app_id,
+        OrgPermission::ManageBilling,
+    )
+    .await
+    {
+        Ok(app) => app,
+        Err(response) => return response,
+    };
+
+    let tx = audited(
+        &mut connection,
+        &http_request,
+        |conn| {
+            db::controllers::misc::reclaim_credits(conn, &app.id, &app.user_id, &payload.amount)
+                .scope_boxed()
+        },
+        |_| {
+            Some(AuditEventCreate {
+                org_id: Some(app.org_id),
+                before: Some(json!({ "credit_balance": app.credit_balance })),
+                after: Some(json!({
+                    "credit_balance": &app.credit_balance - &payload.amount,
+                    "amount": payload.amount,
+                })),
+                ..AuditEventCreate::new(&user, AuditAction::CreditsReclaim, app.id)
+            })
+        },
     )
     .await;
 
@@ -814,7 +986,8 @@ pub struct GenerateApiKey {
 /// Generate a new API key for the authenticated user
 ///
 /// # Description
-/// Creates a new API key associated with the specified account.
+/// Creates a new API key associated with the specified account. Requires a role allowed to manage
+/// keys in the owning organisation.
 ///
 /// # Route
 /// `POST /v1/user/generate_api_key`
@@ -862,16 +1035,38 @@ async fn generate_api_key(
         Err(response) => return response,
     };
 
+    let app = match authorize_app(
+        &mut connection,
+        &user,
+        &payload.app_id,
+        OrgPermission::ManageKeys,
+    )
+    .await
+    {
+        Ok(app) => app,
+        Err(response) => return response,
+    };
+
     let mut hasher = Keccak256::new();
     hasher.update(key.as_bytes());
     let hashed_password = hasher.finalize();
-    let tx = db::controllers::api_keys::create_api_key(
+    let identifier = key[key.len() - 5..].to_string();
+    let api_key = ApiKeyCreate {
+        api_key: hex::encode(hashed_password),
+        user_id: app.user_id,
+        identifier: identifier.clone(),
+        app_id: payload.app_id,
+    };
+    let tx = audited(
         &mut connection,
-        &ApiKeyCreate {
-            api_key: hex::encode(hashed_password),
-            user_id: user,
-            identifier: key[key.len() - 5..].to_string(),
-            app_id: payload.app_id,
+        &http_request,
+        |conn| db::controllers::api_keys::create_api_key(conn, &api_key).scope_boxed(),
+        |_| {
+            Some(AuditEventCreate {
+                org_id: Some(app.org_id),
+                after: Some(json!({ "app_id": app.id, "identifier": identifier })),
+                ..AuditEventCreate::new(&user, AuditAction::ApiKeyCreate, &identifier)
+            })
         },
     )
     .await;
@@ -894,7 +1089,7 @@ async fn generate_api_key(
 /// Retrieve all API keys for the authenticated user
 ///
 /// # Description
-/// Returns a list of all API keys associated with the authenticated user.
+/// Returns a list of all API keys of apps in the organisations the authenticated user is a member of.
 ///
 /// # Route
 /// `GET /v1/user/get_api_key`
@@ -943,7 +1138,12 @@ pub async fn get_api_keys(
         Err(response) => return response,
     };
 
-    let query = db::controllers::api_keys::get_api_keys(&mut connection, &user).await;
+    let apps = match authorized_apps(&mut connection, &user, &None, OrgPermission::ViewApps).await {
+        Ok(apps) => apps.into_iter().map(|app| app.id).collect(),
+        Err(response) => return response,
+    };
+
+    let query = db::controllers::api_keys::get_api_keys(&mut connection, &apps).await;
 
     match query {
         Ok(key) => HttpResponse::Ok().json(json!({
@@ -967,7 +1167,8 @@ pub struct DeleteApiKey {
 /// Delete an API key for the authenticated user
 ///
 /// # Description
-/// Removes the specified API key from both the database and Redis cache.
+/// Removes the specified API key from both the database and Redis cache. Only keys of apps the
+/// authenticated user is allowed to manage keys for are considered.
 ///
 /// # Route
 /// `DELETE /v1/user/delete_api_key`
@@ -1015,9 +1216,35 @@ async fn delete_api_key(
         Err(response) => return response,
     };
 
-    let query =
-        db::controllers::api_keys::delete_api_key(&mut connection, &user, &payload.identifier)
-            .await;
+    let apps = match authorized_apps(&mut connection, &user, &None, OrgPermission::ManageKeys).await
+    {
+        Ok(apps) => apps,
+        Err(response) => return response,
+    };
+    let app_ids = apps.iter().map(|app| app.id).collect();
+
+    let query = audited(
+        &mut connection,
+        &http_request,
+        |conn| {
+            db::controllers::api_keys::delete_api_key(conn, &app_ids, &payload.identifier)
+                .scope_boxed()
+        },
+        |row| {
+            row.first().map(|key| AuditEventCreate {
+                org_id: apps
+                    .iter()
+                    .find(|app| app.id == key.app_id)
+                    .map(|app| app.org_id),
+                before: Some(json!({
+                    "app_id": key.app_id,
+                    "identifier": key.identifier,
+                })),
+                ..AuditEventCreate::new(&user, AuditAction::ApiKeyDelete, &payload.identifier)
+            })
+        },
+    )
+    .await;
 
     match query {
         Ok(row) => {
@@ -1046,6 +1273,319 @@ async fn delete_api_key(
     };
 }
 
+/// Request payload for registering a request signing key
+#[derive(Deserialize, Serialize, Validate)]
+pub struct RegisterSigningKey {
+    pub app_id: Uuid,
+    /// Either `ed25519` or `secp256k1`
+    pub key_type: String,
+    /// Hex encoded public key. 32 bytes for ed25519, 33 or 65 bytes (SEC1) for secp256k1
+    pub public_key: String,
+}
+
+/// Register a public key used to sign data submission requests
+///
+/// # Description
+/// Registers an ed25519 or secp256k1 public key for the specified app. Requests signed with the
+/// matching private key are accepted by the data submission service in place of an API key.
+///
+/// # Route
+/// `POST /v1/user/register_signing_key`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+///
+/// # Request Body
+/// ```json
+/// {
+///   "app_id": "uuid-string",
+///   "key_type": "ed25519",
+///   "public_key": "0x3b6a27bcceb6a42d62a3a8d02a6f0d73653215771de243a63ac048a18b59da29"
+/// }
+/// ```
+///
+/// # Returns
+/// * 200 OK with the key id if registration succeeds
+/// * 400 Bad Request if the key type or public key is invalid
+/// * 500 Internal Server Error if registration fails
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Signing key registered successfully",
+///   "data": {
+///     "key_id": "uuid-string"
+///   }
+/// }
+/// ```
+#[post("/register_signing_key")]
+pub async fn register_signing_key(
+    payload: web::Json<RegisterSigningKey>,
+    http_request: HttpRequest,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => return HttpResponse::InternalServerError().body("User Id not retrieved"),
+    };
+
+    let public_key = payload.public_key.trim_start_matches("0x").to_lowercase();
+    let key_length = match hex::decode(&public_key) {
+        Ok(bytes) => bytes.len(),
+        Err(_) => {
+            return HttpResponse::BadRequest().json(json!({
+                "state": "ERROR",
+                "error": "Public key is not valid hex",
+            }))
+        }
+    };
+    let valid_length = match payload.key_type.as_str() {
+        "ed25519" => key_length == 32,
+        "secp256k1" => key_length == 33 || key_length == 65,
+        _ => {
+            return HttpResponse::BadRequest().json(json!({
+                "state": "ERROR",
+                "error": "Key type must be one of ed25519 or secp256k1",
+            }))
+        }
+    };
+    if !valid_length {
+        return HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": format!("Invalid public key length for {}", payload.key_type),
+        }));
+    }
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let app = match authorize_app(
+        &mut connection,
+        &user,
+        &payload.app_id,
+        OrgPermission::ManageKeys,
+    )
+    .await
+    {
+        Ok(app) => app,
+        Err(response) => return response,
+    };
+
+    let key_id = Uuid::new_v4();
+    let signing_key = SigningKeyCreate {
+        id: key_id,
+        app_id: app.id,
+        user_id: app.user_id,
+        key_type: payload.key_type.clone(),
+        public_key: public_key.clone(),
+    };
+    let tx = audited(
+        &mut connection,
+        &http_request,
+        |conn| db::controllers::signing_keys::create_signing_key(conn, &signing_key).scope_boxed(),
+        |_| {
+            Some(AuditEventCreate {
+                org_id: Some(app.org_id),
+                after: Some(json!({
+                    "app_id": app.id,
+                    "key_type": payload.key_type,
+                    "public_key": public_key,
+                })),
+                ..AuditEventCreate::new(&user, AuditAction::SigningKeyCreate, key_id)
+            })
+        },
+    )
+    .await;
+
+    match tx {
+        Ok(_) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Signing key registered successfully",
+            "data": {
+                "key_id": key_id
+            }
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e.to_string(),
+        })),
+    }
+}
+
+/// Retrieve all request signing keys for the authenticated user
+///
+/// # Route
+/// `GET /v1/user/get_signing_keys`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Signing keys retrieved successfully",
+///   "data": [
+///     {
+///       "id": "uuid-string",
+///       "app_id": "uuid-string",
+///       "user_id": "user@example.com",
+///       "key_type": "ed25519",
+///       "public_key": "3b6a27bc...",
+///       "created_at": "2023-01-01T12:00:00Z"
+///     }
+///   ]
+/// }
+/// ```
+#[get("/get_signing_keys")]
+pub async fn get_signing_keys(
+    http_request: HttpRequest,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let apps = match authorized_apps(&mut connection, &user, &None, OrgPermission::ViewApps).await {
+        Ok(apps) => apps.into_iter().map(|app| app.id).collect(),
+        Err(response) => return response,
+    };
+
+    match db::controllers::signing_keys::get_signing_keys(&mut connection, &apps).await {
+        Ok(keys) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Signing keys retrieved successfully",
+            "data": keys
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e.to_string(),
+        })),
+    }
+}
+
+/// Request payload for deleting a request signing key
+#[derive(Deserialize, Serialize, Validate)]
+pub struct DeleteSigningKey {
+    pub key_id: Uuid,
+}
+
+/// Delete a request signing key for the authenticated user
+///
+/// # Description
+/// Removes the signing key from both the database and the Redis cache used by the
+/// data submission service.
+///
+/// # Route
+/// `DELETE /v1/user/delete_signing_key`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+///
+/// # Request Body
+/// ```json
+/// {
+///   "key_id": "uuid-string"
+/// }
+/// ```
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Signing key deleted successfully"
+/// }
+/// ```
+#[delete("/delete_signing_key")]
+pub async fn delete_signing_key(
+    payload: web::Json<DeleteSigningKey>,
+    http_request: HttpRequest,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    config: web::Data<AppConfig>,
+) -> impl Responder {
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => return HttpResponse::InternalServerError().body("User Id not retrieved"),
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let apps = match authorized_apps(&mut connection, &user, &None, OrgPermission::ManageKeys).await
+    {
+        Ok(apps) => apps,
+        Err(response) => return response,
+    };
+    let app_ids = apps.iter().map(|app| app.id).collect();
+
+    let query = audited(
+        &mut connection,
+        &http_request,
+        |conn| {
+            db::controllers::signing_keys::delete_signing_key(conn, &app_ids, &payload.key_id)
+                .scope_boxed()
+        },
+        |row| {
+            row.first().map(|key| AuditEventCreate {
+                org_id: apps
+                    .iter()
+                    .find(|app| app.id == key.app_id)
+                    .map(|app| app.org_id),
+                before: Some(json!({
+                    "app_id": key.app_id,
+                    "key_type": key.key_type,
+                    "public_key": key.public_key,
+                })),
+                ..AuditEventCreate::new(&user, AuditAction::SigningKeyDelete, payload.key_id)
+            })
+        },
+    )
+    .await;
+
+    match query {
+        Ok(row) => {
+            if row.is_empty() {
+                return HttpResponse::NotFound().body("Signing key not found");
+            }
+            let cache_key = format!("signing_key:{}", payload.key_id);
+            match redis::Client::open(config.redis_url.clone().as_str()) {
+                Ok(mut client) => match client.del::<_, ()>(&cache_key) {
+                    // Otherwise the cached key stays valid until it expires
+                    Ok(_) => info(&format!("Deleted signing key from Redis: {}", cache_key)),
+                    Err(e) => error(&format!(
+                        "Failed to delete signing key {} from Redis: {}",
+                        cache_key, e
+                    )),
+                },
+                Err(e) => {
+                    error(&format!("Error connecting to Redis: {}", e));
+                }
+            }
+            HttpResponse::Ok().json(json!({
+                "state": "SUCCESS",
+                "message": "Signing key deleted successfully",
+            }))
+        }
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
 /// Update the app_id for a user account
 ///
 /// # Description
@@ -1099,11 +1639,33 @@ async fn update_app_id(
         }
     };
 
-    let query = db::controllers::apps::update_app_id(
+    let app = match authorize_app(
         &mut connection,
-        &payload.app_id,
         &user,
-        payload.avail_app_id,
+        &payload.app_id,
+        OrgPermission::ManageApps,
+    )
+    .await
+    {
+        Ok(app) => app,
+        Err(response) => return response,
+    };
+
+    let query = audited(
+        &mut connection,
+        &http_request,
+        |conn| {
+            db::controllers::apps::update_app_id(conn, &app.id, &app.user_id, payload.avail_app_id)
+                .scope_boxed()
+        },
+        |_| {
+            Some(AuditEventCreate {
+                org_id: Some(app.org_id),
+                before: Some(json!({ "app_id": app.app_id })),
+                after: Some(json!({ "app_id": payload.avail_app_id })),
+                ..AuditEventCreate::new(&user, AuditAction::AppUpdateAppId, app.id)
+            })
+        },
     )
     .await;
 
@@ -1177,8 +1739,32 @@ async fn toggle_encryption(
         }
     };
 
-    let query =
-        db::controllers::apps::toggle_encryption(&mut connection, &user, &payload.app_id).await;
+    let app = match authorize_app(
+        &mut connection,
+        &user,
+        &payload.app_id,
+        OrgPermission::ManageApps,
+    )
+    .await
+    {
+        Ok(app) => app,
+        Err(response) => return response,
+    };
+
+    let query = audited(
+        &mut connection,
+        &http_request,
+        |conn| db::controllers::apps::toggle_encryption(conn, &app.user_id, &app.id).scope_boxed(),
+        |_| {
+            Some(AuditEventCreate {
+                org_id: Some(app.org_id),
+                before: Some(json!({ "encryption": app.encryption })),
+                after: Some(json!({ "encryption": !app.encryption })),
+                ..AuditEventCreate::new(&user, AuditAction::AppToggleEncryption, app.id)
+            })
+        },
+    )
+    .await;
     match query {
         Ok(_) => HttpResponse::Ok().json(json!({
             "state": "SUCCESS",
@@ -0,0 +1,560 @@
+/// Withdrawals of unused credits back to chain
+/// The credits are priced back to a token and held from the balance when requested. An operator
+/// signs the payout, which is then claimed from the contract, or rejects it and the credits are
+/// returned. The funds monitor marks the withdrawal paid from the `Withdrawal` event.
+use crate::{
+    config::AppConfig,
+    identity::admin::{AdminPermission, RequirePermission},
+    price_oracle::PriceFeed,
+    utils::{
+        audited, authorize_organisation, get_amount_to_be_withdrawn, get_balance_unit,
+        get_connection, get_enabled_token, is_valid_ethereum_address, retrieve_user_id_from_jwt,
+    },
+    withdrawal::{sign_withdrawal, WithdrawalPayout},
+};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use bigdecimal::{BigDecimal, Zero};
+use db::{
+    controllers::withdrawals::{
+        create_withdrawal, get_withdrawal, get_withdrawals, reject_withdrawal,
+        sign_withdrawal as store_withdrawal_signature,
+    },
+    models::{
+        audit_events::{AuditAction, AuditEventCreate},
+        organisations::OrgPermission,
+        withdrawals::{WithdrawalCreate, WithdrawalStatus},
+    },
+};
+use diesel_async::{
+    pooled_connection::deadpool::Pool, scoped_futures::ScopedFutureExt, AsyncPgConnection,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Request payload for withdrawing credits
+#[derive(Deserialize, Serialize, Validate)]
+struct RequestWithdrawalParams {
+    pub org_id: Option<Uuid>,
+    pub chain: i32,
+    /// Token the credits are paid out in
+    #[validate(custom = "is_valid_ethereum_address")]
+    pub token_address: String,
+    /// Address receiving the payout
+    #[validate(custom = "is_valid_ethereum_address")]
+    pub recipient: String,
+    /// Amount withdrawn, in the unit the balance is held in
+    pub credits: BigDecimal,
+}
+
+/// Requests the withdrawal of unused credits to chain
+///
+/// # Description
+/// Prices the credits back to the token at the current price and moves them from the balance of
+/// the organisation into a hold. Only credits bought with deposits can be withdrawn. The payout
+/// is signed or rejected by an operator, a rejected withdrawal returns the credits. Requires a
+/// role allowed to manage billing.
+///
+/// # Route
+/// `POST /v1/user/request_withdrawal`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+///
+/// # Request Body
+/// ```json
+/// {
+///   "org_id": "uuid-string",
+///   "chain": 11155111,
+///   "token_address": "0x99a907545815c289fb6de86d55fe61d996063a94",
+///   "recipient": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8",
+///   "credits": "100"
+/// }
+/// ```
+///
+/// # Returns
+/// * 200 OK with the withdrawal
+/// * 400 Bad Request if a field is invalid, the token is not supported, withdrawals are not
+///   enabled on the chain or the credits are worth less than one unit of the token
+/// * 409 Conflict if the credits exceed the balance or the purchased credits not yet spent
+///
+/// # Example Response
+/// ```json
+/// {
+///   "state": "SUCCESS",
+///   "message": "Withdrawal requested successfully",
+///   "data": {
+///     "id": 7,
+///     "user_id": "user@example.com",
+///     "chain_id": 11155111,
+///     "token_address": "0x99a907545815c289fb6de86d55fe61d996063a94",
+///     "recipient": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8",
+///     "credits": "100",
+///     "amount": "1428571428571428571",
+///     "token_usd_price": "0.07",
+///     "avail_usd_price": "0.07",
+///     "price_source": "coingecko",
+///     "priced_at": "2023-01-01T12:00:00",
+///     "status": "pending",
+///     "signature": null,
+///     "resolved_by": null,
+///     "tx_hash": null,
+///     "block_number": null,
+///     "block_hash": null,
+///     "created_at": "2023-01-01T12:00:00",
+///     "resolved_at": null,
+///     "paid_at": null
+///   }
+/// }
+/// ```
+#[post("/request_withdrawal")]
+pub async fn request_withdrawal(
+    payload: web::Json<RequestWithdrawalParams>,
+    config: web::Data<AppConfig>,
+    prices: web::Data<PriceFeed>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+    if let Err(errors) = payload.validate() {
+        return HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": errors,
+        }));
+    }
+    if payload.credits <= BigDecimal::zero() {
+        return HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": "Credits must be positive",
+        }));
+    }
+    if config
+        .chain(payload.chain)
+        .is_none_or(|chain| chain.contract_address.is_empty())
+    {
+        return HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": "Withdrawals are not enabled on this chain",
+        }));
+    }
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let org = match authorize_organisation(
+        &mut connection,
+        &user,
+        &payload.org_id,
+        OrgPermission::ManageBilling,
+    )
+    .await
+    {
+        Ok(org) => org,
+        Err(response) => return response,
+    };
+
+    let token_address = payload.token_address.to_lowercase();
+    let token = match get_enabled_token(&mut connection, payload.chain, &token_address).await {
+        Ok(token) => token,
+        Err(response) => return response,
+    };
+
+    let unit = match get_balance_unit(&mut connection, &org.billing_id).await {
+        Ok(unit) => unit,
+        Err(response) => return response,
+    };
+    let Some(avail_rpc_url) = config.avail_rpc_endpoint.first() else {
+        return HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": "No Avail RPC endpoint configured",
+        }));
+    };
+    let (amount, price) =
+        match get_amount_to_be_withdrawn(&prices, avail_rpc_url, &token, &payload.credits, unit)
+            .await
+        {
+            Ok(priced) => priced,
+            Err(e) => {
+                return HttpResponse::InternalServerError().json(json!({
+                    "state": "ERROR",
+                    "error": e,
+                }))
+            }
+        };
+    if amount <= BigDecimal::zero() {
+        return HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": "Credits are worth less than one unit of the token",
+        }));
+    }
+
+    let withdrawal = WithdrawalCreate {
+        user_id: org.billing_id.clone(),
+        chain_id: payload.chain,
+        token_address,
+        recipient: payload.recipient.to_lowercase(),
+        credits: payload.credits.clone(),
+        amount,
+        token_usd_price: price.token_usd_price,
+        avail_usd_price: price.avail_usd_price,
+        price_source: price.price_source,
+        priced_at: price.priced_at,
+    };
+    match audited(
+        &mut connection,
+        &http_request,
+        |conn| create_withdrawal(conn, &withdrawal, unit).scope_boxed(),
+        |(withdrawal, owner)| {
+            Some(AuditEventCreate {
+                org_id: Some(org.id),
+                before: Some(
+                    json!({ "credit_balance": &owner.credit_balance + &withdrawal.credits }),
+                ),
+                after: Some(json!({
+                    "credit_balance": owner.credit_balance,
+                    "withdrawal": withdrawal,
+                })),
+                ..AuditEventCreate::new(&user, AuditAction::CreditsWithdraw, withdrawal.id)
+            })
+        },
+    )
+    .await
+    {
+        Ok((withdrawal, _)) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Withdrawal requested successfully",
+            "data": withdrawal,
+        })),
+        Err(e) => HttpResponse::Conflict().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Query parameters for retrieving the withdrawals of an organisation
+#[derive(Deserialize, Serialize)]
+struct GetWithdrawalsParams {
+    org_id: Option<Uuid>,
+    limit: Option<i64>,
+}
+
+/// Retrieves the withdrawals of an organisation, newest first
+///
+/// # Description
+/// A signed withdrawal carries the operator signature the payout is claimed with, by calling
+/// `withdraw` on the contract of the chain.
+///
+/// # Route
+/// `GET /v1/user/get_withdrawals?org_id={org_id}&limit={limit}`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication
+#[get("/get_withdrawals")]
+pub async fn get_user_withdrawals(
+    params: web::Query<GetWithdrawalsParams>,
+    config: web::Data<AppConfig>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    let user = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let org = match authorize_organisation(
+        &mut connection,
+        &user,
+        &params.org_id,
+        OrgPermission::ViewBilling,
+    )
+    .await
+    {
+        Ok(org) => org,
+        Err(response) => return response,
+    };
+
+    match get_withdrawals(
+        &mut connection,
+        &Some(org.billing_id),
+        &None,
+        params.limit.unwrap_or(config.total_users_query_limit),
+    )
+    .await
+    {
+        Ok(withdrawals) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Withdrawals retrieved successfully",
+            "data": withdrawals,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Query parameters for retrieving withdrawals across all users
+#[derive(Deserialize, Serialize)]
+struct GetAllWithdrawalsParams {
+    status: Option<WithdrawalStatus>,
+    limit: Option<i64>,
+}
+
+/// Retrieves withdrawals across all users (admin only)
+///
+/// # Route
+/// `GET /v1/admin/get_withdrawals?status={pending|signed|rejected|paid}&limit={limit}`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `support-read`)
+#[get(
+    "/get_withdrawals",
+    wrap = "RequirePermission::new(AdminPermission::SupportRead)"
+)]
+pub async fn get_all_withdrawals(
+    params: web::Query<GetAllWithdrawalsParams>,
+    config: web::Data<AppConfig>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+) -> impl Responder {
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    match get_withdrawals(
+        &mut connection,
+        &None,
+        &params.status,
+        params.limit.unwrap_or(config.total_users_query_limit),
+    )
+    .await
+    {
+        Ok(withdrawals) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Withdrawals retrieved successfully",
+            "data": withdrawals,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Request payload for resolving a withdrawal
+#[derive(Deserialize, Serialize)]
+pub struct ResolveWithdrawalParams {
+    pub id: i32,
+}
+
+/// Signs the payout of a pending withdrawal
+///
+/// # Description
+/// Signs the payout with the operator key for the contract of the chain, the withdrawal id is the
+/// nonce. The signature can be submitted to `withdraw` by anyone and is paid out once.
+///
+/// # Route
+/// `POST /v1/admin/sign_withdrawal`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `finance-grant`)
+///
+/// # Request Body
+/// ```json
+/// {
+///   "id": 7
+/// }
+/// ```
+///
+/// # Returns
+/// * 200 OK with the signed withdrawal
+/// * 404 Not Found if the withdrawal does not exist
+/// * 409 Conflict if the withdrawal is not pending
+/// * 503 Service Unavailable if no operator key or contract is configured for the chain
+#[post(
+    "/sign_withdrawal",
+    wrap = "RequirePermission::new(AdminPermission::FinanceGrant)"
+)]
+pub async fn sign_pending_withdrawal(
+    payload: web::Json<ResolveWithdrawalParams>,
+    config: web::Data<AppConfig>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    let admin = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    let withdrawal = match get_withdrawal(&mut connection, payload.id).await {
+        Ok(withdrawal) => withdrawal,
+        Err(_) => {
+            return HttpResponse::NotFound().json(json!({
+                "state": "ERROR",
+                "error": "Withdrawal not found",
+            }))
+        }
+    };
+
+    let contract = match config.chain(withdrawal.chain_id) {
+        Some(chain)
+            if !chain.contract_address.is_empty() && !config.withdrawal.signer_key.is_empty() =>
+        {
+            &chain.contract_address
+        }
+        _ => {
+            return HttpResponse::ServiceUnavailable().json(json!({
+                "state": "ERROR",
+                "error": "Withdrawals are not configured for this chain",
+            }))
+        }
+    };
+    let payout = WithdrawalPayout {
+        chain_id: withdrawal.chain_id,
+        contract,
+        user_id: &withdrawal.user_id,
+        token_address: &withdrawal.token_address,
+        amount: &withdrawal.amount,
+        recipient: &withdrawal.recipient,
+        nonce: withdrawal.id,
+    };
+    let signature = match sign_withdrawal(&config.withdrawal.signer_key, &payout) {
+        Ok(signature) => signature,
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": e,
+            }))
+        }
+    };
+
+    match audited(
+        &mut connection,
+        &http_request,
+        |conn| store_withdrawal_signature(conn, withdrawal.id, &admin, &signature).scope_boxed(),
+        |signed| {
+            Some(AuditEventCreate {
+                before: Some(json!({ "status": withdrawal.status })),
+                after: Some(json!(signed)),
+                ..AuditEventCreate::new(&admin, AuditAction::AdminWithdrawalSign, signed.id)
+            })
+        },
+    )
+    .await
+    {
+        Ok(signed) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Withdrawal signed",
+            "data": signed,
+        })),
+        Err(e) => HttpResponse::Conflict().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
+
+/// Rejects a pending withdrawal and returns its credits
+///
+/// # Route
+/// `POST /v1/admin/reject_withdrawal`
+///
+/// # Headers
+/// * `Authorization: Bearer <token>` - JWT token for authentication (requires `finance-grant`)
+///
+/// # Request Body
+/// ```json
+/// {
+///   "id": 7
+/// }
+/// ```
+///
+/// # Returns
+/// * 200 OK with the rejected withdrawal
+/// * 409 Conflict if the withdrawal does not exist or is not pending
+#[post(
+    "/reject_withdrawal",
+    wrap = "RequirePermission::new(AdminPermission::FinanceGrant)"
+)]
+pub async fn reject_pending_withdrawal(
+    payload: web::Json<ResolveWithdrawalParams>,
+    injected_dependency: web::Data<Pool<AsyncPgConnection>>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    let admin = match retrieve_user_id_from_jwt(&http_request) {
+        Some(val) => val,
+        None => {
+            return HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": "User Id not retrieved",
+            }))
+        }
+    };
+
+    let mut connection = match get_connection(&injected_dependency).await {
+        Ok(conn) => conn,
+        Err(response) => return response,
+    };
+
+    match audited(
+        &mut connection,
+        &http_request,
+        |conn| reject_withdrawal(conn, payload.id, &admin).scope_boxed(),
+        |(withdrawal, user)| {
+            Some(AuditEventCreate {
+                before: Some(
+                    json!({ "credit_balance": &user.credit_balance - &withdrawal.credits }),
+                ),
+                after: Some(json!({
+                    "credit_balance": user.credit_balance,
+                    "withdrawal": withdrawal,
+                })),
+                ..AuditEventCreate::new(&admin, AuditAction::AdminWithdrawalReject, withdrawal.id)
+            })
+        },
+    )
+    .await
+    {
+        Ok((withdrawal, _)) => HttpResponse::Ok().json(json!({
+            "state": "SUCCESS",
+            "message": "Withdrawal rejected",
+            "data": withdrawal,
+        })),
+        Err(e) => HttpResponse::Conflict().json(json!({
+            "state": "ERROR",
+            "error": e,
+        })),
+    }
+}
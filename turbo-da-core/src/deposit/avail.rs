@@ -0,0 +1,198 @@
+/// Detection of deposits in Avail extrinsics
+/// - `transfer_keep_alive`, `transfer_allow_death` and `transfer_all` are accepted, on their own or
+///   inside a `batch`, `batch_all` or `force_batch`
+/// - The transfer destination must be one of our deposit addresses
+/// - Transfers to the treasury reference the order in the hex encoded remark of the batch, transfers
+///   to a derived address are credited to the user the address is assigned to
+/// - Failed extrinsics are included in blocks too, the amount moved is read from the events
+use crate::logger::{error, info};
+use avail_rust::avail::{timestamp::storage::Now, RuntimeCall};
+use avail_rust::codec::Decode;
+use avail_rust::prelude::*;
+use chrono::{DateTime, NaiveDateTime};
+use std::collections::HashMap;
+
+/// Addresses deposits are accepted on
+pub struct DepositAddresses {
+    /// Shared deposit address, the order id is carried in a remark
+    pub treasury: AccountId,
+    /// Addresses derived from the deposit seed, keyed by account id and mapped to their user
+    pub derived: HashMap<[u8; 32], String>,
+}
+
+impl DepositAddresses {
+    pub fn new(treasury: AccountId) -> Self {
+        DepositAddresses {
+            treasury,
+            derived: HashMap::new(),
+        }
+    }
+
+    pub fn contains(&self, account: &AccountId) -> bool {
+        account == &self.treasury || self.derived.contains_key(&account.0)
+    }
+}
+
+/// What a deposit is credited against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DepositReference {
+    /// Hex encoded id of a credit request
+    Order(String),
+    /// User a derived deposit address is assigned to
+    User(String),
+}
+
+/// Transfer to one of our deposit addresses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AvailTransfer {
+    pub destination: AccountId,
+    /// Amount of the call, `None` for `transfer_all` where it is only known from the events
+    pub value: Option<u128>,
+    /// `None` when a transfer to the treasury carries no remark
+    pub reference: Option<DepositReference>,
+}
+
+/// Decodes the signer and call of a signed extrinsic
+///
+/// Returns `None` for unsigned extrinsics and for calls that are not part of `RuntimeCall`.
+pub fn decode_signed_call(data: &str) -> Option<(MultiAddress, RuntimeCall)> {
+    let extrinsic = RawExtrinsic::try_from(data).ok()?;
+    let signer = extrinsic.signature?.address;
+    let call = RuntimeCall::decode(&mut extrinsic.call.as_slice()).ok()?;
+    Some((signer, call))
+}
+
+/// Finds the first transfer to one of `addresses` in a call
+pub fn match_deposit(call: &RuntimeCall, addresses: &DepositAddresses) -> Option<AvailTransfer> {
+    let calls = match call {
+        RuntimeCall::UtilityBatch(batch) => batch.decode_calls().ok()?,
+        RuntimeCall::UtilityBatchAll(batch) => batch.decode_calls().ok()?,
+        RuntimeCall::UtilityForceBatch(batch) => batch.decode_calls().ok()?,
+        call => vec![call.clone()],
+    };
+
+    let remark = calls.iter().find_map(|call| match call {
+        RuntimeCall::SystemRemark(remark) => Some(hex::encode(&remark.remark)),
+        RuntimeCall::SystemRemarkWithEvent(remark) => Some(hex::encode(&remark.remark)),
+        _ => None,
+    });
+
+    calls.iter().find_map(|call| {
+        let (dest, value) = match call {
+            RuntimeCall::BalancesTransferKeepAlive(transfer) => {
+                (&transfer.dest, Some(transfer.value))
+            }
+            RuntimeCall::BalancesTransferAllDeath(transfer) => {
+                (&transfer.dest, Some(transfer.value))
+            }
+            RuntimeCall::BalancesTransferAll(transfer) => (&transfer.dest, None),
+            _ => return None,
+        };
+        let MultiAddress::Id(destination) = dest else {
+            return None;
+        };
+        if !addresses.contains(destination) {
+            return None;
+        }
+
+        let reference = match addresses.derived.get(&destination.0) {
+            Some(user) => Some(DepositReference::User(user.clone())),
+            None => remark.clone().map(DepositReference::Order),
+        };
+        Some(AvailTransfer {
+            destination: destination.clone(),
+            value,
+            reference,
+        })
+    })
+}
+
+/// Deposit made by a successful extrinsic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AvailDeposit {
+    pub transfer: AvailTransfer,
+    /// Amount moved, as read from the `Transfer` event
+    pub amount: u128,
+    pub from: String,
+    pub tx_hash: H256,
+    pub tx_index: u32,
+}
+
+/// Reads the deposit to one of `addresses` made by an extrinsic
+///
+/// Returns `None` for extrinsics that are not deposits, including sweeps from the deposit
+/// addresses, and for deposits that failed on chain.
+pub async fn read_deposit(
+    client: &Client,
+    ext: &BlockRawExtrinsic,
+    addresses: &DepositAddresses,
+) -> Result<Option<AvailDeposit>, String> {
+    let Some((signer, call)) = ext.data.as_deref().and_then(decode_signed_call) else {
+        return Ok(None);
+    };
+    // Sweeps from the deposit addresses to the treasury are not deposits
+    if matches!(&signer, MultiAddress::Id(signer) if addresses.contains(signer)) {
+        return Ok(None);
+    }
+    let Some(transfer) = match_deposit(&call, addresses) else {
+        return Ok(None);
+    };
+
+    let tx_hash = ext.ext_hash();
+    info(&format!(
+        "Found transfer to deposit address {}, tx hash: {}",
+        transfer.destination, tx_hash
+    ));
+
+    let events = ext
+        .events(client.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+    if !events.is_extrinsic_success_present() {
+        info(&format!("Transfer failed on chain, skipping: {}", tx_hash));
+        return Ok(None);
+    }
+    let transfers = events
+        .all::<avail::balances::events::Transfer>()
+        .map_err(|e| format!("Failed to decode transfer events: {}", e))?;
+    let Some(event) = transfers.iter().find(|x| x.to == transfer.destination) else {
+        error(&format!("Transfer event not found, tx hash: {}", tx_hash));
+        return Ok(None);
+    };
+
+    Ok(Some(AvailDeposit {
+        amount: event.amount,
+        from: event.from.to_string(),
+        tx_hash,
+        tx_index: ext.ext_index(),
+        transfer,
+    }))
+}
+
+/// Reads the deposit made to the treasury by the extrinsic `tx_hash` of block `block_hash`
+pub async fn find_treasury_deposit(
+    client: &Client,
+    block_hash: H256,
+    tx_hash: H256,
+    treasury: &AccountId,
+) -> Result<Option<AvailDeposit>, String> {
+    let ext = BlockWithRawExt::new(client.clone(), block_hash)
+        .get(tx_hash, EncodeSelector::Extrinsic)
+        .await
+        .map_err(|e| format!("Failed to fetch extrinsic: {}", e))?;
+    match ext {
+        Some(ext) => read_deposit(client, &ext, &DepositAddresses::new(treasury.clone())).await,
+        None => Ok(None),
+    }
+}
+
+/// Time block `block_hash` was produced at, read from the `Timestamp` pallet
+pub async fn block_timestamp(client: &Client, block_hash: H256) -> Result<NaiveDateTime, String> {
+    let millis = Now::fetch(&client.rpc_client, Some(block_hash))
+        .await
+        .map_err(|e| format!("Failed to fetch block timestamp: {}", e))?
+        .ok_or("Block timestamp not found")?;
+    DateTime::from_timestamp_millis(millis as i64)
+        .map(|time| time.naive_utc())
+        .ok_or(format!("Invalid block timestamp {}", millis))
+}
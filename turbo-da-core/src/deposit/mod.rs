@@ -0,0 +1,46 @@
+/// Deposits made on chain, shared by the funds monitor and the verification of the transactions
+/// reported with `add_inclusion_details`
+/// - `Deposit` is the event of the `TurboDAResolver` contract, the order id is the hex encoded id
+///   of the credit request
+/// - `avail` matches transfers to the deposit addresses in Avail extrinsics
+pub mod avail;
+#[cfg(test)]
+mod test;
+
+use alloy::{primitives::Address, rpc::types::Log, sol, sol_types::SolEvent};
+use std::str::FromStr;
+
+sol! {
+    event Deposit(
+        bytes32 indexed orderId,
+        address indexed tokenAddress,
+        uint256 amount,
+        address from
+    );
+}
+
+/// Id of the credit request referenced by a hex encoded order id, `0x` prefixed or not
+pub fn parse_order_id(order_id: &str) -> Result<i32, String> {
+    i32::from_str_radix(order_id.trim_start_matches("0x"), 16)
+        .map_err(|e| format!("Failed to parse order ID {}: {}", order_id, e))
+}
+
+/// `Deposit` event emitted by `contract` for the credit request `order_id` among the logs of a
+/// transaction
+pub fn find_evm_deposit<'a>(
+    logs: &'a [Log],
+    contract: &str,
+    order_id: i32,
+) -> Result<Option<(&'a Log, Deposit)>, String> {
+    let contract =
+        Address::from_str(contract).map_err(|e| format!("Invalid address {}: {}", contract, e))?;
+    Ok(logs
+        .iter()
+        .filter(|log| log.address() == contract && log.topic0() == Some(&Deposit::SIGNATURE_HASH))
+        .filter_map(|log| {
+            let deposit = Deposit::decode_log_data(log.data(), true).ok()?;
+            (parse_order_id(&deposit.orderId.to_string()).ok()? == order_id)
+                .then_some((log, deposit))
+        })
+        .next())
+}
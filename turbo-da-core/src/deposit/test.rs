@@ -0,0 +1,182 @@
+use super::{
+    avail::{decode_signed_call, match_deposit, AvailTransfer, DepositAddresses, DepositReference},
+    find_evm_deposit, parse_order_id, Deposit,
+};
+use alloy::{
+    primitives::{Address, FixedBytes, LogData, U256},
+    rpc::types::Log,
+    sol_types::SolEvent,
+};
+use avail_rust::prelude::*;
+
+const CONTRACT: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+const TOKEN: &str = "0x99a907545815c289fb6de86d55fe61d996063a94";
+
+fn deposit_log(emitter: &str, order_id: u64, data: Option<LogData>) -> Log {
+    let deposit = Deposit {
+        orderId: FixedBytes::from(U256::from(order_id)),
+        tokenAddress: TOKEN.parse().unwrap(),
+        amount: U256::from(1_000_000u64),
+        from: Address::ZERO,
+    };
+    Log {
+        inner: alloy::primitives::Log {
+            address: emitter.parse().unwrap(),
+            data: data.unwrap_or_else(|| deposit.encode_log_data()),
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_order_id_is_parsed_from_hex() {
+    assert_eq!(parse_order_id("0x2a"), Ok(42));
+    assert_eq!(parse_order_id("0000002a"), Ok(42));
+    assert_eq!(
+        parse_order_id("0x000000000000000000000000000000000000000000000000000000000000002a"),
+        Ok(42)
+    );
+    assert!(parse_order_id("not hex").is_err());
+}
+
+#[test]
+fn test_deposit_of_the_order_is_found() {
+    let logs = vec![
+        deposit_log(CONTRACT, 41, None),
+        deposit_log(CONTRACT, 42, None),
+    ];
+
+    let (log, deposit) = find_evm_deposit(&logs, CONTRACT, 42).unwrap().unwrap();
+    assert_eq!(log.inner.data, logs[1].inner.data);
+    assert_eq!(deposit.amount, U256::from(1_000_000u64));
+    assert_eq!(deposit.tokenAddress, TOKEN.parse::<Address>().unwrap());
+}
+
+#[test]
+fn test_deposits_of_other_contracts_and_events_are_ignored() {
+    let other = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+    let logs = vec![
+        deposit_log(other, 42, None),
+        deposit_log(
+            CONTRACT,
+            42,
+            Some(LogData::new_unchecked(vec![], vec![].into())),
+        ),
+    ];
+
+    assert!(find_evm_deposit(&logs, CONTRACT, 42).unwrap().is_none());
+    assert!(find_evm_deposit(&logs, "not an address", 42).is_err());
+}
+
+// Extrinsics signed by Alice, the signatures are not valid as only the calls are decoded
+/// `batch_all(transfer_keep_alive(Bob, 1 AVAIL), remark(0x0000002a))`
+const BATCH_KEEP_ALIVE: &str = "0x75028400d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d0107070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707000c00000102080603008eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a4813000064a7b3b6e00d0000100000002a";
+/// `batch_all(remark(0x0000002a), transfer_all(Bob, false))`
+const BATCH_REMARK_FIRST_TRANSFER_ALL: &str = "0x55028400d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d0107070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707000c00000102080000100000002a0604008eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a4800";
+/// `transfer_allow_death(Charlie, 5000)`
+const ALLOW_DEATH_CHARLIE: &str = "0x31028400d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d0107070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707000c000006000090b5ab205c6974c9ea841be688864633dc9ca8a357843eeacf2314649965fe22214e";
+/// `transfer_keep_alive(Bob, 5000)`
+const KEEP_ALIVE_BOB: &str = "0x31028400d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d0107070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707000c00000603008eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48214e";
+/// `batch_all(transfer_keep_alive(Charlie, 1000), remark(0x0000002a))`
+const BATCH_TO_CHARLIE: &str = "0x59028400d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d0107070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707070707000c000001020806030090b5ab205c6974c9ea841be688864633dc9ca8a357843eeacf2314649965fe22a10f0000100000002a";
+
+const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+const CHARLIE: &str = "5FLSigC9HGRKVhB9FiEo4Y3koPsNmBmLJbpXg2mp1hXcS59Y";
+
+fn account(address: &str) -> AccountId {
+    AccountId::from_str(address).unwrap()
+}
+
+fn matched(extrinsic: &str, addresses: &DepositAddresses) -> Option<AvailTransfer> {
+    let (_, call) = decode_signed_call(extrinsic).expect("fixture should decode");
+    match_deposit(&call, addresses)
+}
+
+#[test]
+fn test_batch_with_remark_to_treasury() {
+    let addresses = DepositAddresses::new(account(BOB));
+
+    assert_eq!(
+        matched(BATCH_KEEP_ALIVE, &addresses),
+        Some(AvailTransfer {
+            destination: account(BOB),
+            value: Some(1_000_000_000_000_000_000),
+            reference: Some(DepositReference::Order("0000002a".to_string())),
+        })
+    );
+}
+
+#[test]
+fn test_transfer_all_with_remark_first() {
+    let addresses = DepositAddresses::new(account(BOB));
+
+    assert_eq!(
+        matched(BATCH_REMARK_FIRST_TRANSFER_ALL, &addresses),
+        Some(AvailTransfer {
+            destination: account(BOB),
+            value: None,
+            reference: Some(DepositReference::Order("0000002a".to_string())),
+        })
+    );
+}
+
+#[test]
+fn test_transfer_to_derived_address() {
+    let mut addresses = DepositAddresses::new(account(BOB));
+    addresses
+        .derived
+        .insert(account(CHARLIE).0, "user@example.com".to_string());
+
+    assert_eq!(
+        matched(ALLOW_DEATH_CHARLIE, &addresses),
+        Some(AvailTransfer {
+            destination: account(CHARLIE),
+            value: Some(5000),
+            reference: Some(DepositReference::User("user@example.com".to_string())),
+        })
+    );
+}
+
+#[test]
+fn test_transfer_to_treasury_without_remark() {
+    let addresses = DepositAddresses::new(account(BOB));
+
+    assert_eq!(
+        matched(KEEP_ALIVE_BOB, &addresses),
+        Some(AvailTransfer {
+            destination: account(BOB),
+            value: Some(5000),
+            reference: None,
+        })
+    );
+}
+
+#[test]
+fn test_transfer_to_other_account_is_ignored() {
+    let addresses = DepositAddresses::new(account(BOB));
+
+    assert_eq!(matched(BATCH_TO_CHARLIE, &addresses), None);
+}
+
+#[test]
+fn test_signer_is_not_the_destination() {
+    // Alice signs every fixture, a transfer from the deposit address is not a deposit
+    let addresses = DepositAddresses::new(account(ALICE));
+
+    assert_eq!(matched(BATCH_KEEP_ALIVE, &addresses), None);
+    assert_eq!(matched(KEEP_ALIVE_BOB, &addresses), None);
+}
+
+#[test]
+fn test_signer_is_decoded() {
+    let (signer, _) = decode_signed_call(KEEP_ALIVE_BOB).unwrap();
+
+    assert_eq!(signer, MultiAddress::Id(account(ALICE)));
+}
+
+#[test]
+fn test_invalid_extrinsic_is_not_decoded() {
+    assert!(decode_signed_call("0x1234").is_none());
+    assert!(decode_signed_call("not hex").is_none());
+}
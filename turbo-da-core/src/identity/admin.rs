@@ -0,0 +1,111 @@
+use super::Identity;
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    error as actix_error, Error, HttpMessage,
+};
+use futures::future::LocalBoxFuture;
+use std::{
+    fmt,
+    future::{ready, Ready},
+    rc::Rc,
+};
+
+/// Permissions of staff on the admin routes, granted as roles of the identity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminPermission {
+    /// Read users, apps, fund requests and the audit log
+    SupportRead,
+    /// Grant credits to users and approve grants of other admins
+    FinanceGrant,
+    /// Operate the submission pipeline, e.g. reset retry counts
+    OpsRetry,
+}
+
+impl AdminPermission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdminPermission::SupportRead => "support-read",
+            AdminPermission::FinanceGrant => "finance-grant",
+            AdminPermission::OpsRetry => "ops-retry",
+        }
+    }
+}
+
+impl fmt::Display for AdminPermission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Route middleware rejecting identities without the given admin permission
+///
+/// Must run inside `IdentityMiddleware`, e.g.
+/// `#[post("/fund_user", wrap = "RequirePermission::new(AdminPermission::FinanceGrant)")]`
+pub struct RequirePermission {
+    permission: AdminPermission,
+}
+
+impl RequirePermission {
+    pub fn new(permission: AdminPermission) -> Self {
+        RequirePermission { permission }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequirePermission
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequirePermissionService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequirePermissionService {
+            service: Rc::new(service),
+            permission: self.permission,
+        }))
+    }
+}
+
+pub struct RequirePermissionService<S> {
+    service: Rc<S>,
+    permission: AdminPermission,
+}
+
+impl<S, B> Service<ServiceRequest> for RequirePermissionService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let allowed = req.extensions().get::<Identity>().is_some_and(|identity| {
+            identity
+                .roles
+                .iter()
+                .any(|role| role == self.permission.as_str())
+        });
+        if !allowed {
+            let permission = self.permission;
+            return Box::pin(async move {
+                Err(actix_error::ErrorForbidden(format!(
+                    "Missing admin permission {}",
+                    permission
+                )))
+            });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(fut)
+    }
+}
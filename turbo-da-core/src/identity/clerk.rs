@@ -0,0 +1,37 @@
+use super::IdentityProvider;
+use clerk_rs::{
+    clerk::Clerk,
+    validators::{authorizer::validate_jwt, jwks::MemoryCacheJwksProvider},
+    ClerkConfiguration,
+};
+use futures::future::BoxFuture;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+/// Validates session tokens issued by Clerk against the JWKS of the Clerk instance
+pub struct ClerkProvider {
+    jwks: Arc<MemoryCacheJwksProvider>,
+}
+
+impl ClerkProvider {
+    pub fn new(secret_key: &str) -> Self {
+        let config = ClerkConfiguration::new(None, None, Some(secret_key.to_string()), None);
+        ClerkProvider {
+            jwks: Arc::new(MemoryCacheJwksProvider::new(Clerk::new(config))),
+        }
+    }
+}
+
+impl IdentityProvider for ClerkProvider {
+    fn verify<'a>(&'a self, token: &'a str) -> BoxFuture<'a, Result<Map<String, Value>, String>> {
+        Box::pin(async move {
+            let jwt = validate_jwt(token, self.jwks.clone())
+                .await
+                .map_err(|e| e.to_string())?;
+            match serde_json::to_value(jwt).map_err(|e| e.to_string())? {
+                Value::Object(claims) => Ok(claims),
+                _ => Err("Clerk token claims are not an object".to_string()),
+            }
+        })
+    }
+}
@@ -0,0 +1,200 @@
+/// Authentication of dashboard users
+/// - `IdentityProvider` validates bearer tokens and returns their claims
+/// - `ClaimMapping` turns the claims into an `Identity`
+/// - `IdentityMiddleware` authenticates requests and enforces the role of a scope
+/// - `RequirePermission` enforces an admin permission on a single route
+pub mod admin;
+pub mod clerk;
+pub mod oidc;
+#[cfg(test)]
+mod test;
+
+use crate::logger::warn;
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    error as actix_error,
+    http::header::AUTHORIZATION,
+    Error, HttpMessage,
+};
+use futures::future::{BoxFuture, LocalBoxFuture};
+use serde_json::{Map, Value};
+use std::{
+    future::{ready, Ready},
+    rc::Rc,
+    sync::Arc,
+};
+
+/// Cookie carrying the session token when no `Authorization` header is sent
+const SESSION_COOKIE: &str = "__session";
+
+/// Validates bearer tokens issued by an identity provider
+pub trait IdentityProvider: Send + Sync {
+    /// Verifies the token and returns its claims
+    fn verify<'a>(&'a self, token: &'a str) -> BoxFuture<'a, Result<Map<String, Value>, String>>;
+}
+
+/// Authenticated user, stored in the request extensions by `IdentityMiddleware`
+#[derive(Debug, Clone)]
+pub struct Identity {
+    pub user_id: String,
+    pub roles: Vec<String>,
+}
+
+/// Claims holding the user id and roles of a token
+///
+/// Claims are looked up by path, nested claims are separated by dots, e.g. `realm_access.roles`.
+#[derive(Debug, Clone)]
+pub struct ClaimMapping {
+    pub user_id_claim: String,
+    pub roles_claim: String,
+}
+
+impl ClaimMapping {
+    pub fn new(user_id_claim: &str, roles_claim: &str) -> Self {
+        ClaimMapping {
+            user_id_claim: user_id_claim.to_string(),
+            roles_claim: roles_claim.to_string(),
+        }
+    }
+
+    pub fn identity(&self, claims: &Map<String, Value>) -> Result<Identity, String> {
+        let user_id = match claim(claims, &self.user_id_claim) {
+            Some(Value::String(user_id)) if !user_id.is_empty() => user_id.clone(),
+            _ => return Err(format!("Claim {} not found in token", self.user_id_claim)),
+        };
+        let roles = match claim(claims, &self.roles_claim) {
+            Some(Value::String(role)) => vec![role.clone()],
+            Some(Value::Array(roles)) => roles
+                .iter()
+                .filter_map(|role| role.as_str().map(|role| role.to_string()))
+                .collect(),
+            _ => vec![],
+        };
+
+        Ok(Identity { user_id, roles })
+    }
+}
+
+fn claim<'a>(claims: &'a Map<String, Value>, path: &str) -> Option<&'a Value> {
+    let mut parts = path.split('.');
+    let mut value = claims.get(parts.next()?)?;
+    for part in parts {
+        value = value.get(part)?;
+    }
+    Some(value)
+}
+
+/// Middleware authenticating requests against an identity provider
+///
+/// Requests without a valid token, or whose identity lacks the required role, are rejected
+/// before reaching the handler.
+pub struct IdentityMiddleware {
+    provider: Arc<dyn IdentityProvider>,
+    mapping: ClaimMapping,
+    required_role: String,
+}
+
+impl IdentityMiddleware {
+    pub fn new(
+        provider: Arc<dyn IdentityProvider>,
+        mapping: ClaimMapping,
+        required_role: &str,
+    ) -> Self {
+        IdentityMiddleware {
+            provider,
+            mapping,
+            required_role: required_role.to_string(),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for IdentityMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = IdentityMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(IdentityMiddlewareService {
+            service: Rc::new(service),
+            provider: self.provider.clone(),
+            mapping: self.mapping.clone(),
+            required_role: self.required_role.clone(),
+        }))
+    }
+}
+
+pub struct IdentityMiddlewareService<S> {
+    service: Rc<S>,
+    provider: Arc<dyn IdentityProvider>,
+    mapping: ClaimMapping,
+    required_role: String,
+}
+
+impl<S, B> Service<ServiceRequest> for IdentityMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let provider = self.provider.clone();
+        let mapping = self.mapping.clone();
+        let required_role = self.required_role.clone();
+
+        Box::pin(async move {
+            let token = match bearer_token(&req) {
+                Some(token) => token,
+                None => {
+                    return Err(actix_error::ErrorUnauthorized(
+                        "Invalid Authorization header",
+                    ))
+                }
+            };
+
+            let identity = match provider
+                .verify(&token)
+                .await
+                .and_then(|claims| mapping.identity(&claims))
+            {
+                Ok(identity) => identity,
+                Err(e) => {
+                    warn(&format!("Rejected token: {}", e));
+                    return Err(actix_error::ErrorUnauthorized("Unauthorized"));
+                }
+            };
+
+            if !identity.roles.contains(&required_role) {
+                return Err(actix_error::ErrorUnauthorized("Unauthorized"));
+            }
+
+            req.extensions_mut().insert(identity);
+            service.call(req).await
+        })
+    }
+}
+
+fn bearer_token(req: &ServiceRequest) -> Option<String> {
+    if let Some(header) = req.headers().get(AUTHORIZATION) {
+        return header
+            .to_str()
+            .ok()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token.trim().to_string());
+    }
+    req.cookie(SESSION_COOKIE)
+        .map(|cookie| cookie.value().to_string())
+}
@@ -0,0 +1,151 @@
+use super::IdentityProvider;
+use crate::logger::info;
+use futures::future::BoxFuture;
+use jsonwebtoken::{
+    decode, decode_header,
+    jwk::{Jwk, JwkSet},
+    Algorithm, DecodingKey, Validation,
+};
+use reqwest::Client;
+use serde_json::{Map, Value};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Time after which the key set is fetched again
+const JWKS_TTL: Duration = Duration::from_secs(3600);
+/// Minimum time between fetches triggered by tokens signed with an unknown key
+const JWKS_MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Validates tokens issued by any OpenID Connect provider publishing a JWKS
+pub struct OidcProvider {
+    jwks_url: String,
+    issuer: Option<String>,
+    audience: Option<String>,
+    algorithm: Option<Algorithm>,
+    client: Client,
+    keys: RwLock<Option<(Instant, JwkSet)>>,
+}
+
+impl OidcProvider {
+    /// # Arguments
+    /// * `jwks_url` - URL of the key set used to sign tokens
+    /// * `issuer` - Expected `iss` claim, not checked when unset
+    /// * `audience` - Expected `aud` claim, not checked when unset
+    /// * `algorithm` - Signing algorithm of keys whose JWK doesn't carry an `alg`
+    pub fn new(
+        jwks_url: &str,
+        issuer: Option<String>,
+        audience: Option<String>,
+        algorithm: Option<Algorithm>,
+    ) -> Self {
+        OidcProvider {
+            jwks_url: jwks_url.to_string(),
+            issuer,
+            audience,
+            algorithm,
+            client: Client::new(),
+            keys: RwLock::new(None),
+        }
+    }
+
+    async fn fetch_keys(&self) -> Result<JwkSet, String> {
+        let keys = self
+            .client
+            .get(&self.jwks_url)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch JWKS: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("Failed to fetch JWKS: {}", e))?
+            .json::<JwkSet>()
+            .await
+            .map_err(|e| format!("Invalid JWKS: {}", e))?;
+        info(&format!(
+            "Fetched {} keys from {}",
+            keys.keys.len(),
+            self.jwks_url
+        ));
+        Ok(keys)
+    }
+
+    /// Returns the key with the given id, fetching the key set when it is stale or the key is
+    /// unknown. Tokens without a key id are only accepted when the set holds a single key.
+    ///
+    /// The algorithm is the one the key is published for, or the configured one, never the one
+    /// named by the token.
+    async fn decoding_key(&self, kid: &Option<String>) -> Result<(DecodingKey, Algorithm), String> {
+        let find = |keys: &JwkSet| match kid {
+            Some(kid) => keys.find(kid).cloned(),
+            None if keys.keys.len() == 1 => keys.keys.first().cloned(),
+            None => None,
+        };
+
+        if let Some((fetched_at, keys)) = self.keys.read().await.as_ref() {
+            if fetched_at.elapsed() < JWKS_TTL {
+                if let Some(jwk) = find(keys) {
+                    return self.key_of(&jwk);
+                }
+            }
+        }
+
+        let mut cache = self.keys.write().await;
+        let refresh = match cache.as_ref() {
+            Some((fetched_at, keys)) => {
+                fetched_at.elapsed() >= JWKS_TTL
+                    || (find(keys).is_none() && fetched_at.elapsed() >= JWKS_MIN_REFRESH_INTERVAL)
+            }
+            None => true,
+        };
+        if refresh {
+            *cache = Some((Instant::now(), self.fetch_keys().await?));
+        }
+
+        match cache.as_ref().and_then(|(_, keys)| find(keys)) {
+            Some(jwk) => self.key_of(&jwk),
+            None => Err(format!("Unknown signing key {:?}", kid)),
+        }
+    }
+
+    fn key_of(&self, jwk: &Jwk) -> Result<(DecodingKey, Algorithm), String> {
+        let algorithm = match jwk.common.key_algorithm {
+            Some(alg) => alg
+                .to_string()
+                .parse::<Algorithm>()
+                .map_err(|_| format!("Unsupported signing algorithm {}", alg))?,
+            None => self
+                .algorithm
+                .ok_or("Signing key has no algorithm and none is configured")?,
+        };
+        let key = DecodingKey::from_jwk(jwk).map_err(|e| e.to_string())?;
+        Ok((key, algorithm))
+    }
+}
+
+impl IdentityProvider for OidcProvider {
+    fn verify<'a>(&'a self, token: &'a str) -> BoxFuture<'a, Result<Map<String, Value>, String>> {
+        Box::pin(async move {
+            let header = decode_header(token).map_err(|e| e.to_string())?;
+            let (key, algorithm) = self.decoding_key(&header.kid).await?;
+            if header.alg != algorithm {
+                return Err(format!(
+                    "Token is signed with {:?}, expected {:?}",
+                    header.alg, algorithm
+                ));
+            }
+
+            let mut validation = Validation::new(algorithm);
+            match &self.issuer {
+                Some(issuer) => validation.set_issuer(&[issuer]),
+                None => validation.iss = None,
+            }
+            match &self.audience {
+                Some(audience) => validation.set_audience(&[audience]),
+                None => validation.validate_aud = false,
+            }
+
+            decode::<Map<String, Value>>(token, &key, &validation)
+                .map(|data| data.claims)
+                .map_err(|e| e.to_string())
+        })
+    }
+}
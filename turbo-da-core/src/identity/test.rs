@@ -0,0 +1,183 @@
+use super::{
+    admin::{AdminPermission, RequirePermission},
+    oidc::OidcProvider,
+    ClaimMapping, IdentityMiddleware, IdentityProvider,
+};
+use crate::utils::retrieve_user_id_from_jwt;
+use actix_web::{
+    http::StatusCode, test, web, App, HttpRequest, HttpResponse, HttpServer, Responder,
+};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const KEY_ID: &str = "test-key";
+const SECRET: &[u8] = b"turbo-da-test-secret";
+/// `SECRET` encoded as base64url, as published in the key set
+const SECRET_JWK: &str = "dHVyYm8tZGEtdGVzdC1zZWNyZXQ";
+const ISSUER: &str = "https://auth.example.com/realms/turbo-da";
+
+/// Serves a key set on a local port standing in for the JWKS endpoint of an OIDC provider
+fn start_jwks_server() -> String {
+    let server = HttpServer::new(|| {
+        App::new().route(
+            "/jwks.json",
+            web::get().to(|| async {
+                HttpResponse::Ok().json(json!({
+                    "keys": [{ "kty": "oct", "kid": KEY_ID, "alg": "HS256", "k": SECRET_JWK }]
+                }))
+            }),
+        )
+    })
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .expect("Can't bind JWKS server");
+    let address = server.addrs()[0];
+    actix_web::rt::spawn(server.run());
+    format!("http://{}/jwks.json", address)
+}
+
+fn token(claims: Value) -> String {
+    signed_token(Algorithm::HS256, claims)
+}
+
+fn signed_token(algorithm: Algorithm, claims: Value) -> String {
+    let header = Header {
+        kid: Some(KEY_ID.to_string()),
+        ..Header::new(algorithm)
+    };
+    encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+}
+
+fn claims(issuer: &str, roles: Vec<&str>) -> Value {
+    json!({
+        "iss": issuer,
+        "sub": "4f1c2a",
+        "email": "test@availproject.org",
+        "realm_access": { "roles": roles },
+        "exp": chrono::Utc::now().timestamp() + 600,
+    })
+}
+
+async fn whoami(http_request: HttpRequest) -> impl Responder {
+    match retrieve_user_id_from_jwt(&http_request) {
+        Some(user) => HttpResponse::Ok().body(user),
+        None => HttpResponse::InternalServerError().finish(),
+    }
+}
+
+async fn call(required_role: &str, token: Option<String>) -> (StatusCode, String) {
+    let provider: Arc<dyn IdentityProvider> = Arc::new(OidcProvider::new(
+        &start_jwks_server(),
+        Some(ISSUER.to_string()),
+        None,
+        None,
+    ));
+    let app = test::init_service(
+        App::new().service(
+            web::scope("/v1")
+                .wrap(IdentityMiddleware::new(
+                    provider,
+                    ClaimMapping::new("email", "realm_access.roles"),
+                    required_role,
+                ))
+                .route("/whoami", web::get().to(whoami))
+                .service(
+                    web::resource("/fund_user")
+                        .wrap(RequirePermission::new(AdminPermission::FinanceGrant))
+                        .route(web::get().to(whoami)),
+                ),
+        ),
+    )
+    .await;
+
+    let uri = match required_role {
+        "admin" => "/v1/fund_user",
+        _ => "/v1/whoami",
+    };
+    let mut req = test::TestRequest::get().uri(uri);
+    if let Some(token) = token {
+        req = req.insert_header(("Authorization", format!("Bearer {}", token)));
+    }
+    match test::try_call_service(&app, req.to_request()).await {
+        Ok(response) => {
+            let status = response.status();
+            let body = test::read_body(response).await;
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+        Err(e) => (e.as_response_error().status_code(), e.to_string()),
+    }
+}
+
+#[test]
+async fn test_oidc_token_is_mapped_to_identity() {
+    let (status, body) = call("member", Some(token(claims(ISSUER, vec!["member"])))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "test@availproject.org");
+}
+
+#[test]
+async fn test_oidc_token_from_other_issuer_is_rejected() {
+    let (status, _) = call(
+        "member",
+        Some(token(claims("https://other.example.com", vec!["member"]))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+async fn test_oidc_token_signed_with_other_algorithm_is_rejected() {
+    let (status, _) = call(
+        "member",
+        Some(signed_token(
+            Algorithm::HS384,
+            claims(ISSUER, vec!["member"]),
+        )),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+async fn test_identity_without_required_role_is_rejected() {
+    let (status, _) = call("admin", Some(token(claims(ISSUER, vec!["member"])))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+async fn test_request_without_token_is_rejected() {
+    let (status, _) = call("member", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+async fn test_claim_mapping_accepts_single_role() {
+    let claims = json!({ "user_email": "test@availproject.org", "role": "admin" });
+    let identity = ClaimMapping::new("user_email", "role")
+        .identity(claims.as_object().unwrap())
+        .unwrap();
+    assert_eq!(identity.user_id, "test@availproject.org");
+    assert_eq!(identity.roles, vec!["admin".to_string()]);
+}
+
+#[test]
+async fn test_admin_without_route_permission_is_forbidden() {
+    let (status, _) = call(
+        "admin",
+        Some(token(claims(ISSUER, vec!["admin", "support-read"]))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[test]
+async fn test_admin_with_route_permission_is_allowed() {
+    let (status, body) = call(
+        "admin",
+        Some(token(claims(ISSUER, vec!["admin", "finance-grant"]))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "test@availproject.org");
+}
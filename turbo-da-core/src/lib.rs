@@ -1,2 +1,9 @@
+pub mod chain;
+pub mod deposit;
+pub mod identity;
 pub mod logger;
+pub mod price_oracle;
+pub mod quote;
+pub mod treasury;
 pub mod utils;
+pub mod withdrawal;
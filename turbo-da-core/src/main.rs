@@ -2,12 +2,18 @@
 /// Customer send the money to us in any ERC20 token, and we fund there user account with equivalant avail.
 /// Customer then directly sends all the payload, in either JSON format or directly as bytes.
 /// The service generates the extrinsic and published it to Avail network.
+pub mod chain;
 pub mod config;
 pub mod controllers;
+pub mod deposit;
+pub mod identity;
 pub mod logger;
+pub mod price_oracle;
+pub mod quote;
 pub mod routes;
 pub mod s3;
 pub mod utils;
+pub mod withdrawal;
 
 use crate::controllers::{
     customer_expenditure::get_all_expenditure,
@@ -23,35 +29,54 @@ use actix_web::{
     dev::Service,
     middleware::Logger,
     web::{self},
-    App, HttpMessage, HttpServer,
+    App, HttpServer,
 };
 use config::AppConfig;
 use controllers::{
+    approvals::{approve_action, get_approvals, reject_action},
+    audit::{get_all_audit_events, get_user_audit_events},
     customer_expenditure::{get_expenditure_by_time_range, get_wallet_usage, reset_retry_count},
+    deposits::{attribute_deposit, get_unmatched_deposit_list, refund_deposit},
     file::{download_file, upload_file},
     fund::{
-        add_inclusion_details, estimate_credits_against_size, estimate_credits_against_token,
-        fund_user, get_fund_list, purchase_cost, register_credit_request,
+        add_inclusion_details, convert_balance_to_usd, estimate_credits_against_size,
+        estimate_credits_against_token, fund_user, get_deposit_address, get_fund_list,
+        purchase_cost, register_credit_request,
     },
     misc::indexer_status,
+    organisations::{
+        add_member, create_new_organisation, get_members, get_organisations, remove_member,
+        update_member,
+    },
+    reconciliation::{get_fee_mismatch_list, get_fee_reconciliation},
+    tokens::{
+        add_supported_token, get_supported_token_list, remove_supported_token,
+        update_supported_token_details,
+    },
     users::{
-        allocate_credit, delete_account, delete_api_key, edit_app_account, generate_api_key,
-        generate_app_account, get_all_apps, get_api_keys, get_apps, reclaim_credits,
+        allocate_credit, delete_account, delete_api_key, delete_signing_key, edit_app_account,
+        generate_api_key, generate_app_account, get_all_apps, get_api_keys, get_apps,
+        get_signing_keys, reclaim_credits, register_signing_key,
+    },
+    withdrawals::{
+        get_all_withdrawals, get_user_withdrawals, reject_pending_withdrawal, request_withdrawal,
+        sign_pending_withdrawal,
     },
 };
 
-use clerk_rs::{
-    clerk::Clerk,
-    validators::{actix::ClerkMiddleware, authorizer::ClerkJwt, jwks::MemoryCacheJwksProvider},
-    ClerkConfiguration,
-};
 use diesel_async::{
     pooled_connection::{deadpool::Pool, AsyncDieselConnectionManager},
     AsyncPgConnection,
 };
+use identity::{
+    clerk::ClerkProvider, oidc::OidcProvider, ClaimMapping, IdentityMiddleware, IdentityProvider,
+};
 use logger::{info, warn};
 use observability::init_tracer;
+use price_oracle::PriceFeed;
 use routes::health::health_check;
+use std::sync::Arc;
+use utils::TrustedProxies;
 
 #[actix_web::main]
 async fn main() -> Result<(), std::io::Error> {
@@ -71,17 +96,35 @@ async fn main() -> Result<(), std::io::Error> {
 
     let shared_pool = web::Data::new(pool);
 
+    let identity_provider: Arc<dyn IdentityProvider> = match app_config.identity_provider.as_str() {
+        "clerk" => Arc::new(ClerkProvider::new(&app_config.clerk_secret_key)),
+        "oidc" => Arc::new(OidcProvider::new(
+            &app_config.oidc_jwks_url,
+            app_config.oidc_issuer.clone(),
+            app_config.oidc_audience.clone(),
+            app_config.oidc_algorithm,
+        )),
+        provider => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Unknown identity provider {}", provider),
+            ))
+        }
+    };
+    let claim_mapping = ClaimMapping::new(&app_config.user_id_claim, &app_config.roles_claim);
+
+    let price_feed = PriceFeed::from_config(
+        &app_config.coingecko_api_url,
+        &app_config.coingecko_api_key,
+        &app_config.price_oracle,
+    )
+    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let shared_price_feed = web::Data::from(price_feed);
+
+    let trusted_proxies = web::Data::new(TrustedProxies(app_config.trusted_proxies.clone()));
     let shared_config = web::Data::new(app_config);
 
     HttpServer::new(move || {
-        let config = ClerkConfiguration::new(
-            None,
-            None,
-            Some(shared_config.clerk_secret_key.clone()),
-            None,
-        );
-        let clerk = Clerk::new(config);
-
         App::new()
             .service(health_check)
             .wrap_fn(|req, srv| {
@@ -113,35 +156,18 @@ async fn main() -> Result<(), std::io::Error> {
             .wrap(Cors::permissive())
             .app_data(shared_config.clone())
             .app_data(shared_pool.clone())
+            .app_data(shared_price_feed.clone())
+            .app_data(trusted_proxies.clone())
             .wrap(Logger::default())
             .service(
                 web::scope("/v1")
                     .service(get_token_map)
                     .service(
                         web::scope("/user")
-                            .wrap_fn(|req, srv| {
-                                let jwt = req.extensions_mut().get::<ClerkJwt>().cloned();
-                                let fut = srv.call(req);
-                                async move {
-                                    let res = fut.await?;
-                                    if let Some(jwt) = jwt {
-                                        if !jwt.other.get("role").map_or(false, |r| r == "member") {
-                                            return Err(actix_web::error::ErrorUnauthorized(
-                                                "Unauthorized",
-                                            ));
-                                        }
-                                    } else {
-                                        return Err(actix_web::error::ErrorUnauthorized(
-                                            "Invalid Authorization header",
-                                        ));
-                                    }
-                                    Ok(res)
-                                }
-                            })
-                            .wrap(ClerkMiddleware::new(
-                                MemoryCacheJwksProvider::new(clerk.clone()),
-                                None,
-                                true,
+                            .wrap(IdentityMiddleware::new(
+                                identity_provider.clone(),
+                                claim_mapping.clone(),
+                                "member",
                             ))
                             .service(get_user)
                             .service(get_all_expenditure)
@@ -150,6 +176,9 @@ async fn main() -> Result<(), std::io::Error> {
                             .service(generate_api_key)
                             .service(delete_api_key)
                             .service(get_api_keys)
+                            .service(register_signing_key)
+                            .service(get_signing_keys)
+                            .service(delete_signing_key)
                             .service(update_app_id)
                             .service(purchase_cost)
                             .service(estimate_credits_for_bytes)
@@ -165,45 +194,53 @@ async fn main() -> Result<(), std::io::Error> {
                             .service(get_expenditure_by_time_range)
                             .service(get_apps)
                             .service(get_fund_list)
+                            .service(get_deposit_address)
                             .service(reclaim_credits)
                             .service(estimate_credits_against_token)
                             .service(add_inclusion_details)
                             .service(get_wallet_usage)
                             .service(generate_access_token)
-                            .service(toggle_encryption),
+                            .service(toggle_encryption)
+                            .service(create_new_organisation)
+                            .service(get_organisations)
+                            .service(get_members)
+                            .service(add_member)
+                            .service(update_member)
+                            .service(remove_member)
+                            .service(get_user_audit_events)
+                            .service(request_withdrawal)
+                            .service(get_user_withdrawals)
+                            .service(convert_balance_to_usd),
                     )
                     .service(
                         web::scope("/admin")
-                            .wrap_fn(|req, srv| {
-                                let jwt = req.extensions_mut().get::<ClerkJwt>().cloned();
-                                let fut = srv.call(req);
-                                async move {
-                                    let res = fut.await?;
-                                    if let Some(jwt) = jwt {
-                                        if !jwt.other.get("role").map_or(false, |r| r == "admin") {
-                                            return Err(actix_web::error::ErrorUnauthorized(
-                                                "Unauthorized",
-                                            ));
-                                        }
-                                    } else {
-                                        return Err(actix_web::error::ErrorUnauthorized(
-                                            "Invalid Authorization header",
-                                        ));
-                                    }
-                                    Ok(res)
-                                }
-                            })
-                            .wrap(ClerkMiddleware::new(
-                                MemoryCacheJwksProvider::new(clerk.clone()),
-                                None,
-                                true,
+                            .wrap(IdentityMiddleware::new(
+                                identity_provider.clone(),
+                                claim_mapping.clone(),
+                                "admin",
                             ))
                             .service(get_all_users)
                             .service(get_all_apps)
                             .service(get_all_fund_requests)
                             .service(fund_user)
                             .service(indexer_status)
-                            .service(reset_retry_count),
+                            .service(reset_retry_count)
+                            .service(get_all_audit_events)
+                            .service(get_approvals)
+                            .service(approve_action)
+                            .service(reject_action)
+                            .service(get_unmatched_deposit_list)
+                            .service(attribute_deposit)
+                            .service(refund_deposit)
+                            .service(get_supported_token_list)
+                            .service(add_supported_token)
+                            .service(update_supported_token_details)
+                            .service(remove_supported_token)
+                            .service(get_all_withdrawals)
+                            .service(sign_pending_withdrawal)
+                            .service(reject_pending_withdrawal)
+                            .service(get_fee_reconciliation)
+                            .service(get_fee_mismatch_list),
                     ),
             )
     })
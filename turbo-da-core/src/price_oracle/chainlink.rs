@@ -0,0 +1,91 @@
+use super::{PriceOracle, PriceQuote};
+use alloy::{
+    primitives::Address,
+    providers::{ProviderBuilder, RootProvider},
+    sol,
+    transports::http::{Client, Http},
+};
+use bigdecimal::{num_bigint::BigInt, BigDecimal};
+use chrono::DateTime;
+use futures::future::BoxFuture;
+use std::{collections::HashMap, str::FromStr};
+
+sol! {
+    #[sol(rpc)]
+    interface AggregatorV3Interface {
+        function decimals() external view returns (uint8);
+        function latestRoundData() external view returns (
+            uint80 roundId,
+            int256 answer,
+            uint256 startedAt,
+            uint256 updatedAt,
+            uint80 answeredInRound
+        );
+    }
+}
+
+/// Prices of Chainlink USD feeds read on chain
+pub struct ChainlinkOracle {
+    provider: RootProvider<Http<Client>>,
+    feeds: HashMap<String, Address>,
+}
+
+impl ChainlinkOracle {
+    pub fn new(rpc_url: &str, feeds: &HashMap<String, String>) -> Result<Self, String> {
+        let url = rpc_url
+            .parse()
+            .map_err(|e| format!("Invalid Chainlink RPC url {}: {:?}", rpc_url, e))?;
+        let feeds = feeds
+            .iter()
+            .map(|(token, feed)| {
+                Address::from_str(feed)
+                    .map(|feed| (token.clone(), feed))
+                    .map_err(|e| format!("Invalid Chainlink feed for {}: {}", token, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(ChainlinkOracle {
+            provider: ProviderBuilder::new().on_http(url),
+            feeds,
+        })
+    }
+}
+
+impl PriceOracle for ChainlinkOracle {
+    fn name(&self) -> &'static str {
+        "chainlink"
+    }
+
+    fn usd_price<'a>(&'a self, token: &'a str) -> BoxFuture<'a, Result<PriceQuote, String>> {
+        Box::pin(async move {
+            let feed = self
+                .feeds
+                .get(token)
+                .ok_or_else(|| format!("No Chainlink feed for {}", token))?;
+            let aggregator = AggregatorV3Interface::new(*feed, &self.provider);
+
+            let decimals = aggregator
+                .decimals()
+                .call()
+                .await
+                .map_err(|e| e.to_string())?
+                ._0;
+            let round = aggregator
+                .latestRoundData()
+                .call()
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let answer = BigInt::from_str(&round.answer.to_string()).map_err(|e| e.to_string())?;
+            let updated_at = i64::try_from(round.updatedAt)
+                .ok()
+                .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+                .ok_or_else(|| format!("Invalid update time of the {} feed", token))?;
+
+            Ok(PriceQuote {
+                usd: BigDecimal::new(answer, decimals as i64),
+                updated_at,
+                source: self.name(),
+            })
+        })
+    }
+}
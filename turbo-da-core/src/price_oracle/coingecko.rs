@@ -0,0 +1,83 @@
+use super::{PriceOracle, PriceOracleConfig, PriceQuote};
+use bigdecimal::BigDecimal;
+use chrono::DateTime;
+use futures::future::BoxFuture;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::Number;
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+#[derive(Deserialize)]
+struct SimplePrice {
+    usd: Option<Number>,
+    last_updated_at: Option<i64>,
+}
+
+/// Prices of the CoinGecko `simple/price` endpoint
+pub struct CoinGeckoOracle {
+    client: Client,
+    api_url: String,
+    api_key: String,
+}
+
+impl CoinGeckoOracle {
+    pub fn new(api_url: &str, api_key: &str, config: &PriceOracleConfig) -> Result<Self, String> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(CoinGeckoOracle {
+            client,
+            api_url: api_url.to_string(),
+            api_key: api_key.to_string(),
+        })
+    }
+}
+
+impl PriceOracle for CoinGeckoOracle {
+    fn name(&self) -> &'static str {
+        "coingecko"
+    }
+
+    fn usd_price<'a>(&'a self, token: &'a str) -> BoxFuture<'a, Result<PriceQuote, String>> {
+        Box::pin(async move {
+            let params = [
+                ("ids", token),
+                ("vs_currencies", "usd"),
+                ("include_last_updated_at", "true"),
+            ];
+            let prices = self
+                .client
+                .get(&self.api_url)
+                .query(&params)
+                .header("accept", "application/json")
+                .header("x-cg-pro-api-key", &self.api_key)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| e.to_string())?
+                .json::<HashMap<String, SimplePrice>>()
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let price = prices
+                .get(token)
+                .ok_or_else(|| format!("{} price not found", token))?;
+            let usd = price
+                .usd
+                .as_ref()
+                .ok_or_else(|| format!("{} price not found", token))?;
+            let updated_at = price
+                .last_updated_at
+                .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+                .ok_or_else(|| format!("{} price has no update time", token))?;
+
+            Ok(PriceQuote {
+                // The decimal representation of the response, not the nearest float
+                usd: BigDecimal::from_str(&usd.to_string()).map_err(|e| e.to_string())?,
+                updated_at,
+                source: self.name(),
+            })
+        })
+    }
+}
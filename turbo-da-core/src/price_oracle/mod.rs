@@ -0,0 +1,231 @@
+/// USD prices of the tokens deposits are accepted in
+/// - `PriceOracle` is a single source of prices, e.g. CoinGecko or a Chainlink feed
+/// - `PriceFeed` queries every configured source, rejects stale quotes and sources that disagree,
+///   and caches the price it settles on
+pub mod chainlink;
+pub mod coingecko;
+#[cfg(test)]
+mod test;
+
+use crate::logger::warn;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use futures::future::{join_all, BoxFuture};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    env,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// USD price of a token as reported by a source
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PriceQuote {
+    pub usd: BigDecimal,
+    /// Time the source last updated the price
+    pub updated_at: DateTime<Utc>,
+    pub source: &'static str,
+}
+
+/// Source of token prices, tokens are identified by their CoinGecko id, e.g. `ethereum`
+pub trait PriceOracle: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn usd_price<'a>(&'a self, token: &'a str) -> BoxFuture<'a, Result<PriceQuote, String>>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceOracleConfig {
+    /// Time a price is served from the cache
+    #[serde(default = "default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+    /// Quotes last updated longer ago are rejected
+    #[serde(default = "default_max_staleness_secs")]
+    pub max_staleness_secs: u64,
+    /// Maximum spread between the sources, in basis points of the lowest quote
+    #[serde(default = "default_max_deviation_bps")]
+    pub max_deviation_bps: u64,
+    /// Timeout of a single request to a source
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// RPC endpoint of the chain the Chainlink feeds are read from
+    #[serde(default)]
+    pub chainlink_rpc_url: Option<String>,
+    /// Chainlink USD feed of each token, keyed by CoinGecko id
+    #[serde(default)]
+    pub chainlink_feeds: HashMap<String, String>,
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    60
+}
+
+fn default_max_staleness_secs() -> u64 {
+    3600
+}
+
+fn default_max_deviation_bps() -> u64 {
+    200
+}
+
+fn default_request_timeout_secs() -> u64 {
+    10
+}
+
+impl Default for PriceOracleConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl_secs: default_cache_ttl_secs(),
+            max_staleness_secs: default_max_staleness_secs(),
+            max_deviation_bps: default_max_deviation_bps(),
+            request_timeout_secs: default_request_timeout_secs(),
+            chainlink_rpc_url: None,
+            chainlink_feeds: HashMap::new(),
+        }
+    }
+}
+
+impl PriceOracleConfig {
+    /// Reads `PRICE_*` and `CHAINLINK_*` variables, falling back to the defaults
+    pub fn from_env() -> Self {
+        let parse = |key: &str, default: fn() -> u64| {
+            env::var(key)
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or_else(default)
+        };
+        let chainlink_feeds = chainlink_feeds(env::vars());
+
+        Self {
+            cache_ttl_secs: parse("PRICE_CACHE_TTL_SECS", default_cache_ttl_secs),
+            max_staleness_secs: parse("PRICE_MAX_STALENESS_SECS", default_max_staleness_secs),
+            max_deviation_bps: parse("PRICE_MAX_DEVIATION_BPS", default_max_deviation_bps),
+            request_timeout_secs: parse("PRICE_REQUEST_TIMEOUT_SECS", default_request_timeout_secs),
+            chainlink_rpc_url: env::var("CHAINLINK_RPC_URL").ok().filter(|s| !s.is_empty()),
+            chainlink_feeds,
+        }
+    }
+}
+
+/// Chainlink feeds set as `CHAINLINK_FEED_<TOKEN>` variables, keyed by CoinGecko id
+///
+/// `<TOKEN>` is the id in upper case with `-` written as `_`, e.g. `CHAINLINK_FEED_USD_COIN` for
+/// `usd-coin`. Empty variables are ignored.
+pub fn chainlink_feeds(vars: impl Iterator<Item = (String, String)>) -> HashMap<String, String> {
+    vars.filter(|(_, value)| !value.is_empty())
+        .filter_map(|(key, value)| {
+            key.strip_prefix("CHAINLINK_FEED_")
+                .map(|token| (token.to_lowercase().replace('_', "-"), value))
+        })
+        .collect()
+}
+
+/// Prices checked across sources and cached
+///
+/// Sources are listed by priority, the price of the first fresh quote is used once every fresh
+/// quote agrees with it within `max_deviation_bps`. A failing source is skipped as long as another
+/// one answers.
+pub struct PriceFeed {
+    sources: Vec<Box<dyn PriceOracle>>,
+    cache: Mutex<HashMap<String, (Instant, PriceQuote)>>,
+    cache_ttl: Duration,
+    max_staleness: chrono::Duration,
+    max_deviation_bps: u64,
+    request_timeout: Duration,
+}
+
+impl PriceFeed {
+    pub fn new(sources: Vec<Box<dyn PriceOracle>>, config: &PriceOracleConfig) -> Self {
+        PriceFeed {
+            sources,
+            cache: Mutex::new(HashMap::new()),
+            cache_ttl: Duration::from_secs(config.cache_ttl_secs),
+            max_staleness: chrono::Duration::seconds(config.max_staleness_secs as i64),
+            max_deviation_bps: config.max_deviation_bps,
+            request_timeout: Duration::from_secs(config.request_timeout_secs),
+        }
+    }
+
+    /// CoinGecko, backed by Chainlink when feeds are configured
+    pub fn from_config(
+        coingecko_api_url: &str,
+        coingecko_api_key: &str,
+        config: &PriceOracleConfig,
+    ) -> Result<Arc<Self>, String> {
+        let mut sources: Vec<Box<dyn PriceOracle>> = vec![Box::new(
+            coingecko::CoinGeckoOracle::new(coingecko_api_url, coingecko_api_key, config)?,
+        )];
+        if let Some(rpc_url) = &config.chainlink_rpc_url {
+            sources.push(Box::new(chainlink::ChainlinkOracle::new(
+                rpc_url,
+                &config.chainlink_feeds,
+            )?));
+        }
+        Ok(Arc::new(PriceFeed::new(sources, config)))
+    }
+
+    pub async fn usd_price(&self, token: &str) -> Result<PriceQuote, String> {
+        if let Some(quote) = self.cached(token) {
+            return Ok(quote);
+        }
+
+        let quotes = join_all(self.sources.iter().map(|source| async move {
+            let quote = tokio::time::timeout(self.request_timeout, source.usd_price(token))
+                .await
+                .unwrap_or_else(|_| Err("Request timed out".to_string()));
+            (source.name(), quote)
+        }))
+        .await;
+
+        let now = Utc::now();
+        let mut fresh = Vec::new();
+        for (source, quote) in quotes {
+            match quote {
+                Ok(quote) if quote.usd <= BigDecimal::from(0) => warn(&format!(
+                    "Invalid {} price from {}: {}",
+                    token, source, quote.usd
+                )),
+                Ok(quote) if now - quote.updated_at <= self.max_staleness => fresh.push(quote),
+                Ok(quote) => warn(&format!(
+                    "Stale {} price from {}, last updated at {}",
+                    token, source, quote.updated_at
+                )),
+                Err(e) => warn(&format!(
+                    "Failed to get {} price from {}: {}",
+                    token, source, e
+                )),
+            }
+        }
+
+        let Some(quote) = fresh.first().cloned() else {
+            return Err(format!("No fresh {} price available", token));
+        };
+        if let Some(deviation) = self.deviation_bps(&fresh) {
+            return Err(format!(
+                "{} prices deviate by {} bps across sources",
+                token, deviation
+            ));
+        }
+
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(token.to_string(), (Instant::now(), quote.clone()));
+        Ok(quote)
+    }
+
+    fn cached(&self, token: &str) -> Option<PriceQuote> {
+        let cache = self.cache.lock().unwrap();
+        let (fetched_at, quote) = cache.get(token)?;
+        (fetched_at.elapsed() < self.cache_ttl).then(|| quote.clone())
+    }
+
+    /// Spread of the quotes in basis points, `None` when it is within `max_deviation_bps`
+    fn deviation_bps(&self, quotes: &[PriceQuote]) -> Option<BigDecimal> {
+        let low = quotes.iter().map(|quote| &quote.usd).min()?;
+        let high = quotes.iter().map(|quote| &quote.usd).max()?;
+        let deviation = ((high - low) * BigDecimal::from(10_000) / low).round(2);
+        (deviation > BigDecimal::from(self.max_deviation_bps)).then_some(deviation)
+    }
+}
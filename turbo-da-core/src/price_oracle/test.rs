@@ -0,0 +1,185 @@
+use super::{chainlink_feeds, PriceFeed, PriceOracle, PriceOracleConfig, PriceQuote};
+use crate::utils::{convert_avail_to_usd, convert_to_avail, convert_to_usd, credit_usd_value};
+use bigdecimal::BigDecimal;
+use chrono::{Duration, Utc};
+use futures::future::BoxFuture;
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// Source answering every token with the same price
+struct FixedOracle {
+    name: &'static str,
+    /// Price and its age in seconds, `None` for a failing source
+    price: Option<(&'static str, i64)>,
+    calls: Arc<AtomicUsize>,
+}
+
+impl FixedOracle {
+    fn new(name: &'static str, price: Option<(&'static str, i64)>) -> Box<Self> {
+        Box::new(FixedOracle {
+            name,
+            price,
+            calls: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+}
+
+impl PriceOracle for FixedOracle {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn usd_price<'a>(&'a self, _token: &'a str) -> BoxFuture<'a, Result<PriceQuote, String>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move {
+            let (usd, age) = self.price.ok_or_else(|| "Source down".to_string())?;
+            Ok(PriceQuote {
+                usd: BigDecimal::from_str(usd).unwrap(),
+                updated_at: Utc::now() - Duration::seconds(age),
+                source: self.name,
+            })
+        })
+    }
+}
+
+fn decimal(value: &str) -> BigDecimal {
+    BigDecimal::from_str(value).unwrap()
+}
+
+#[tokio::test]
+async fn test_first_source_is_used_when_sources_agree() {
+    let feed = PriceFeed::new(
+        vec![
+            FixedOracle::new("primary", Some(("2.00", 0))),
+            FixedOracle::new("secondary", Some(("2.03", 0))),
+        ],
+        &PriceOracleConfig::default(),
+    );
+
+    let quote = feed.usd_price("ethereum").await.unwrap();
+
+    assert_eq!(quote.usd, decimal("2.00"));
+    assert_eq!(quote.source, "primary");
+}
+
+#[tokio::test]
+async fn test_failing_source_falls_back() {
+    let feed = PriceFeed::new(
+        vec![
+            FixedOracle::new("primary", None),
+            FixedOracle::new("secondary", Some(("2.5", 0))),
+        ],
+        &PriceOracleConfig::default(),
+    );
+
+    let quote = feed.usd_price("ethereum").await.unwrap();
+
+    assert_eq!(quote.usd, decimal("2.5"));
+    assert_eq!(quote.source, "secondary");
+}
+
+#[tokio::test]
+async fn test_stale_quote_is_rejected() {
+    let config = PriceOracleConfig {
+        max_staleness_secs: 60,
+        ..PriceOracleConfig::default()
+    };
+    let feed = PriceFeed::new(vec![FixedOracle::new("primary", Some(("2", 120)))], &config);
+
+    assert!(feed.usd_price("ethereum").await.is_err());
+}
+
+#[tokio::test]
+async fn test_deviating_sources_are_rejected() {
+    let feed = PriceFeed::new(
+        vec![
+            FixedOracle::new("primary", Some(("2.00", 0))),
+            FixedOracle::new("secondary", Some(("2.10", 0))),
+        ],
+        &PriceOracleConfig::default(),
+    );
+
+    let error = feed.usd_price("ethereum").await.unwrap_err();
+
+    assert!(error.contains("500"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_price_is_cached() {
+    let source = FixedOracle::new("primary", Some(("2", 0)));
+    let calls = source.calls.clone();
+    let feed = PriceFeed::new(vec![source], &PriceOracleConfig::default());
+
+    feed.usd_price("ethereum").await.unwrap();
+    feed.usd_price("ethereum").await.unwrap();
+    feed.usd_price("avail").await.unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_conversion_is_exact() {
+    // 1.5 tokens with 6 decimals at $1, AVAIL at $0.07 and 18 decimals
+    let amount = convert_to_avail(&decimal("1500000"), &decimal("1"), 6, &decimal("0.07"), 18);
+
+    assert_eq!(amount, decimal("21428571428571428571"));
+}
+
+#[test]
+fn test_usd_conversion_rounds_down() {
+    // 1.5 tokens with 6 decimals at $0.999
+    assert_eq!(
+        convert_to_usd(&decimal("1500000"), &decimal("0.999"), 6),
+        decimal("1.4985")
+    );
+    // Less than the smallest USD amount held
+    assert_eq!(
+        convert_to_usd(&decimal("1"), &decimal("1"), 18),
+        decimal("0")
+    );
+}
+
+#[test]
+fn test_fee_in_usd_rounds_up() {
+    // 0.001 AVAIL at $0.07
+    assert_eq!(
+        convert_avail_to_usd(&decimal("1e15"), &decimal("0.07")),
+        decimal("0.00007")
+    );
+    // A fee is never billed as nothing
+    assert_eq!(
+        convert_avail_to_usd(&decimal("1"), &decimal("0.07")),
+        decimal("0.00000001")
+    );
+}
+
+#[test]
+fn test_credit_usd_value() {
+    // 0.1 AVAIL per KB at $0.07 makes a byte worth $0.0000068359375
+    assert_eq!(
+        credit_usd_value(&decimal("1e17"), &decimal("1024"), &decimal("0.07")),
+        decimal("0.0000068359375")
+    );
+}
+
+#[test]
+fn test_chainlink_feeds_map_hyphenated_ids() {
+    let vars = [
+        ("CHAINLINK_FEED_ETHEREUM", "0x5f4e"),
+        ("CHAINLINK_FEED_USD_COIN", "0x8fff"),
+        ("CHAINLINK_FEED_TETHER", ""),
+        ("CHAINLINK_RPC_URL", "http://localhost:8545"),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()));
+
+    let feeds = chainlink_feeds(vars);
+    assert_eq!(feeds.len(), 2);
+    assert_eq!(feeds["ethereum"], "0x5f4e");
+    assert_eq!(feeds["usd-coin"], "0x8fff");
+}
@@ -0,0 +1,143 @@
+/// Quotes locking in the credits of a token purchase
+/// - `register_credit_request` prices the announced token and amount and signs the quote
+/// - A deposit of the quoted token and amount made before the quote expires is credited the
+///   quoted credits by `apply_quote`, any other deposit is repriced
+#[cfg(test)]
+mod test;
+
+use crate::logger::warn_json;
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use db::models::{
+    credit_requests::{CreditQuote, CreditRequestQuote, QuoteOutcome},
+    supported_tokens::normalize_token_address,
+};
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Credit request a quote is issued for
+pub struct QuoteSubject<'a> {
+    pub request_id: i32,
+    pub user_id: &'a str,
+    pub chain_id: i32,
+}
+
+fn quote_mac(key: &str, subject: &QuoteSubject, quote: &CreditQuote) -> Result<HmacSha256, String> {
+    let mut mac =
+        HmacSha256::new_from_slice(key.as_bytes()).map_err(|e| format!("Invalid key: {}", e))?;
+
+    let message = format!(
+        "{}:{}:{}:{}:{}:{}:{}",
+        subject.request_id,
+        subject.user_id,
+        subject.chain_id,
+        normalize_token_address(&quote.token_address),
+        quote.amount.normalized(),
+        quote.credits.normalized(),
+        quote.expires_at.and_utc().timestamp()
+    );
+    mac.update(message.as_bytes());
+    Ok(mac)
+}
+
+/// Hex encoded signature of a quote, the `signature` field of `quote` is ignored
+pub fn sign_quote(
+    key: &str,
+    subject: &QuoteSubject,
+    quote: &CreditQuote,
+) -> Result<String, String> {
+    Ok(hex::encode(
+        quote_mac(key, subject, quote)?.finalize().into_bytes(),
+    ))
+}
+
+pub fn verify_quote(key: &str, subject: &QuoteSubject, quote: &CreditQuote) -> bool {
+    let Ok(signature) = hex::decode(&quote.signature) else {
+        return false;
+    };
+    quote_mac(key, subject, quote)
+        .map(|mac| mac.verify_slice(&signature).is_ok())
+        .unwrap_or(false)
+}
+
+/// Decides whether a deposit of `amount` of `token_address` on `chain_id` made at `deposited_at`
+/// is credited the quoted credits
+///
+/// `deposited_at` is the time of the block the deposit is in, a deposit made in time is honoured
+/// however late it is processed.
+pub fn assess_quote(
+    key: &str,
+    subject: &QuoteSubject,
+    quote: &CreditQuote,
+    chain_id: i32,
+    token_address: &str,
+    amount: &BigDecimal,
+    deposited_at: NaiveDateTime,
+) -> QuoteOutcome {
+    if !verify_quote(key, subject, quote) {
+        QuoteOutcome::Invalid
+    } else if deposited_at > quote.expires_at {
+        QuoteOutcome::Expired
+    } else if chain_id != subject.chain_id
+        || normalize_token_address(&quote.token_address) != normalize_token_address(token_address)
+        || amount != &quote.amount
+    {
+        QuoteOutcome::Mismatched
+    } else {
+        QuoteOutcome::Honoured
+    }
+}
+
+/// Credits of a deposit against the quote of its credit request
+///
+/// A deposit matching a valid, unexpired quote is credited the quoted credits, any other deposit
+/// keeps the `credits` it was repriced at. Returns the credits with, for a quoted request, the
+/// outcome of the quote and the repriced credits minus the quoted credits. Quotes are `invalid`
+/// without a signing key.
+pub fn apply_quote(
+    key: Option<&str>,
+    request: &CreditRequestQuote,
+    chain_id: i32,
+    token_address: &str,
+    amount_paid: &BigDecimal,
+    credits: BigDecimal,
+    deposited_at: NaiveDateTime,
+) -> (BigDecimal, Option<(QuoteOutcome, BigDecimal)>) {
+    let Some(quote) = request.quote() else {
+        return (credits, None);
+    };
+
+    let outcome = match key {
+        Some(key) => assess_quote(
+            key,
+            &QuoteSubject {
+                request_id: request.id,
+                user_id: &request.user_id,
+                chain_id: request.chain_id.unwrap_or_default(),
+            },
+            &quote,
+            chain_id,
+            token_address,
+            amount_paid,
+            deposited_at,
+        ),
+        None => QuoteOutcome::Invalid,
+    };
+    let difference = &credits - &quote.credits;
+    if outcome == QuoteOutcome::Honoured {
+        return (quote.credits, Some((outcome, difference)));
+    }
+
+    warn_json(json!({
+        "message": "Deposit repriced against its quote",
+        "credit_request_id": request.id,
+        "outcome": outcome.as_str(),
+        "quoted_credits": quote.credits,
+        "credits": credits,
+        "level": "warn"
+    }));
+    (credits, Some((outcome, difference)))
+}
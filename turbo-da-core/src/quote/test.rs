@@ -0,0 +1,136 @@
+use super::{assess_quote, sign_quote, verify_quote, QuoteSubject};
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDateTime, Utc};
+use db::models::credit_requests::{CreditQuote, QuoteOutcome};
+use std::str::FromStr;
+
+const KEY: &str = "quote-signing-key";
+const TOKEN: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+fn subject() -> QuoteSubject<'static> {
+    QuoteSubject {
+        request_id: 42,
+        user_id: "user@example.com",
+        chain_id: 1,
+    }
+}
+
+fn signed_quote(expires_at: NaiveDateTime) -> CreditQuote {
+    let mut quote = CreditQuote {
+        token_address: TOKEN.to_string(),
+        amount: BigDecimal::from(1_000_000),
+        credits: BigDecimal::from_str("153.125").unwrap(),
+        expires_at,
+        signature: String::new(),
+    };
+    quote.signature = sign_quote(KEY, &subject(), &quote).unwrap();
+    quote
+}
+
+#[test]
+fn test_signature_binds_quote_and_request() {
+    let quote = signed_quote(Utc::now().naive_utc());
+    assert!(verify_quote(KEY, &subject(), &quote));
+    assert!(!verify_quote("other-key", &subject(), &quote));
+
+    let other_request = QuoteSubject {
+        request_id: 43,
+        ..subject()
+    };
+    assert!(!verify_quote(KEY, &other_request, &quote));
+
+    let inflated = CreditQuote {
+        credits: BigDecimal::from(1000),
+        ..quote.clone()
+    };
+    assert!(!verify_quote(KEY, &subject(), &inflated));
+
+    let garbled = CreditQuote {
+        signature: "not hex".to_string(),
+        ..quote
+    };
+    assert!(!verify_quote(KEY, &subject(), &garbled));
+}
+
+#[test]
+fn test_signature_survives_database_roundtrip() {
+    // NUMERIC columns may return the amount with a different scale
+    let quote = signed_quote(Utc::now().naive_utc());
+    let stored = CreditQuote {
+        amount: BigDecimal::from_str("1000000.000").unwrap(),
+        token_address: TOKEN.to_lowercase(),
+        ..quote
+    };
+    assert!(verify_quote(KEY, &subject(), &stored));
+}
+
+#[test]
+fn test_deposit_is_assessed_against_quote() {
+    let now = Utc::now().naive_utc();
+    let quote = signed_quote(now + Duration::minutes(15));
+    let amount = BigDecimal::from(1_000_000);
+
+    let assess = |chain: i32, token: &str, amount: &BigDecimal, at: NaiveDateTime| {
+        assess_quote(KEY, &subject(), &quote, chain, token, amount, at)
+    };
+    assert_eq!(
+        assess(1, &TOKEN.to_lowercase(), &amount, now),
+        QuoteOutcome::Honoured
+    );
+    assert_eq!(
+        assess(1, TOKEN, &amount, now + Duration::minutes(16)),
+        QuoteOutcome::Expired
+    );
+    assert_eq!(
+        assess(1, TOKEN, &BigDecimal::from(999_999), now),
+        QuoteOutcome::Mismatched
+    );
+    assert_eq!(
+        assess(
+            1,
+            "0x0000000000000000000000000000000000000000",
+            &amount,
+            now
+        ),
+        QuoteOutcome::Mismatched
+    );
+    assert_eq!(assess(10, TOKEN, &amount, now), QuoteOutcome::Mismatched);
+
+    let tampered = CreditQuote {
+        credits: BigDecimal::from(1000),
+        ..quote.clone()
+    };
+    assert_eq!(
+        assess_quote(KEY, &subject(), &tampered, 1, TOKEN, &amount, now),
+        QuoteOutcome::Invalid
+    );
+}
+
+#[test]
+fn test_mint_of_a_quote_is_case_sensitive() {
+    let now = Utc::now().naive_utc();
+    let mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    let mut quote = CreditQuote {
+        token_address: mint.to_string(),
+        ..signed_quote(now + Duration::minutes(15))
+    };
+    quote.signature = sign_quote(KEY, &subject(), &quote).unwrap();
+    let amount = BigDecimal::from(1_000_000);
+
+    assert_eq!(
+        assess_quote(KEY, &subject(), &quote, 1, mint, &amount, now),
+        QuoteOutcome::Honoured
+    );
+    assert_eq!(
+        assess_quote(
+            KEY,
+            &subject(),
+            &quote,
+            1,
+            &mint.to_lowercase(),
+            &amount,
+            now
+        ),
+        QuoteOutcome::Mismatched
+    );
+}
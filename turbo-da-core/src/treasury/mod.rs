@@ -0,0 +1,272 @@
+/// Monitoring of the signer accounts paying the AVAIL fees of submissions
+/// - `Signers` holds the signer keys and whether each of them can still pay fees, work is only
+///   routed to funded signers
+/// - `Treasury` periodically reads the free balance of every signer, exports it as a metric,
+///   alerts below the configured thresholds and optionally tops signers up from a treasury key
+/// - Every service holding signers checks their balances, only the data submission service tops
+///   them up so a top up is never sent twice
+#[allow(clippy::module_inception)]
+mod test;
+
+use crate::{
+    logger::{error, info, warn},
+    utils::create_keypair,
+};
+use avail_rust::{prelude::*, Client};
+use observability::{log_signer_low_balance, record_signer_balance};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreasuryConfig {
+    /// Interval between two balance checks
+    #[serde(default = "default_check_interval_secs")]
+    pub check_interval_secs: u64,
+    /// Signers holding less are reported as running low and topped up
+    #[serde(default = "default_warn_balance_avail")]
+    pub warn_balance_avail: u64,
+    /// Signers holding less are underfunded, no work is routed to them
+    #[serde(default = "default_min_balance_avail")]
+    pub min_balance_avail: u64,
+    /// Amount transferred to a signer running low, 0 disables top ups
+    #[serde(default)]
+    pub top_up_amount_avail: u64,
+    /// Hex encoded key of the account signers are topped up from
+    #[serde(default)]
+    pub private_key: Option<String>,
+}
+
+fn default_check_interval_secs() -> u64 {
+    60
+}
+
+fn default_warn_balance_avail() -> u64 {
+    100
+}
+
+fn default_min_balance_avail() -> u64 {
+    10
+}
+
+impl Default for TreasuryConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: default_check_interval_secs(),
+            warn_balance_avail: default_warn_balance_avail(),
+            min_balance_avail: default_min_balance_avail(),
+            top_up_amount_avail: 0,
+            private_key: None,
+        }
+    }
+}
+
+impl TreasuryConfig {
+    /// Reads `TREASURY_*` variables, falling back to the defaults
+    pub fn from_env() -> Self {
+        let parse = |key: &str, default: fn() -> u64| {
+            env::var(key)
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or_else(default)
+        };
+
+        Self {
+            check_interval_secs: parse("TREASURY_CHECK_INTERVAL_SECS", default_check_interval_secs),
+            warn_balance_avail: parse("TREASURY_WARN_BALANCE_AVAIL", default_warn_balance_avail),
+            min_balance_avail: parse("TREASURY_MIN_BALANCE_AVAIL", default_min_balance_avail),
+            top_up_amount_avail: parse("TREASURY_TOP_UP_AMOUNT_AVAIL", || 0),
+            private_key: env::var("TREASURY_PRIVATE_KEY")
+                .ok()
+                .filter(|s| !s.is_empty()),
+        }
+    }
+
+    /// Status of a signer holding `free` AVAIL, in the smallest unit
+    pub fn status(&self, free: u128) -> SignerStatus {
+        if free < self.min_balance_avail as u128 * ONE_AVAIL {
+            SignerStatus::Underfunded
+        } else if free < self.warn_balance_avail as u128 * ONE_AVAIL {
+            SignerStatus::Low
+        } else {
+            SignerStatus::Funded
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignerStatus {
+    Funded,
+    Low,
+    Underfunded,
+}
+
+impl SignerStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignerStatus::Funded => "funded",
+            SignerStatus::Low => "low",
+            SignerStatus::Underfunded => "underfunded",
+        }
+    }
+}
+
+/// Signer keys and whether they can pay the fees of a submission
+///
+/// Signers are considered funded until their balance is first checked.
+pub struct Signers {
+    keypairs: Vec<Keypair>,
+    funded: Vec<AtomicBool>,
+}
+
+impl Signers {
+    pub fn new(keypairs: Vec<Keypair>) -> Self {
+        let funded = keypairs.iter().map(|_| AtomicBool::new(true)).collect();
+        Signers { keypairs, funded }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keypairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keypairs.is_empty()
+    }
+
+    pub fn keypair(&self, index: usize) -> &Keypair {
+        &self.keypairs[index]
+    }
+
+    pub fn is_funded(&self, index: usize) -> bool {
+        self.funded
+            .get(index)
+            .is_some_and(|funded| funded.load(Ordering::Relaxed))
+    }
+
+    pub fn set_funded(&self, index: usize, funded: bool) {
+        self.funded[index].store(funded, Ordering::Relaxed);
+    }
+
+    /// Index of the signer with the hex encoded public key `public_key`
+    pub fn position(&self, public_key: &str) -> Option<usize> {
+        self.keypairs
+            .iter()
+            .position(|keypair| hex::encode(keypair.account_id().0) == public_key)
+    }
+
+    /// Picks a random funded signer, `None` when every signer is underfunded
+    pub fn pick_funded(&self) -> Option<usize> {
+        let funded: Vec<usize> = (0..self.len()).filter(|i| self.is_funded(*i)).collect();
+        funded.choose(&mut rand::thread_rng()).copied()
+    }
+}
+
+/// Watches the balance of the signers and tops them up from the treasury key
+pub struct Treasury {
+    client: Client,
+    signers: Arc<Signers>,
+    config: TreasuryConfig,
+    treasury: Option<Keypair>,
+}
+
+impl Treasury {
+    pub fn new(client: Client, signers: Arc<Signers>, config: TreasuryConfig) -> Self {
+        let treasury = match (&config.private_key, config.top_up_amount_avail) {
+            (Some(key), amount) if amount > 0 => Some(create_keypair(key)),
+            _ => None,
+        };
+        Treasury {
+            client,
+            signers,
+            config,
+            treasury,
+        }
+    }
+
+    pub async fn run(self) {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(self.config.check_interval_secs));
+        loop {
+            interval.tick().await;
+            self.check_balances().await;
+        }
+    }
+
+    async fn check_balances(&self) {
+        // A signer whose balance can't be read keeps its last status
+        for index in 0..self.signers.len() {
+            let account = self.signers.keypair(index).account_id();
+            let free = match self.client.best().account_balance(account.clone()).await {
+                Ok(balance) => balance.free,
+                Err(e) => {
+                    error(&format!(
+                        "Failed to query balance of signer {}: {}",
+                        account, e
+                    ));
+                    continue;
+                }
+            };
+            let address = account.to_string();
+            record_signer_balance(&address, free as f64 / ONE_AVAIL as f64);
+
+            let status = self.config.status(free);
+            match status {
+                SignerStatus::Funded => {}
+                SignerStatus::Low => {
+                    warn(&format!("Signer {} is running low: {}", address, free));
+                    log_signer_low_balance(&address, status.as_str());
+                }
+                SignerStatus::Underfunded => {
+                    error(&format!(
+                        "Signer {} is underfunded, no work is routed to it: {}",
+                        address, free
+                    ));
+                    log_signer_low_balance(&address, status.as_str());
+                }
+            }
+            self.signers
+                .set_funded(index, status != SignerStatus::Underfunded);
+
+            if status != SignerStatus::Funded {
+                if let Some(treasury) = &self.treasury {
+                    self.top_up(treasury, account).await;
+                }
+            }
+        }
+    }
+
+    /// Transfers `top_up_amount_avail` to a signer and waits for its inclusion, so the next check
+    /// sees the new balance
+    async fn top_up(&self, treasury: &Keypair, account: AccountId) {
+        let amount = self.config.top_up_amount_avail as u128 * ONE_AVAIL;
+        let submitted = match self
+            .client
+            .tx()
+            .balances()
+            .transfer_keep_alive(account.clone(), amount)
+            .sign_and_submit(treasury, Options::default())
+            .await
+        {
+            Ok(submitted) => submitted,
+            Err(e) => {
+                error(&format!("Failed to top up signer {}: {}", account, e));
+                return;
+            }
+        };
+        match submitted.receipt(false).await {
+            Ok(Some(_)) => info(&format!(
+                "Topped up signer {} with {}, tx hash: {:?}",
+                account, amount, submitted.tx_hash
+            )),
+            Ok(None) => error(&format!("Top up of signer {} was dropped", account)),
+            Err(e) => error(&format!("Failed to top up signer {}: {}", account, e)),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+#[cfg(test)]
+pub mod test {
+    use crate::treasury::{SignerStatus, Signers, TreasuryConfig};
+    use avail_rust::{constants::dev_accounts, prelude::ONE_AVAIL};
+
+    fn config() -> TreasuryConfig {
+        TreasuryConfig {
+            warn_balance_avail: 100,
+            min_balance_avail: 10,
+            ..TreasuryConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_signer_status_thresholds() {
+        let config = config();
+
+        assert_eq!(config.status(0), SignerStatus::Underfunded);
+        assert_eq!(config.status(10 * ONE_AVAIL - 1), SignerStatus::Underfunded);
+        assert_eq!(config.status(10 * ONE_AVAIL), SignerStatus::Low);
+        assert_eq!(config.status(100 * ONE_AVAIL - 1), SignerStatus::Low);
+        assert_eq!(config.status(100 * ONE_AVAIL), SignerStatus::Funded);
+    }
+
+    #[test]
+    fn test_only_funded_signers_are_picked() {
+        let signers = Signers::new(vec![dev_accounts::alice(), dev_accounts::bob()]);
+        assert!(signers.is_funded(0) && signers.is_funded(1));
+
+        signers.set_funded(0, false);
+        for _ in 0..20 {
+            assert_eq!(signers.pick_funded(), Some(1));
+        }
+
+        signers.set_funded(1, false);
+        assert_eq!(signers.pick_funded(), None);
+        assert!(!signers.is_funded(2));
+    }
+}
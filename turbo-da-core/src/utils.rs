@@ -8,18 +8,41 @@ use actix_web::{
 use alloy::primitives::Address;
 use avail_rust::{constants::dev_accounts, Client as AvailClient, Keypair, Options};
 
-use crate::logger::{debug_json, error, info, warn};
-use bigdecimal::BigDecimal;
-use clerk_rs::validators::authorizer::ClerkJwt;
+use crate::{
+    identity::Identity,
+    logger::{debug, debug_json, error, info, warn},
+    price_oracle::{PriceFeed, PriceQuote},
+};
+use bigdecimal::{BigDecimal, RoundingMode};
+use chrono::Utc;
+use db::{
+    controllers::{
+        audit_events::create_audit_event,
+        organisations::{
+            get_app_membership, get_member_apps, get_organisation_membership,
+            get_personal_organisation,
+        },
+        supported_tokens::get_supported_token,
+        unmatched_deposits::AttributeError,
+        users::get_user,
+    },
+    models::{
+        apps::Apps,
+        audit_events::AuditEventCreate,
+        organisations::{OrgPermission, Organisation},
+        processed_deposits::DepositPrice,
+        supported_tokens::SupportedToken,
+        user_model::BalanceUnit,
+    },
+};
 use diesel_async::{
     pooled_connection::deadpool::{Object, Pool},
-    AsyncPgConnection,
+    scoped_futures::{ScopedBoxFuture, ScopedFutureExt},
+    AsyncConnection, AsyncPgConnection,
 };
-use lazy_static::lazy_static;
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::{collections::HashMap, str::FromStr, sync::Arc};
+use std::{collections::HashMap, net::IpAddr, str::FromStr, sync::Arc};
 use tokio::time::{sleep, Duration};
 use uuid::Uuid;
 use validator::ValidationError;
@@ -72,22 +95,6 @@ pub fn generate_submission_id() -> Uuid {
     Uuid::new_v4()
 }
 
-/// Finds a token address by its key in the token map
-///
-/// # Arguments
-/// * `key` - Token key to look up
-pub fn find_key_by_value<'a>(chain: &'a u64, key: &'a String) -> Option<&'a String> {
-    if let Some(token) = TOKEN_MAP.get(chain) {
-        if let Some(token) = token.get(key) {
-            Some(&token.token_address)
-        } else {
-            None
-        }
-    } else {
-        None
-    }
-}
-
 /// Validates if a string is a valid Ethereum address
 ///
 /// # Arguments
@@ -99,6 +106,17 @@ pub fn is_valid_ethereum_address(address: &str) -> Result<(), ValidationError> {
     }
 }
 
+/// Validates a token address, an Ethereum address or a base58 encoded Solana mint
+pub fn is_valid_token_address(address: &str) -> Result<(), ValidationError> {
+    const BASE58: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    let is_mint = (32..=44).contains(&address.len()) && address.chars().all(|c| BASE58.contains(c));
+    if is_mint || Address::from_str(address).is_ok() {
+        Ok(())
+    } else {
+        Err(ValidationError::new("Invalid token address"))
+    }
+}
+
 /// Gets a database connection from the connection pool
 ///
 /// # Arguments
@@ -137,95 +155,296 @@ pub fn retrieve_user_id(http_request: &HttpRequest) -> Option<String> {
 /// Retrieves user ID from JWT in HTTP request
 ///
 /// # Arguments
-/// * `http_request` - HTTP request containing JWT with user ID
+/// * `http_request` - HTTP request authenticated by `IdentityMiddleware`
 ///
 /// # Returns
-/// * `Option<String>` - User ID if found in JWT, None otherwise
+/// * `Option<String>` - User ID mapped from the token claims, None if the request is not authenticated
 pub fn retrieve_user_id_from_jwt(http_request: &HttpRequest) -> Option<String> {
-    let jwt = http_request.extensions().get::<ClerkJwt>().cloned();
-    if let Some(jwt) = jwt {
-        jwt.other
-            .get("user_email")
-            .map(|id| id.to_string().trim_matches('"').to_string())
-    } else {
-        None
-    }
+    http_request
+        .extensions()
+        .get::<Identity>()
+        .map(|identity| identity.user_id.clone())
 }
 
-/// Retrieves email address from HTTP request headers
+/// Authorizes a user against the organisation owning an app
 ///
 /// # Arguments
-/// * `http_request` - HTTP request to extract email from
-pub fn retrieve_email_address(http_request: &HttpRequest) -> Option<String> {
-    let headers = http_request.headers();
+/// * `connection` - Database connection
+/// * `user` - User ID retrieved from the JWT
+/// * `app` - App to be accessed
+/// * `permission` - Permission required within the owning organisation
+///
+/// # Returns
+/// * `Ok(Apps)` - The app. Its `user_id` is the billing account of the organisation
+/// * `Err(HttpResponse)` - 404 if the user is not a member, 403 if the role lacks the permission
+pub async fn authorize_app(
+    connection: &mut AsyncPgConnection,
+    user: &String,
+    app: &Uuid,
+    permission: OrgPermission,
+) -> Result<Apps, HttpResponse> {
+    match get_app_membership(connection, app, user).await {
+        Ok((app, member)) if member.allows(permission) => Ok(app),
+        Ok((_, member)) => Err(HttpResponse::Forbidden().json(json!({
+            "state": "ERROR",
+            "error": format!("Role {} is not allowed to perform this action", member.role),
+        }))),
+        Err(e) => {
+            debug(&format!("App membership lookup failed for {}: {}", user, e));
+            Err(HttpResponse::NotFound().json(json!({
+                "state": "ERROR",
+                "error": "App not found",
+            })))
+        }
+    }
+}
 
-    for (name, value) in headers.iter() {
-        if name == "user_email" {
-            if let Ok(user_email) = value.to_str() {
-                return Some(user_email.to_string());
+/// Authorizes a user against an organisation
+///
+/// # Arguments
+/// * `connection` - Database connection
+/// * `user` - User ID retrieved from the JWT
+/// * `org` - Organisation to be accessed. Defaults to the personal organisation of the user
+/// * `permission` - Permission required within the organisation
+///
+/// # Returns
+/// * `Ok(Organisation)` - The organisation. Its `billing_id` is the billing account
+/// * `Err(HttpResponse)` - 404 if the user is not a member, 403 if the role lacks the permission
+pub async fn authorize_organisation(
+    connection: &mut AsyncPgConnection,
+    user: &String,
+    org: &Option<Uuid>,
+    permission: OrgPermission,
+) -> Result<Organisation, HttpResponse> {
+    let org = match org {
+        Some(org) => *org,
+        None => match get_personal_organisation(connection, user).await {
+            Ok(org) => org.id,
+            Err(e) => {
+                debug(&format!("No personal organisation for {}: {}", user, e));
+                return Err(HttpResponse::NotFound().json(json!({
+                    "state": "ERROR",
+                    "error": "Organisation not found",
+                })));
             }
+        },
+    };
+
+    match get_organisation_membership(connection, &org, user).await {
+        Ok((org, member)) if member.allows(permission) => Ok(org),
+        Ok((_, member)) => Err(HttpResponse::Forbidden().json(json!({
+            "state": "ERROR",
+            "error": format!("Role {} is not allowed to perform this action", member.role),
+        }))),
+        Err(e) => {
+            debug(&format!(
+                "Organisation membership lookup failed for {}: {}",
+                user, e
+            ));
+            Err(HttpResponse::NotFound().json(json!({
+                "state": "ERROR",
+                "error": "Organisation not found",
+            })))
         }
     }
-    None
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Price {
-    pub btc: Option<f64>,
-    pub btc_market_cap: Option<f64>,
-    pub eth: Option<f64>,
-    pub usd: Option<f64>,
+/// Looks up a token deposits may be made in
+///
+/// # Returns
+/// * `Ok(SupportedToken)` - The registered token
+/// * `Err(HttpResponse)` - 400 if the token is not registered or disabled
+pub async fn get_enabled_token(
+    connection: &mut AsyncPgConnection,
+    chain: i32,
+    token_address: &str,
+) -> Result<SupportedToken, HttpResponse> {
+    match get_supported_token(connection, chain, token_address).await {
+        Ok(Some(token)) if token.enabled => Ok(token),
+        Ok(_) => Err(HttpResponse::BadRequest().json(json!({
+            "state": "ERROR",
+            "error": "Token is not supported",
+        }))),
+        Err(e) => Err(HttpResponse::InternalServerError().json(json!({
+            "state": "ERROR",
+            "error": e,
+        }))),
+    }
 }
 
-/// Gets current prices for Avail and specified token from CoinGecko API
+/// Looks up the unit the balances of a user are held in
 ///
-/// # Arguments
-/// * `client` - HTTP client for making requests
-/// * `coingecko_api_url` - CoinGecko API URL
-/// * `coingecko_api_key` - CoinGecko API key
-/// * `token` - Token to get price for
-pub async fn get_prices(
-    client: &Client,
-    coingecko_api_url: &str,
-    coingecko_api_key: &str,
-    token: &str,
-) -> Result<(f64, f64), String> {
-    let params = [
-        ("ids", format!("avail,{}", token)),
-        ("vs_currencies", "usd".to_string()),
-    ];
-
-    let response = client
-        .get(coingecko_api_url)
-        .query(&params)
-        .header("accept", "application/json")
-        .header("x-cg-pro-api-key", coingecko_api_key)
-        .send()
+/// # Returns
+/// * `Ok(BalanceUnit)` - Unit of the balances of the user
+/// * `Err(HttpResponse)` - 500 if the user cannot be loaded
+pub async fn get_balance_unit(
+    connection: &mut AsyncPgConnection,
+    user: &String,
+) -> Result<BalanceUnit, HttpResponse> {
+    get_user(connection, user)
         .await
-        .map_err(|e| e.to_string())?;
+        .and_then(|user| user.balance_unit())
+        .map_err(|e| {
+            HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": e,
+            }))
+        })
+}
+
+/// Retrieves the apps a user holds a permission on through organisation membership
+///
+/// # Arguments
+/// * `connection` - Database connection
+/// * `user` - User ID retrieved from the JWT
+/// * `org` - Optionally restricts the apps to a single organisation
+/// * `permission` - Permission required within the owning organisation
+pub async fn authorized_apps(
+    connection: &mut AsyncPgConnection,
+    user: &String,
+    org: &Option<Uuid>,
+    permission: OrgPermission,
+) -> Result<Vec<Apps>, HttpResponse> {
+    match get_member_apps(connection, user, org).await {
+        Ok(apps) => Ok(apps
+            .into_iter()
+            .filter(|(_, member)| member.allows(permission))
+            .map(|(app, _)| app)
+            .collect()),
+        Err(e) => {
+            error(&format!("Failed to retrieve apps for {}: {}", user, e));
+            Err(HttpResponse::InternalServerError().json(json!({
+                "state": "ERROR",
+                "error": e,
+            })))
+        }
+    }
+}
 
-    let json = response
-        .json::<HashMap<String, Price>>()
+/// Errors of an audited action, which also fails when its event can't be recorded
+pub trait AuditedError: Send {
+    fn audit_failed(message: String) -> Self;
+}
+
+impl AuditedError for String {
+    fn audit_failed(message: String) -> Self {
+        message
+    }
+}
+
+impl AuditedError for diesel::result::Error {
+    fn audit_failed(message: String) -> Self {
+        diesel::result::Error::QueryBuilderError(message.into())
+    }
+}
+
+impl AuditedError for AttributeError {
+    fn audit_failed(message: String) -> Self {
+        AttributeError::Database(diesel::result::Error::audit_failed(message))
+    }
+}
+
+enum Audited<E> {
+    Action(E),
+    Audit(String),
+}
+
+impl<E> From<diesel::result::Error> for Audited<E> {
+    fn from(e: diesel::result::Error) -> Self {
+        Audited::Audit(e.to_string())
+    }
+}
+
+/// Applies an action and records its event in the audit log, in a single transaction
+///
+/// # Arguments
+/// * `connection` - Database connection
+/// * `http_request` - Request performing the action, used for the client IP address
+/// * `action` - Action to be audited
+/// * `event` - Event recorded for the result of the action, nothing is recorded when `None`
+///
+/// # Description
+/// The action is rolled back when its event can't be recorded, so no applied action goes
+/// unaudited.
+pub async fn audited<'a, T, E, A, F>(
+    connection: &mut AsyncPgConnection,
+    http_request: &HttpRequest,
+    action: A,
+    event: F,
+) -> Result<T, E>
+where
+    A: for<'c> FnOnce(&'c mut AsyncPgConnection) -> ScopedBoxFuture<'a, 'c, Result<T, E>>
+        + Send
+        + 'a,
+    F: FnOnce(&T) -> Option<AuditEventCreate> + Send + 'a,
+    T: Send + 'a,
+    E: AuditedError + 'a,
+{
+    let ip_address = client_ip(http_request);
+
+    connection
+        .transaction::<_, Audited<E>, _>(|conn| {
+            async move {
+                let result = action(conn).await.map_err(Audited::Action)?;
+                if let Some(mut event) = event(&result) {
+                    event.ip_address = ip_address;
+                    create_audit_event(conn, &event).await.map_err(|e| {
+                        Audited::Audit(format!(
+                            "Failed to record audit event {} by {} on {}: {}",
+                            event.action, event.actor, event.target_id, e
+                        ))
+                    })?;
+                }
+                Ok(result)
+            }
+            .scope_boxed()
+        })
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| match e {
+            Audited::Action(e) => e,
+            Audited::Audit(e) => {
+                error(&e);
+                E::audit_failed(e)
+            }
+        })
+}
 
-    let coin_price = match json.get(token) {
-        Some(val) => val.usd.ok_or_else(|| price_not_found_error(token))?,
-        None => {
-            let msg = price_not_found_error(token);
-            return Err(msg);
-        }
-    };
+/// Proxies whose forwarded headers are trusted, registered as app data
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(pub Vec<IpAddr>);
 
-    let avail_price = match json.get("avail") {
-        Some(val) => val.usd.ok_or_else(|| price_not_found_error("avail"))?,
-        None => {
-            let msg = price_not_found_error("avail");
-            return Err(msg);
+/// IP address of the client making a request
+///
+/// Forwarded headers can be set by any client, they are only trusted on requests coming from one
+/// of the [`TrustedProxies`].
+pub fn client_ip(http_request: &HttpRequest) -> Option<String> {
+    let peer = http_request.peer_addr()?.ip();
+    let trusted_proxy = http_request
+        .app_data::<web::Data<TrustedProxies>>()
+        .is_some_and(|proxies| proxies.0.contains(&peer));
+    if trusted_proxy {
+        if let Some(ip) = http_request.connection_info().realip_remote_addr() {
+            return Some(ip.to_string());
         }
-    };
+    }
+    Some(peer.to_string())
+}
+
+/// Retrieves email address from HTTP request headers
+///
+/// # Arguments
+/// * `http_request` - HTTP request to extract email from
+pub fn retrieve_email_address(http_request: &HttpRequest) -> Option<String> {
+    let headers = http_request.headers();
 
-    Ok((coin_price, avail_price))
+    for (name, value) in headers.iter() {
+        if name == "user_email" {
+            if let Ok(user_email) = value.to_str() {
+                return Some(user_email.to_string());
+            }
+        }
+    }
+    None
 }
 
 pub struct Convertor<'a> {
@@ -272,6 +491,31 @@ impl<'a> Convertor<'a> {
 
         one_kb_fee / data_posted_fee * BigDecimal::from(data_posted_amount as u128)
     }
+
+    /// USD cost of submitting `data` at `avail_usd_price`, data under 1 KB is billed as 1 KB
+    pub async fn calculate_usd_utilisation(
+        &self,
+        data: Vec<u8>,
+        avail_usd_price: &BigDecimal,
+    ) -> BigDecimal {
+        let data = if data.len() < self.one_kb.len() {
+            self.one_kb.clone()
+        } else {
+            data
+        };
+        let fee = self.get_gas_price_for_data(data).await;
+        convert_avail_to_usd(&fee, avail_usd_price)
+    }
+
+    /// Value of a credit at the current fee in the smallest unit of AVAIL, as
+    /// `get_credit_avail_value`, `None` when the fee can't be estimated
+    pub async fn credit_avail_value(&self) -> Option<BigDecimal> {
+        let price_per_kb = self.get_gas_price_for_data(self.one_kb.clone()).await;
+        if price_per_kb == BigDecimal::from(u128::MAX) {
+            return None;
+        }
+        Some(price_per_kb / BigDecimal::from(self.one_kb.len() as u128))
+    }
 }
 
 /// Token information structure
@@ -279,32 +523,30 @@ impl<'a> Convertor<'a> {
 pub struct Token {
     pub token_address: String,
     pub token_decimals: u32,
+    pub symbol: String,
 }
 
-lazy_static! {
-    pub static ref TOKEN_MAP: HashMap<u64, HashMap<String, Token>> = {
-        let mut m = HashMap::new();
-        let mut chain_map = HashMap::new();
-        chain_map.insert(
-            "ethereum".to_string(),
-            Token {
-                token_address: "0x8b42845d23c68b845e262dc3e5caa1c9ce9edb44".to_string(),
-                token_decimals: 18,
-            },
-        );
-        chain_map.insert(
-            "avail".to_string(),
+/// Groups tokens by chain, keyed by their price feed id
+///
+/// Of tokens sharing a price feed on a chain, the last registered one is listed.
+pub fn token_map(tokens: Vec<SupportedToken>) -> HashMap<i32, HashMap<String, Token>> {
+    let mut map: HashMap<i32, HashMap<String, Token>> = HashMap::new();
+    for token in tokens {
+        map.entry(token.chain_id).or_default().insert(
+            token.price_feed_id,
             Token {
-                token_address: "0x99a907545815c289fb6de86d55fe61d996063a94".to_string(),
-                token_decimals: 18,
+                token_address: token.token_address,
+                token_decimals: token.decimals as u32,
+                symbol: token.symbol,
             },
         );
-        m.insert(11155111, chain_map.clone());
-        m.insert(84532, chain_map.clone());
-        m
-    };
+    }
+    map
 }
 
+/// Decimals of AVAIL, credits are priced in its smallest unit
+const AVAIL_DECIMALS: i64 = 18;
+
 const WAIT_TIME: u64 = 5;
 pub async fn generate_avail_sdk(endpoints: &Arc<Vec<String>>) -> AvailClient {
     let mut attempts = 0;
@@ -353,125 +595,140 @@ pub fn retrieve_account_id(http_request: &HttpRequest) -> Option<Uuid> {
     None
 }
 
-fn price_not_found_error(token: &str) -> String {
-    format!("{:?} price not found from coingecho", token)
+/// `10^exponent` as an exact decimal
+fn pow10(exponent: i64) -> BigDecimal {
+    BigDecimal::new(1.into(), -exponent)
 }
 
-pub async fn calculate_avail_token_equivalent(
-    coingecko_api_url: &str,
-    coingecko_api_key: &str,
+/// Converts `token_amount`, in the smallest unit of the token, to the smallest unit of AVAIL
+///
+/// The conversion is carried out in decimals, the result is rounded to a whole unit.
+pub fn convert_to_avail(
     token_amount: &BigDecimal,
-    chain: &u64,
-    token_address: &str,
-) -> Result<BigDecimal, String> {
-    let http_client = Client::new();
+    token_usd_price: &BigDecimal,
+    token_decimals: i64,
+    avail_usd_price: &BigDecimal,
+    avail_decimals: i64,
+) -> BigDecimal {
+    (token_amount * token_usd_price * pow10(avail_decimals)
+        / (avail_usd_price * pow10(token_decimals)))
+    .round(0)
+}
 
-    debug_json(json!({
-        "message": "Token Address",
-        "token_address": token_address,
-        "level": "debug"
-    }));
+/// Converts `avail_amount`, in the smallest unit of AVAIL, to the smallest unit of a token
+///
+/// The inverse of `convert_to_avail`, the result is rounded down to a whole unit.
+pub fn convert_from_avail(
+    avail_amount: &BigDecimal,
+    avail_usd_price: &BigDecimal,
+    avail_decimals: i64,
+    token_usd_price: &BigDecimal,
+    token_decimals: i64,
+) -> BigDecimal {
+    (avail_amount * avail_usd_price * pow10(token_decimals)
+        / (token_usd_price * pow10(avail_decimals)))
+    .with_scale_round(0, RoundingMode::Down)
+}
 
-    let equivalent_amount;
-    if token_address == "0x0000000000000000000000000000000000000000".to_string() {
-        let token_symbol = "avail".to_string();
-        let (token_usd_price, avail_usd_price) = get_prices(
-            &http_client,
-            &coingecko_api_url,
-            &coingecko_api_key,
-            token_symbol.as_str(),
-        )
-        .await
-        .map_err(|e| format!("Failed to fetch prices for {}: {}", token_symbol, e))?;
+/// Converts `token_amount`, in the smallest unit of the token, to USD, rounded down
+pub fn convert_to_usd(
+    token_amount: &BigDecimal,
+    token_usd_price: &BigDecimal,
+    token_decimals: i64,
+) -> BigDecimal {
+    (token_amount * token_usd_price / pow10(token_decimals))
+        .with_scale_round(BalanceUnit::Usd.scale(), RoundingMode::Down)
+}
 
-        let token_avail_ratio = token_usd_price / avail_usd_price;
-        let token_avail_ratio_decimal =
-            BigDecimal::from_str(token_avail_ratio.to_string().as_str())
-                .map_err(|e| format!("Failed to convert price ratio to decimal: {}", e))?;
+/// Converts `usd` to the smallest unit of a token, the inverse of `convert_to_usd`
+pub fn convert_from_usd(
+    usd: &BigDecimal,
+    token_usd_price: &BigDecimal,
+    token_decimals: i64,
+) -> BigDecimal {
+    (usd * pow10(token_decimals) / token_usd_price).with_scale_round(0, RoundingMode::Down)
+}
 
-        equivalent_amount = token_amount * token_avail_ratio_decimal;
-    } else {
-        let token_symbol = TOKEN_MAP
-            .iter()
-            .find_map(|(chain_id, tokens)| {
-                if chain_id == chain {
-                    tokens.iter().find_map(|(key, token)| {
-                        if token.token_address == token_address {
-                            Some(key.clone())
-                        } else {
-                            None
-                        }
-                    })
-                } else {
-                    None
-                }
-            })
-            .ok_or_else(|| String::from("Token address not found in token mapping"))?;
-
-        let (token_usd_price, avail_usd_price) = get_prices(
-            &http_client,
-            &coingecko_api_url,
-            &coingecko_api_key,
-            token_symbol.as_str(),
-        )
+/// USD cost of `avail_amount`, a fee in the smallest unit of AVAIL, rounded up
+pub fn convert_avail_to_usd(avail_amount: &BigDecimal, avail_usd_price: &BigDecimal) -> BigDecimal {
+    (avail_amount * avail_usd_price / pow10(AVAIL_DECIMALS))
+        .with_scale_round(BalanceUnit::Usd.scale(), RoundingMode::Up)
+}
+
+/// USD value of a credit, a byte-equivalent priced from the AVAIL fee of 1 KB
+pub fn credit_usd_value(
+    price_per_kb: &BigDecimal,
+    one_kb: &BigDecimal,
+    avail_usd_price: &BigDecimal,
+) -> BigDecimal {
+    price_per_kb * avail_usd_price / (one_kb * pow10(AVAIL_DECIMALS))
+}
+
+/// USD prices of `token` and AVAIL, and the sources they were taken from
+async fn token_and_avail_prices(
+    prices: &PriceFeed,
+    token: &SupportedToken,
+) -> Result<(PriceQuote, PriceQuote, DepositPrice), String> {
+    let avail_price = prices
+        .usd_price("avail")
         .await
-        .map_err(|e| format!("Failed to fetch prices for {}: {}", token_symbol, e))?;
-
-        debug_json(json!({
-            "message": "Current Token USD price",
-            "token_usd_price": token_usd_price,
-            "level": "debug"
-        }));
-        debug_json(json!({
-            "message": "Current AVAIL USD price",
-            "avail_usd_price": avail_usd_price,
-            "level": "debug"
-        }));
-
-        let token_avail_ratio = token_usd_price / avail_usd_price;
-        let source_token_decimals = TOKEN_MAP
-            .get(chain)
-            .ok_or_else(|| String::from("Source Token address not found in token mapping"))?
-            .get(token_symbol.as_str())
-            .ok_or_else(|| String::from("Source Token address not found in token mapping"))?
-            .token_decimals;
-        let avail_token_decimals = TOKEN_MAP
-            .get(chain)
-            .ok_or_else(|| String::from("Avail Token address not found in token mapping"))?
-            .get("avail")
-            .ok_or_else(|| String::from("Avail Token address not found in token mapping"))?
-            .token_decimals;
-        let token_avail_ratio_decimal =
-            BigDecimal::from_str(token_avail_ratio.to_string().as_str())
-                .map_err(|e| format!("Failed to convert price ratio to decimal: {}", e))?;
-
-        equivalent_amount = token_avail_ratio_decimal
-            * token_amount
-            * BigDecimal::from(10_u64.pow(avail_token_decimals as u32))
-            / BigDecimal::from(10_u64.pow(source_token_decimals as u32));
+        .map_err(|e| format!("Failed to fetch prices for avail: {}", e))?;
+    let token_price = if token.price_feed_id == "avail" {
+        avail_price.clone()
+    } else {
+        prices
+            .usd_price(&token.price_feed_id)
+            .await
+            .map_err(|e| format!("Failed to fetch prices for {}: {}", token.price_feed_id, e))?
     };
 
-    Ok(equivalent_amount.round(0))
+    debug_json(json!({
+        "message": "Current USD prices",
+        "token_usd_price": token_price,
+        "avail_usd_price": avail_price,
+        "level": "debug"
+    }));
+
+    let price_source = if token_price.source == avail_price.source {
+        token_price.source.to_string()
+    } else {
+        format!("{}/{}", token_price.source, avail_price.source)
+    };
+    let deposit_price = DepositPrice {
+        token_usd_price: token_price.usd.clone(),
+        avail_usd_price: avail_price.usd.clone(),
+        price_source,
+        priced_at: Utc::now().naive_utc(),
+    };
+    Ok((token_price, avail_price, deposit_price))
 }
 
-pub async fn get_amount_to_be_credited(
-    coin_gecho_api_url: &String,
-    coin_gecho_api_key: &String,
-    avail_rpc_url: &String,
-    chain: &u64,
-    address: &String,
-    amount: &BigDecimal,
-) -> Result<BigDecimal, String> {
-    let price = calculate_avail_token_equivalent(
-        &coin_gecho_api_url,
-        &coin_gecho_api_key,
-        &amount,
-        &chain,
-        &address,
-    )
-    .await
-    .map_err(|e| format!("Failed to get price for {}: {}", address, e))?;
+/// Converts a deposit of `token` to its AVAIL equivalent, returning the prices it was converted with
+pub async fn calculate_avail_token_equivalent(
+    prices: &PriceFeed,
+    token_amount: &BigDecimal,
+    token: &SupportedToken,
+) -> Result<(BigDecimal, DepositPrice), String> {
+    debug_json(json!({
+        "message": "Token Address",
+        "token_address": token.token_address,
+        "level": "debug"
+    }));
+
+    let (token_price, avail_price, deposit_price) = token_and_avail_prices(prices, token).await?;
+    let equivalent_amount = convert_to_avail(
+        token_amount,
+        &token_price.usd,
+        token.decimals as i64,
+        &avail_price.usd,
+        AVAIL_DECIMALS,
+    );
 
+    Ok((equivalent_amount, deposit_price))
+}
+
+/// Fee of submitting 1 KB of data, in the smallest unit of AVAIL, and the size of 1 KB in bytes
+async fn one_kb_fee(avail_rpc_url: &str) -> Result<(BigDecimal, BigDecimal), String> {
     let client = AvailClient::new(avail_rpc_url)
         .await
         .map_err(|e| format!("Failed to create SDK client: {:?}", e))?;
@@ -482,5 +739,107 @@ pub async fn get_amount_to_be_credited(
         .get_gas_price_for_data(converter.one_kb.clone())
         .await;
 
-    Ok((price / price_per_kb * BigDecimal::from(converter.one_kb.len() as u128)).round(3))
+    Ok((
+        price_per_kb,
+        BigDecimal::from(converter.one_kb.len() as u128),
+    ))
+}
+
+/// Prices a deposit of `amount` of `token` in the `unit` of the balance it is credited to
+///
+/// Returns the amount to credit and the prices it was converted with.
+pub async fn get_amount_to_be_credited(
+    prices: &PriceFeed,
+    avail_rpc_url: &str,
+    token: &SupportedToken,
+    amount: &BigDecimal,
+    unit: BalanceUnit,
+) -> Result<(BigDecimal, DepositPrice), String> {
+    if unit == BalanceUnit::Usd {
+        let (token_price, _, deposit_price) = token_and_avail_prices(prices, token)
+            .await
+            .map_err(|e| format!("Failed to get price for {}: {}", token.token_address, e))?;
+        return Ok((
+            convert_to_usd(amount, &token_price.usd, token.decimals as i64),
+            deposit_price,
+        ));
+    }
+
+    let (price, deposit_price) = calculate_avail_token_equivalent(prices, amount, token)
+        .await
+        .map_err(|e| format!("Failed to get price for {}: {}", token.token_address, e))?;
+
+    let (price_per_kb, one_kb) = one_kb_fee(avail_rpc_url).await?;
+
+    Ok(((price / price_per_kb * one_kb).round(3), deposit_price))
+}
+
+/// Prices `credits`, in the `unit` of the balance, back to `token`, the inverse of
+/// `get_amount_to_be_credited`
+///
+/// Returns the amount in the smallest unit of the token, rounded down, and the prices it was
+/// converted with.
+pub async fn get_amount_to_be_withdrawn(
+    prices: &PriceFeed,
+    avail_rpc_url: &str,
+    token: &SupportedToken,
+    credits: &BigDecimal,
+    unit: BalanceUnit,
+) -> Result<(BigDecimal, DepositPrice), String> {
+    let (token_price, avail_price, deposit_price) = token_and_avail_prices(prices, token)
+        .await
+        .map_err(|e| format!("Failed to get price for {}: {}", token.token_address, e))?;
+    if unit == BalanceUnit::Usd {
+        return Ok((
+            convert_from_usd(credits, &token_price.usd, token.decimals as i64),
+            deposit_price,
+        ));
+    }
+
+    let (price_per_kb, one_kb) = one_kb_fee(avail_rpc_url).await?;
+    let avail_amount = credits * price_per_kb / one_kb;
+
+    Ok((
+        convert_from_avail(
+            &avail_amount,
+            &avail_price.usd,
+            AVAIL_DECIMALS,
+            &token_price.usd,
+            token.decimals as i64,
+        ),
+        deposit_price,
+    ))
+}
+
+/// Value of a credit at the current fee, in the smallest unit of AVAIL
+pub async fn get_credit_avail_value(avail_rpc_url: &str) -> Result<BigDecimal, String> {
+    let (price_per_kb, one_kb) = one_kb_fee(avail_rpc_url).await?;
+    // The fee estimate falls back to the largest fee when it fails
+    if price_per_kb == BigDecimal::from(u128::MAX) {
+        return Err("Failed to estimate the fee of 1 KB".to_string());
+    }
+
+    Ok(price_per_kb / one_kb)
+}
+
+/// USD value of a credit at the current AVAIL fee and price, the rate credit balances are
+/// converted to USD at, and the AVAIL price it was taken with
+pub async fn get_credit_usd_value(
+    prices: &PriceFeed,
+    avail_rpc_url: &str,
+) -> Result<(BigDecimal, PriceQuote), String> {
+    let avail_price = prices
+        .usd_price("avail")
+        .await
+        .map_err(|e| format!("Failed to fetch prices for avail: {}", e))?;
+    let (price_per_kb, one_kb) = one_kb_fee(avail_rpc_url).await?;
+    // The fee estimate falls back to the largest fee when it fails
+    if price_per_kb == BigDecimal::from(u128::MAX) {
+        return Err("Failed to estimate the fee of 1 KB".to_string());
+    }
+
+    Ok((
+        credit_usd_value(&price_per_kb, &one_kb, &avail_price.usd),
+        avail_price,
+    ))
 }
@@ -0,0 +1,109 @@
+/// Withdrawals of credits back to chain
+/// - `request_withdrawal` prices the credits back to a token and holds them from the balance
+/// - An operator signs the payout with the key of an account holding `OPERATOR_ROLE` on the
+///   `TurboDAResolver` contract, anyone can then claim it with `withdraw`
+/// - The funds monitor marks the withdrawal paid once it sees the `Withdrawal` event
+#[cfg(test)]
+mod test;
+
+use alloy::{
+    primitives::{keccak256, Address, Bytes, PrimitiveSignature, B256, U256},
+    signers::{local::PrivateKeySigner, SignerSync},
+    sol,
+    sol_types::SolValue,
+};
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use std::{env, str::FromStr};
+
+sol! {
+    event Withdrawal(
+        uint256 indexed nonce,
+        bytes userID,
+        address indexed tokenAddress,
+        uint256 amount,
+        address indexed recipient
+    );
+}
+
+/// Withdrawals are paid out by the contract of the chain configured in `AppConfig::chains`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WithdrawalConfig {
+    /// Hex encoded private key of an operator of the contracts, withdrawals are not signed when
+    /// empty
+    #[serde(default)]
+    pub signer_key: String,
+}
+
+impl WithdrawalConfig {
+    /// Reads the `WITHDRAWAL_SIGNER_KEY` variable
+    pub fn from_env() -> Self {
+        Self {
+            signer_key: env::var("WITHDRAWAL_SIGNER_KEY").unwrap_or_default(),
+        }
+    }
+}
+
+/// Payout of a withdrawal as signed by the operator
+pub struct WithdrawalPayout<'a> {
+    pub chain_id: i32,
+    pub contract: &'a str,
+    pub user_id: &'a str,
+    pub token_address: &'a str,
+    /// Amount in the smallest unit of the token
+    pub amount: &'a BigDecimal,
+    pub recipient: &'a str,
+    pub nonce: i32,
+}
+
+fn parse_address(address: &str) -> Result<Address, String> {
+    Address::from_str(address).map_err(|e| format!("Invalid address {}: {}", address, e))
+}
+
+/// Hash the contract checks the operator signature against,
+/// `keccak256(abi.encode(chainid, contract, userID, tokenAddress, amount, recipient, nonce))`
+pub fn withdrawal_digest(payout: &WithdrawalPayout) -> Result<B256, String> {
+    let amount = U256::from_str(&payout.amount.with_scale(0).to_string())
+        .map_err(|e| format!("Invalid amount {}: {}", payout.amount, e))?;
+    let encoded = (
+        U256::from(payout.chain_id),
+        parse_address(payout.contract)?,
+        Bytes::copy_from_slice(payout.user_id.as_bytes()),
+        parse_address(payout.token_address)?,
+        amount,
+        parse_address(payout.recipient)?,
+        U256::from(payout.nonce),
+    )
+        .abi_encode_params();
+    Ok(keccak256(encoded))
+}
+
+fn signer(key: &str) -> Result<PrivateKeySigner, String> {
+    PrivateKeySigner::from_str(key).map_err(|e| format!("Invalid signer key: {}", e))
+}
+
+/// Address of the operator signing with `key`
+pub fn withdrawal_signer(key: &str) -> Result<Address, String> {
+    Ok(signer(key)?.address())
+}
+
+/// Hex encoded signature of a payout, as an Ethereum signed message of its digest
+pub fn sign_withdrawal(key: &str, payout: &WithdrawalPayout) -> Result<String, String> {
+    let signature = signer(key)?
+        .sign_message_sync(withdrawal_digest(payout)?.as_slice())
+        .map_err(|e| format!("Failed to sign withdrawal: {}", e))?;
+    Ok(format!("0x{}", hex::encode(signature.as_bytes())))
+}
+
+/// Address that signed a payout
+pub fn recover_withdrawal_signer(
+    payout: &WithdrawalPayout,
+    signature: &str,
+) -> Result<Address, String> {
+    let bytes = hex::decode(signature.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid signature: {}", e))?;
+    PrimitiveSignature::try_from(bytes.as_slice())
+        .map_err(|e| format!("Invalid signature: {}", e))?
+        .recover_address_from_msg(withdrawal_digest(payout)?.as_slice())
+        .map_err(|e| format!("Failed to recover signer: {}", e))
+}
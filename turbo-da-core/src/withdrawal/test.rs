@@ -0,0 +1,95 @@
+use super::{
+    recover_withdrawal_signer, sign_withdrawal, withdrawal_digest, withdrawal_signer,
+    WithdrawalPayout,
+};
+use crate::utils::{convert_from_avail, convert_from_usd, convert_to_avail, convert_to_usd};
+use bigdecimal::BigDecimal;
+use std::str::FromStr;
+
+/// Private key of the first Anvil account
+const KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+const CONTRACT: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+const TOKEN: &str = "0x99a907545815c289fb6de86d55fe61d996063a94";
+const RECIPIENT: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+
+fn decimal(value: &str) -> BigDecimal {
+    BigDecimal::from_str(value).unwrap()
+}
+
+fn payout<'a>(amount: &'a BigDecimal, nonce: i32) -> WithdrawalPayout<'a> {
+    WithdrawalPayout {
+        chain_id: 11155111,
+        contract: CONTRACT,
+        user_id: "user@example.com",
+        token_address: TOKEN,
+        amount,
+        recipient: RECIPIENT,
+        nonce,
+    }
+}
+
+#[test]
+fn test_signature_recovers_to_the_operator() {
+    let amount = decimal("1000000000000000000");
+    let signature = sign_withdrawal(KEY, &payout(&amount, 7)).unwrap();
+
+    assert_eq!(signature.len(), 2 + 65 * 2);
+    assert_eq!(
+        recover_withdrawal_signer(&payout(&amount, 7), &signature).unwrap(),
+        withdrawal_signer(KEY).unwrap()
+    );
+}
+
+#[test]
+fn test_signature_does_not_cover_another_payout() {
+    let amount = decimal("1000000000000000000");
+    let signature = sign_withdrawal(KEY, &payout(&amount, 7)).unwrap();
+    let signer = withdrawal_signer(KEY).unwrap();
+
+    let other_amount = decimal("2000000000000000000");
+    assert_ne!(
+        recover_withdrawal_signer(&payout(&other_amount, 7), &signature).unwrap(),
+        signer
+    );
+    assert_ne!(
+        recover_withdrawal_signer(&payout(&amount, 8), &signature).unwrap(),
+        signer
+    );
+}
+
+#[test]
+fn test_digest_rejects_invalid_addresses() {
+    let amount = decimal("1");
+    let mut invalid = payout(&amount, 1);
+    invalid.recipient = "not an address";
+
+    assert!(withdrawal_digest(&invalid).is_err());
+}
+
+#[test]
+fn test_conversion_from_avail_rounds_down() {
+    // 21.428... AVAIL at $0.07 is worth 1.5 tokens of 6 decimals at $1
+    let avail = convert_to_avail(&decimal("1500000"), &decimal("1"), 6, &decimal("0.07"), 18);
+    let amount = convert_from_avail(&avail, &decimal("0.07"), 18, &decimal("1"), 6);
+
+    assert_eq!(amount, decimal("1499999"));
+    assert_eq!(
+        convert_from_avail(&decimal("1e18"), &decimal("3"), 18, &decimal("2"), 6),
+        decimal("1500000")
+    );
+}
+
+#[test]
+fn test_conversion_from_usd_rounds_down() {
+    // $1.4985 bought with 1.5 tokens of 6 decimals at $0.999 pays back the same tokens
+    let usd = convert_to_usd(&decimal("1500000"), &decimal("0.999"), 6);
+
+    assert_eq!(
+        convert_from_usd(&usd, &decimal("0.999"), 6),
+        decimal("1500000")
+    );
+    assert_eq!(
+        convert_from_usd(&decimal("1"), &decimal("3"), 6),
+        decimal("333333")
+    );
+}